@@ -0,0 +1,32 @@
+// * * ** *** ***** ******** ************* *********************
+// Benchmarking workloads on a single core
+// * * ** *** ***** ******** ************* *********************
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use concrust::api;
+
+
+// Benchmarks
+
+const BENCH_CYCLES: [usize; 3] = [1000, 10000, 100000];
+const BENCH_SEED: u64 = 42;
+
+/// Throughput comes in cycles, so it compares with Cycles/sec of reports.
+fn bench_workloads(c: &mut Criterion) {
+    for workload in api::list_workloads() {
+        let mut group = c.benchmark_group(workload);
+        for n_cycles in BENCH_CYCLES {
+            group.throughput(Throughput::Elements(n_cycles as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(n_cycles), &n_cycles, |b, &n_cycles| {
+                b.iter(|| api::run_workload(workload, black_box(n_cycles), BENCH_SEED))
+            });
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_workloads);
+criterion_main!(benches);
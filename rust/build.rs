@@ -0,0 +1,45 @@
+// * * ** *** ***** ******** ************* *********************
+// Embedding the build profile into the binary
+// * * ** *** ***** ******** ************* *********************
+
+use std::env;
+use std::fs;
+
+
+/// Cargo tells build scripts the profile and opt level but not LTO, so it is read from the
+/// manifest unless overridden by the environment.
+fn find_lto(profile: &str) -> String {
+
+    let variable = format!("CARGO_PROFILE_{}_LTO", profile.to_uppercase());
+    println!("cargo:rerun-if-env-changed={}", variable);
+    if let Ok(lto) = env::var(&variable) {
+        return lto;
+    }
+
+    let manifest = fs::read_to_string("Cargo.toml").unwrap_or_default();
+    let section = format!("[profile.{}]", profile);
+    let mut in_section = false;
+
+    for line in manifest.lines().map(|line| line.trim()) {
+        if line.starts_with('[') {
+            in_section = line == section;
+        } else if in_section && line.starts_with("lto") {
+            if let Some((_, value)) = line.split_once('=') {
+                return value.trim().trim_matches('"').to_string();
+            }
+        }
+    }
+
+    "false".to_string()
+}
+
+fn main() {
+
+    let profile = env::var("PROFILE").unwrap_or_default();
+
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rustc-env=CONCTEST_BUILD_PROFILE={}", profile);
+    println!("cargo:rustc-env=CONCTEST_OPT_LEVEL={}", env::var("OPT_LEVEL").unwrap_or_default());
+    println!("cargo:rustc-env=CONCTEST_TARGET={}", env::var("TARGET").unwrap_or_default());
+    println!("cargo:rustc-env=CONCTEST_LTO={}", find_lto(&profile));
+}
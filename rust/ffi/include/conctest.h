@@ -0,0 +1,42 @@
+/* * * ** *** ***** ******** ************* *********************
+ * Embedding the conctest measurement engine from C
+ * * * ** *** ***** ******** ************* ********************* */
+
+#ifndef CONCTEST_H
+#define CONCTEST_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Configurations start with one task in a series, one repeat, no retries, and seed 0 */
+typedef struct conctest_config conctest_config;
+
+/* Reports keep metadata, totals of observations, and schedules of their tasks */
+typedef struct conctest_report conctest_report;
+
+conctest_config *conctest_config_new(size_t tasks_max, size_t n_cycles);
+void conctest_config_set_series_size(conctest_config *config, size_t series_size);
+/* Returns 0 for a backend that is not valid UTF-8 */
+int conctest_config_set_backend(conctest_config *config, const char *backend);
+void conctest_config_set_repeats(conctest_config *config, size_t repeats);
+void conctest_config_set_retries(conctest_config *config, size_t retries_max);
+void conctest_config_set_seed(conctest_config *config, uint64_t seed);
+void conctest_config_free(conctest_config *config);
+
+/* Sweeps 1 to tasks_max tasks, NULL for an invalid configuration */
+conctest_report *conctest_measure(const conctest_config *config);
+/* Freed by conctest_string_free */
+char *conctest_report_to_json(const conctest_report *report);
+void conctest_report_free(conctest_report *report);
+
+void conctest_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,158 @@
+// * * ** *** ***** ******** ************* *********************
+// Embedding the measurement engine through a C interface
+// * * ** *** ***** ******** ************* *********************
+
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+
+use concrust::api::{self, ReportRecords, RunConfig};
+
+
+// Configurations
+
+/// Builder calls replace the configuration behind a handle, as C keeps the pointer.
+unsafe fn update_config(config: *mut RunConfig, update: impl FnOnce(RunConfig) -> RunConfig) {
+    if let Some(config) = config.as_mut() {
+        *config = update(config.clone());
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn conctest_config_new(tasks_max: usize, n_cycles: usize) -> *mut RunConfig {
+    Box::into_raw(Box::new(RunConfig::new().tasks(1..=tasks_max).cycles(n_cycles)))
+}
+
+/// # Safety
+/// The configuration comes from `conctest_config_new` and is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conctest_config_set_series_size(config: *mut RunConfig, series_size: usize) {
+    update_config(config, |config| config.series_size(series_size));
+}
+
+/// # Safety
+/// The configuration comes from `conctest_config_new`, the backend is a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn conctest_config_set_backend(config: *mut RunConfig,
+                                                     backend: *const c_char) -> c_int {
+    if backend.is_null() {
+        return 0;
+    }
+    match CStr::from_ptr(backend).to_str() {
+        Ok(backend) => {
+            update_config(config, |config| config.backend(backend));
+            1
+        }
+        Err(_) => 0
+    }
+}
+
+/// # Safety
+/// The configuration comes from `conctest_config_new` and is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conctest_config_set_repeats(config: *mut RunConfig, repeats: usize) {
+    update_config(config, |config| config.repeats(repeats));
+}
+
+/// # Safety
+/// The configuration comes from `conctest_config_new` and is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conctest_config_set_retries(config: *mut RunConfig, retries_max: usize) {
+    update_config(config, |config| config.retries(retries_max));
+}
+
+/// # Safety
+/// The configuration comes from `conctest_config_new` and is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conctest_config_set_seed(config: *mut RunConfig, seed: u64) {
+    update_config(config, |config| config.seed(seed));
+}
+
+/// # Safety
+/// The configuration comes from `conctest_config_new`, or is null.
+#[no_mangle]
+pub unsafe extern "C" fn conctest_config_free(config: *mut RunConfig) {
+    if !config.is_null() {
+        drop(Box::from_raw(config));
+    }
+}
+
+
+// Measuring
+
+/// # Safety
+/// The configuration comes from `conctest_config_new` and is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conctest_measure(config: *const RunConfig) -> *mut ReportRecords {
+    match config.as_ref().map(api::measure) {
+        Some(Ok(records)) => Box::into_raw(Box::new(records)),
+        _ => ptr::null_mut()
+    }
+}
+
+
+// Retrieving reports
+
+/// Reports come as JSON, so every language reads them with a library it already has.
+///
+/// # Safety
+/// The report comes from `conctest_measure` and is not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn conctest_report_to_json(report: *const ReportRecords) -> *mut c_char {
+    let json = report.as_ref().and_then(|report| serde_json::to_string(report).ok());
+    match json.and_then(|json| CString::new(json).ok()) {
+        Some(json) => json.into_raw(),
+        None => ptr::null_mut()
+    }
+}
+
+/// # Safety
+/// The report comes from `conctest_measure`, or is null.
+#[no_mangle]
+pub unsafe extern "C" fn conctest_report_free(report: *mut ReportRecords) {
+    if !report.is_null() {
+        drop(Box::from_raw(report));
+    }
+}
+
+/// # Safety
+/// The string comes from `conctest_report_to_json`, or is null.
+#[no_mangle]
+pub unsafe extern "C" fn conctest_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn simulated_reports_come_as_json() {
+        unsafe {
+            let config = conctest_config_new(2, 1000000);
+            assert_eq!(conctest_config_set_backend(config, c"simulated".as_ptr()), 1);
+            conctest_config_set_seed(config, 42);
+            let report = conctest_measure(config);
+            assert!(!report.is_null());
+            let json = conctest_report_to_json(report);
+            let value: serde_json::Value = serde_json::from_str(CStr::from_ptr(json).to_str().unwrap()).unwrap();
+            assert_eq!(value["observations"].as_array().unwrap().len(), 2);
+            assert_eq!(value["tasks"].as_array().unwrap().len(), 3);
+            conctest_string_free(json);
+            conctest_report_free(report);
+            conctest_config_free(config);
+        }
+    }
+
+    #[test]
+    fn invalid_configs_give_no_report() {
+        unsafe {
+            let config = conctest_config_new(0, 1000000);
+            assert!(conctest_measure(config).is_null());
+            conctest_config_free(config);
+        }
+    }
+}
@@ -0,0 +1,166 @@
+// * * ** *** ***** ******** ************* *********************
+// Aggregating tasks of huge observations in constant memory
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+use stats::StreamingStats;
+
+
+// Aggregates
+
+/// What totals of an observation take from its tasks, folded in as tasks finish, so the
+/// tasks themselves need not be kept.
+pub struct TaskAggregate {
+    durations: StreamingStats,
+    histogram: Histogram<u64>,
+    sum_duration: TaskDuration,
+    max_duration: TaskDuration,
+    earliest_start: Option<Timestamp>,
+    latest_finish: Option<Timestamp>,
+    n_timed_out: usize,
+    n_failed: usize,
+    branch_misses: Option<u64>,
+    allocations: Option<Allocations>
+}
+
+impl TaskAggregate {
+
+    pub fn add(self: &mut Self, task: &Task) {
+        self.durations.add(task.get_duration().as_millis_f64());
+        // Durations are recorded in microseconds, percentiles come out with three significant digits
+        if let Err(error) = self.histogram.record(task.get_duration().as_duration().as_micros() as u64) {
+            warn!("A task duration of {} ms was left out of percentiles: {}", task.get_duration(), error);
+        }
+        self.sum_duration += task.get_duration();
+        self.max_duration = self.max_duration.max(task.get_duration());
+        self.earliest_start = Some(self.earliest_start.map_or(task.get_start(),
+                                                              |start| start.min(task.get_start())));
+        self.latest_finish = Some(self.latest_finish.map_or(task.get_finish(),
+                                                            |finish| finish.max(task.get_finish())));
+        self.n_timed_out += task.is_timed_out() as usize;
+        self.n_failed += task.is_failed() as usize;
+        if let Some(branch_misses) = task.get_branch_misses() {
+            self.branch_misses = Some(self.branch_misses.unwrap_or(0) + branch_misses);
+        }
+        if let Some(allocations) = task.get_allocations() {
+            let sum = self.allocations.unwrap_or_default();
+            self.allocations = Some(Allocations {allocated: sum.allocated + allocations.allocated,
+                                                 freed: sum.freed + allocations.freed});
+        }
+    }
+
+    pub fn count_tasks(self: &Self) -> usize {
+        self.durations.count()
+    }
+
+    pub fn count_timed_out_tasks(self: &Self) -> usize {
+        self.n_timed_out
+    }
+
+    pub fn count_failed_tasks(self: &Self) -> usize {
+        self.n_failed
+    }
+
+    pub fn sum_duration(self: &Self) -> TaskDuration {
+        self.sum_duration
+    }
+
+    pub fn get_max_task_duration(self: &Self) -> TaskDuration {
+        self.max_duration
+    }
+
+    pub fn get_percentile(self: &Self, percentile: f64) -> TaskDuration {
+        TaskDuration::from_nanos(self.histogram.value_at_percentile(percentile) as u128*1000)
+    }
+
+    /// In milliseconds, as calc_standard_deviation gives it.
+    pub fn get_standard_deviation(self: &Self, deviation_kind: DeviationKind) -> f64 {
+        self.durations.get_standard_deviation(deviation_kind)
+    }
+
+    pub fn get_earliest_start(self: &Self) -> Timestamp {
+        self.earliest_start.unwrap_or(Timestamp::EPOCH)
+    }
+
+    pub fn get_latest_finish(self: &Self) -> Timestamp {
+        self.latest_finish.unwrap_or(Timestamp::EPOCH)
+    }
+
+    pub fn sum_branch_misses(self: &Self) -> Option<u64> {
+        self.branch_misses
+    }
+
+    pub fn sum_allocations(self: &Self) -> Option<Allocations> {
+        self.allocations
+    }
+
+    pub fn create() -> TaskAggregate {
+        TaskAggregate {
+            durations: StreamingStats::default(),
+            histogram: create_histogram(),
+            sum_duration: TaskDuration::ZERO,
+            max_duration: TaskDuration::ZERO,
+            earliest_start: None,
+            latest_finish: None,
+            n_timed_out: 0,
+            n_failed: 0,
+            branch_misses: None,
+            allocations: None
+        }
+    }
+}
+
+
+// Choosing aggregates only
+
+static AGGREGATE_ONLY: OnceLock<bool> = OnceLock::new();
+
+pub fn set_aggregate_only(aggregate_only: bool) {
+    let _ = AGGREGATE_ONLY.set(aggregate_only);
+}
+
+/// Observations keep aggregates of their tasks then, and schedules of reports come out empty.
+pub fn is_aggregate_only() -> bool {
+    *AGGREGATE_ONLY.get().unwrap_or(&false)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::FakeWorkload;
+
+    #[test]
+    fn aggregates_give_totals_of_kept_tasks() {
+
+        let mut kept = FakeWorkload::create(2, 4, 100).observe(7);
+        kept.tasks[3].set_status(TaskStatus::TimedOut);
+        let mut aggregated = Observation::create_aggregate(kept.n_cycles);
+        for task in &kept.tasks {
+            aggregated.register_task(task.clone());
+        }
+
+        assert_eq!(aggregated.count_tasks(), 7);
+        assert_eq!(aggregated.tasks.len(), 0);
+        assert_eq!(aggregated.get_total_duration(), kept.get_total_duration());
+        assert_eq!(aggregated.get_mean_task_duration(), kept.get_mean_task_duration());
+        assert!((aggregated.get_median_task_duration().as_millis_f64() -
+                 kept.get_median_task_duration().as_millis_f64()).abs() < 0.1);
+        assert_eq!(aggregated.get_standard_deviation(), kept.get_standard_deviation());
+        assert_eq!(aggregated.count_timed_out_tasks(), 1);
+    }
+
+    #[test]
+    fn aggregates_leave_schedule_statistics_unknown() {
+
+        let mut aggregated = FakeWorkload::create(2, 4, 100).observe(4);
+        aggregated.aggregate_tasks();
+
+        assert_eq!(aggregated.count_concurrent_tasks_max(), None);
+        assert_eq!(aggregated.get_start_spread(), None);
+        assert_eq!(aggregated.get_series_idle_share(), None);
+        assert_eq!(aggregated.get_series_gaps(), None);
+        assert_eq!(format_optional_cell(aggregated.get_start_spread()), "n/a");
+    }
+}
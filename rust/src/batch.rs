@@ -0,0 +1,113 @@
+// * * ** *** ***** ******** ************* *********************
+// Iterating working sets of triplets
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+
+
+// Working sets
+
+pub const DEFAULT_TRIPLETS: usize = 1;
+
+const TRIPLET_BYTES: usize = std::mem::size_of::<Triplet>();
+
+static N_TRIPLETS: OnceLock<usize> = OnceLock::new();
+
+pub fn set_n_triplets(n_triplets: usize) {
+    let _ = N_TRIPLETS.set(n_triplets);
+}
+
+pub fn get_n_triplets() -> usize {
+    *N_TRIPLETS.get().unwrap_or(&DEFAULT_TRIPLETS)
+}
+
+pub fn format_working_set(n_triplets: usize) -> String {
+    memory::format_bytes((n_triplets*TRIPLET_BYTES) as u64)
+}
+
+
+// Iterating
+
+/// Every cycle advances all triplets of the working set, so a working set outgrowing
+/// a cache makes every cycle wait for memory. Stopping at convergence waits for all triplets.
+pub fn iterate_batch_until(initial_triplets: &[Triplet], n_cycles: usize, deadline: Option<Timestamp>,
+                           convergence_mode: ConvergenceMode) -> Option<f64> {
+
+    let mut triplets = initial_triplets.to_vec();
+    let mut converged = vec![false; triplets.len()];
+    let mut n_converged = 0usize;
+
+    for step in 0..n_cycles {
+
+        if step % DEADLINE_CHECK_PERIOD == 0 {
+            platform::check_stack();
+            if is_deadline_passed(deadline) {
+                return None;
+            }
+        }
+
+        for (triplet, converged) in triplets.iter_mut().zip(converged.iter_mut()) {
+            let next_triplet = get_next_triplet(*triplet);
+            if !*converged && is_convergent(*triplet, next_triplet) {
+                *converged = true;
+                n_converged += 1;
+            }
+            *triplet = next_triplet;
+        }
+
+        if n_converged == triplets.len() && convergence_mode == ConvergenceMode::Stop {
+            debug!(step, "All {} triplets have converged", triplets.len());
+            break;
+        }
+    }
+
+    Some(triplets.iter().map(|triplet| triplet.2).sum())
+}
+
+
+// Displaying throughput
+
+/// Triplets a task advances per second, which drops as the working set leaves caches.
+fn get_updates_per_sec(obs: &Observation, n_triplets: usize) -> f64 {
+    obs.get_cycles_per_sec()*n_triplets as f64/obs.count_tasks().max(1) as f64
+}
+
+pub fn print_working_set(report: &Report) {
+
+    let n_triplets = report.get_metadata("Triplets").map_or(DEFAULT_TRIPLETS, |value| parse_usize(value));
+
+    if n_triplets <= DEFAULT_TRIPLETS {
+        return;
+    }
+
+    if let (Some(first), Some(last)) = (report.observations.first(), report.observations.last()) {
+        info!("Working set of {} triplets ({}): {} updates/sec per task at {} tasks, {} at {} tasks",
+              format_count(n_triplets), format_working_set(n_triplets),
+              format_count(get_updates_per_sec(first, n_triplets) as usize), first.count_tasks(),
+              format_count(get_updates_per_sec(last, n_triplets) as usize), last.count_tasks());
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn single_triplets_iterate_as_the_workload_does() {
+        let triplet = (0.5, 0.25, 0.75);
+        assert_eq!(iterate_batch_until(&[triplet], 1000, None, ConvergenceMode::AllCycles),
+                   Some(iterate(triplet, 1000)));
+        let member = iterate(triplet, 1000) + iterate((0.1, 0.2, 0.3), 1000);
+        assert_eq!(iterate_batch_until(&[triplet, (0.1, 0.2, 0.3)], 1000, None, ConvergenceMode::AllCycles),
+                   Some(member));
+    }
+
+    #[test]
+    fn working_sets_are_sized_in_bytes() {
+        assert_eq!(format_working_set(1), "24 bytes");
+        assert_eq!(format_working_set(1024), "24.0 KiB");
+        assert_eq!(format_working_set(1048576), "24.0 MiB");
+    }
+}
@@ -0,0 +1,84 @@
+// * * ** *** ***** ******** ************* *********************
+// Cancelling long measurements between series
+// * * ** *** ***** ******** ************* *********************
+
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+
+// Cancellation tokens
+
+/// Clones share the state, so an embedder keeps one and cancels the measuring from elsewhere.
+/// Embedders pass a token a run, and the global one stands for Ctrl+C of the executable.
+#[derive(Clone)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>
+}
+
+impl CancellationToken {
+
+    pub fn create() -> CancellationToken {
+        CancellationToken {cancelled: Arc::new(AtomicBool::new(false))}
+    }
+
+    pub fn cancel(self: &Self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(self: &Self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+static TOKEN: OnceLock<CancellationToken> = OnceLock::new();
+
+pub fn get_cancellation_token() -> &'static CancellationToken {
+    TOKEN.get_or_init(CancellationToken::create)
+}
+
+pub fn is_cancelled() -> bool {
+    get_cancellation_token().is_cancelled()
+}
+
+
+// Cancelling on Ctrl+C
+
+#[cfg(unix)]
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// A second Ctrl+C terminates at once, in case the series in progress takes long.
+#[cfg(unix)]
+extern "C" fn handle_interrupt(_signal: libc::c_int) {
+    match TOKEN.get() {
+        Some(token) if !token.is_cancelled() => token.cancel(),
+        _ => unsafe {libc::_exit(EXIT_INTERRUPTED)}
+    }
+}
+
+#[cfg(unix)]
+pub fn cancel_on_interrupt() {
+    get_cancellation_token();
+    let handler = handle_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    unsafe {libc::signal(libc::SIGINT, handler);}
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn handle_interrupt(_ctrl_type: u32) -> windows_sys::Win32::Foundation::BOOL {
+    match TOKEN.get() {
+        Some(token) if !token.is_cancelled() => {
+            token.cancel();
+            1
+        }
+        _ => 0
+    }
+}
+
+#[cfg(windows)]
+pub fn cancel_on_interrupt() {
+    get_cancellation_token();
+    unsafe {windows_sys::Win32::System::Console::SetConsoleCtrlHandler(Some(handle_interrupt), 1);}
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn cancel_on_interrupt() {}
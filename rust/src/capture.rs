@@ -0,0 +1,162 @@
+// * * ** *** ***** ******** ************* *********************
+// Capturing raw task records in a binary format
+// * * ** *** ***** ******** ************* *********************
+
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+
+// Records
+
+/// Captures are told by this prefix, whatever their files are named. Integers that follow are
+/// of variable length, so small numbers and durations take a byte or two.
+const CAPTURE_MAGIC: &[u8; 8] = b"CONCCAP1";
+
+/// A task as measured, epoch timestamps kept, so captures read back as the reports they come from.
+#[derive(Serialize, Deserialize)]
+struct CapturedTask {
+    idx: u32,
+    series: u32,
+    start: Timestamp,
+    duration: TaskDuration,
+    status: String,
+    thread_name: String,
+    thread_id: u64,
+    seed: u64,
+    branch_misses: Option<u64>,
+    allocations: Option<(u64, u64)>
+}
+
+impl CapturedTask {
+
+    fn create(task: &Task) -> CapturedTask {
+        CapturedTask {
+            idx: task.get_idx() as u32,
+            series: task.get_series() as u32,
+            start: task.get_start(),
+            duration: task.get_duration(),
+            status: format_task_status(task),
+            thread_name: task.get_thread_name().clone(),
+            thread_id: task.get_thread_id(),
+            seed: task.get_seed(),
+            branch_misses: task.get_branch_misses(),
+            allocations: task.get_allocations().map(|allocations| (allocations.allocated, allocations.freed))
+        }
+    }
+
+    fn to_task(self: &Self) -> Task {
+        let mut task = Task::create(self.idx as usize, self.start, self.duration);
+        task.set_status(saved::parse_task_status(&self.status));
+        task.set_series(self.series as usize);
+        task.set_thread(self.thread_name.clone(), self.thread_id);
+        task.set_seed(self.seed);
+        task.set_branch_misses(self.branch_misses);
+        task.set_allocations(self.allocations.map(|(allocated, freed)| Allocations {allocated, freed}));
+        task
+    }
+}
+
+/// Totals are left out, reading a capture computes them as reading a CSV report does.
+#[derive(Serialize, Deserialize)]
+struct CapturedObservation {
+    n_cycles: usize,
+    retries: usize,
+    repeat_durations: Vec<TaskDuration>,
+    tasks: Vec<CapturedTask>
+}
+
+#[derive(Serialize, Deserialize)]
+struct Capture {
+    metadata: Vec<(String, String)>,
+    observations: Vec<CapturedObservation>
+}
+
+
+// Writing and reading captures
+
+static CAPTURE: OnceLock<bool> = OnceLock::new();
+
+pub fn set_capture(capture: bool) {
+    let _ = CAPTURE.set(capture);
+}
+
+/// Reports of profit runs are saved as captures then, other outputs keep their formats.
+pub fn get_capture() -> bool {
+    *CAPTURE.get().unwrap_or(&false)
+}
+
+pub fn format_capture(report: &Report) -> Vec<u8> {
+
+    let capture = Capture {
+        metadata: report.metadata.clone(),
+        observations: report.observations.iter()
+            .map(|obs| CapturedObservation {
+                n_cycles: obs.n_cycles,
+                retries: obs.get_retries(),
+                repeat_durations: obs.get_repeat_durations().clone(),
+                tasks: obs.tasks.iter().map(CapturedTask::create).collect()
+            })
+            .collect()
+    };
+
+    let mut bytes = CAPTURE_MAGIC.to_vec();
+    bytes.extend(bincode::DefaultOptions::new().serialize(&capture).unwrap());
+    bytes
+}
+
+pub fn is_capture(bytes: &[u8]) -> bool {
+    bytes.starts_with(CAPTURE_MAGIC)
+}
+
+pub fn parse_capture(bytes: &[u8]) -> Result<Report, String> {
+
+    let capture: Capture = bincode::DefaultOptions::new().deserialize(&bytes[CAPTURE_MAGIC.len()..])
+        .map_err(|e| format!("Error while parsing a capture: {}", e))?;
+
+    let mut report = Report::create(capture.observations.len());
+    for (key, value) in &capture.metadata {
+        report.set_metadata(key, value);
+    }
+
+    for captured in capture.observations {
+        let mut obs = Observation::create(captured.tasks.len(), captured.n_cycles);
+        for captured_task in &captured.tasks {
+            if captured_task.idx as usize >= obs.count_tasks() {
+                return Err("Error while parsing a capture: a task out of its observation".to_string());
+            }
+            obs.register_task(captured_task.to_task());
+        }
+        obs.set_retries(captured.retries);
+        obs.set_repeat_durations(captured.repeat_durations);
+        report.register_observation(obs);
+    }
+
+    Ok(report)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::FakeWorkload;
+
+    #[test]
+    fn captures_read_back_as_reports() {
+        let mut report = FakeWorkload::create(2, 4, 100).create_report(1..=6);
+        report.observations[2].tasks[1].set_allocations(Some(Allocations {allocated: 4096, freed: 1024}));
+        let bytes = format_capture(&report);
+        assert!(is_capture(&bytes));
+        assert!(bytes.len() < format_report(&report).len());
+        assert_eq!(format_report(&parse_capture(&bytes).unwrap()), format_report(&report));
+    }
+
+    #[test]
+    fn broken_captures_are_rejected() {
+        let bytes = format_capture(&FakeWorkload::create(2, 4, 100).create_report(1..=2));
+        assert!(parse_capture(&bytes[..bytes.len() - 1]).is_err());
+        assert!(!is_capture(b"Parameter,Value\n"));
+    }
+}
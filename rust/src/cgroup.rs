@@ -0,0 +1,130 @@
+// * * ** *** ***** ******** ************* *********************
+// Finding CPUs left to a container by its control groups
+// * * ** *** ***** ******** ************* *********************
+
+use std::fs;
+
+
+// Locating control groups of the process
+
+const CGROUP_MEMBERSHIP_PATH: &str = "/proc/self/cgroup";
+const CGROUP_ROOT_PATH: &str = "/sys/fs/cgroup";
+const ONLINE_CPUS_PATH: &str = "/sys/devices/system/cpu/online";
+
+/// A container often mounts its own control group at the root, so the root is tried as well.
+fn find_cgroup_dirs(controller: &str) -> Vec<String> {
+
+    let mut dirs: Vec<String> = Vec::new();
+    let membership = fs::read_to_string(CGROUP_MEMBERSHIP_PATH).unwrap_or_default();
+
+    // Lines look like "4:cpu,cpuacct:/docker/1f2e" in version 1 and "0::/user.slice" in version 2
+    for line in membership.lines() {
+        let fields: Vec<&str> = line.splitn(3, ':').collect();
+        if fields.len() < 3 {
+            continue;
+        }
+        let (controllers, path) = (fields[1], fields[2].trim_end_matches('/'));
+        if controllers.is_empty() {
+            dirs.push(format!("{}{}", CGROUP_ROOT_PATH, path));
+            dirs.push(CGROUP_ROOT_PATH.to_string());
+        } else if controllers.split(',').any(|name| name == controller) {
+            for mount in [controllers, controller] {
+                dirs.push(format!("{}/{}{}", CGROUP_ROOT_PATH, mount, path));
+                dirs.push(format!("{}/{}", CGROUP_ROOT_PATH, mount));
+            }
+        }
+    }
+
+    dirs
+}
+
+fn read_cgroup_file(controller: &str, file_names: &[&str]) -> Option<(String, String)> {
+    find_cgroup_dirs(controller).iter()
+        .flat_map(|dir| file_names.iter().map(move |file_name| (dir, *file_name)))
+        .find_map(|(dir, file_name)| {
+            fs::read_to_string(format!("{}/{}", dir, file_name)).ok()
+                .map(|text| (file_name.to_string(), text.trim().to_string()))
+        })
+}
+
+
+// Reading limits of CPUs
+
+/// Counts CPUs in a list like "0-3,6,8-9".
+fn count_cpu_list(list: &str) -> Option<usize> {
+
+    let mut n_cpus = 0usize;
+
+    for range in list.split(',').filter(|range| !range.is_empty()) {
+        n_cpus += match range.split_once('-') {
+            Some((first, last)) =>
+                last.parse::<usize>().ok()?.checked_sub(first.parse::<usize>().ok()?)? + 1,
+            None => {
+                range.parse::<usize>().ok()?;
+                1
+            }
+        };
+    }
+
+    if n_cpus > 0 {Some(n_cpus)} else {None}
+}
+
+/// CPU time the group may spend per period, in CPUs, or nothing if unlimited.
+fn read_cpu_quota() -> Option<f64> {
+
+    let (file_name, text) = read_cgroup_file("cpu", &["cpu.max", "cpu.cfs_quota_us"])?;
+
+    let (quota, period) = if file_name == "cpu.max" {
+        let (quota, period) = text.split_once(' ')?;
+        (quota.parse::<f64>().ok()?, period.parse::<f64>().ok()?)
+    } else {
+        let (_, period) = read_cgroup_file("cpu", &["cpu.cfs_period_us"])?;
+        (text.parse::<f64>().ok()?, period.parse::<f64>().ok()?)
+    };
+
+    // An unlimited quota reads "max" in version 2, which fails to parse, and -1 in version 1
+    if quota > 0.0 && period > 0.0 {Some(quota/period)} else {None}
+}
+
+fn read_cpuset() -> Option<usize> {
+    let (_, text) = read_cgroup_file("cpuset", &["cpuset.cpus.effective", "cpuset.cpus"])?;
+    count_cpu_list(&text)
+}
+
+pub struct CpuLimits {
+    pub host: usize,
+    pub quota: Option<f64>,
+    pub cpuset: Option<usize>
+}
+
+impl CpuLimits {
+
+    pub fn read(fallback_host: usize) -> CpuLimits {
+        CpuLimits {
+            host: fs::read_to_string(ONLINE_CPUS_PATH).ok()
+                      .and_then(|text| count_cpu_list(text.trim()))
+                      .unwrap_or(fallback_host),
+            quota: read_cpu_quota(),
+            cpuset: read_cpuset()
+        }
+    }
+
+    /// A fractional quota still lets a thread run on one more CPU part of the time.
+    pub fn count_effective(self: &Self) -> usize {
+
+        let mut n_cpus = self.host;
+
+        if let Some(quota) = self.quota {
+            n_cpus = n_cpus.min(quota.ceil() as usize);
+        }
+        if let Some(cpuset) = self.cpuset {
+            n_cpus = n_cpus.min(cpuset);
+        }
+
+        n_cpus.max(1)
+    }
+
+    pub fn is_limited(self: &Self) -> bool {
+        self.count_effective() < self.host
+    }
+}
@@ -0,0 +1,158 @@
+// * * ** *** ***** ******** ************* *********************
+// Choosing a timing source for task durations
+// * * ** *** ***** ******** ************* *********************
+
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use super::duration::Timestamp;
+
+
+// Timing sources
+
+/// The monotonic clock reads QueryPerformanceCounter on Windows and CLOCK_MONOTONIC elsewhere.
+#[derive(Copy, Clone, PartialEq)]
+pub enum ClockSource {
+    Monotonic,
+    Tsc
+}
+
+impl ClockSource {
+
+    pub fn parse(s: &str) -> Option<ClockSource> {
+        match s {
+            "" | "monotonic" => Some(ClockSource::Monotonic),
+            "tsc" => Some(ClockSource::Tsc),
+            _ => None
+        }
+    }
+
+    pub fn get_name(self: &Self) -> &'static str {
+        match self {
+            ClockSource::Monotonic => "monotonic",
+            ClockSource::Tsc => "tsc"
+        }
+    }
+}
+
+static CLOCK_SOURCE: OnceLock<ClockSource> = OnceLock::new();
+
+/// The TSC is only taken where it ticks at a constant rate regardless of power states.
+pub fn set_clock_source(clock_source: ClockSource) -> ClockSource {
+    let clock_source = if clock_source == ClockSource::Tsc && !is_invariant_tsc() {
+        ClockSource::Monotonic
+    } else {
+        clock_source
+    };
+    *CLOCK_SOURCE.get_or_init(|| clock_source)
+}
+
+pub fn get_clock_source() -> ClockSource {
+    *CLOCK_SOURCE.get().unwrap_or(&ClockSource::Monotonic)
+}
+
+
+// Reading the time of observations
+
+/// Observations take their start from a clock passed in, so they run on real or virtual time.
+pub trait Clock: Sync {
+    fn now(self: &Self) -> Timestamp;
+}
+
+
+// Reading the time stamp counter
+
+#[cfg(target_arch = "x86_64")]
+#[allow(unused_unsafe)] // CPUID is only safe to call since Rust 1.87
+pub fn is_invariant_tsc() -> bool {
+    use std::arch::x86_64::__cpuid;
+
+    // CPUID leaf 0x80000007 reports the invariant TSC in bit 8 of EDX
+    let max_extended_leaf = unsafe {__cpuid(0x80000000)}.eax;
+    max_extended_leaf >= 0x80000007 && unsafe {__cpuid(0x80000007)}.edx & (1 << 8) != 0
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub fn is_invariant_tsc() -> bool {
+    false
+}
+
+#[cfg(target_arch = "x86_64")]
+fn read_tsc() -> u64 {
+    unsafe {std::arch::x86_64::_rdtsc()}
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn read_tsc() -> u64 {
+    0
+}
+
+const TSC_CALIBRATION_PERIOD: Duration = Duration::from_millis(50);
+
+static TSC_TICKS_PER_NANO: OnceLock<f64> = OnceLock::new();
+
+fn get_tsc_ticks_per_nano() -> f64 {
+    *TSC_TICKS_PER_NANO.get_or_init(|| {
+        let (watch, start) = (Instant::now(), read_tsc());
+        while watch.elapsed() < TSC_CALIBRATION_PERIOD {}
+        (read_tsc() - start) as f64/watch.elapsed().as_nanos() as f64
+    })
+}
+
+
+// Measuring elapsed time
+
+#[derive(Copy, Clone)]
+pub enum Stopwatch {
+    Monotonic(Instant),
+    Tsc(u64)
+}
+
+impl Stopwatch {
+
+    pub fn start() -> Stopwatch {
+        Self::start_with(get_clock_source())
+    }
+
+    fn start_with(clock_source: ClockSource) -> Stopwatch {
+        match clock_source {
+            ClockSource::Monotonic => Stopwatch::Monotonic(Instant::now()),
+            ClockSource::Tsc => {
+                get_tsc_ticks_per_nano();
+                Stopwatch::Tsc(read_tsc())
+            }
+        }
+    }
+
+    pub fn elapsed_nanos(self: &Self) -> u128 {
+        match self {
+            Stopwatch::Monotonic(instant) => instant.elapsed().as_nanos(),
+            Stopwatch::Tsc(start) =>
+                (read_tsc().saturating_sub(*start) as f64/get_tsc_ticks_per_nano()) as u128
+        }
+    }
+}
+
+const RESOLUTION_SAMPLES: usize = 10000;
+
+/// The smallest step between consecutive reads in nanoseconds, unavailable sources give nothing.
+pub fn measure_resolution(clock_source: ClockSource) -> Option<u128> {
+
+    if clock_source == ClockSource::Tsc && !is_invariant_tsc() {
+        return None;
+    }
+
+    let watch = Stopwatch::start_with(clock_source);
+    let mut previous = watch.elapsed_nanos();
+    let mut resolution = u128::MAX;
+
+    for _ in 0..RESOLUTION_SAMPLES {
+        let current = watch.elapsed_nanos();
+        if current > previous {
+            resolution = resolution.min(current - previous);
+        }
+        previous = current;
+    }
+
+    if resolution < u128::MAX {Some(resolution)} else {None}
+}
@@ -0,0 +1,176 @@
+// * * ** *** ***** ******** ************* *********************
+// Comparing two runs with repeated observations
+// * * ** *** ***** ******** ************* *********************
+
+use std::cmp::Ordering;
+
+use super::*;
+
+
+// Testing differences per number of tasks
+
+struct Difference {
+    n_tasks: usize,
+    baseline_mean: f64,
+    candidate_mean: f64,
+    p_value: Option<f64>
+}
+
+impl Difference {
+
+    fn get_change(self: &Self) -> f64 {
+        if self.baseline_mean > 0.0 {
+            self.candidate_mean/self.baseline_mean - 1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn is_significant(self: &Self) -> bool {
+        self.p_value.is_some_and(stats::is_significant)
+    }
+
+    fn is_regression(self: &Self) -> bool {
+        self.is_significant() && self.get_change() > 0.0
+    }
+}
+
+fn get_repeat_durations(obs: &Observation) -> Vec<f64> {
+    if obs.get_repeat_durations().is_empty() {
+        vec![obs.get_total_duration().as_millis_f64()]
+    } else {
+        obs.get_repeat_durations().iter().map(|d| d.as_millis_f64()).collect()
+    }
+}
+
+/// Only numbers of tasks observed in both runs are compared.
+fn find_differences(baseline: &Report, candidate: &Report) -> Vec<Difference> {
+
+    baseline.observations.iter()
+        .filter_map(|baseline_obs| {
+            let n_tasks = baseline_obs.count_tasks();
+            candidate.find_observation(n_tasks).map(|candidate_obs| {
+                let baseline_durations = get_repeat_durations(baseline_obs);
+                let candidate_durations = get_repeat_durations(candidate_obs);
+                Difference {
+                    n_tasks,
+                    baseline_mean: baseline_durations.iter().sum::<f64>()/
+                                   baseline_durations.len() as f64,
+                    candidate_mean: candidate_durations.iter().sum::<f64>()/
+                                    candidate_durations.len() as f64,
+                    p_value: stats::welch_t_test(&baseline_durations, &candidate_durations)
+                }
+            })
+        })
+        .collect()
+}
+
+
+// Reporting differences
+
+fn create_comparison_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Tasks", 5, Align::Right, 0),
+        Column::create("Baseline duration", 17, Align::Right, 0),
+        Column::create("Candidate duration", 18, Align::Right, 0),
+        Column::create("Change", 7, Align::Right, 0),
+        Column::create("p-value", 7, Align::Right, 1),
+        Column::create("Significant", 11, Align::Left, 0)
+    ], colored)
+}
+
+fn print_difference(table: &ConsoleTable, difference: &Difference) {
+
+    let change = difference.get_change();
+    let (p_value, significant) = match difference.p_value {
+        Some(p_value) => (format!("{:.3}", p_value),
+                          if difference.is_significant() {"yes"} else {"no"}),
+        None => ("".to_string(), "no repeats")
+    };
+
+    table.print_row(&vec![
+        Cell::Plain(difference.n_tasks.to_string()),
+        Cell::Plain(format!("{:.1}", difference.baseline_mean)),
+        Cell::Plain(format!("{:.1}", difference.candidate_mean)),
+        if difference.is_significant() {
+            Cell::Signed(format_percent_cell(change), -change)
+        } else {
+            Cell::Plain(format_percent_cell(change))
+        },
+        Cell::Plain(p_value),
+        Cell::Plain(significant.to_string())
+    ]);
+}
+
+
+// Running a comparison
+
+/// Rows are ordered by the candidate, as it is the run in question.
+fn sort_differences(differences: &mut [Difference], candidate: &Report, query: &ReportQuery) {
+    differences.sort_by(|a, b| match (candidate.find_observation(a.n_tasks), 
+                                      candidate.find_observation(b.n_tasks)) {
+        (Some(a), Some(b)) => query.compare_observations(a, b),
+        _ => Ordering::Equal
+    });
+}
+
+/// Prints differences of a candidate from a baseline, telling whether it regressed.
+fn compare_runs(baseline: &Report, candidate: &Report, query: &ReportQuery, colored: bool) -> bool {
+
+    let mut differences: Vec<Difference> = find_differences(baseline, candidate).into_iter()
+        .filter(|difference| query.is_selected(difference.n_tasks))
+        .collect();
+    sort_differences(&mut differences, candidate, query);
+
+    if !query.only_totals {
+        let table = create_comparison_table(colored);
+        table.print_header();
+        for difference in &differences {
+            print_difference(&table, difference);
+        }
+        table.print_footer();
+    }
+
+    let n_faster = differences.iter().filter(|d| d.is_significant() && !d.is_regression()).count();
+    let n_slower = differences.iter().filter(|d| d.is_regression()).count();
+    info!("Significantly faster: {}, slower: {}, of {} compared",
+          n_faster, n_slower, differences.len());
+
+    n_slower > 0
+}
+
+/// Runs of merged files are compared to the first run, each of them after another.
+/// Fails with the regression code if any number of tasks got significantly slower.
+pub fn run_comparison(args: &Args) -> i32 {
+
+    let mut runs = merge::load_runs(&args.get_baseline_file_path());
+    if args.get_candidate_file_path() != "" {
+        runs.extend(merge::load_runs(&args.get_candidate_file_path()));
+    }
+
+    if runs.len() < 2 {
+        return reject_args(args.get_command());
+    }
+
+    let (baseline_label, baseline) = &runs[0];
+    let mut regressed: Vec<&str> = Vec::new();
+
+    for (label, candidate) in &runs[1..] {
+        if runs.len() > 2 {
+            println!("{} vs. {}", label, baseline_label);
+        }
+        if compare_runs(baseline, candidate, &args.get_report_query(), args.get_colored()) {
+            regressed.push(label);
+        }
+    }
+
+    if runs.len() == 2 && !regressed.is_empty() {
+        warn!("The candidate run regressed");
+        EXIT_REGRESSION
+    } else if !regressed.is_empty() {
+        warn!("Runs {} regressed", regressed.join(", "));
+        EXIT_REGRESSION
+    } else {
+        EXIT_SUCCESS
+    }
+}
@@ -0,0 +1,195 @@
+// * * ** *** ***** ******** ************* *********************
+// Generating shell completion scripts
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+
+
+// Describing what can be completed
+
+const FILE_OPTIONS: &[&str] = &[OPT_CHART, OPT_HISTORY, OPT_RESUME, OPT_OUTPUT];
+
+fn list_option_choices(option: &str) -> Vec<&'static str> {
+    match option {
+        OPT_NUMBER_STYLE => vec!["plain", "grouped"],
+        OPT_PRESET => vec!["quick", "thorough", "oversubscribe"],
+        OPT_ARRIVALS => vec!["constant", "poisson"],
+        OPT_STD_DEV => vec!["sample", "population"],
+        OPT_CLOCK => vec!["monotonic", "tsc"],
+        OPT_QOS => platform::QOS_CLASS_NAMES.to_vec(),
+        OPT_SCHED => platform::SCHED_POLICY_NAMES.to_vec(),
+        OPT_PLACEMENT => placement::PLACEMENT_NAMES.to_vec(),
+        OPT_COMPRESS => compression::COMPRESSION_NAMES.to_vec(),
+        OPT_METRICS => metrics::METRIC_NAMES.to_vec(),
+        OPT_FORMAT => render::RENDER_FORMATS.to_vec(),
+        OPT_SORT_BY => query::SORT_KEYS.to_vec(),
+        OPT_SEPARATORS => vec!["none"],
+        OPT_DAG => dag::DAG_SHAPES.to_vec(),
+        OPT_RNG => rng::RNG_NAMES.to_vec(),
+        OPT_WORKLOAD => registry::WORKLOADS.iter().map(|workload| workload.name).collect(),
+        OPT_BUFFER_DATA => workload::BUFFER_DATA.to_vec(),
+        OPT_BACKEND => registry::BACKENDS.iter().map(|backend| backend.name).collect(),
+        _ => Vec::new()
+    }
+}
+
+fn list_free_value_options() -> Vec<&'static str> {
+    OPTIONS_WITH_VALUES.iter()
+        .filter(|option| !FILE_OPTIONS.contains(option) && list_option_choices(option).is_empty())
+        .copied()
+        .collect()
+}
+
+fn list_choice_options() -> Vec<&'static str> {
+    OPTIONS.iter()
+        .filter(|option| !list_option_choices(option).is_empty())
+        .copied()
+        .collect()
+}
+
+
+// Writing scripts for each shell
+
+fn format_bash_script() -> String {
+
+    let mut cases: String = "".to_string();
+
+    for option in list_choice_options() {
+        cases += &format!("        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return;;\n",
+                          option, list_option_choices(option).join(" "));
+    }
+    cases += &format!("        {}) COMPREPLY=($(compgen -f -- \"$cur\")); return;;\n",
+                      FILE_OPTIONS.join("|"));
+    cases += &format!("        {}) return;;\n", list_free_value_options().join("|"));
+
+    format!("_{program}() {{\n\
+             \x20   local cur=\"${{COMP_WORDS[COMP_CWORD]}}\"\n\
+             \x20   local prev=\"${{COMP_WORDS[COMP_CWORD-1]}}\"\n\
+             \x20   case \"$prev\" in\n\
+             {cases}\
+             \x20   esac\n\
+             \x20   if [ \"$COMP_CWORD\" -eq 1 ]; then\n\
+             \x20       COMPREPLY=($(compgen -W \"{commands}\" -- \"$cur\"))\n\
+             \x20   elif [[ \"$cur\" == -* ]]; then\n\
+             \x20       COMPREPLY=($(compgen -W \"{options}\" -- \"$cur\"))\n\
+             \x20   else\n\
+             \x20       COMPREPLY=($(compgen -f -- \"$cur\"))\n\
+             \x20   fi\n\
+             }}\n\
+             complete -F _{program} {program}\n",
+            program = PROGRAM_NAME,
+            cases = cases,
+            commands = list_commands().join(" "),
+            options = OPTIONS.join(" "))
+}
+
+fn format_zsh_script() -> String {
+
+    let mut cases: String = "".to_string();
+
+    for option in list_choice_options() {
+        cases += &format!("        {}) compadd {}; return;;\n",
+                          option, list_option_choices(option).join(" "));
+    }
+    cases += &format!("        {}) _files; return;;\n", FILE_OPTIONS.join("|"));
+    cases += &format!("        {}) return;;\n", list_free_value_options().join("|"));
+
+    format!("#compdef {program}\n\n\
+             _{program}() {{\n\
+             \x20   case \"$words[CURRENT-1]\" in\n\
+             {cases}\
+             \x20   esac\n\
+             \x20   if (( CURRENT == 2 )); then\n\
+             \x20       compadd {commands}\n\
+             \x20   elif [[ \"$PREFIX\" == -* ]]; then\n\
+             \x20       compadd -- {options}\n\
+             \x20   else\n\
+             \x20       _files\n\
+             \x20   fi\n\
+             }}\n\n\
+             compdef _{program} {program}\n",
+            program = PROGRAM_NAME,
+            cases = cases,
+            commands = list_commands().join(" "),
+            options = OPTIONS.join(" "))
+}
+
+fn format_fish_option(option: &str) -> String {
+    match option.strip_prefix("--") {
+        Some(name) => format!("-l {}", name),
+        None => format!("-o {}", option.trim_start_matches('-'))
+    }
+}
+
+fn format_fish_script() -> String {
+
+    let mut script = format!("complete -c {} -f -n __fish_use_subcommand -a \"{}\"\n",
+                             PROGRAM_NAME, list_commands().join(" "));
+
+    for option in OPTIONS {
+        let choices = list_option_choices(option);
+        let value_spec = if !choices.is_empty() {
+            format!(" -x -a \"{}\"", choices.join(" "))
+        } else if FILE_OPTIONS.contains(option) {
+            " -r -F".to_string()
+        } else if OPTIONS_WITH_VALUES.contains(option) {
+            " -x".to_string()
+        } else {
+            "".to_string()
+        };
+        script += &format!("complete -c {} {}{}\n",
+                           PROGRAM_NAME, format_fish_option(option), value_spec);
+    }
+
+    script
+}
+
+fn format_powershell_list(items: &[&str]) -> String {
+    items.iter().map(|item| format!("'{}'", item)).collect::<Vec<String>>().join(", ")
+}
+
+fn format_powershell_script() -> String {
+
+    let mut cases: String = "".to_string();
+
+    for option in list_choice_options() {
+        cases += &format!("        '{}' {{ @({}) }}\n",
+                          option, format_powershell_list(&list_option_choices(option)));
+    }
+
+    format!("Register-ArgumentCompleter -Native -CommandName {program} -ScriptBlock {{\n\
+             \x20   param($wordToComplete, $commandAst, $cursorPosition)\n\
+             \x20   $words = @($commandAst.CommandElements | ForEach-Object {{ $_.ToString() }})\n\
+             \x20   $previous = if ($wordToComplete) {{ $words[-2] }} else {{ $words[-1] }}\n\
+             \x20   $candidates = switch ($previous) {{\n\
+             {cases}\
+             \x20       default {{\n\
+             \x20           if ($words.Count -le 2 -and $previous -eq '{program}') {{ @({commands}) }}\n\
+             \x20           elseif ($wordToComplete.StartsWith('-')) {{ @({options}) }}\n\
+             \x20           else {{ @() }}\n\
+             \x20       }}\n\
+             \x20   }}\n\
+             \x20   $candidates | Where-Object {{ $_ -like \"$wordToComplete*\" }} | ForEach-Object {{\n\
+             \x20       [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)\n\
+             \x20   }}\n\
+             }}\n",
+            program = PROGRAM_NAME,
+            cases = cases,
+            commands = format_powershell_list(&list_commands()),
+            options = format_powershell_list(OPTIONS))
+}
+
+pub fn print_completions(shell: &String) -> bool {
+
+    let script = match &**shell {
+        "bash" => format_bash_script(),
+        "zsh" => format_zsh_script(),
+        "fish" => format_fish_script(),
+        "powershell" => format_powershell_script(),
+        _ => return false
+    };
+
+    print!("{}", script);
+
+    true
+}
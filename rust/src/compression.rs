@@ -0,0 +1,144 @@
+// * * ** *** ***** ******** ************* *********************
+// Compressing saved reports
+// * * ** *** ***** ******** ************* *********************
+
+use std::fs;
+use std::io::Read;
+
+use flate2::Compression as GzipLevel;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use ruzstd::decoding::StreamingDecoder;
+use ruzstd::encoding::CompressionLevel;
+
+use super::*;
+
+
+// Compressions
+
+pub const COMPRESSION_NAMES: [&str; 2] = ["gz", "zstd"];
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Compression {
+    Off,
+    Gzip,
+    Zstd
+}
+
+impl Compression {
+
+    pub fn parse(s: &str) -> Option<Compression> {
+        match s {
+            "" => Some(Compression::Off),
+            "gz" => Some(Compression::Gzip),
+            "zstd" => Some(Compression::Zstd),
+            _ => None
+        }
+    }
+
+    fn get_extension(self: &Self) -> &'static str {
+        match self {
+            Compression::Off => "",
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst"
+        }
+    }
+
+    /// Paths already ending with the extension are kept as they are.
+    pub fn append_extension(self: &Self, file_path: &str) -> String {
+        if file_path.ends_with(self.get_extension()) {
+            file_path.to_string()
+        } else {
+            file_path.to_string() + self.get_extension()
+        }
+    }
+
+    pub fn compress(self: &Self, bytes: &[u8]) -> Vec<u8> {
+        match self {
+            Compression::Off => bytes.to_vec(),
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), GzipLevel::default());
+                encoder.write_all(bytes).unwrap();
+                encoder.finish().unwrap()
+            }
+            Compression::Zstd => ruzstd::encoding::compress_to_vec(bytes, CompressionLevel::Fastest)
+        }
+    }
+}
+
+static COMPRESSION: OnceLock<Compression> = OnceLock::new();
+
+pub fn set_compression(compression: Compression) {
+    let _ = COMPRESSION.set(compression);
+}
+
+/// Reports and merged runs are written through it, chart scripts and renderings never are.
+pub fn get_compression() -> Compression {
+    *COMPRESSION.get().unwrap_or(&Compression::Off)
+}
+
+
+// Reading compressed files
+
+/// Compressed files are told by their magic bytes, not by extensions, which may be renamed away.
+fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+
+    let mut decompressed: Vec<u8> = Vec::new();
+
+    let result = if bytes.starts_with(&GZIP_MAGIC) {
+        GzDecoder::new(bytes.as_slice()).read_to_end(&mut decompressed).map_err(|e| e.to_string())
+    } else if bytes.starts_with(&ZSTD_MAGIC) {
+        StreamingDecoder::new(bytes.as_slice())
+            .map_err(|e| e.to_string())
+            .and_then(|mut decoder| decoder.read_to_end(&mut decompressed).map_err(|e| e.to_string()))
+    } else {
+        return Ok(bytes);
+    };
+
+    result.map(|_| decompressed)
+}
+
+/// Labels of runs come from file names, which compression should not change.
+pub fn strip_extension(file_path: &str) -> &str {
+    [Compression::Gzip, Compression::Zstd].iter()
+        .find_map(|compression| file_path.strip_suffix(compression.get_extension()))
+        .unwrap_or(file_path)
+}
+
+/// Reads a file, compressed or not, a report or a binary capture, told apart by their callers.
+pub fn read_bytes(file_path: &str) -> Result<Vec<u8>, String> {
+    fs::read(Path::new(file_path))
+        .map_err(|e| e.to_string())
+        .and_then(decompress)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn compressed_texts_read_back() {
+        let text = "Tasks,Task,Started\n".repeat(100);
+        for name in COMPRESSION_NAMES {
+            let compression = Compression::parse(name).unwrap();
+            let bytes = compression.compress(text.as_bytes());
+            assert!(bytes.len() < text.len());
+            assert_eq!(decompress(bytes), Ok(text.clone().into_bytes()));
+        }
+        assert_eq!(decompress(Compression::Off.compress(text.as_bytes())), Ok(text.into_bytes()));
+    }
+
+    #[test]
+    fn extensions_are_appended_once() {
+        assert_eq!(Compression::Gzip.append_extension("report.csv"), "report.csv.gz");
+        assert_eq!(Compression::Zstd.append_extension("report.csv.zst"), "report.csv.zst");
+        assert_eq!(Compression::Off.append_extension("report.csv"), "report.csv");
+        assert_eq!(Compression::parse("bz2"), None);
+        assert_eq!(strip_extension("runs/baseline.csv.zst"), "runs/baseline.csv");
+    }
+}
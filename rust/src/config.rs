@@ -0,0 +1,171 @@
+// * * ** *** ***** ******** ************* *********************
+// Configuring runs of observations
+// * * ** *** ***** ******** ************* *********************
+
+use std::ops::RangeInclusive;
+
+use cancel::CancellationToken;
+
+use super::*;
+
+
+// Run configurations
+
+/// Everything the measurement engine needs, built the same way by the CLI and by embedders:
+/// `RunConfig::new().tasks(1..=32).cycles(10_000_000).backend("threads").repeats(5)`
+#[derive(Clone)]
+pub struct RunConfig {
+    tasks: RangeInclusive<usize>,
+    n_cycles: usize,
+    series_size: usize,
+    backend: String,
+    n_processes: usize,
+    dag: String,
+    repeats: usize,
+    timeouts: Timeouts,
+    retries_max: usize,
+    seed: u64,
+    cancellation: CancellationToken
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunConfig {
+
+    pub fn new() -> RunConfig {
+        RunConfig {
+            tasks: 1..=1,
+            n_cycles: 0,
+            series_size: 1,
+            backend: DEFAULT_BACKEND.to_string(),
+            n_processes: fanout::DEFAULT_PROCESSES,
+            dag: "".to_string(),
+            repeats: 1,
+            timeouts: Timeouts::create(TaskDuration::ZERO, TaskDuration::ZERO),
+            retries_max: 0,
+            seed: 0,
+            cancellation: CancellationToken::create()
+        }
+    }
+
+    pub fn tasks(mut self: Self, tasks: RangeInclusive<usize>) -> Self {
+        self.tasks = tasks;
+        self
+    }
+
+    pub fn cycles(mut self: Self, n_cycles: usize) -> Self {
+        self.n_cycles = n_cycles;
+        self
+    }
+
+    pub fn series_size(mut self: Self, series_size: usize) -> Self {
+        self.series_size = series_size;
+        self
+    }
+
+    pub fn backend(mut self: Self, backend: &str) -> Self {
+        self.backend = backend.to_string();
+        self
+    }
+
+    /// Worker processes sharing the tasks of observations, with the processes backend.
+    pub fn processes(mut self: Self, n_processes: usize) -> Self {
+        self.n_processes = n_processes;
+        self
+    }
+
+    /// Tasks depending on each other as a chain, a tree, or a random DAG, instead of independent ones.
+    pub fn dag(mut self: Self, dag: &str) -> Self {
+        self.dag = dag.to_string();
+        self
+    }
+
+    pub fn repeats(mut self: Self, repeats: usize) -> Self {
+        self.repeats = repeats;
+        self
+    }
+
+    pub(crate) fn timeouts(mut self: Self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    pub fn retries(mut self: Self, retries_max: usize) -> Self {
+        self.retries_max = retries_max;
+        self
+    }
+
+    pub fn seed(mut self: Self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Cancelling the token stops the run after the observation in progress, runs of other
+    /// configurations go on.
+    pub fn cancellation(mut self: Self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    pub fn get_tasks(self: &Self) -> RangeInclusive<usize> {
+        self.tasks.clone()
+    }
+
+    pub fn get_tasks_max(self: &Self) -> usize {
+        *self.tasks.end()
+    }
+
+    pub fn get_n_cycles(self: &Self) -> usize {
+        self.n_cycles
+    }
+
+    pub fn get_series_size(self: &Self) -> usize {
+        self.series_size
+    }
+
+    pub fn get_backend(self: &Self) -> &String {
+        &self.backend
+    }
+
+    pub fn get_n_processes(self: &Self) -> usize {
+        self.n_processes
+    }
+
+    pub fn get_dag(self: &Self) -> &String {
+        &self.dag
+    }
+
+    pub fn get_repeats(self: &Self) -> usize {
+        self.repeats
+    }
+
+    pub(crate) fn get_timeouts(self: &Self) -> Timeouts {
+        self.timeouts
+    }
+
+    pub fn get_retries_max(self: &Self) -> usize {
+        self.retries_max
+    }
+
+    pub fn get_seed(self: &Self) -> u64 {
+        self.seed
+    }
+
+    /// Ctrl+C cancels every run, the token of the configuration only this one.
+    pub fn is_cancelled(self: &Self) -> bool {
+        cancel::is_cancelled() || self.cancellation.is_cancelled()
+    }
+
+    /// Only registered backends run tasks, and every observation needs work and workers.
+    /// Dependencies are only waited for by threads of a process.
+    pub fn is_valid(self: &Self) -> bool {
+        !self.tasks.is_empty() && *self.tasks.start() > 0 && self.n_cycles > 0 &&
+        self.series_size > 0 && self.n_processes > 0 && self.repeats > 0 &&
+        registry::BACKENDS.iter().any(|backend| backend.name == self.get_backend().as_str()) &&
+        (self.dag.is_empty() || (DagShape::parse(&self.dag).is_some() && self.backend == DEFAULT_BACKEND))
+    }
+}
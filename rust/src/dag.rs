@@ -0,0 +1,319 @@
+// * * ** *** ***** ******** ************* *********************
+// Running tasks depending on each other
+// * * ** *** ***** ******** ************* *********************
+
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+
+use super::*;
+
+
+// Shapes of dependencies
+
+pub const DAG_SHAPES: &[&str] = &["chain", "tree", "random"];
+
+/// Most parents a task of a random DAG waits for.
+const RANDOM_PARENTS_MAX: usize = 2;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DagShape {
+    Chain,
+    Tree,
+    Random
+}
+
+impl DagShape {
+
+    pub fn parse(s: &str) -> Option<DagShape> {
+        match s {
+            "chain" => Some(DagShape::Chain),
+            "tree" => Some(DagShape::Tree),
+            "random" => Some(DagShape::Random),
+            _ => None
+        }
+    }
+}
+
+/// Tasks depend on tasks of smaller indices only, so the order of indices is a topological one.
+pub struct Dag {
+    parents: Vec<Vec<usize>>
+}
+
+impl Dag {
+
+    /// A chain runs tasks one after another, a binary tree fans out from its root,
+    /// and a random DAG, the same for a seed, gives every task up to two earlier parents.
+    pub fn create(shape: DagShape, n_tasks: usize, seed: u64) -> Dag {
+
+        let mut rng = StdRng::seed_from_u64(derive_task_seed(seed, n_tasks, n_tasks));
+
+        let parents = (0..n_tasks)
+            .map(|task_idx| match (shape, task_idx) {
+                (_, 0) => Vec::new(),
+                (DagShape::Chain, _) => vec![task_idx - 1],
+                (DagShape::Tree, _) => vec![(task_idx - 1)/2],
+                (DagShape::Random, _) => {
+                    let mut parents: Vec<usize> = (0..rng.gen_range(1..=RANDOM_PARENTS_MAX))
+                        .map(|_| rng.gen_range(0..task_idx))
+                        .collect();
+                    parents.sort();
+                    parents.dedup();
+                    parents
+                }
+            })
+            .collect();
+
+        Dag{parents}
+    }
+
+    fn count_tasks(self: &Self) -> usize {
+        self.parents.len()
+    }
+
+    fn list_children(self: &Self) -> Vec<Vec<usize>> {
+        let mut children = vec![Vec::new(); self.count_tasks()];
+        for (task_idx, parents) in self.parents.iter().enumerate() {
+            for parent in parents {
+                children[*parent].push(task_idx);
+            }
+        }
+        children
+    }
+
+    /// The longest chain of dependencies, weighed by durations of its tasks:
+    /// no number of CPUs finishes the tasks sooner.
+    pub fn get_critical_path(self: &Self, durations: &[TaskDuration]) -> TaskDuration {
+
+        let mut finishes: Vec<TaskDuration> = Vec::with_capacity(self.count_tasks());
+
+        for (task_idx, parents) in self.parents.iter().enumerate() {
+            let ready = parents.iter().map(|parent| finishes[*parent]).max().unwrap_or_default();
+            finishes.push(ready + durations.get(task_idx).copied().unwrap_or_default());
+        }
+
+        finishes.into_iter().max().unwrap_or_default()
+    }
+}
+
+
+// Scheduling
+
+/// Tasks whose parents are all done, and the parents left to the others.
+struct DagState {
+    ready: VecDeque<usize>,
+    n_parents_left: Vec<usize>,
+    n_tasks_left: usize
+}
+
+impl DagState {
+
+    fn create(dag: &Dag) -> DagState {
+        DagState {
+            ready: (0..dag.count_tasks()).filter(|task_idx| dag.parents[*task_idx].is_empty()).collect(),
+            n_parents_left: dag.parents.iter().map(|parents| parents.len()).collect(),
+            n_tasks_left: dag.count_tasks()
+        }
+    }
+
+    fn complete(self: &mut Self, children: &[usize]) {
+        self.n_tasks_left -= 1;
+        for child in children {
+            self.n_parents_left[*child] -= 1;
+            if self.n_parents_left[*child] == 0 {
+                self.ready.push_back(*child);
+            }
+        }
+    }
+}
+
+/// Workers take ready tasks as they come, and wait while there are none but some left.
+/// A cancelled run takes no more tasks, ready or not.
+fn run_worker<W, C>(dag_state: &(Mutex<DagState>, Condvar), children: &[Vec<usize>], workload: &W,
+                    is_cancelled: &C) -> Vec<Task>
+    where W: Fn(usize) -> Task, C: Fn() -> bool {
+
+    let (state, ready_changed) = dag_state;
+    let mut tasks: Vec<Task> = Vec::new();
+
+    loop {
+        let task_idx = {
+            let mut state = state.lock().unwrap();
+            loop {
+                if state.n_tasks_left == 0 || is_cancelled() {
+                    break None;
+                }
+                if let Some(task_idx) = state.ready.pop_front() {
+                    break Some(task_idx);
+                }
+                state = ready_changed.wait(state).unwrap();
+            }
+        };
+
+        let task_idx = match task_idx {
+            Some(task_idx) => task_idx,
+            None => return tasks
+        };
+
+        tasks.push(workload(task_idx));
+        state.lock().unwrap().complete(&children[task_idx]);
+        ready_changed.notify_all();
+    }
+}
+
+/// As many workers as tasks in a series run the DAG to its end, in a single series.
+pub fn observe(n_tasks: usize, n_cycles: usize, series_size: usize, timeouts: Timeouts,
+               seed: u64, shape: DagShape) -> Observation {
+
+    hooks::notify_observation_start(n_tasks);
+
+    let dag = Dag::create(shape, n_tasks, seed);
+    let children = dag.list_children();
+    let dag_state = (Mutex::new(DagState::create(&dag)), Condvar::new());
+    let observation_start = read_run_clock();
+    let cpu_sampler = CpuSampler::start();
+    let memory_sampler = MemorySampler::start();
+
+    let workload = |task_idx: usize| {
+        let task_seed = derive_task_seed(seed, n_tasks, task_idx);
+        let mut task = standard_task(task_idx, n_cycles, timeouts, observation_start, task_seed);
+        task.set_thread(platform::get_thread_name(), platform::get_thread_id());
+        hooks::notify_task_complete(n_tasks, observation_start, &task);
+        task
+    };
+
+    let tasks: Vec<Task> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..series_size.min(n_tasks))
+            .map(|worker_idx| platform::create_worker_builder(format_worker_name(n_tasks, worker_idx))
+                .spawn_scoped(scope, || {
+                    let _ = platform::prepare_worker_thread();
+                    run_worker(&dag_state, &children, &workload, &cancel::is_cancelled)
+                }))
+            .collect();
+        handles.into_iter()
+            .filter_map(|handle| handle.ok())
+            .flat_map(|handle| handle.join().unwrap_or_default())
+            .collect()
+    });
+
+    let mut obs = Observation::create(n_tasks, n_cycles);
+    obs.set_cpu_utilization(cpu_sampler.finish());
+    obs.set_memory_usage(memory_sampler.finish());
+    for task in tasks {
+        obs.register_task(task);
+    }
+
+    if obs.count_failed_tasks() > 0 {
+        warn!("Observation of {} tasks has {} failed tasks", n_tasks, obs.count_failed_tasks());
+    }
+
+    hooks::notify_observation_complete(&obs);
+
+    obs
+}
+
+
+// Displaying critical paths
+
+fn create_report_dag(report: &Report, n_tasks: usize) -> Option<Dag> {
+    let shape = report.get_metadata("Dependencies").and_then(|value| DagShape::parse(value))?;
+    let seed = report.get_metadata("Seed").map_or(0, |value| parse_usize(value) as u64);
+    Some(Dag::create(shape, n_tasks, seed))
+}
+
+/// The critical path of the durations measured, for reports of tasks depending on each other.
+pub fn get_report_critical_path(report: &Report, obs: &Observation) -> Option<TaskDuration> {
+    let durations: Vec<TaskDuration> = obs.tasks.iter().map(|task| task.get_duration()).collect();
+    create_report_dag(report, obs.count_tasks()).map(|dag| dag.get_critical_path(&durations))
+}
+
+/// The makespan achieved against the critical path of the durations measured, which bounds
+/// the duration along with the work spread over CPUs.
+pub fn print_critical_paths(report: &Report) {
+
+    let n_cpus = count_report_cpus(report);
+
+    for obs in &report.observations {
+        if let Some(critical_path) = get_report_critical_path(report, obs) {
+            let bound = critical_path.max(obs.get_duration_bound(n_cpus));
+            info!("Critical path of {} tasks: {} ms, bound {} ms, achieved in {} ms (quality {})",
+                  obs.count_tasks(), critical_path, bound, obs.get_total_duration(),
+                  format_percent_cell(bound.ratio(obs.get_total_duration()).unwrap_or(1.0)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use super::*;
+
+    fn millis(durations: &[u64]) -> Vec<TaskDuration> {
+        durations.iter().map(|duration| TaskDuration::from_millis(*duration)).collect()
+    }
+
+    #[test]
+    fn shapes_depend_on_earlier_tasks() {
+        assert_eq!(Dag::create(DagShape::Chain, 3, 1).parents, vec![vec![], vec![0], vec![1]]);
+        assert_eq!(Dag::create(DagShape::Tree, 5, 1).parents, vec![vec![], vec![0], vec![0], vec![1], vec![1]]);
+        let dag = Dag::create(DagShape::Random, 20, 7);
+        assert!(dag.parents.iter().enumerate().skip(1)
+            .all(|(task_idx, parents)| !parents.is_empty() && parents.iter().all(|parent| *parent < task_idx)));
+        assert_eq!(dag.parents, Dag::create(DagShape::Random, 20, 7).parents);
+    }
+
+    #[test]
+    fn critical_path_is_the_longest_chain() {
+        let durations = millis(&[10, 20, 30, 5, 40]);
+        assert_eq!(Dag::create(DagShape::Chain, 5, 1).get_critical_path(&durations).as_millis(), 105);
+        assert_eq!(Dag::create(DagShape::Tree, 5, 1).get_critical_path(&durations).as_millis(), 70);
+    }
+
+    #[test]
+    fn every_task_runs_once_after_its_parents() {
+        let dag = Dag::create(DagShape::Random, 12, 3);
+        let children = dag.list_children();
+        let dag_state = (Mutex::new(DagState::create(&dag)), Condvar::new());
+        let order = Mutex::new(Vec::new());
+        let workload = |task_idx: usize| {
+            order.lock().unwrap().push(task_idx);
+            Task::create(task_idx, Timestamp::EPOCH, TaskDuration::ZERO)
+        };
+        let n_tasks: usize = thread::scope(|scope| {
+            let handles: Vec<_> = (0..3)
+                .map(|_| scope.spawn(|| run_worker(&dag_state, &children, &workload, &|| false)))
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap().len()).sum()
+        });
+        let order = order.into_inner().unwrap();
+        assert_eq!(n_tasks, 12);
+        for (task_idx, parents) in dag.parents.iter().enumerate() {
+            let position = order.iter().position(|idx| *idx == task_idx).unwrap();
+            assert!(parents.iter().all(|parent| order.iter().position(|idx| idx == parent).unwrap() < position));
+        }
+    }
+
+    #[test]
+    fn cancelled_dags_take_no_more_tasks() {
+        let dag = Dag::create(DagShape::Chain, 10, 1);
+        let children = dag.list_children();
+        let dag_state = (Mutex::new(DagState::create(&dag)), Condvar::new());
+        let cancelled = AtomicBool::new(false);
+        let workload = |task_idx: usize| {
+            if task_idx == 3 {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+            Task::create(task_idx, Timestamp::EPOCH, TaskDuration::ZERO)
+        };
+        let is_cancelled = || cancelled.load(Ordering::Relaxed);
+        let n_tasks: usize = thread::scope(|scope| {
+            let handles: Vec<_> = (0..3)
+                .map(|_| scope.spawn(|| run_worker(&dag_state, &children, &workload, &is_cancelled)))
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap().len()).sum()
+        });
+        assert_eq!(n_tasks, 4);
+        assert_eq!(dag_state.0.lock().unwrap().ready.len(), 1);
+    }
+}
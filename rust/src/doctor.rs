@@ -0,0 +1,179 @@
+// * * ** *** ***** ******** ************* *********************
+// Checking the system for common pitfalls of benchmarking
+// * * ** *** ***** ******** ************* *********************
+
+use std::fs;
+
+use super::*;
+
+
+// Findings of checks
+
+pub struct Finding {
+    title: &'static str,
+    ok: bool,
+    details: String,
+    suggestion: &'static str
+}
+
+impl Finding {
+
+    fn create(title: &'static str, ok: bool, details: String, suggestion: &'static str) -> Finding {
+        Finding{title, ok, details, suggestion}
+    }
+}
+
+
+// Checks
+
+const SMT_ACTIVE_PATH: &str = "/sys/devices/system/cpu/smt/active";
+const ASLR_PATH: &str = "/proc/sys/kernel/randomize_va_space";
+
+fn read_sys_value(path: &str) -> Option<String> {
+    fs::read_to_string(path).ok().map(|text| text.trim().to_string())
+}
+
+fn check_build_profile() -> Finding {
+    Finding::create(
+        "Build profile",
+        !is_debug_build(),
+        format_build_profile(),
+        "Build with cargo build --release")
+}
+
+fn check_governor() -> Finding {
+    let governor = PowerSettings::read().governor;
+    Finding::create(
+        "Frequency governor",
+        governor.as_deref().is_none_or(|governor| governor == "performance"),
+        governor.unwrap_or("unknown".to_string()),
+        "Run cpupower frequency-set -g performance")
+}
+
+fn check_turbo() -> Finding {
+    let turbo = PowerSettings::read().turbo;
+    Finding::create(
+        "Turbo boost",
+        turbo != Some(true),
+        turbo.map_or("unknown", |turbo| if turbo {"on"} else {"off"}).to_string(),
+        "Turn turbo off in the firmware or the frequency driver")
+}
+
+fn check_power_source() -> Finding {
+    let on_battery = PowerSettings::read().on_battery;
+    Finding::create(
+        "Power source",
+        on_battery != Some(true),
+        on_battery.map_or("unknown", |on_battery| if on_battery {"battery"} else {"AC"}).to_string(),
+        "Plug the machine in")
+}
+
+fn check_load() -> Finding {
+    let load_average = environment::read_load_average();
+    Finding::create(
+        "System load",
+        load_average.is_none_or(|load_average| !environment::is_load_high(load_average)),
+        load_average.map_or("unknown".to_string(),
+                            |load_average| format!("{:.2} for {} CPUs", load_average, count_host_cpus())),
+        "Close busy programs and wait for background jobs")
+}
+
+/// Sibling hardware threads share execution units, so tasks on them slow each other down.
+fn check_smt() -> Finding {
+    let smt_active = read_sys_value(SMT_ACTIVE_PATH).map(|active| active == "1");
+    Finding::create(
+        "Simultaneous multithreading",
+        smt_active != Some(true),
+        smt_active.map_or("unknown", |active| if active {"on"} else {"off"}).to_string(),
+        "Write off to /sys/devices/system/cpu/smt/control")
+}
+
+/// Randomized layouts of memory change cache and branch predictor behavior between runs.
+fn check_aslr() -> Finding {
+    let aslr = read_sys_value(ASLR_PATH);
+    Finding::create(
+        "Address space randomization",
+        aslr.as_deref().is_none_or(|aslr| aslr == "0"),
+        match aslr.as_deref() {
+            Some("0") => "off",
+            Some("1") => "partial",
+            Some(_) => "full",
+            None => "unknown"
+        }.to_string(),
+        "Run under setarch -R to measure with a fixed layout")
+}
+
+fn check_hypervisor() -> Finding {
+    let hypervisor = environment::detect_hypervisor();
+    Finding::create(
+        "Virtualization",
+        hypervisor.is_none(),
+        hypervisor.unwrap_or("none".to_string()),
+        "Measure on bare metal, or pin virtual CPUs on the host")
+}
+
+fn check_cpu_limits() -> Finding {
+    let cpu_limits = get_cpu_limits();
+    Finding::create(
+        "CPU limits of control groups",
+        !cpu_limits.is_limited(),
+        format!("{} of {} CPUs", cpu_limits.count_effective(), cpu_limits.host),
+        "Lift the CPU quota or the cpuset of the container")
+}
+
+pub fn find_findings() -> Vec<Finding> {
+
+    let checks: [fn() -> Finding; 9] =
+        [check_build_profile, check_governor, check_turbo, check_power_source, check_load,
+         check_smt, check_aslr, check_hypervisor, check_cpu_limits];
+
+    checks.iter().map(|check| check()).collect()
+}
+
+/// Only pitfalls found go into the metadata of a report.
+pub fn format_findings(findings: &[Finding]) -> String {
+
+    let pitfalls: Vec<String> = findings.iter()
+        .filter(|finding| !finding.ok)
+        .map(|finding| format!("{}: {}", finding.title, finding.details))
+        .collect();
+
+    if pitfalls.is_empty() {"none".to_string()} else {pitfalls.join("; ")}
+}
+
+
+// Printing findings
+
+fn create_doctor_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Check", 28, Align::Left, 0),
+        Column::create("Result", 6, Align::Left, 0),
+        Column::create("Details", 20, Align::Left, 1),
+        Column::create("Suggestion", 54, Align::Left, 0)
+    ], colored)
+}
+
+fn print_finding(table: &ConsoleTable, finding: &Finding) {
+    let (result, sign) = if finding.ok {("ok", 1.0)} else {("warn", -1.0)};
+    table.print_row(&vec![
+        Cell::Plain(finding.title.to_string()),
+        Cell::Signed(result.to_string(), sign),
+        Cell::Plain(finding.details.clone()),
+        Cell::Plain(if finding.ok {""} else {finding.suggestion}.to_string())
+    ]);
+}
+
+/// Gives the number of pitfalls found.
+pub fn run_doctor(colored: bool) -> usize {
+
+    let findings = find_findings();
+
+    let table = create_doctor_table(colored);
+    table.print_header();
+    for finding in &findings {
+        print_finding(&table, finding);
+    }
+    table.print_footer();
+
+    findings.iter().filter(|finding| !finding.ok).count()
+}
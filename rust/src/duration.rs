@@ -0,0 +1,290 @@
+// * * ** *** ***** ******** ************* *********************
+// Typing durations and timestamps of tasks
+// * * ** *** ***** ******** ************* *********************
+
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Sub};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+
+// Durations
+
+/// A span of time, of a task or of anything measured alongside tasks. Spans never go negative,
+/// subtracting a longer one gives zero. Displayed and serialized in whole milliseconds.
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct TaskDuration(Duration);
+
+impl TaskDuration {
+
+    pub const ZERO: TaskDuration = TaskDuration(Duration::ZERO);
+
+    pub const fn from_millis(ms: u64) -> TaskDuration {
+        TaskDuration(Duration::from_millis(ms))
+    }
+
+    pub fn from_nanos(nanos: u128) -> TaskDuration {
+        TaskDuration(Duration::from_nanos(nanos as u64))
+    }
+
+    /// Fractions of milliseconds are kept to the nanosecond, negative spans come out as zero.
+    pub fn from_millis_f64(ms: f64) -> TaskDuration {
+        TaskDuration(Duration::from_nanos((ms.max(0.0)*1e6).round() as u64))
+    }
+
+    pub fn as_duration(self: &Self) -> Duration {
+        self.0
+    }
+
+    pub fn as_millis(self: &Self) -> u64 {
+        self.0.as_millis() as u64
+    }
+
+    /// Whole milliseconds stay exact, so they print as integers.
+    pub fn as_millis_f64(self: &Self) -> f64 {
+        self.0.as_nanos() as f64/1e6
+    }
+
+    pub fn is_zero(self: &Self) -> bool {
+        self.0.is_zero()
+    }
+
+    /// How many times the other span fits into this one, or nothing for an empty span.
+    pub fn ratio(self: &Self, other: TaskDuration) -> Option<f64> {
+        if other.is_zero() {None} else {Some(self.0.as_nanos() as f64/other.0.as_nanos() as f64)}
+    }
+}
+
+impl Add for TaskDuration {
+    type Output = TaskDuration;
+    fn add(self: Self, other: TaskDuration) -> TaskDuration {
+        TaskDuration(self.0 + other.0)
+    }
+}
+
+impl AddAssign for TaskDuration {
+    fn add_assign(self: &mut Self, other: TaskDuration) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for TaskDuration {
+    type Output = TaskDuration;
+    fn sub(self: Self, other: TaskDuration) -> TaskDuration {
+        TaskDuration(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Mul<usize> for TaskDuration {
+    type Output = TaskDuration;
+    fn mul(self: Self, factor: usize) -> TaskDuration {
+        TaskDuration(Duration::from_nanos((self.0.as_nanos()*factor as u128) as u64))
+    }
+}
+
+impl Div<usize> for TaskDuration {
+    type Output = TaskDuration;
+    fn div(self: Self, divisor: usize) -> TaskDuration {
+        TaskDuration(Duration::from_nanos((self.0.as_nanos()/divisor.max(1) as u128) as u64))
+    }
+}
+
+impl Sum for TaskDuration {
+    fn sum<I: Iterator<Item = TaskDuration>>(iter: I) -> TaskDuration {
+        iter.fold(TaskDuration::ZERO, |sum, duration| sum + duration)
+    }
+}
+
+impl fmt::Display for TaskDuration {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.as_millis(), f)
+    }
+}
+
+/// Parses whole milliseconds, as durations are written to reports.
+impl FromStr for TaskDuration {
+    type Err = std::num::ParseIntError;
+    fn from_str(s: &str) -> Result<TaskDuration, Self::Err> {
+        s.parse::<u64>().map(TaskDuration::from_millis)
+    }
+}
+
+impl Serialize for TaskDuration {
+    fn serialize<S: Serializer>(self: &Self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.as_millis())
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<TaskDuration, D::Error> {
+        u64::deserialize(deserializer).map(TaskDuration::from_millis)
+    }
+}
+
+
+// Timestamps
+
+/// A moment as a span since the Unix epoch, displayed and serialized in milliseconds.
+#[derive(Copy, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Timestamp(Duration);
+
+impl Timestamp {
+
+    pub const EPOCH: Timestamp = Timestamp(Duration::ZERO);
+
+    pub const fn from_millis(ms: u64) -> Timestamp {
+        Timestamp(Duration::from_millis(ms))
+    }
+
+    pub fn from_nanos(nanos: u128) -> Timestamp {
+        Timestamp(Duration::from_nanos(nanos as u64))
+    }
+
+    pub fn as_millis(self: &Self) -> u64 {
+        self.0.as_millis() as u64
+    }
+
+    pub fn as_nanos(self: &Self) -> u128 {
+        self.0.as_nanos()
+    }
+
+    /// The span from an earlier moment, zero if the other moment is later.
+    pub fn since(self: &Self, earlier: Timestamp) -> TaskDuration {
+        TaskDuration(self.0.saturating_sub(earlier.0))
+    }
+}
+
+impl Add<TaskDuration> for Timestamp {
+    type Output = Timestamp;
+    fn add(self: Self, duration: TaskDuration) -> Timestamp {
+        Timestamp(self.0 + duration.0)
+    }
+}
+
+impl Sub for Timestamp {
+    type Output = TaskDuration;
+    fn sub(self: Self, earlier: Timestamp) -> TaskDuration {
+        self.since(earlier)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(self: &Self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.as_millis(), f)
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = std::num::ParseIntError;
+    fn from_str(s: &str) -> Result<Timestamp, Self::Err> {
+        s.parse::<u64>().map(Timestamp::from_millis)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(self: &Self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(self.as_millis())
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Timestamp, D::Error> {
+        u64::deserialize(deserializer).map(Timestamp::from_millis)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use proptest::prelude::*;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Timing {
+        start: Timestamp,
+        duration: TaskDuration
+    }
+
+    #[test]
+    fn durations_never_go_negative() {
+        let (short, long) = (TaskDuration::from_millis(10), TaskDuration::from_millis(30));
+        assert_eq!(long - short, TaskDuration::from_millis(20));
+        assert_eq!(short - long, TaskDuration::ZERO);
+        assert_eq!(Timestamp::from_millis(5).since(Timestamp::from_millis(8)), TaskDuration::ZERO);
+    }
+
+    #[test]
+    fn durations_scale_by_counts() {
+        let duration = TaskDuration::from_millis(250);
+        assert_eq!(duration*4, TaskDuration::from_millis(1000));
+        assert_eq!(duration/2, TaskDuration::from_nanos(125000000));
+        assert_eq!(duration/0, duration);
+        assert_eq!([duration, duration].into_iter().sum::<TaskDuration>(), duration*2);
+    }
+
+    #[test]
+    fn ratio_of_empty_span_is_none() {
+        assert_eq!(TaskDuration::from_millis(300).ratio(TaskDuration::from_millis(200)), Some(1.5));
+        assert_eq!(TaskDuration::from_millis(300).ratio(TaskDuration::ZERO), None);
+    }
+
+    #[test]
+    fn fractions_of_milliseconds_are_kept() {
+        assert_eq!(TaskDuration::from_millis_f64(1.2345678), TaskDuration::from_nanos(1234568));
+        assert_eq!(TaskDuration::from_millis_f64(-1.0), TaskDuration::ZERO);
+        assert_eq!(Timestamp::from_nanos(1500000).as_nanos(), 1500000);
+        assert_eq!(TaskDuration::from_nanos(1500000).as_millis_f64(), 1.5);
+    }
+
+    #[test]
+    fn timestamps_move_by_durations() {
+        let start = Timestamp::from_millis(1000);
+        let finish = start + TaskDuration::from_millis(250);
+        assert_eq!(finish, Timestamp::from_millis(1250));
+        assert_eq!(finish - start, TaskDuration::from_millis(250));
+    }
+
+    #[test]
+    fn display_is_in_whole_milliseconds() {
+        assert_eq!(TaskDuration::from_nanos(2999999).to_string(), "2");
+        assert_eq!(format!("{:>5}", TaskDuration::from_millis(42)), "   42");
+        assert_eq!(Timestamp::from_millis(1700000000000).to_string(), "1700000000000");
+        assert_eq!("250".parse::<TaskDuration>(), Ok(TaskDuration::from_millis(250)));
+        assert!("-1".parse::<TaskDuration>().is_err());
+    }
+
+    #[test]
+    fn serde_uses_milliseconds() {
+        let timing = Timing {start: Timestamp::from_millis(1000), duration: TaskDuration::from_millis(250)};
+        let text = toml::to_string(&timing).unwrap();
+        assert_eq!(text, "start = 1000\nduration = 250\n");
+        assert_eq!(toml::from_str::<Timing>(&text).unwrap(), timing);
+    }
+
+    proptest! {
+
+        #[test]
+        fn adding_then_subtracting_restores(a in 0u64..1 << 40, b in 0u64..1 << 40) {
+            let (a, b) = (TaskDuration::from_millis(a), TaskDuration::from_millis(b));
+            prop_assert_eq!((a + b) - b, a);
+            prop_assert_eq!((Timestamp::EPOCH + a + b) - (Timestamp::EPOCH + b), a);
+        }
+
+        #[test]
+        fn scaling_then_dividing_restores(ms in 0u64..1 << 30, n in 1usize..1024) {
+            let duration = TaskDuration::from_millis(ms);
+            prop_assert_eq!((duration*n)/n, duration);
+        }
+
+        #[test]
+        fn milliseconds_survive_display(ms in any::<u32>()) {
+            let duration = TaskDuration::from_millis(ms as u64);
+            prop_assert_eq!(duration.to_string().parse::<TaskDuration>(), Ok(duration));
+            prop_assert_eq!(duration.as_millis_f64(), ms as f64);
+        }
+    }
+}
@@ -0,0 +1,245 @@
+// * * ** *** ***** ******** ************* *********************
+// Measuring for bindings to other languages
+// * * ** *** ***** ******** ************* *********************
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+pub use super::config::RunConfig;
+
+
+// Flat records
+
+/// Totals of an observation, flat so rows load into a dataframe as they are.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ObservationRecord {
+    pub n_tasks: usize,
+    pub mean_task_duration_ms: u64,
+    pub std_dev_ms: u64,
+    pub cv: f64,
+    pub total_duration_ms: u64,
+    pub parallelism: f64,
+    pub max_running: Option<usize>,
+    pub start_spread_ms: Option<u64>,
+    pub tasks_per_sec: f64,
+    pub cycles_per_sec: f64,
+    pub speedup: f64,
+    pub cost: f64,
+    pub profit: f64,
+    pub profit_ci_low: Option<f64>,
+    pub profit_ci_high: Option<f64>,
+    pub timed_out: usize,
+    pub failed: usize,
+    pub retries: usize,
+    #[serde(default)]
+    pub repeat_durations_ms: Vec<u64>
+}
+
+impl ObservationRecord {
+
+    pub(crate) fn create(obs: &Observation) -> ObservationRecord {
+        ObservationRecord {
+            n_tasks: obs.count_tasks(),
+            mean_task_duration_ms: obs.get_mean_task_duration().as_millis(),
+            std_dev_ms: obs.get_standard_deviation().as_millis(),
+            cv: obs.get_variation_coefficient(),
+            total_duration_ms: obs.get_total_duration().as_millis(),
+            parallelism: obs.get_parallelism(),
+            max_running: obs.count_concurrent_tasks_max(),
+            start_spread_ms: obs.get_start_spread().map(|spread| spread.as_millis()),
+            tasks_per_sec: obs.get_tasks_per_sec(),
+            cycles_per_sec: obs.get_cycles_per_sec(),
+            speedup: obs.get_speedup(),
+            cost: obs.get_concurrency_cost(),
+            profit: obs.get_concurrency_profit(),
+            profit_ci_low: obs.get_profit_interval().map(|(low, _)| low),
+            profit_ci_high: obs.get_profit_interval().map(|(_, high)| high),
+            timed_out: obs.count_timed_out_tasks(),
+            failed: obs.count_failed_tasks(),
+            retries: obs.get_retries(),
+            repeat_durations_ms: obs.get_repeat_durations().iter().map(|d| d.as_millis()).collect()
+        }
+    }
+}
+
+/// A task of an observation, timed from the start of the observation as schedules are.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TaskRecord {
+    pub n_tasks: usize,
+    pub task: usize,
+    pub series: usize,
+    pub started_ms: u64,
+    pub finished_ms: u64,
+    pub duration_ms: u64,
+    pub status: String,
+    pub thread: String,
+    #[serde(default)]
+    pub seed: u64
+}
+
+impl TaskRecord {
+
+    fn create(obs: &Observation, task: &Task) -> TaskRecord {
+        TaskRecord::create_since(obs.count_tasks(), obs.get_earliest_start(), task)
+    }
+
+    /// Timed from the given start, as tasks of observations still running are.
+    pub(crate) fn create_since(n_tasks: usize, start: Timestamp, task: &Task) -> TaskRecord {
+        TaskRecord {
+            n_tasks,
+            task: task.get_idx() + 1,
+            series: task.get_series() + 1,
+            started_ms: (task.get_start() - start).as_millis(),
+            finished_ms: (task.get_finish() - start).as_millis(),
+            duration_ms: task.get_duration().as_millis(),
+            status: format_task_status(task),
+            thread: task.get_thread_name().clone(),
+            seed: task.get_seed()
+        }
+    }
+}
+
+/// A report as records: metadata, totals of observations, and their schedules.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReportRecords {
+    pub metadata: Vec<(String, String)>,
+    pub observations: Vec<ObservationRecord>,
+    pub tasks: Vec<TaskRecord>
+}
+
+impl ReportRecords {
+
+    pub(crate) fn create(report: &Report) -> ReportRecords {
+        ReportRecords {
+            metadata: report.metadata.clone(),
+            observations: report.observations.iter().map(ObservationRecord::create).collect(),
+            tasks: report.observations.iter()
+                .flat_map(|obs| obs.tasks.iter().map(move |task| TaskRecord::create(obs, task)))
+                .collect()
+        }
+    }
+}
+
+/// Reads records saved as JSON by bindings, such as the C interface.
+pub fn parse_records(json: &str) -> Result<ReportRecords, String> {
+    serde_json::from_str(json).map_err(|e| format!("Error while parsing a report: {}", e))
+}
+
+
+// Measuring
+
+fn register_config_metadata(report: &mut Report, config: &RunConfig) {
+    report.set_metadata("Started", &format_utc_datetime(read_timestamp(&SystemTime::now())));
+    report.set_metadata("Tasks", &config.get_tasks_max().to_string());
+    report.set_metadata("Cycles in a task", &config.get_n_cycles().to_string());
+    report.set_metadata("Tasks in a series", &config.get_series_size().to_string());
+    report.set_metadata("Seed", &config.get_seed().to_string());
+    report.set_metadata("Backend", config.get_backend());
+    if config.get_dag() != "" {
+        report.set_metadata("Dependencies", config.get_dag());
+    }
+    report.set_metadata("Repeats", &config.get_repeats().to_string());
+    report.set_metadata("Effective CPUs", &count_cpus().to_string());
+}
+
+/// Sweeps the configured numbers of tasks as the CLI does, without printing anything.
+pub fn measure(config: &RunConfig) -> Result<ReportRecords, String> {
+
+    if !config.is_valid() {
+        return Err("The run configuration is invalid".to_string());
+    }
+
+    if config.get_backend() == fanout::BACKEND {
+        return Err("Worker processes are only spawned by the executable".to_string());
+    }
+
+    let mut report = Report::create(config.get_tasks_max());
+    register_config_metadata(&mut report, config);
+
+    for n_tasks in config.get_tasks() {
+        let obs = observe_repeatedly(n_tasks, config);
+        if config.is_cancelled() {
+            break;
+        }
+        report.register_observation(obs);
+    }
+
+    Ok(ReportRecords::create(&report))
+}
+
+
+
+// Running workloads alone
+
+/// Workloads a task of which runs on the calling thread.
+pub fn list_workloads() -> Vec<&'static str> {
+    registry::WORKLOADS.iter().map(|workload| workload.name).collect()
+}
+
+/// Runs a task of a registered workload on the calling thread, so benchmarks time a single core.
+pub fn run_workload(name: &str, n_cycles: usize, seed: u64) -> Option<f64> {
+    let workload_kind = WorkloadKind::parse(name)?;
+    workload::run_task(&workload::prepare_task(workload_kind, seed), n_cycles, None, ConvergenceMode::AllCycles)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use cancel::CancellationToken;
+    use testing::FakeWorkload;
+
+    #[test]
+    fn records_follow_the_report() {
+        let records = ReportRecords::create(&FakeWorkload::create(2, 4, 100).create_report(1..=3));
+        assert_eq!(records.observations.len(), 3);
+        assert_eq!(records.tasks.len(), 6);
+        assert_eq!(records.observations[2].total_duration_ms, 200);
+        let last = &records.tasks[5];
+        assert_eq!((last.n_tasks, last.task, last.started_ms, last.duration_ms), (3, 3, 100, 100));
+        assert_eq!(last.status, "completed");
+    }
+
+    #[test]
+    fn records_survive_json() {
+        let records = ReportRecords::create(&FakeWorkload::create(2, 2, 100).create_report(1..=2));
+        let parsed = parse_records(&serde_json::to_string(&records).unwrap()).unwrap();
+        assert_eq!(parsed.metadata, records.metadata);
+        assert_eq!(parsed.observations[1].speedup, records.observations[1].speedup);
+        assert_eq!(parsed.tasks.len(), 3);
+        assert!(parse_records("{}").is_err());
+    }
+
+    #[test]
+    fn registered_workloads_run() {
+        for workload in list_workloads() {
+            assert!(run_workload(workload, 1000, 42).is_some(), "{} does not run", workload);
+        }
+        assert_eq!(run_workload("standard", 1000, 42), run_workload("standard", 1000, 42));
+        assert!(run_workload("unknown", 1000, 42).is_none());
+    }
+
+    #[test]
+    fn invalid_configs_are_rejected() {
+        assert!(measure(&RunConfig::new()).is_err());
+    }
+
+    #[test]
+    fn simulated_sweeps_measure_every_number_of_tasks() {
+        let config = RunConfig::new().tasks(1..=3).cycles(1000000).series_size(2).backend("simulated");
+        let records = measure(&config).unwrap();
+        assert_eq!(records.observations.iter().map(|obs| obs.n_tasks).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(records.metadata.contains(&("Backend".to_string(), "simulated".to_string())));
+    }
+
+    #[test]
+    fn cancelled_runs_leave_later_runs_alone() {
+        let config = RunConfig::new().tasks(1..=3).cycles(1000000).series_size(2).backend("simulated");
+        let token = CancellationToken::create();
+        token.cancel();
+        assert!(measure(&config.clone().cancellation(token)).unwrap().observations.is_empty());
+        assert_eq!(measure(&config).unwrap().observations.len(), 3);
+    }
+}
@@ -0,0 +1,283 @@
+// * * ** *** ***** ******** ************* *********************
+// Watching the environment for disturbances of measurements
+// * * ** *** ***** ******** ************* *********************
+
+use std::fs;
+
+use super::*;
+
+
+// Reading system counters
+
+const LOAD_AVERAGE_PATH: &str = "/proc/loadavg";
+const CPU_DIR_PATH: &str = "/sys/devices/system/cpu";
+const THROTTLE_COUNT_PATH: &str = "thermal_throttle/core_throttle_count";
+
+pub fn read_load_average() -> Option<f64> {
+    fs::read_to_string(LOAD_AVERAGE_PATH).ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Throttling counters of all cores are summed up, so any change means throttling.
+fn read_throttle_count() -> Option<u64> {
+
+    let mut throttle_count: Option<u64> = None;
+
+    for cpu_idx in 0..count_host_cpus() {
+        let path = format!("{}/cpu{}/{}", CPU_DIR_PATH, cpu_idx, THROTTLE_COUNT_PATH);
+        if let Some(count) = fs::read_to_string(path).ok()
+                                .and_then(|text| text.trim().parse::<u64>().ok()) {
+            throttle_count = Some(throttle_count.unwrap_or(0) + count);
+        }
+    }
+
+    throttle_count
+}
+
+
+// Detecting virtual machines
+
+/// CPUID reports a hypervisor in bit 31 of ECX of leaf 1, and its vendor in leaf 0x40000000.
+#[cfg(target_arch = "x86_64")]
+#[allow(unused_unsafe)] // CPUID is only safe to call since Rust 1.87
+pub fn detect_hypervisor() -> Option<String> {
+    use std::arch::x86_64::__cpuid;
+
+    if unsafe {__cpuid(1)}.ecx & (1 << 31) == 0 {
+        return None;
+    }
+
+    let vendor_leaf = unsafe {__cpuid(0x40000000)};
+    let vendor: Vec<u8> = [vendor_leaf.ebx, vendor_leaf.ecx, vendor_leaf.edx].iter()
+        .flat_map(|register| register.to_le_bytes())
+        .filter(|byte| *byte != 0)
+        .collect();
+    let vendor = String::from_utf8_lossy(&vendor).trim().to_string();
+
+    Some(if vendor.is_empty() {"unknown".to_string()} else {vendor})
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+pub fn detect_hypervisor() -> Option<String> {
+    None
+}
+
+const STEAL_SHARE_MAX: f64 = 0.05;
+
+/// Observations losing CPU time to other guests of a hypervisor are slower for no fault of theirs.
+pub fn find_stolen_observations(observations: &[Observation]) -> Vec<usize> {
+    observations.iter()
+        .filter(|obs| obs.get_cpu_utilization()
+                         .is_some_and(|utilization| utilization.steal > STEAL_SHARE_MAX))
+        .map(|obs| obs.count_tasks())
+        .collect()
+}
+
+
+// Reading power settings
+
+const GOVERNOR_PATH: &str = "cpufreq/scaling_governor";
+const NO_TURBO_PATH: &str = "/sys/devices/system/cpu/intel_pstate/no_turbo";
+const BOOST_PATH: &str = "/sys/devices/system/cpu/cpufreq/boost";
+const POWER_SUPPLY_DIR_PATH: &str = "/sys/class/power_supply";
+
+fn read_sys_value(path: &str) -> Option<String> {
+    fs::read_to_string(path).ok().map(|text| text.trim().to_string())
+}
+
+/// Intel's driver tells whether turbo is disabled, the generic one whether boost is enabled.
+fn read_turbo() -> Option<bool> {
+    match read_sys_value(NO_TURBO_PATH) {
+        Some(no_turbo) => Some(no_turbo == "0"),
+        None => read_sys_value(BOOST_PATH).map(|boost| boost == "1")
+    }
+}
+
+/// Machines without batteries give nothing, since they run on AC anyway.
+fn read_on_battery() -> Option<bool> {
+
+    let (mut has_battery, mut on_mains) = (false, false);
+
+    for entry in fs::read_dir(POWER_SUPPLY_DIR_PATH).ok()?.flatten() {
+        let dir = entry.path().to_string_lossy().to_string();
+        match read_sys_value(&format!("{}/type", dir)).as_deref() {
+            Some("Battery") => has_battery = true,
+            Some("Mains") => on_mains |= read_sys_value(&format!("{}/online", dir)).as_deref() == Some("1"),
+            _ => {}
+        }
+    }
+
+    if has_battery {Some(!on_mains)} else {None}
+}
+
+pub struct PowerSettings {
+    pub governor: Option<String>,
+    pub turbo: Option<bool>,
+    pub on_battery: Option<bool>
+}
+
+impl PowerSettings {
+
+    pub fn read() -> PowerSettings {
+        PowerSettings {
+            governor: read_sys_value(&format!("{}/cpu0/{}", CPU_DIR_PATH, GOVERNOR_PATH)),
+            turbo: read_turbo(),
+            on_battery: read_on_battery()
+        }
+    }
+
+    /// Frequencies scaled on demand, boosted while cool, or saved on battery vary between runs.
+    pub fn find_warnings(self: &Self) -> Vec<String> {
+
+        let mut warnings: Vec<String> = Vec::new();
+
+        if let Some(governor) = &self.governor {
+            if governor != "performance" {
+                warnings.push(format!("The {} frequency governor scales CPUs on demand, \
+                                       consider the performance one", governor));
+            }
+        }
+        if self.turbo == Some(true) {
+            warnings.push("Turbo boost depends on temperatures, consider turning it off".to_string());
+        }
+        if self.on_battery == Some(true) {
+            warnings.push("Running on battery may throttle CPUs, consider plugging in".to_string());
+        }
+
+        warnings
+    }
+}
+
+
+// Comparing the environment before and after measuring
+
+pub struct EnvironmentSnapshot {
+    load_average: Option<f64>,
+    throttle_count: Option<u64>
+}
+
+impl EnvironmentSnapshot {
+
+    pub fn capture() -> EnvironmentSnapshot {
+        EnvironmentSnapshot {
+            load_average: read_load_average(),
+            throttle_count: read_throttle_count()
+        }
+    }
+}
+
+const LOAD_PER_CPU_MAX: f64 = 0.5;
+
+pub fn is_load_high(load_average: f64) -> bool {
+    load_average > LOAD_PER_CPU_MAX*count_host_cpus() as f64
+}
+
+/// The load is judged before measuring, since the measurements load the system themselves.
+pub fn find_environment_warnings(before: &EnvironmentSnapshot,
+                                 after: &EnvironmentSnapshot) -> Vec<String> {
+
+    let mut warnings: Vec<String> = Vec::new();
+
+    if let Some(load_average) = before.load_average {
+        if is_load_high(load_average) {
+            warnings.push(format!("The system load of {:.2} is high for {} CPUs",
+                                  load_average, count_host_cpus()));
+        }
+    }
+
+    if let (Some(count_before), Some(count_after)) = (before.throttle_count, after.throttle_count) {
+        if count_after > count_before {
+            warnings.push(format!("CPUs were throttled {} times while measuring",
+                                  count_after - count_before));
+        }
+    }
+
+    warnings
+}
+
+
+// Pinning frequencies for a run
+
+const PERFORMANCE_GOVERNOR: &str = "performance";
+
+/// Power settings changed for a run, with the values they had, written back when dropped.
+pub struct FrequencyPin {
+    changes: Vec<(String, String)>,
+    description: Vec<String>
+}
+
+impl FrequencyPin {
+
+    pub fn describe(self: &Self) -> String {
+        if self.description.is_empty() {
+            "already pinned".to_string()
+        } else {
+            self.description.join(", ")
+        }
+    }
+
+    fn change(self: &mut Self, path: &str, value: &str) -> Result<(), String> {
+        match read_sys_value(path) {
+            Some(previous) if previous != value => {
+                fs::write(path, value).map_err(|error| format!("Setting {} failed: {}", path, error))?;
+                self.changes.push((path.to_string(), previous));
+                Ok(())
+            }
+            _ => Ok(())
+        }
+    }
+
+    fn restore(self: &mut Self) {
+        for (path, previous) in self.changes.drain(..).rev() {
+            if let Err(error) = fs::write(&path, &previous) {
+                warn!("Restoring {} to {} failed: {}", path, previous, error);
+            }
+        }
+    }
+}
+
+impl Drop for FrequencyPin {
+    fn drop(self: &mut Self) {
+        self.restore();
+    }
+}
+
+/// Sets the performance governor on every CPU and turns turbo off, which needs root.
+/// Whatever was changed before a failure is written back at once.
+pub fn pin_frequency() -> Result<FrequencyPin, String> {
+
+    let mut pin = FrequencyPin{changes: Vec::new(), description: Vec::new()};
+
+    let governor_paths: Vec<String> = (0..count_host_cpus())
+        .map(|cpu_idx| format!("{}/cpu{}/{}", CPU_DIR_PATH, cpu_idx, GOVERNOR_PATH))
+        .filter(|path| read_sys_value(path).is_some())
+        .collect();
+    let turbo_setting = match (read_sys_value(NO_TURBO_PATH), read_sys_value(BOOST_PATH)) {
+        (Some(_), _) => Some((NO_TURBO_PATH, "1")),
+        (None, Some(_)) => Some((BOOST_PATH, "0")),
+        (None, None) => None
+    };
+
+    if governor_paths.is_empty() && turbo_setting.is_none() {
+        return Err("No cpufreq settings of Linux found".to_string());
+    }
+
+    if let Some(governor) = PowerSettings::read().governor.filter(|governor| governor != PERFORMANCE_GOVERNOR) {
+        for path in &governor_paths {
+            pin.change(path, PERFORMANCE_GOVERNOR)?;
+        }
+        pin.description.push(format!("governor {} to {}", governor, PERFORMANCE_GOVERNOR));
+    }
+    if let Some((path, value)) = turbo_setting {
+        let n_changes = pin.changes.len();
+        pin.change(path, value)?;
+        if pin.changes.len() > n_changes {
+            pin.description.push("turbo off".to_string());
+        }
+    }
+
+    Ok(pin)
+}
@@ -0,0 +1,283 @@
+// * * ** *** ***** ******** ************* *********************
+// Fanning observations out to worker processes
+// * * ** *** ***** ******** ************* *********************
+
+use std::io::{BufRead, BufReader};
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+
+// Sharing tasks
+
+pub const BACKEND: &str = "processes";
+
+pub const DEFAULT_PROCESSES: usize = 2;
+
+/// Set for worker processes, telling their share of an observation.
+const ENV_FANOUT_SHARE: &str = "CONCTEST_FANOUT_SHARE";
+
+/// What a worker process needs to run its share of an observation.
+#[derive(Clone, Serialize, Deserialize)]
+struct Share {
+    process: usize,
+    n_processes: usize,
+    n_tasks: usize,
+    n_cycles: usize,
+    series_size: usize,
+    seed: u64,
+    task_timeout_ms: u64,
+    observation_timeout_ms: u64,
+    observation_start_ms: u64
+}
+
+impl Share {
+
+    /// Tasks are dealt out in turn, so every process gets tasks of every series.
+    fn list_tasks(self: &Self) -> Vec<usize> {
+        (self.process..self.n_tasks).step_by(self.n_processes).collect()
+    }
+
+    /// Series are split between processes as tasks are, so processes together run as many
+    /// tasks at a time as a single one would.
+    fn get_series_size(self: &Self) -> usize {
+        count_series(self.series_size, self.n_processes).max(1)
+    }
+
+    fn get_timeouts(self: &Self) -> Timeouts {
+        Timeouts::create(TaskDuration::from_millis(self.task_timeout_ms),
+                         TaskDuration::from_millis(self.observation_timeout_ms))
+    }
+}
+
+fn get_process(n_processes: usize, task_idx: usize) -> usize {
+    task_idx % n_processes.max(1)
+}
+
+/// A task as a worker process reports it, one JSON line each.
+#[derive(Serialize, Deserialize)]
+struct SharedTask {
+    task: usize,
+    start_ms: u64,
+    duration_ms: u64,
+    status: String,
+    thread: String,
+    thread_id: u64,
+    #[serde(default)]
+    seed: u64
+}
+
+
+// Running shares in worker processes
+
+/// Runs the share of a worker process and tells its exit code, unless the process is no worker.
+pub fn serve_share() -> Option<i32> {
+
+    let share: Share = match env::var(ENV_FANOUT_SHARE) {
+        Ok(text) => serde_json::from_str(&text)
+            .unwrap_or_else(|e| panic!("Error while parsing a share of tasks: {}", e)),
+        Err(_) => return None
+    };
+
+    let tasks = share.list_tasks();
+    let observation_start = Timestamp::from_millis(share.observation_start_ms);
+    let timeouts = share.get_timeouts();
+
+    // Tasks are observed by their place in the share, and seeded by their place in the observation
+    let obs = observe_workload(&RunClock, tasks.len(), share.n_cycles, share.get_series_size(), share.seed,
+                               &|local_idx, _, _| {
+                                   let task_seed = derive_task_seed(share.seed, share.n_tasks, tasks[local_idx]);
+                                   standard_task(local_idx, share.n_cycles, timeouts, observation_start, task_seed)
+                               });
+
+    for task in &obs.tasks {
+        let shared = SharedTask {
+            task: tasks[task.get_idx()],
+            start_ms: task.get_start().as_millis(),
+            duration_ms: task.get_duration().as_millis(),
+            status: format_task_status(task),
+            thread: task.get_thread_name().clone(),
+            thread_id: task.get_thread_id(),
+            seed: task.get_seed()
+        };
+        println!("{}", serde_json::to_string(&shared).unwrap());
+    }
+
+    Some(EXIT_SUCCESS)
+}
+
+/// Worker processes run the same command, so they take the same worker settings.
+fn spawn_worker(share: &Share) -> std::io::Result<process::Child> {
+    process::Command::new(env::current_exe()?)
+        .args(env::args().skip(1))
+        .env(ENV_FANOUT_SHARE, serde_json::to_string(share).unwrap())
+        .stdout(process::Stdio::piped())
+        .spawn()
+}
+
+fn fail_tasks(share: &Share, error: &str) -> Vec<Task> {
+    share.list_tasks().into_iter()
+        .map(|task_idx| {
+            let mut task = Task::create(task_idx, read_run_clock(), TaskDuration::ZERO);
+            task.set_status(TaskStatus::Failed(error.to_string()));
+            task
+        })
+        .collect()
+}
+
+/// Lines other than tasks, such as messages of the kernel, are skipped.
+fn parse_shared_tasks(share: &Share, lines: impl Iterator<Item = String>) -> Vec<Task> {
+    lines
+        .filter_map(|line| serde_json::from_str::<SharedTask>(&line).ok())
+        .map(|shared| {
+            let mut task = Task::create(shared.task, Timestamp::from_millis(shared.start_ms),
+                                        TaskDuration::from_millis(shared.duration_ms));
+            task.set_status(saved::parse_task_status(&shared.status));
+            task.set_series(shared.task/share.series_size.max(1));
+            task.set_thread(format!("p{}/{}", share.process + 1, shared.thread), shared.thread_id);
+            task.set_seed(shared.seed);
+            task
+        })
+        .collect()
+}
+
+/// A worker process failing to start or to finish fails its tasks, as a worker thread does.
+fn join_worker_process(share: &Share, child: std::io::Result<process::Child>) -> Vec<Task> {
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(error) => return fail_tasks(share, &format!("spawning a process failed: {}", error))
+    };
+
+    let tasks = match child.stdout.take() {
+        Some(stdout) => parse_shared_tasks(share, BufReader::new(stdout).lines().map_while(Result::ok)),
+        None => Vec::new()
+    };
+
+    match child.wait() {
+        Ok(status) if status.success() && tasks.len() == share.list_tasks().len() => tasks,
+        Ok(status) => fail_tasks(share, &format!("process {} exited with {}", share.process + 1, status)),
+        Err(error) => fail_tasks(share, &format!("waiting for a process failed: {}", error))
+    }
+}
+
+
+// Observing
+
+/// Spawns the processes of an observation at once and merges their tasks, so the schedule
+/// of the observation is the aggregate of the processes.
+pub fn observe(n_tasks: usize, n_cycles: usize, series_size: usize, timeouts: Timeouts,
+               seed: u64, n_processes: usize) -> Observation {
+
+    hooks::notify_observation_start(n_tasks);
+
+    let observation_start = read_run_clock();
+    let cpu_sampler = CpuSampler::start();
+    let n_processes = n_processes.clamp(1, n_tasks.max(1));
+
+    let shares: Vec<Share> = (0..n_processes)
+        .map(|process| Share {
+            process,
+            n_processes,
+            n_tasks,
+            n_cycles,
+            series_size,
+            seed,
+            task_timeout_ms: timeouts.task.as_millis(),
+            observation_timeout_ms: timeouts.observation.as_millis(),
+            observation_start_ms: observation_start.as_millis()
+        })
+        .collect();
+
+    let children: Vec<std::io::Result<process::Child>> = shares.iter().map(spawn_worker).collect();
+    let mut tasks: Vec<Task> = shares.iter()
+        .zip(children)
+        .flat_map(|(share, child)| join_worker_process(share, child))
+        .collect();
+    tasks.sort_by_key(|task| task.get_idx());
+
+    let mut obs = Observation::create(n_tasks, n_cycles);
+    obs.set_cpu_utilization(cpu_sampler.finish());
+    for task in tasks {
+        hooks::notify_task_complete(n_tasks, observation_start, &task);
+        obs.register_task(task);
+    }
+
+    if obs.count_failed_tasks() > 0 {
+        warn!("Observation of {} tasks has {} failed tasks", n_tasks, obs.count_failed_tasks());
+    }
+
+    hooks::notify_observation_complete(&obs);
+
+    obs
+}
+
+
+// Displaying processes
+
+/// Spans of processes within the aggregate schedule, telling whether some lag behind.
+pub fn print_process_spans(obs: &Observation, n_processes: usize) {
+
+    for process in 0..n_processes.min(obs.count_tasks()) {
+
+        let tasks: Vec<&Task> = obs.tasks.iter()
+            .filter(|task| get_process(n_processes, task.get_idx()) == process)
+            .collect();
+
+        let (start, finish) = match (tasks.iter().map(|task| task.get_start()).min(),
+                                     tasks.iter().map(|task| task.get_finish()).max()) {
+            (Some(start), Some(finish)) => (start, finish),
+            _ => continue
+        };
+
+        let mean_duration = tasks.iter().map(|task| task.get_duration().as_millis()).sum::<u64>()/tasks.len() as u64;
+
+        info!("Process {}: {} tasks from {} to {} ms, {} ms on average",
+              process + 1, tasks.len(), obs.to_relative(start), obs.to_relative(finish), mean_duration);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn create_share(process: usize, n_processes: usize, n_tasks: usize, series_size: usize) -> Share {
+        Share {
+            process, n_processes, n_tasks, n_cycles: 1000, series_size, seed: 1,
+            task_timeout_ms: 0, observation_timeout_ms: 0, observation_start_ms: 0
+        }
+    }
+
+    #[test]
+    fn tasks_are_dealt_out_in_turn() {
+        assert_eq!(create_share(0, 3, 8, 8).list_tasks(), vec![0, 3, 6]);
+        assert_eq!(create_share(2, 3, 8, 8).list_tasks(), vec![2, 5]);
+        assert_eq!(create_share(1, 3, 8, 8).get_series_size(), 3);
+        assert!((0..8).all(|task_idx| create_share(get_process(3, task_idx), 3, 8, 8).list_tasks().contains(&task_idx)));
+    }
+
+    #[test]
+    fn shared_tasks_read_back() {
+        let share = create_share(1, 2, 4, 2);
+        let lines = vec![
+            "The sequence has converged".to_string(),
+            r#"{"task":3,"start_ms":1010,"duration_ms":90,"status":"timed out","thread":"conctest-w4-2","thread_id":7}"#.to_string()
+        ];
+        let tasks = parse_shared_tasks(&share, lines.into_iter());
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].get_series(), 1);
+        assert_eq!(tasks[0].get_thread_name(), "p2/conctest-w4-2");
+        assert!(tasks[0].is_timed_out());
+    }
+
+    #[test]
+    fn failed_processes_fail_their_tasks() {
+        let share = create_share(0, 2, 4, 4);
+        let tasks = join_worker_process(&share, Err(std::io::Error::other("no such file")));
+        assert_eq!(tasks.iter().map(|task| task.get_idx()).collect::<Vec<_>>(), vec![0, 2]);
+        assert!(tasks.iter().all(|task| task.is_failed()));
+    }
+}
@@ -0,0 +1,210 @@
+// * * ** *** ***** ******** ************* *********************
+// Offloading the standard arithmetic to a GPU
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+
+
+// Offloading
+
+/// Triplets of every task of an observation, from the seeds the tasks had, so the GPU runs
+/// the same iterations as CPU threads did, yet in single precision where they run double.
+fn collect_triplets(n_tasks: usize, seed: u64) -> Vec<Triplet> {
+    (0..n_tasks)
+        .flat_map(|task_idx| rng::seed_triplets(rng::get_rng_kind(), derive_task_seed(seed, n_tasks, task_idx),
+                                                batch::get_n_triplets()))
+        .collect()
+}
+
+/// Duration of iterating the triplets of all tasks of an observation on the GPU, uploading
+/// and reading back included, or nothing in builds without the wgpu feature or GPU adapter.
+/// The sum is only logged, as single precision leaves it apart from the one of CPU threads.
+pub fn offload_observation(n_tasks: usize, n_cycles: usize, seed: u64) -> Option<TaskDuration> {
+    if !device::open_gpu() {
+        return None;
+    }
+    let triplets = collect_triplets(n_tasks, seed);
+    let watch = Stopwatch::start();
+    let sum = device::iterate_on_gpu(&triplets, n_cycles)?;
+    let duration = TaskDuration::from_nanos(watch.elapsed_nanos());
+    debug!(n_tasks, duration = duration.as_millis(), sum, "Observation offloaded to the GPU");
+    Some(duration)
+}
+
+#[cfg(all(feature = "wgpu", not(target_arch = "wasm32")))]
+mod device {
+
+    use std::sync::OnceLock;
+
+    use wgpu::util::DeviceExt;
+
+    /// One invocation per triplet, in single precision, as GPUs rarely have double.
+    const SHADER: &str = r"
+        @group(0) @binding(0) var<storage, read_write> triplets: array<f32>;
+        @group(0) @binding(1) var<uniform> params: vec4<u32>;
+
+        @compute @workgroup_size(64)
+        fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+            let idx = id.x;
+            if (idx >= params.y) {
+                return;
+            }
+            var a = triplets[3u*idx];
+            var b = triplets[3u*idx + 1u];
+            var c = triplets[3u*idx + 2u];
+            for (var step = 0u; step < params.x; step++) {
+                let applicant = a + b - c;
+                a = b;
+                b = c;
+                if (abs(applicant) <= 1.0) {c = applicant;} else {c = 1.0/applicant;}
+            }
+            triplets[3u*idx] = a;
+            triplets[3u*idx + 1u] = b;
+            triplets[3u*idx + 2u] = c;
+        }";
+
+    const WORKGROUP_SIZE: usize = 64;
+
+    struct Gpu {
+        device: wgpu::Device,
+        queue: wgpu::Queue,
+        pipeline: wgpu::ComputePipeline
+    }
+
+    static GPU: OnceLock<Option<Gpu>> = OnceLock::new();
+
+    /// Opening the adapter takes long, so it happens before offloads are timed.
+    pub fn open_gpu() -> bool {
+        GPU.get_or_init(create_gpu).is_some()
+    }
+
+    fn create_gpu() -> Option<Gpu> {
+
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())).ok()?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default())).ok()?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("triplets"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into())
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("triplets"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None
+        });
+
+        Some(Gpu{device, queue, pipeline})
+    }
+
+    /// The sum of the last members of triplets, as tasks on CPU threads give, less precise.
+    pub fn iterate_on_gpu(triplets: &[super::Triplet], n_cycles: usize) -> Option<f64> {
+
+        let gpu = GPU.get_or_init(create_gpu).as_ref()?;
+
+        let members: Vec<u8> = triplets.iter()
+            .flat_map(|triplet| [triplet.0 as f32, triplet.1 as f32, triplet.2 as f32])
+            .flat_map(|member| member.to_le_bytes())
+            .collect();
+        let params: Vec<u8> = [n_cycles as u32, triplets.len() as u32, 0, 0].iter()
+            .flat_map(|param| param.to_le_bytes())
+            .collect();
+
+        let storage = gpu.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("triplets"),
+            contents: &members,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC
+        });
+        let uniform = gpu.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("params"),
+            contents: &params,
+            usage: wgpu::BufferUsages::UNIFORM
+        });
+        let readback = gpu.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: members.len() as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false
+        });
+        let bind_group = gpu.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("triplets"),
+            layout: &gpu.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {binding: 0, resource: storage.as_entire_binding()},
+                wgpu::BindGroupEntry {binding: 1, resource: uniform.as_entire_binding()}
+            ]
+        });
+
+        let mut encoder = gpu.device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&gpu.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(triplets.len().div_ceil(WORKGROUP_SIZE) as u32, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&storage, 0, &readback, 0, members.len() as u64);
+        gpu.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| {});
+        gpu.device.poll(wgpu::PollType::Wait).ok()?;
+
+        let sum = slice.get_mapped_range()
+            .chunks_exact(12)
+            .map(|triplet| f32::from_le_bytes([triplet[8], triplet[9], triplet[10], triplet[11]]) as f64)
+            .sum();
+
+        Some(sum)
+    }
+}
+
+#[cfg(not(all(feature = "wgpu", not(target_arch = "wasm32"))))]
+mod device {
+
+    pub fn open_gpu() -> bool {
+        false
+    }
+
+    pub fn iterate_on_gpu(_triplets: &[super::Triplet], _n_cycles: usize) -> Option<f64> {
+        None
+    }
+}
+
+
+// Displaying offload
+
+pub fn print_offload(report: &Report) {
+
+    if report.get_metadata("Workload").map(|name| name.as_str()) != Some(WorkloadKind::Gpu.get_name()) {
+        return;
+    }
+
+    if report.observations.iter().all(|obs| obs.get_offload_duration().is_none()) {
+        warn!("Nothing offloaded, as the build lacks the wgpu feature or no GPU adapter was found");
+        return;
+    }
+
+    for obs in &report.observations {
+        if let Some(offload_duration) = obs.get_offload_duration() {
+            info!("Work of {} tasks: {} ms on CPU threads, {} ms offloaded to the GPU in single precision",
+                  obs.count_tasks(), obs.get_total_duration(), offload_duration);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn offload_covers_the_triplets_of_every_task() {
+        let triplets = collect_triplets(4, 42);
+        assert_eq!(triplets.len(), 4*batch::get_n_triplets());
+        assert_eq!(triplets[1], rng::seed_triplet(rng::get_rng_kind(), derive_task_seed(42, 4, 1)));
+    }
+}
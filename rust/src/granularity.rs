@@ -0,0 +1,125 @@
+// * * ** *** ***** ******** ************* *********************
+// Studying how chunk granularity affects work stealing
+// * * ** *** ***** ******** ************* *********************
+
+use rayon::prelude::*;
+
+use super::*;
+
+
+// Splitting work into chunks
+
+pub const DEFAULT_CHUNK_POWER_MAX: usize = 16;
+pub const CHUNK_POWER_LIMIT: usize = 24;
+
+const GRANULARITY_REPEATS: usize = 3;
+
+/// Cycles of every chunk, the first ones taking one more when the total does not divide evenly.
+fn split_cycles(n_cycles: usize, n_chunks: usize) -> Vec<usize> {
+    (0..n_chunks)
+        .map(|chunk_idx| n_cycles/n_chunks + if chunk_idx < n_cycles % n_chunks {1} else {0})
+        .collect()
+}
+
+/// Rayon splits the chunks between its workers and lets idle ones steal from busy ones,
+/// so few chunks leave workers idle and many make stealing and joining cost more.
+fn run_chunks(chunk_cycles: &[usize], seed: u64) -> f64 {
+    chunk_cycles.par_iter()
+        .enumerate()
+        .map(|(chunk_idx, n_cycles)| {
+            let triplet = rng::seed_triplet(rng::get_rng_kind(), derive_task_seed(seed, chunk_cycles.len(), chunk_idx));
+            iterate(triplet, *n_cycles)
+        })
+        .sum()
+}
+
+/// The shortest of a few runs, as a run can only be slowed down by noise.
+fn time_chunks(chunk_cycles: &[usize], seed: u64) -> TaskDuration {
+    (0..GRANULARITY_REPEATS)
+        .map(|_| {
+            let watch = Stopwatch::start();
+            let sum = run_chunks(chunk_cycles, seed);
+            trace!(n_chunks = chunk_cycles.len(), sum, "Chunks completed");
+            TaskDuration::from_nanos(watch.elapsed_nanos())
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+
+// Displaying the study
+
+fn create_granularity_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Chunks", 8, Align::Right, 0),
+        Column::create("Cycles in a chunk", 17, Align::Right, 0),
+        Column::create("Duration, ms", 12, Align::Right, 0),
+        Column::create("Relative to one chunk", 21, Align::Right, 0)
+    ], colored)
+}
+
+pub fn run_granularity(args: &Args) -> i32 {
+
+    let (n_cycles, power_max, seed) = (args.get_n_cycles(), args.get_chunk_power_max(), args.get_seed());
+
+    info!("Splitting {} cycles into 1 to {} chunks on {} rayon workers",
+          format_count(n_cycles), format_count(1 << power_max), rayon::current_num_threads());
+
+    let table = create_granularity_table(args.get_colored());
+    table.print_header();
+
+    let mut single_duration = TaskDuration::ZERO;
+    let mut best: Option<(usize, TaskDuration)> = None;
+
+    for power in 0..power_max + 1 {
+
+        if cancel::is_cancelled() {
+            break;
+        }
+
+        let n_chunks = 1usize << power;
+        let duration = time_chunks(&split_cycles(n_cycles, n_chunks), seed);
+
+        if power == 0 {
+            single_duration = duration;
+        }
+        if best.is_none_or(|(_, best_duration)| duration < best_duration) {
+            best = Some((n_chunks, duration));
+        }
+
+        table.print_row(&vec![
+            Cell::Plain(format_count(n_chunks)),
+            Cell::Plain(format_count(n_cycles/n_chunks)),
+            Cell::Plain(duration.to_string()),
+            Cell::Plain(format!("{:.2}", duration.as_millis_f64()/single_duration.as_millis_f64().max(1.0)))
+        ]);
+    }
+
+    table.print_footer();
+
+    if let Some((n_chunks, duration)) = best {
+        info!("Shortest with {} chunks: {} ms", format_count(n_chunks), duration);
+    }
+
+    EXIT_SUCCESS
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn chunks_add_up_to_the_total() {
+        assert_eq!(split_cycles(10, 4), vec![3, 3, 2, 2]);
+        assert_eq!(split_cycles(3, 4), vec![1, 1, 1, 0]);
+        assert_eq!(split_cycles(1 << 20, 1 << 16).iter().sum::<usize>(), 1 << 20);
+    }
+
+    #[test]
+    fn chunks_iterate_as_tasks_do() {
+        let triplet = rng::seed_triplet(rng::get_rng_kind(), derive_task_seed(7, 1, 0));
+        assert_eq!(run_chunks(&[1000], 7), iterate(triplet, 1000));
+    }
+}
@@ -0,0 +1,212 @@
+// * * ** *** ***** ******** ************* *********************
+// Tracking results of runs over time
+// * * ** *** ***** ******** ************* *********************
+
+use std::fs;
+use std::fs::OpenOptions;
+
+use super::*;
+
+
+// Keeping a summary of each run
+
+const HISTORY_HEADER: &str = "Commit,Date,Label,Tasks,Total duration,Profit\n";
+
+struct HistoryEntry {
+    commit: String,
+    date: String,
+    label: String,
+    n_tasks: usize,
+    total_duration: TaskDuration,
+    profit: f64
+}
+
+impl HistoryEntry {
+
+    fn parse(line: &str) -> Option<HistoryEntry> {
+
+        let fields = split_csv_line(line);
+
+        if fields.len() < 6 {
+            return None;
+        }
+
+        Some(HistoryEntry {
+            commit: fields[0].to_string(),
+            date: fields[1].to_string(),
+            label: fields[2].to_string(),
+            n_tasks: fields[3].parse().ok()?,
+            total_duration: fields[4].parse().ok()?,
+            profit: fields[5].parse().ok()?
+        })
+    }
+}
+
+fn format_history_entries(report: &Report) -> String {
+
+    let commit = report.get_metadata("Commit").cloned().unwrap_or_default();
+    let date = report.get_metadata("Started").cloned().unwrap_or_default();
+    let label = report.get_metadata("Label").cloned().unwrap_or_default();
+
+    let mut formatted_entries: String = "".to_string();
+
+    for obs in &report.observations {
+        formatted_entries += &format!("{},{},{},{},{},{:.4}\n",
+                                      quote_csv(&commit),
+                                      quote_csv(&date),
+                                      quote_csv(&label),
+                                      obs.count_tasks(),
+                                      obs.get_total_duration(),
+                                      obs.get_concurrency_profit());
+    }
+
+    formatted_entries
+}
+
+pub fn append_history(history_file_path: &String, report: &Report) {
+
+    if history_file_path.is_empty() {
+        return;
+    }
+
+    match OpenOptions::new().create(true).append(true).open(Path::new(history_file_path)) {
+        Ok(mut history_file) => {
+            if history_file.metadata().map_or(true, |metadata| metadata.len() == 0) {
+                history_file.write_all(HISTORY_HEADER.as_bytes()).unwrap();
+            }
+            history_file.write_all(format_history_entries(report).as_bytes()).unwrap();
+        }
+        Err(e) => {
+            panic!("Error while opening a history file: {}", e);
+        }
+    }
+}
+
+
+// Rendering trends
+
+fn load_history(history_file_path: &String) -> Vec<HistoryEntry> {
+    match fs::read_to_string(Path::new(history_file_path)) {
+        Ok(text) => text.lines().skip(1).filter_map(HistoryEntry::parse).collect(),
+        Err(e) => {
+            panic!("Error while reading a history file: {}", e);
+        }
+    }
+}
+
+fn list_task_counts(history: &[HistoryEntry]) -> Vec<usize> {
+    let mut task_counts: Vec<usize> = history.iter().map(|entry| entry.n_tasks).collect();
+    task_counts.sort();
+    task_counts.dedup();
+    task_counts
+}
+
+fn create_trend_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Date", 20, Align::Left, 0),
+        Column::create("Commit", 10, Align::Left, 2),
+        Column::create("Label", 24, Align::Left, 1),
+        Column::create("Total duration", 15, Align::Right, 0),
+        Column::create("Change", 7, Align::Right, 3),
+        Column::create("Profit", 7, Align::Right, 0)
+    ], colored)
+}
+
+fn format_change(total_duration: TaskDuration, previous_duration: Option<TaskDuration>) -> (String, f64) {
+    match previous_duration.and_then(|previous| total_duration.ratio(previous)) {
+        Some(ratio) => {
+            let change = ratio - 1.0;
+            (format_percent_cell(change), change)
+        }
+        None => ("".to_string(), 0.0)
+    }
+}
+
+const REGRESSION_THRESHOLD: f64 = 0.1;
+
+/// A run regresses when it is slower than the previous one beyond the threshold.
+fn print_trend(history: &[HistoryEntry], n_tasks: usize, colored: bool) -> bool {
+
+    let table = create_trend_table(colored);
+    let mut previous_duration: Option<TaskDuration> = None;
+    let mut latest_change = 0.0;
+
+    println!("\nTasks: {}", n_tasks);
+    table.print_header();
+
+    for entry in history.iter().filter(|entry| entry.n_tasks == n_tasks) {
+        let (change_text, change) = format_change(entry.total_duration, previous_duration);
+        table.print_row(&vec![
+            Cell::Plain(entry.date.to_string()),
+            Cell::Plain(entry.commit.to_string()),
+            Cell::Plain(entry.label.chars().take(24).collect()),
+            Cell::Plain(entry.total_duration.to_string()),
+            Cell::Signed(change_text, -change),
+            Cell::Signed(format_percent_cell(entry.profit), entry.profit)
+        ]);
+        previous_duration = Some(entry.total_duration);
+        latest_change = change;
+    }
+
+    table.print_footer();
+
+    latest_change > REGRESSION_THRESHOLD
+}
+
+/// Tells whether the latest run regressed for any of the numbers of tasks.
+pub fn render_trend(history_file_path: &String, task_counts: &[usize], colored: bool) -> bool {
+
+    let history = load_history(history_file_path);
+
+    let selected_task_counts = if task_counts.is_empty() {
+        list_task_counts(&history)
+    } else {
+        task_counts.to_vec()
+    };
+
+    let mut regressed = false;
+
+    for n_tasks in selected_task_counts {
+        regressed |= print_trend(&history, n_tasks, colored);
+    }
+
+    regressed
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::{assert_golden, FakeWorkload};
+
+    #[test]
+    fn history_entries_match_golden_file() {
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=4);
+        assert_golden("history.csv", &(HISTORY_HEADER.to_string() + &format_history_entries(&report)));
+    }
+
+    #[test]
+    fn history_entries_read_back_as_written() {
+
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=2);
+        let entries: Vec<HistoryEntry> = format_history_entries(&report).lines()
+            .filter_map(HistoryEntry::parse)
+            .collect();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].commit, "abc1234");
+        assert_eq!(entries[1].n_tasks, 2);
+        assert_eq!(entries[1].total_duration, TaskDuration::from_millis(101));
+        assert_eq!(list_task_counts(&entries), vec![1, 2]);
+    }
+
+    #[test]
+    fn change_is_relative_to_previous_run() {
+        let (text, change) = format_change(TaskDuration::from_millis(110), Some(TaskDuration::from_millis(100)));
+        assert_eq!(text, "10%");
+        assert!((change - 0.1).abs() < 1e-9);
+        assert_eq!(format_change(TaskDuration::from_millis(110), None).0, "");
+        assert_eq!(format_change(TaskDuration::from_millis(110), Some(TaskDuration::ZERO)).0, "");
+    }
+}
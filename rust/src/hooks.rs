@@ -0,0 +1,98 @@
+// * * ** *** ***** ******** ************* *********************
+// Calling back embedders while observing
+// * * ** *** ***** ******** ************* *********************
+
+use std::sync::OnceLock;
+
+use embed::{ObservationRecord, TaskRecord};
+
+use super::*;
+
+
+// Hooks
+
+/// Callbacks of the measurement engine, so embedders stream results into their own dashboards.
+/// Tasks complete on worker threads, so hooks are called concurrently.
+pub trait ObservationHooks: Send + Sync {
+
+    fn on_observation_start(self: &Self, _n_tasks: usize) {}
+
+    /// Tasks are timed from the start of their observation, as it is still running.
+    fn on_task_complete(self: &Self, _task: &TaskRecord) {}
+
+    /// Retried and repeated observations are reported each, not just the ones kept.
+    fn on_observation_complete(self: &Self, _obs: &ObservationRecord) {}
+}
+
+
+// Registering hooks
+
+static HOOKS: OnceLock<Box<dyn ObservationHooks>> = OnceLock::new();
+
+/// Hooks are registered once, before measuring, as other global settings are.
+pub fn set_hooks(hooks: Box<dyn ObservationHooks>) {
+    let _ = HOOKS.set(hooks);
+}
+
+
+// Calling hooks
+
+/// Records are only made when hooks are registered, so the CLI pays nothing for them.
+pub fn notify_observation_start(n_tasks: usize) {
+    if let Some(hooks) = HOOKS.get() {
+        hooks.on_observation_start(n_tasks);
+    }
+}
+
+pub fn notify_task_complete(n_tasks: usize, observation_start: Timestamp, task: &Task) {
+    if let Some(hooks) = HOOKS.get() {
+        hooks.on_task_complete(&TaskRecord::create_since(n_tasks, observation_start, task));
+    }
+}
+
+pub fn notify_observation_complete(obs: &Observation) {
+    if let Some(hooks) = HOOKS.get() {
+        hooks.on_observation_complete(&ObservationRecord::create(obs));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use testing::FakeWorkload;
+
+    /// Hooks are global, so only observations of a number of tasks no other test takes are counted.
+    const HOOKED_TASKS: usize = 13;
+
+    static TASKS_COMPLETED: AtomicUsize = AtomicUsize::new(0);
+    static OBSERVATIONS_COMPLETED: AtomicUsize = AtomicUsize::new(0);
+
+    struct CountingHooks;
+
+    impl ObservationHooks for CountingHooks {
+
+        fn on_task_complete(self: &Self, task: &TaskRecord) {
+            if task.n_tasks == HOOKED_TASKS {
+                TASKS_COMPLETED.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        fn on_observation_complete(self: &Self, obs: &ObservationRecord) {
+            if obs.n_tasks == HOOKED_TASKS {
+                OBSERVATIONS_COMPLETED.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    #[test]
+    fn registered_hooks_fire() {
+        set_hooks(Box::new(CountingHooks));
+        FakeWorkload::create(2, 4, 100).observe(HOOKED_TASKS);
+        assert_eq!(TASKS_COMPLETED.load(Ordering::Relaxed), 13);
+        assert_eq!(OBSERVATIONS_COMPLETED.load(Ordering::Relaxed), 1);
+    }
+}
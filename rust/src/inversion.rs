@@ -0,0 +1,258 @@
+// * * ** *** ***** ******** ************* *********************
+// Demonstrating priority inversion
+// * * ** *** ***** ******** ************* *********************
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use super::*;
+
+
+// Roles of tasks
+
+const N_HIGH_TASKS: usize = 2;
+const MEDIUM_CYCLES_FACTOR: usize = 4;
+
+/// The low task takes the lock first, the medium ones never need it, and the high ones
+/// come last and wait for it. Nice values are only raised, which needs no privileges.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Role {
+    Low,
+    Medium,
+    High
+}
+
+impl Role {
+
+    fn get_name(self: &Self) -> &'static str {
+        match self {
+            Role::Low => "Low",
+            Role::Medium => "Medium",
+            Role::High => "High"
+        }
+    }
+
+    fn get_nice(self: &Self) -> i32 {
+        match self {
+            Role::Low => platform::NICE_MAX,
+            Role::Medium => 10,
+            Role::High => 0
+        }
+    }
+}
+
+/// Moments of a task since the scenario started, with when it asked for the lock,
+/// got it, and let it go, if it needed the lock at all.
+struct Span {
+    role: Role,
+    start: TaskDuration,
+    lock_wanted: Option<TaskDuration>,
+    lock_acquired: Option<TaskDuration>,
+    lock_released: Option<TaskDuration>,
+    finish: TaskDuration
+}
+
+
+// Running the scenario
+
+fn run_role(role: Role, n_cycles: usize, lock: Option<(&Mutex<()>, &AtomicBool)>, watch: &SystemTime) -> Span {
+
+    if let Err(error) = platform::apply_nice(role.get_nice()) {
+        warn!("{} priority task runs at the default priority: {}", role.get_name(), error);
+    }
+
+    let start = read_elapsed(watch);
+    let triplet = (0.5, 0.25, 0.75);
+
+    let (lock_wanted, lock_acquired, lock_released) = match lock {
+        Some((lock, held)) => {
+            let lock_wanted = read_elapsed(watch);
+            let guard = lock.lock().unwrap();
+            let lock_acquired = read_elapsed(watch);
+            held.store(true, Ordering::Release);
+            iterate(triplet, n_cycles);
+            // Taken before unlocking, as the low task may wait long for a CPU right after
+            let lock_released = read_elapsed(watch);
+            drop(guard);
+            (Some(lock_wanted), Some(lock_acquired), Some(lock_released))
+        }
+        None => {
+            iterate(triplet, n_cycles);
+            (None, None, None)
+        }
+    };
+
+    Span{role, start, lock_wanted, lock_acquired, lock_released, finish: read_elapsed(watch)}
+}
+
+/// The medium tasks start once the low one holds the lock, and the high ones after them,
+/// so the medium ones keep the lock holder off the CPUs the high ones wait for.
+fn run_scenario(n_cycles: usize, n_medium_tasks: usize) -> Vec<Span> {
+
+    let lock = Mutex::new(());
+    let held = AtomicBool::new(false);
+    let watch = SystemTime::now();
+
+    thread::scope(|scope| {
+
+        let mut handles = Vec::with_capacity(1 + n_medium_tasks + N_HIGH_TASKS);
+        let (lock, held, watch) = (&lock, &held, &watch);
+
+        handles.push(platform::create_worker_builder("conctest-low".to_string()).spawn_scoped(scope, move || {
+            run_role(Role::Low, n_cycles, Some((lock, held)), watch)
+        }).expect("Spawning a worker failed"));
+
+        while !held.load(Ordering::Acquire) {
+            thread::yield_now();
+        }
+
+        for task_idx in 0..n_medium_tasks {
+            let name = format!("conctest-medium-{}", task_idx + 1);
+            handles.push(platform::create_worker_builder(name).spawn_scoped(scope, move || {
+                run_role(Role::Medium, MEDIUM_CYCLES_FACTOR*n_cycles, None, watch)
+            }).expect("Spawning a worker failed"));
+        }
+
+        for task_idx in 0..N_HIGH_TASKS {
+            let name = format!("conctest-high-{}", task_idx + 1);
+            handles.push(platform::create_worker_builder(name).spawn_scoped(scope, move || {
+                run_role(Role::High, n_cycles, Some((lock, held)), watch)
+            }).expect("Spawning a worker failed"));
+        }
+
+        handles.into_iter().map(|handle| handle.join().expect("A task panicked")).collect()
+    })
+}
+
+
+// Displaying the timeline
+
+/// From the first high task asking for the lock until the low task lets it go, high tasks
+/// wait for a task that medium ones keep from running.
+fn get_inversion_window(spans: &[Span]) -> Option<(TaskDuration, TaskDuration)> {
+
+    let released = spans.iter().find(|span| span.role == Role::Low)?.lock_released?;
+    let wanted = spans.iter()
+        .filter(|span| span.role == Role::High)
+        .filter_map(|span| span.lock_wanted)
+        .min()?;
+
+    if wanted < released {Some((wanted, released))} else {None}
+}
+
+fn is_within(moment: usize, from: usize, to: usize) -> bool {
+    moment >= from && moment < to.max(from + 1)
+}
+
+/// Holding the lock is drawn solid, waiting for it light, and running without it shaded.
+fn format_span_bar(span: &Span, total_duration: TaskDuration) -> String {
+
+    let column = |moment: TaskDuration| scale_to_schedule(moment, total_duration);
+    let (start, finish) = (column(span.start), column(span.finish).max(column(span.start) + 1));
+
+    (0..SCHEDULE_WIDTH).map(|idx| {
+        match (span.lock_wanted, span.lock_acquired, span.lock_released) {
+            _ if !is_within(idx, start, finish) => ' ',
+            (Some(wanted), Some(acquired), _) if is_within(idx, column(wanted), column(acquired)) &&
+                                                  column(acquired) > column(wanted) => '░',
+            (_, Some(acquired), Some(released)) if is_within(idx, column(acquired), column(released)) => '█',
+            _ => '▒'
+        }
+    }).collect()
+}
+
+fn format_window_bar(window: (TaskDuration, TaskDuration), total_duration: TaskDuration) -> String {
+    let (from, to) = (scale_to_schedule(window.0, total_duration), scale_to_schedule(window.1, total_duration));
+    (0..SCHEDULE_WIDTH).map(|idx| if is_within(idx, from, to) {'^'} else {' '}).collect()
+}
+
+fn get_lock_hold(spans: &[Span], role: Role) -> Option<TaskDuration> {
+    spans.iter()
+        .filter(|span| span.role == role)
+        .filter_map(|span| Some(span.lock_released? - span.lock_acquired?))
+        .max()
+}
+
+fn print_timeline(spans: &[Span]) {
+
+    let total_duration = spans.iter().map(|span| span.finish).max().unwrap_or_default();
+
+    println!("\nTasks: {}, total duration: {} ms", spans.len(), total_duration);
+
+    for span in spans {
+        println!("{:>6} {:>3} |{}| {:>6} ms",
+                 span.role.get_name(), span.role.get_nice(), format_span_bar(span, total_duration),
+                 span.finish - span.start);
+    }
+
+    match get_inversion_window(spans) {
+        Some(window) => {
+            println!("{:>10} |{}| {:>6} ms", "Inversion", format_window_bar(window, total_duration), window.1 - window.0);
+            println!("\n█ holding the lock, ░ waiting for it, ▒ running without it");
+            info!("High priority tasks waited {} ms for the lock, held by the low priority task for {} ms, \
+                   while a high priority one holds it for {} ms",
+                  window.1 - window.0,
+                  get_lock_hold(spans, Role::Low).unwrap_or_default(),
+                  get_lock_hold(spans, Role::High).unwrap_or_default());
+        }
+        None => info!("No inversion: the low priority task let the lock go before high priority ones asked for it")
+    }
+}
+
+pub fn run_inversion(args: &Args) -> i32 {
+
+    let n_medium_tasks = if args.get_tasks_max() > 0 {args.get_tasks_max()} else {count_cpus()};
+
+    info!("Priority inversion: a low priority task holds a lock {} high priority tasks need, \
+           while {} medium priority tasks run {} cycles each",
+          N_HIGH_TASKS, n_medium_tasks, format_count(MEDIUM_CYCLES_FACTOR*args.get_n_cycles()));
+
+    print_timeline(&run_scenario(args.get_n_cycles(), n_medium_tasks));
+
+    EXIT_SUCCESS
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn create_span(role: Role, moments: [u64; 4]) -> Span {
+        let [start, acquired, released, finish] = moments.map(TaskDuration::from_millis);
+        let locked = role != Role::Medium;
+        Span {
+            role,
+            start,
+            lock_wanted: if locked {Some(start)} else {None},
+            lock_acquired: if locked {Some(acquired)} else {None},
+            lock_released: if locked {Some(released)} else {None},
+            finish
+        }
+    }
+
+    #[test]
+    fn inversion_lasts_until_the_low_task_lets_the_lock_go() {
+        let spans = vec![create_span(Role::Low, [0, 0, 50, 50]),
+                         create_span(Role::Medium, [1, 0, 0, 40]),
+                         create_span(Role::High, [2, 50, 60, 60])];
+        assert_eq!(get_inversion_window(&spans),
+                   Some((TaskDuration::from_millis(2), TaskDuration::from_millis(50))));
+        assert_eq!(get_lock_hold(&spans, Role::Low), Some(TaskDuration::from_millis(50)));
+        assert!(get_inversion_window(&[create_span(Role::Low, [0, 0, 5, 5]),
+                                       create_span(Role::High, [10, 10, 20, 20])]).is_none());
+    }
+
+    #[test]
+    fn bars_tell_waiting_from_holding() {
+        let total_duration = TaskDuration::from_millis(60);
+        let bar = format_span_bar(&create_span(Role::High, [0, 30, 60, 60]), total_duration);
+        assert_eq!(bar, "░".repeat(30) + &"█".repeat(30));
+        let bar = format_span_bar(&create_span(Role::Medium, [30, 0, 0, 60]), total_duration);
+        assert_eq!(bar, " ".repeat(30) + &"▒".repeat(30));
+        let window = (TaskDuration::from_millis(10), TaskDuration::from_millis(20));
+        assert_eq!(format_window_bar(window, total_duration),
+                   " ".repeat(10) + &"^".repeat(10) + &" ".repeat(40));
+    }
+}
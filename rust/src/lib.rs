@@ -0,0 +1,15 @@
+// * * ** *** ***** ******** ************* *********************
+// Loading the measurement engine as a library for bindings
+// * * ** *** ***** ******** ************* *********************
+
+// The command line is only used by the executable
+#![allow(dead_code)]
+
+// The api module bindings build on comes along
+include!("main.rs");
+
+#[cfg(feature = "python")]
+mod python;
+
+#[cfg(target_arch = "wasm32")]
+mod wasm;
@@ -7,25 +7,114 @@
 use std::env;
 use std::panic;
 use std::time::*;
-use num_cpus;
-use rand;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 use thousands::Separable;
 use regex::Regex;
-use crossbeam::ScopedJoinHandle;
 use std::path::Path;
 use std::fs::File;
 use std::io::Write;
+use std::process;
+use std::thread;
+use std::thread::ScopedJoinHandle;
+use std::sync::OnceLock;
+use tracing::{debug, info, trace, warn};
+use tracing::level_filters::LevelFilter;
+use hdrhistogram::Histogram;
+
+mod aggregate;
+mod batch;
+mod cancel;
+mod capture;
+mod cgroup;
+mod dag;
+mod clock;
+mod compare;
+mod completions;
+mod compression;
+mod config;
+mod doctor;
+mod duration;
+#[allow(dead_code)] // Only used by bindings
+pub(crate) mod embed;
+mod environment;
+mod fanout;
+mod gpu;
+mod granularity;
+mod history;
+mod hooks;
+mod inversion;
+mod memory;
+mod merge;
+mod metrics;
+mod openloop;
+mod perf;
+mod placement;
+mod platform;
+mod query;
+mod registry;
+mod replay;
+mod rng;
+mod render;
+mod saved;
+mod selftest;
+mod sharing;
+mod simulate;
+mod smt;
+mod speedscope;
+mod soak;
+mod stats;
+mod stream;
+mod stress;
+mod suite;
+mod svg;
+mod table;
+mod template;
+mod tracy;
+mod utilization;
+mod workload;
+mod workspan;
+#[cfg(feature = "tui")]
+mod tui;
+#[cfg(test)]
+mod testing;
+
+/// What bindings in other crates build on, exported by the executable too, so nothing in it
+/// counts as unused.
+pub mod api {
+    pub use super::embed::{list_workloads, measure, run_workload};
+    pub use super::embed::{ObservationRecord, ReportRecords, RunConfig, TaskRecord};
+    pub use super::cancel::CancellationToken;
+    pub use super::hooks::{set_hooks, ObservationHooks};
+    pub use super::stream::{observe_stream, ObservationStream};
+}
 
+use table::{Align, Cell, Column, ConsoleTable};
+use environment::{EnvironmentSnapshot, PowerSettings};
+use openloop::Arrivals;
+use cgroup::CpuLimits;
+use clock::{Clock, ClockSource, Stopwatch};
+use compression::Compression;
+use config::RunConfig;
+use dag::DagShape;
+use duration::{TaskDuration, Timestamp};
+use platform::{QosClass, SchedPolicy, WorkerSettings};
+use placement::Placement;
+use query::{ReportQuery, SortKey};
+use rng::RngKind;
+use workload::{BufferData, WorkloadKind};
+use utilization::{CpuSampler, CpuUtilization};
+use memory::{AllocationCounter, Allocations, MemorySampler, MemoryUsage};
+use aggregate::TaskAggregate;
+use metrics::Metric;
 
-// Measuring time
 
-type TimeMs = i128;
-type TimeCompatibleInt = i128;
+// Measuring time
 
-fn now_ms(watch: &SystemTime) -> TimeMs {
+fn read_timestamp(watch: &SystemTime) -> Timestamp {
     match watch.duration_since(SystemTime::UNIX_EPOCH) {
         Ok(duration) => {
-            return duration.as_millis() as TimeMs;
+            Timestamp::from_nanos(duration.as_nanos())
         }
         Err(e) => {
             panic!("Someone stole my watch: {}!", e);
@@ -33,10 +122,10 @@ fn now_ms(watch: &SystemTime) -> TimeMs {
     }
 }
 
-fn duration_ms(watch: &SystemTime) -> TimeMs {
+fn read_elapsed(watch: &SystemTime) -> TaskDuration {
     match watch.elapsed() {
         Ok(elapsed) => {
-            return elapsed.as_millis() as TimeMs;
+            TaskDuration::from_nanos(elapsed.as_nanos())
         }
         Err(e) => {
             panic!("Follow the White Rabbit: {}!", e);
@@ -44,120 +133,632 @@ fn duration_ms(watch: &SystemTime) -> TimeMs {
     }
 }
 
+/// Task timestamps derive from a single monotonic epoch rather than from 
+/// per-thread reads of the system clock, which may disagree or step back.
+struct RunEpoch {
+    instant: Instant,
+    epoch: Timestamp
+}
+
+static RUN_EPOCH: OnceLock<RunEpoch> = OnceLock::new();
+
+fn get_run_epoch() -> &'static RunEpoch {
+    RUN_EPOCH.get_or_init(|| RunEpoch {
+        instant: Instant::now(),
+        epoch: read_timestamp(&SystemTime::now())
+    })
+}
+
+fn read_run_clock() -> Timestamp {
+    let epoch = get_run_epoch();
+    epoch.epoch + TaskDuration::from_nanos(epoch.instant.elapsed().as_nanos())
+}
+
+/// The clock of observations running real tasks.
+struct RunClock;
+
+impl Clock for RunClock {
+    fn now(self: &Self) -> Timestamp {
+        read_run_clock()
+    }
+}
+
+const CLOCK_SKEW_SAMPLES: usize = 1000;
+const CLOCK_SKEW_MAX: TaskDuration = TaskDuration::from_millis(1);
+
+/// The largest disagreement between the system clock read in a thread and the run clock,
+/// beyond the offset seen by the calling thread.
+fn measure_clock_skew(n_threads: usize) -> TaskDuration {
+
+    // Offsets go either way, so they are signed nanoseconds
+    let read_offset = || read_timestamp(&SystemTime::now()).as_nanos() as i128 - 
+                         read_run_clock().as_nanos() as i128;
+    let base_offset = read_offset();
+
+    thread::scope(|scope| {
+        let handles: Vec<_> = (0..n_threads.max(1)).map(|_| scope.spawn(move || {
+            (0..CLOCK_SKEW_SAMPLES)
+                .map(|_| (read_offset() - base_offset).abs())
+                .max()
+                .unwrap_or(0)
+        })).collect();
+        let skew = handles.into_iter().map(|handle| handle.join().unwrap_or(0)).max().unwrap_or(0);
+        TaskDuration::from_nanos(skew as u128)
+    })
+}
+
+fn format_utc_datetime(moment: Timestamp) -> String {
+
+    let epoch_sec = (moment.as_millis()/1000) as i64;
+    let (days, sec_of_day) = (epoch_sec.div_euclid(86400), epoch_sec.rem_euclid(86400));
+
+    // Converting days since 1970-01-01 to a civil date
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era/1460 + day_of_era/36524 - day_of_era/146096)/365;
+    let day_of_year = day_of_era - (365*year_of_era + year_of_era/4 - year_of_era/100);
+    let mp = (5*day_of_year + 2)/153;
+    let day = day_of_year - (153*mp + 2)/5 + 1;
+    let month = if mp < 10 {mp + 3} else {mp - 9};
+    let year = year_of_era + era*400 + if month <= 2 {1} else {0};
+
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year, month, day, sec_of_day/3600, sec_of_day%3600/60, sec_of_day%60)
+}
+
 
 // Managing observation outcomes
 
-#[derive(Copy, Clone)]
+#[derive(Clone, PartialEq)]
+enum TaskStatus {
+    Completed,
+    TimedOut,
+    Failed(String)
+}
+
+#[derive(Clone)]
 struct Task {
     idx:    usize,
-    start: TimeMs,
-    duration: TimeMs
+    series: usize,
+    start: Timestamp,
+    duration: TaskDuration,
+    status: TaskStatus,
+    thread_name: String,
+    thread_id: u64,
+    seed: u64,
+    branch_misses: Option<u64>,
+    allocations: Option<Allocations>
 }
 
 impl Task {
 
+    fn get_status(self: &Self) -> &TaskStatus {
+        &self.status
+    }
+
+    fn set_status(self: &mut Self, status: TaskStatus) {
+        self.status = status
+    }
+
+    fn is_timed_out(self: &Self) -> bool {
+        self.status == TaskStatus::TimedOut
+    }
+
+    fn is_failed(self: &Self) -> bool {
+        matches!(self.status, TaskStatus::Failed(_))
+    }
+
     fn get_idx(self: &Self) -> usize {
         self.idx
     }
 
-    fn get_start(self: &Self) -> TimeMs {
-        self.start
+    fn get_series(self: &Self) -> usize {
+        self.series
+    }
+
+    fn set_series(self: &mut Self, series: usize) {
+        self.series = series
+    }
+
+    fn get_thread_name(self: &Self) -> &String {
+        &self.thread_name
+    }
+
+    fn get_thread_id(self: &Self) -> u64 {
+        self.thread_id
+    }
+
+    fn set_thread(self: &mut Self, thread_name: String, thread_id: u64) {
+        self.thread_name = thread_name;
+        self.thread_id = thread_id
+    }
+
+    /// The seed the task derived its initial triplet from, zero for tasks of no seed.
+    fn get_seed(self: &Self) -> u64 {
+        self.seed
+    }
+
+    fn set_seed(self: &mut Self, seed: u64) {
+        self.seed = seed
+    }
+
+    /// Counted in builds with the perf feature only.
+    fn get_branch_misses(self: &Self) -> Option<u64> {
+        self.branch_misses
+    }
+
+    fn set_branch_misses(self: &mut Self, branch_misses: Option<u64>) {
+        self.branch_misses = branch_misses
+    }
+
+    /// Counted in builds with the alloc-count feature only.
+    fn get_allocations(self: &Self) -> Option<Allocations> {
+        self.allocations
+    }
+
+    fn set_allocations(self: &mut Self, allocations: Option<Allocations>) {
+        self.allocations = allocations
     }
 
-    fn recalc_start_relative(self: &mut Self, initial_moment: TimeMs) {
-        self.start -= initial_moment
+    fn get_start(self: &Self) -> Timestamp {
+        self.start
     }
 
-    fn get_finish(self: &Self) -> TimeMs {
+    fn get_finish(self: &Self) -> Timestamp {
         self.start + self.duration
     }
     
-    fn get_duration(self: &Self) -> TimeMs {
+    fn get_duration(self: &Self) -> TaskDuration {
         self.duration
     }
 
-    fn create(idx: usize, start: TimeMs, duration: TimeMs) -> Task {
-        Task{idx, start, duration}
+    fn create(idx: usize, start: Timestamp, duration: TaskDuration) -> Task {
+        Task{idx, series: 0, start, duration, status: TaskStatus::Completed,
+             thread_name: String::new(), thread_id: 0, seed: 0, branch_misses: None, allocations: None}
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum DeviationKind {
+    Sample,
+    Population
+}
+
+impl DeviationKind {
+
+    fn parse(s: &str) -> Option<DeviationKind> {
+        match s {
+            "" | "sample" => Some(DeviationKind::Sample),
+            "population" => Some(DeviationKind::Population),
+            _ => None
+        }
+    }
+
+    fn get_name(self: &Self) -> &'static str {
+        match self {
+            DeviationKind::Sample => "sample",
+            DeviationKind::Population => "population"
+        }
+    }
+}
+
+static DEVIATION_KIND: OnceLock<DeviationKind> = OnceLock::new();
+
+fn set_deviation_kind(deviation_kind: DeviationKind) {
+    let _ = DEVIATION_KIND.set(deviation_kind);
+}
+
+fn get_deviation_kind() -> DeviationKind {
+    *DEVIATION_KIND.get().unwrap_or(&DeviationKind::Sample)
+}
+
+/// The sample deviation applies Bessel's correction, dividing by n - 1 instead of n.
+fn calc_standard_deviation(durations: &[TaskDuration], deviation_kind: DeviationKind) -> f64 {
+
+    let n = durations.len() as f64;
+    let divisor = match deviation_kind {
+        DeviationKind::Sample => n - 1.0,
+        DeviationKind::Population => n
+    };
+
+    if divisor <= 0.0 {
+        return 0.0;
+    }
+
+    let mean = durations.iter().map(|duration| duration.as_millis_f64()).sum::<f64>()/n;
+    let dispersion: f64 = durations.iter()
+        .map(|duration| (duration.as_millis_f64() - mean).powi(2))
+        .sum();
+
+    (dispersion/divisor).sqrt()
+}
+
+/// A series of tasks spawned together and joined before the next series starts.
+struct SeriesSpan {
+    idx: usize,
+    n_tasks: usize,
+    start: Timestamp,
+    finish: Timestamp,
+    sum_duration: TaskDuration
+}
+
+impl SeriesSpan {
+
+    fn get_duration(self: &Self) -> TaskDuration {
+        self.finish - self.start
+    }
+
+    /// The share of thread time idling until the slowest task of the series finishes.
+    fn get_idle_share(self: &Self) -> f64 {
+        let slot_duration = self.get_duration()*self.n_tasks;
+        self.sum_duration.ratio(slot_duration).map_or(0.0, |ratio| 1.0 - ratio)
     }
 }
 
+/// Running all the tasks one after another, each as fast as a single task running alone.
+fn calc_serial_duration(n_tasks: usize, task_duration_min: TaskDuration) -> TaskDuration {
+    task_duration_min*n_tasks
+}
+
+/// The share of thread time lost to running tasks concurrently rather than serially.
+fn calc_cost(sum_duration: TaskDuration, serial_duration: TaskDuration) -> f64 {
+    1.0 - serial_duration.as_millis_f64()/sum_duration.as_millis_f64()
+}
+
+/// The share of wall-clock time saved by running tasks concurrently rather than serially.
+fn calc_profit(total_duration: TaskDuration, serial_duration: TaskDuration) -> f64 {
+    1.0 - total_duration.as_millis_f64()/serial_duration.as_millis_f64()
+}
+
 struct Observation {
     tasks: Vec<Task>,
+    aggregate: Option<TaskAggregate>,
+    n_cycles: usize,
+    repeat_durations: Vec<TaskDuration>,
+    duration_interval: Option<(f64, f64)>,
+    profit_interval: Option<(f64, f64)>,
+    cpu_utilization: Option<CpuUtilization>,
+    memory_usage: Option<MemoryUsage>,
+    offload_duration: Option<TaskDuration>,
     concurrency_cost: f64,
-    concurrency_profit: f64
+    concurrency_profit: f64,
+    retries: usize
 }
 
+const OUTLIER_FACTOR: usize = 3;
+
 impl Observation {
 
+    fn get_retries(self: &Self) -> usize {
+        self.retries
+    }
+
+    fn set_retries(self: &mut Self, retries: usize) {
+        self.retries = retries
+    }
+
+    fn get_median_task_duration(self: &Self) -> TaskDuration {
+        if let Some(aggregate) = &self.aggregate {
+            return aggregate.get_percentile(50.0);
+        }
+        let mut durations: Vec<TaskDuration> = self.tasks.iter().map(|task| task.get_duration()).collect();
+        durations.sort();
+        durations[durations.len()/2]
+    }
+
+    /// Nearest-rank percentiles, as of start delays.
+    fn get_task_duration_percentile(self: &Self, percentile: f64) -> TaskDuration {
+        if let Some(aggregate) = &self.aggregate {
+            return aggregate.get_percentile(percentile);
+        }
+        let mut durations: Vec<TaskDuration> = self.tasks.iter().map(|task| task.get_duration()).collect();
+        durations.sort();
+        let rank = (percentile/100.0*durations.len() as f64).ceil() as usize;
+        durations.get(rank.max(1) - 1).copied().unwrap_or_default()
+    }
+
+    fn get_max_task_duration(self: &Self) -> TaskDuration {
+        match &self.aggregate {
+            Some(aggregate) => aggregate.get_max_task_duration(),
+            None => self.tasks.iter().map(|task| task.get_duration()).max().unwrap()
+        }
+    }
+
+    /// A task lasting several times longer than the median one hints at a disturbance.
+    fn is_outlier(self: &Self) -> bool {
+        let median_task_duration = self.get_median_task_duration();
+        !median_task_duration.is_zero() && 
+        self.get_max_task_duration() > median_task_duration*OUTLIER_FACTOR
+    }
+
+    fn needs_retry(self: &Self) -> bool {
+        self.is_timed_out() || self.count_failed_tasks() > 0 || self.is_outlier()
+    }
+
     fn register_task(self: &mut Self, task: Task) {
-        self.tasks[task.get_idx()] = task;    
+        match &mut self.aggregate {
+            Some(aggregate) => aggregate.add(&task),
+            None => {
+                let idx = task.get_idx();
+                self.tasks[idx] = task;
+            }
+        }
+    }
+
+    /// Folds tasks kept so far into an aggregate, as backends keeping all their tasks end.
+    fn aggregate_tasks(self: &mut Self) {
+        if self.aggregate.is_none() {
+            let mut aggregate = TaskAggregate::create();
+            self.tasks.drain(..).for_each(|task| aggregate.add(&task));
+            self.aggregate = Some(aggregate);
+        }
     }
 
     fn count_tasks(self: &Self) -> usize {
-        self.tasks.len()
+        match &self.aggregate {
+            Some(aggregate) => aggregate.count_tasks(),
+            None => self.tasks.len()
+        }
+    }
+
+    fn count_timed_out_tasks(self: &Self) -> usize {
+        match &self.aggregate {
+            Some(aggregate) => aggregate.count_timed_out_tasks(),
+            None => self.tasks.iter().filter(|task| task.is_timed_out()).count()
+        }
+    }
+
+    fn is_timed_out(self: &Self) -> bool {
+        self.count_timed_out_tasks() > 0
+    }
+
+    fn count_failed_tasks(self: &Self) -> usize {
+        match &self.aggregate {
+            Some(aggregate) => aggregate.count_failed_tasks(),
+            None => self.tasks.iter().filter(|task| task.is_failed()).count()
+        }
     }
 
-    fn get_earliest_start(self: &Self) -> TimeMs {
-        self.tasks.iter().map(|task| task.get_start()).min().unwrap()
+    fn get_earliest_start(self: &Self) -> Timestamp {
+        match &self.aggregate {
+            Some(aggregate) => aggregate.get_earliest_start(),
+            None => self.tasks.iter().map(|task| task.get_start()).min().unwrap()
+        }
     }
 
-    fn get_latest_finish(self: &Self) -> TimeMs {
-        self.tasks.iter().map(|task| task.get_finish()).max().unwrap()
+    fn get_latest_finish(self: &Self) -> Timestamp {
+        match &self.aggregate {
+            Some(aggregate) => aggregate.get_latest_finish(),
+            None => self.tasks.iter().map(|task| task.get_finish()).max().unwrap()
+        }
     }
 
-    fn recalc_tasks_relative_earliest_start(self: &mut Self) {
+    /// Tasks keep epoch timestamps, so schedules of observations and runs can be correlated.
+    fn to_relative(self: &Self, moment: Timestamp) -> TaskDuration {
+        moment - self.get_earliest_start()
+    }
 
-        let initial_moment = self.get_earliest_start();
+    fn get_total_duration(self: &Self) -> TaskDuration {
+        self.get_latest_finish() - self.get_earliest_start()
+    }
 
-        for task in &mut self.tasks {
-            task.recalc_start_relative(initial_moment);
+    /// Nothing unless some tasks counted their branch misses.
+    fn sum_branch_misses(self: &Self) -> Option<u64> {
+        if let Some(aggregate) = &self.aggregate {
+            return aggregate.sum_branch_misses();
         }
+        self.tasks.iter().filter_map(|task| task.get_branch_misses()).reduce(|sum, misses| sum + misses)
     }
 
-    fn get_total_duration(self: &Self) -> TimeMs {
-        (self.get_latest_finish() - self.get_earliest_start()) as TimeMs
+    /// Nothing unless some tasks counted their allocations.
+    fn sum_allocations(self: &Self) -> Option<Allocations> {
+        if let Some(aggregate) = &self.aggregate {
+            return aggregate.sum_allocations();
+        }
+        self.tasks.iter()
+            .filter_map(|task| task.get_allocations())
+            .reduce(|sum, allocations| Allocations {allocated: sum.allocated + allocations.allocated,
+                                                    freed: sum.freed + allocations.freed})
     }
 
-    fn sum_duration(self: &Self) -> TimeMs {
-        let mut sum: TimeMs = 0;
+    fn sum_duration(self: &Self) -> TaskDuration {
+        if let Some(aggregate) = &self.aggregate {
+            return aggregate.sum_duration();
+        }
+        let mut sum = TaskDuration::ZERO;
         self.tasks.iter().for_each(|task| sum += task.get_duration());
         sum    
     }
     
-    fn get_mean_task_duration(self: &Self) -> TimeMs {
-        self.sum_duration()/(self.count_tasks() as TimeCompatibleInt)       
+    fn get_mean_task_duration(self: &Self) -> TaskDuration {
+        self.sum_duration()/self.count_tasks()       
     }
     
-    fn get_standard_deviation(self: &Self) -> TimeMs {
-    
-        let mut dispersion: TimeMs = 0;
-        let mut deviation: TimeMs;
+    /// In milliseconds, taken from the aggregate of tasks when only that is kept.
+    fn calc_task_deviation(self: &Self) -> f64 {
+        match &self.aggregate {
+            Some(aggregate) => aggregate.get_standard_deviation(get_deviation_kind()),
+            None => {
+                let durations: Vec<TaskDuration> = self.tasks.iter().map(|task| task.get_duration()).collect();
+                calc_standard_deviation(&durations, get_deviation_kind())
+            }
+        }
+    }
 
-        let mean_task_duration = self.get_mean_task_duration();
+    fn get_standard_deviation(self: &Self) -> TaskDuration {
+        TaskDuration::from_millis_f64(self.calc_task_deviation())
+    }
     
+    fn get_series_spans(self: &Self) -> Vec<SeriesSpan> {
+
+        let mut spans: Vec<SeriesSpan> = Vec::new();
+
         for task in &self.tasks {
-            deviation = mean_task_duration - task.get_duration();
-            dispersion += deviation*deviation;
+            match spans.iter_mut().find(|span| span.idx == task.get_series()) {
+                Some(span) => {
+                    span.n_tasks += 1;
+                    span.start = span.start.min(task.get_start());
+                    span.finish = span.finish.max(task.get_finish());
+                    span.sum_duration += task.get_duration();
+                }
+                None => spans.push(SeriesSpan {
+                    idx: task.get_series(),
+                    n_tasks: 1,
+                    start: task.get_start(),
+                    finish: task.get_finish(),
+                    sum_duration: task.get_duration()
+                })
+            }
         }
-    
-        ((dispersion as f64).sqrt()/(self.count_tasks() as f64 - 1.0)) as TimeMs       
+
+        spans.sort_by_key(|span| span.idx);
+        spans
     }
-    
-    fn get_serial_duration(self: &mut Self, task_duration_min: TimeMs) -> TimeMs {
-        (self.count_tasks() as TimeCompatibleInt)*task_duration_min
+
+    fn get_cpu_utilization(self: &Self) -> Option<&CpuUtilization> {
+        self.cpu_utilization.as_ref()
+    }
+
+    fn set_cpu_utilization(self: &mut Self, cpu_utilization: Option<CpuUtilization>) {
+        self.cpu_utilization = cpu_utilization;
+    }
+
+    fn get_memory_usage(self: &Self) -> Option<MemoryUsage> {
+        self.memory_usage
+    }
+
+    fn set_memory_usage(self: &mut Self, memory_usage: Option<MemoryUsage>) {
+        self.memory_usage = memory_usage;
+    }
+
+    /// Duration of the same iterations offloaded to a GPU in single precision, by the gpu workload.
+    fn get_offload_duration(self: &Self) -> Option<TaskDuration> {
+        self.offload_duration
+    }
+
+    fn set_offload_duration(self: &mut Self, offload_duration: Option<TaskDuration>) {
+        self.offload_duration = offload_duration;
+    }
+
+    /// How many tasks ran at the same time on average.
+    fn get_parallelism(self: &Self) -> f64 {
+        self.sum_duration().ratio(self.get_total_duration()).unwrap_or(0.0)
+    }
+
+    /// An estimate of the shortest schedule: its longest task, or its work spread over all CPUs.
+    /// Durations of tasks are wall-clock ones, time slices of other tasks sharing a CPU
+    /// included, so oversubscribed observations may finish sooner than the estimate.
+    fn get_duration_bound(self: &Self, n_cpus: usize) -> TaskDuration {
+        self.get_max_task_duration().max(self.sum_duration()/n_cpus)
+    }
+
+    /// The estimated bound over the duration achieved, 100% at most, for a schedule as short
+    /// as the estimate or shorter.
+    fn get_scheduling_quality(self: &Self, n_cpus: usize) -> f64 {
+        self.get_duration_bound(n_cpus).ratio(self.get_total_duration()).unwrap_or(1.0).min(1.0)
+    }
+
+    /// The longest task over the mean one, 1 for tasks doing the same work at the same pace.
+    fn get_duration_imbalance(self: &Self) -> f64 {
+        self.get_max_task_duration().ratio(self.get_mean_task_duration()).unwrap_or(1.0)
+    }
+
+    /// Sweeps starts and finishes in time order, a finish coming first at the same moment.
+    /// Aggregated observations no longer know when their tasks ran, nor does any statistic of
+    /// their schedules.
+    fn count_concurrent_tasks_max(self: &Self) -> Option<usize> {
+
+        if self.aggregate.is_some() {
+            return None;
+        }
+
+        let mut events: Vec<(Timestamp, i32)> = self.tasks.iter()
+            .flat_map(|task| [(task.get_start(), 1), (task.get_finish(), -1)])
+            .collect();
+        events.sort();
+
+        let (mut running, mut running_max) = (0i32, 0i32);
+        for (_, change) in events {
+            running += change;
+            running_max = running_max.max(running);
+        }
+
+        Some(running_max as usize)
+    }
+
+    /// Delays of tasks starting after the earliest task of their series, which were spawned together.
+    fn get_start_delays(self: &Self) -> Vec<TaskDuration> {
+        let spans = self.get_series_spans();
+        self.tasks.iter()
+            .map(|task| match spans.iter().find(|span| span.idx == task.get_series()) {
+                Some(span) => task.get_start() - span.start,
+                None => TaskDuration::ZERO
+            })
+            .collect()
+    }
+
+    fn get_start_spread(self: &Self) -> Option<TaskDuration> {
+        match &self.aggregate {
+            Some(_) => None,
+            None => Some(self.get_start_delays().into_iter().max().unwrap_or_default())
+        }
+    }
+
+    fn get_start_delay_percentile(self: &Self, percentile: f64) -> Option<TaskDuration> {
+        if self.aggregate.is_some() {
+            return None;
+        }
+        let mut delays = self.get_start_delays();
+        delays.sort();
+        let rank = (percentile/100.0*delays.len() as f64).ceil() as usize;
+        Some(delays.get(rank.max(1) - 1).copied().unwrap_or_default())
+    }
+
+    /// The share of thread time idling in all series, which batching adds to the concurrency cost.
+    fn get_series_idle_share(self: &Self) -> Option<f64> {
+        if self.aggregate.is_some() {
+            return None;
+        }
+        let slot_duration: TaskDuration = self.get_series_spans().iter()
+            .map(|span| span.get_duration()*span.n_tasks)
+            .sum();
+        Some(self.sum_duration().ratio(slot_duration).map_or(0.0, |ratio| 1.0 - ratio))
+    }
+
+    /// Time between a series finishing and the next one starting, spent on joining and spawning.
+    fn get_series_gaps(self: &Self) -> Option<Vec<TaskDuration>> {
+        if self.aggregate.is_some() {
+            return None;
+        }
+        Some(self.get_series_spans().windows(2)
+            .map(|pair| pair[1].start - pair[0].finish)
+            .collect())
+    }
+
+    /// The standard deviation relative to the mean, comparable across durations of tasks.
+    fn get_variation_coefficient(self: &Self) -> f64 {
+        let mean = self.sum_duration().as_millis_f64()/self.count_tasks() as f64;
+        if mean > 0.0 {
+            self.calc_task_deviation()/mean
+        } else {
+            0.0
+        }
+    }
+
+    fn get_serial_duration(self: &mut Self, task_duration_min: TaskDuration) -> TaskDuration {
+        calc_serial_duration(self.count_tasks(), task_duration_min)
     }
 
     fn get_concurrency_cost(self: &Self) -> f64 {
         self.concurrency_cost
     }
 
-    fn calc_concurrency_cost(self: &mut Self, task_duration_min: TimeMs) -> f64 {
+    fn calc_concurrency_cost(self: &mut Self, task_duration_min: TaskDuration) -> f64 {
                 
-        let sum_duration = self.sum_duration() as f64;
-        let serial_duration = 
-            self.get_serial_duration(task_duration_min) as f64;
+        let serial_duration = self.get_serial_duration(task_duration_min);
 
-        self.concurrency_cost = 1.0 - serial_duration/sum_duration;
+        self.concurrency_cost = calc_cost(self.sum_duration(), serial_duration);
 
         self.concurrency_cost
     }
@@ -166,67 +767,178 @@ impl Observation {
         self.concurrency_profit
     }    
 
-    fn calc_concurrency_profit(self: &mut Self, task_duration_min: TimeMs) -> f64 {
+    fn get_speedup(self: &Self) -> f64 {
+        1.0/(1.0 - self.concurrency_profit)
+    }
+
+    /// The speedup per CPU the tasks could occupy, 100% for tasks sharing nothing but CPUs.
+    fn get_efficiency(self: &Self, n_cpus: usize) -> f64 {
+        self.get_speedup()/self.count_tasks().min(n_cpus).max(1) as f64
+    }
+
+    fn get_repeat_durations(self: &Self) -> &Vec<TaskDuration> {
+        &self.repeat_durations
+    }
+
+    fn set_repeat_durations(self: &mut Self, repeat_durations: Vec<TaskDuration>) {
+        self.repeat_durations = repeat_durations;
+    }
+
+    fn get_duration_interval(self: &Self) -> Option<(f64, f64)> {
+        self.duration_interval
+    }
+
+    fn get_profit_interval(self: &Self) -> Option<(f64, f64)> {
+        self.profit_interval
+    }
+
+    /// Profits of repeats are taken against the same baseline as the profit itself.
+    fn calc_intervals(self: &mut Self, task_duration_min: TaskDuration) {
+
+        let durations: Vec<f64> = self.repeat_durations.iter().map(|d| d.as_millis_f64()).collect();
+        let serial_duration = calc_serial_duration(self.count_tasks(), task_duration_min);
+        let profits: Vec<f64> = self.repeat_durations.iter()
+            .map(|d| calc_profit(*d, serial_duration))
+            .collect();
+        let seed = self.count_tasks() as u64;
+
+        self.duration_interval = stats::bootstrap_mean_interval(&durations, seed);
+        self.profit_interval = if !serial_duration.is_zero() {
+            stats::bootstrap_mean_interval(&profits, seed)
+        } else {
+            None
+        };
+    }
+
+    fn get_tasks_per_sec(self: &Self) -> f64 {
+        let total_duration = self.get_total_duration();
+        if !total_duration.is_zero() {
+            1000.0*self.count_tasks() as f64/total_duration.as_millis_f64()
+        } else {
+            0.0
+        }
+    }
+
+    fn get_cycles_per_sec(self: &Self) -> f64 {
+        self.get_tasks_per_sec()*self.n_cycles as f64
+    }
+
+    fn calc_concurrency_profit(self: &mut Self, task_duration_min: TaskDuration) -> f64 {
                 
-        let total_duration = self.get_total_duration() as f64;
-        let serial_duration = 
-            self.get_serial_duration(task_duration_min) as f64;
+        let serial_duration = self.get_serial_duration(task_duration_min);
 
-        self.concurrency_profit = 1.0 - total_duration/serial_duration;
+        self.concurrency_profit = calc_profit(self.get_total_duration(), serial_duration);
 
         self.concurrency_profit
     }
 
-    fn create(n_tasks: usize) -> Observation {
+    fn create(n_tasks: usize, n_cycles: usize) -> Observation {
 
         let mut obs = Observation {
             tasks: Vec::with_capacity(n_tasks),
+            aggregate: None,
+            n_cycles,
+            repeat_durations: Vec::new(),
+            duration_interval: None,
+            profit_interval: None,
+            cpu_utilization: None,
+            memory_usage: None,
+            offload_duration: None,
             concurrency_cost: 0f64, 
-            concurrency_profit: 0f64 
+            concurrency_profit: 0f64,
+            retries: 0
         };
 
         for idx in 0..n_tasks {
-            obs.tasks.push(Task::create(idx, 0, 0));
+            obs.tasks.push(Task::create(idx, Timestamp::EPOCH, TaskDuration::ZERO));
         }
 
         obs
     }   
+
+    /// Tasks registered are folded into the aggregate, which makes up the number of tasks.
+    fn create_aggregate(n_cycles: usize) -> Observation {
+        let mut obs = Observation::create(0, n_cycles);
+        obs.aggregate = Some(TaskAggregate::create());
+        obs
+    }
 }
 
 struct Report {
-    observations: Vec<Observation>    
+    metadata: Vec<(String, String)>,
+    observations: Vec<Observation>,
+    skipped: Vec<usize>
 }
 
 impl Report {
 
+    fn register_skipped(self: &mut Self, n_tasks: usize) {
+        if !self.skipped.contains(&n_tasks) {
+            self.skipped.push(n_tasks);
+            self.skipped.sort();
+        }
+    }
+
+    fn find_observation(self: &Self, n_tasks: usize) -> Option<&Observation> {
+        self.observations.iter().find(|obs| obs.count_tasks() == n_tasks)
+    }
+
+    fn set_metadata(self: &mut Self, key: &str, value: &str) {
+        match self.metadata.iter_mut().find(|(k, _)| k == key) {
+            Some(entry) => entry.1 = value.to_string(),
+            None => self.metadata.push((key.to_string(), value.to_string()))
+        }
+    }
+
+    fn get_metadata(self: &Self, key: &str) -> Option<&String> {
+        self.metadata.iter().find(|(k, _)| k == key).map(|(_, value)| value)
+    }
+
     fn count_observations(self: &Self) -> usize {
         self.observations.len()
     }
 
-    fn get_task_duration_min(self: &Self) -> TimeMs {
+    fn get_task_duration_min(self: &Self) -> TaskDuration {
         self.observations[0].get_total_duration()
     }
 
+    /// The median coefficient of variation, so a single disturbed observation does not dominate.
+    fn get_noise_score(self: &Self) -> f64 {
+
+        let mut coefficients: Vec<f64> = 
+            self.observations.iter().map(|obs| obs.get_variation_coefficient()).collect();
+
+        if coefficients.is_empty() {
+            return 0.0;
+        }
+
+        coefficients.sort_by(|a, b| a.total_cmp(b));
+        coefficients[coefficients.len()/2]
+    }
+
     fn register_observation(self: &mut Self, mut obs: Observation) {
         
         if self.count_observations() > 0 {
             let task_duration_min = self.get_task_duration_min();
             obs.calc_concurrency_cost(task_duration_min);
             obs.calc_concurrency_profit(task_duration_min);
+            obs.calc_intervals(task_duration_min);
+        } else {
+            let task_duration_min = obs.get_total_duration();
+            obs.calc_intervals(task_duration_min);
         }
 
-        obs.recalc_tasks_relative_earliest_start();
-
-        self.observations.push(obs);
-    }
-
-    fn get_observation(self: &Self, idx: usize) -> &Observation {
-        &(self.observations[idx])
+        let n_tasks = obs.count_tasks();
+        let idx = self.observations.partition_point(|other| other.count_tasks() < n_tasks);
+        self.skipped.retain(|skipped_n_tasks| *skipped_n_tasks != n_tasks);
+        self.observations.insert(idx, obs);
     }
 
     fn create(ntasks_max: usize) -> Report {
         Report {
-            observations: Vec::with_capacity(ntasks_max)
+            metadata: Vec::new(),
+            observations: Vec::with_capacity(ntasks_max),
+            skipped: Vec::new()
         }
     }
 }
@@ -236,12 +948,24 @@ impl Report {
 
 type Triplet = (f64, f64, f64);
 
-fn random_item() -> f64 {    
-    rand::random()
+fn random_item<R: Rng>(rng: &mut R) -> f64 {    
+    rng.gen()
+}
+
+fn random_triplet<R: Rng>(rng: &mut R) -> Triplet {
+    (random_item(rng), random_item(rng), random_item(rng))
 }
 
-fn random_triplet() -> Triplet {
-    (random_item(), random_item(), random_item())
+/// Every task of every observation gets its own reproducible seed.
+fn derive_task_seed(seed: u64, n_tasks: usize, task_idx: usize) -> u64 {
+
+    // Mixing bits with SplitMix64
+    let mut z = seed
+        .wrapping_add((n_tasks as u64).wrapping_mul(0x9E3779B97F4A7C15))
+        .wrapping_add((task_idx as u64 + 1).wrapping_mul(0xD1B54A32D192ED03));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
 }
 
 fn get_next_triplet(triplet: Triplet) -> Triplet {
@@ -249,14 +973,14 @@ fn get_next_triplet(triplet: Triplet) -> Triplet {
     let applicant = triplet.0 + triplet.1 - triplet.2;
 
     if applicant.abs() <= 1.0 {
-        return (triplet.1, triplet.2, applicant);
+        (triplet.1, triplet.2, applicant)
     } else {
-        return (triplet.1, triplet.2, 1.0/applicant);
+        (triplet.1, triplet.2, 1.0/applicant)
     }
 }
 
 fn approx_eq(f1: f64, f2: f64) -> bool {
-    return (f1 - f2).abs() < 1e-15
+    (f1 - f2).abs() < 1e-15
 }
 
 fn is_convergent(triplet: Triplet, next_triplet: Triplet) -> bool {
@@ -265,37 +989,182 @@ fn is_convergent(triplet: Triplet, next_triplet: Triplet) -> bool {
     approx_eq(triplet.2, next_triplet.2)
 }
 
-fn iterate(initial_triplet: Triplet, n_cycles: usize) -> f64 {
-    
-    let mut triplet = initial_triplet;
+const DEADLINE_CHECK_PERIOD: usize = 65536;
 
-    let mut prokukarek = false;
+fn is_deadline_passed(deadline: Option<Timestamp>) -> bool {
+    match deadline {
+        Some(deadline) => read_run_clock() >= deadline,
+        None => false
+    }
+}
 
-    for step in 0..n_cycles {
-    
-        let next_triplet = get_next_triplet(triplet);
+/// Tasks run all their cycles unless told to stop at convergence, making their work depend on data.
+#[derive(Copy, Clone, PartialEq)]
+enum ConvergenceMode {
+    AllCycles,
+    Stop
+}
 
-        if is_convergent(triplet, next_triplet) && !prokukarek {
-            print_convergency(initial_triplet, step, triplet.2);
-            prokukarek = true;
-        }
+impl ConvergenceMode {
 
-        triplet = next_triplet;
-    }    
+    fn parse(s: &str) -> ConvergenceMode {
+        if s == "stop" {ConvergenceMode::Stop} else {ConvergenceMode::AllCycles}
+    }
 
-    triplet.2
+    fn get_name(self: &Self) -> &'static str {
+        match self {
+            ConvergenceMode::AllCycles => "all cycles",
+            ConvergenceMode::Stop => "stop"
+        }
+    }
+
+    fn get_description(self: &Self) -> &'static str {
+        match self {
+            ConvergenceMode::AllCycles => "running all cycles",
+            ConvergenceMode::Stop => "stopping at convergence"
+        }
+    }
 }
 
-fn standard_task(task_idx: usize, n_cycles: usize) -> Task {     
-    let watch = SystemTime::now();
-    let start= now_ms(&watch);
-    iterate(random_triplet(), n_cycles);
-    Task::create(task_idx, start, duration_ms(&watch))
+static CONVERGENCE_MODE: OnceLock<ConvergenceMode> = OnceLock::new();
+
+fn set_convergence_mode(convergence_mode: ConvergenceMode) {
+    let _ = CONVERGENCE_MODE.set(convergence_mode);
+}
+
+fn get_convergence_mode() -> ConvergenceMode {
+    *CONVERGENCE_MODE.get().unwrap_or(&ConvergenceMode::AllCycles)
+}
+
+fn iterate_until(initial_triplet: Triplet, n_cycles: usize, deadline: Option<Timestamp>,
+                 convergence_mode: ConvergenceMode) -> Option<f64> {
+    
+    let mut triplet = initial_triplet;
+
+    let mut prokukarek = false;
+
+    for step in 0..n_cycles {
+
+        if step % DEADLINE_CHECK_PERIOD == 0 {
+            platform::check_stack();
+            if is_deadline_passed(deadline) {
+                return None;
+            }
+        }
+    
+        let next_triplet = get_next_triplet(triplet);
+
+        if is_convergent(triplet, next_triplet) && !prokukarek {
+            print_convergency(initial_triplet, step, triplet.2);
+            prokukarek = true;
+            if convergence_mode == ConvergenceMode::Stop {
+                return Some(next_triplet.2);
+            }
+        }
+
+        triplet = next_triplet;
+    }    
+
+    Some(triplet.2)
+}
+
+fn iterate(initial_triplet: Triplet, n_cycles: usize) -> f64 {
+    iterate_until(initial_triplet, n_cycles, None, ConvergenceMode::AllCycles).unwrap()
+}
+
+fn describe_panic(payload: &Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+const TIMER_OVERHEAD_SAMPLES: usize = 100000;
+const TIMER_OVERHEAD_SHARE_MAX: f64 = 0.01;
+
+static TIMER_OVERHEAD: OnceLock<u128> = OnceLock::new();
+
+/// Nanoseconds spent on timing and bookkeeping of a task having nothing to do.
+fn calibrate_timer_overhead() -> u128 {
+
+    let watch = Instant::now();
+
+    for idx in 0..TIMER_OVERHEAD_SAMPLES {
+        let task_watch = Stopwatch::start();
+        let start = read_run_clock();
+        let task = Task::create(idx, start, TaskDuration::from_nanos(task_watch.elapsed_nanos()));
+        std::hint::black_box(task);
+    }
+
+    watch.elapsed().as_nanos()/TIMER_OVERHEAD_SAMPLES as u128
+}
+
+fn get_timer_overhead() -> u128 {
+    *TIMER_OVERHEAD.get_or_init(calibrate_timer_overhead)
+}
+
+/// The timer overhead is subtracted from task durations, so instrumentation does not count as work.
+fn compensate_timer_overhead(elapsed_nanos: u128, timer_overhead: u128) -> TaskDuration {
+    TaskDuration::from_nanos(elapsed_nanos.saturating_sub(timer_overhead))
+}
+
+fn standard_task(task_idx: usize, n_cycles: usize, timeouts: Timeouts, 
+                 observation_start: Timestamp, task_seed: u64) -> Task {     
+    let input = workload::prepare_task(workload::get_workload_kind(), task_seed);
+    let branch_miss_counter = perf::BranchMissCounter::start();
+    let allocation_counter = AllocationCounter::start();
+    let watch = Stopwatch::start();
+    let start = read_run_clock();
+    let deadline = timeouts.get_task_deadline(start, observation_start);
+    // Inputs holding caches, as compiled regexes do, are dropped after a panic, never looked at again
+    let outcome = panic::catch_unwind(panic::AssertUnwindSafe(
+        || workload::run_task(&input, n_cycles, deadline, get_convergence_mode())));
+    let duration = compensate_timer_overhead(watch.elapsed_nanos(), get_timer_overhead());
+    let mut task = Task::create(task_idx, start, duration);
+    task.set_seed(task_seed);
+    task.set_branch_misses(branch_miss_counter.and_then(|counter| counter.finish()));
+    task.set_allocations(allocation_counter.and_then(|counter| counter.finish()));
+    match outcome {
+        Ok(Some(_)) => {}
+        Ok(None) => task.set_status(TaskStatus::TimedOut),
+        Err(payload) => task.set_status(TaskStatus::Failed(describe_panic(&payload)))
+    }
+    task
 }
 
 
 // Performing observations
 
+/// Limits of task and observation durations, zero stands for no limit.
+#[derive(Copy, Clone)]
+struct Timeouts {
+    task: TaskDuration,
+    observation: TaskDuration
+}
+
+impl Timeouts {
+
+    fn get_task_deadline(self: &Self, task_start: Timestamp, observation_start: Timestamp) -> Option<Timestamp> {
+
+        let task_deadline = if !self.task.is_zero() {Some(task_start + self.task)} else {None};
+        let observation_deadline = 
+            if !self.observation.is_zero() {Some(observation_start + self.observation)} else {None};
+
+        match (task_deadline, observation_deadline) {
+            (Some(task_deadline), Some(observation_deadline)) => 
+                Some(task_deadline.min(observation_deadline)),
+            (task_deadline, observation_deadline) => task_deadline.or(observation_deadline)
+        }
+    }
+
+    fn create(task: TaskDuration, observation: TaskDuration) -> Timeouts {
+        Timeouts{task, observation}
+    }
+}
+
 fn count_series(n_tasks: usize, series_size: usize) -> usize {
 
     let mut n_series = n_tasks/series_size;
@@ -307,405 +1176,4474 @@ fn count_series(n_tasks: usize, series_size: usize) -> usize {
     n_series
 }
 
-fn observe(n_tasks: usize, n_cycles: usize, series_size: usize) -> Observation {
+/// Workers are named after the observation, given by its number of tasks, and their task.
+fn format_worker_name(n_tasks: usize, task_idx: usize) -> String {
+    format!("conctest-w{}-{}", n_tasks, task_idx + 1)
+}
+
+/// A worker failing to spawn or to finish fails its task rather than the observation.
+fn join_worker(task_idx: usize, series_idx: usize, 
+               handle: std::io::Result<ScopedJoinHandle<Task>>) -> Task {
+
+    let outcome = match handle {
+        Ok(handle) => handle.join().map_err(|payload| describe_panic(&payload)),
+        Err(error) => Err(format!("spawning failed: {}", error))
+    };
+
+    outcome.unwrap_or_else(|error| {
+        let mut task = Task::create(task_idx, read_run_clock(), TaskDuration::ZERO);
+        task.set_series(series_idx);
+        task.set_status(TaskStatus::Failed(error));
+        task
+    })
+}
+
+fn observe(n_tasks: usize, n_cycles: usize, series_size: usize, timeouts: Timeouts, 
+           seed: u64) -> Observation {
+
+    let obs = if aggregate::is_aggregate_only() {
+        Observation::create_aggregate(n_cycles)
+    } else {
+        Observation::create(n_tasks, n_cycles)
+    };
+    let mut obs = observe_workload_into(obs, &RunClock, n_tasks, series_size, seed, 
+                                        &|task_idx, observation_start, task_seed| 
+                                            standard_task(task_idx, n_cycles, timeouts, observation_start, task_seed));
+
+    if workload::get_workload_kind() == WorkloadKind::Gpu {
+        obs.set_offload_duration(gpu::offload_observation(n_tasks, n_cycles, seed));
+    }
+
+    obs
+}
+
+/// Spawns and joins series of tasks of any workload, which gets the index of a task,
+/// the start of the observation on the clock, and the seed of the task.
+fn observe_workload<W>(clock: &dyn Clock, n_tasks: usize, n_cycles: usize, series_size: usize, 
+                       seed: u64, workload: &W) -> Observation 
+    where W: Fn(usize, Timestamp, u64) -> Task + Sync {
+    observe_workload_into(Observation::create(n_tasks, n_cycles), clock, n_tasks, series_size, seed, workload)
+}
+
+/// Tasks are registered with the observation given as their series are joined, so an
+/// aggregate one keeps no more tasks than a series has.
+fn observe_workload_into<W>(mut obs: Observation, clock: &dyn Clock, n_tasks: usize, series_size: usize, 
+                            seed: u64, workload: &W) -> Observation 
+    where W: Fn(usize, Timestamp, u64) -> Task + Sync {
+
+    hooks::notify_observation_start(n_tasks);
 
+    let observation_start = clock.now();
     let n_series = count_series(n_tasks, series_size);
     let mut count_tasks_series = 0usize;
     let mut task_idx = 0usize;
-    let mut handles: Vec<ScopedJoinHandle<Task>> = Vec::with_capacity(n_tasks); 
+    let cpu_sampler = CpuSampler::start();
+    let memory_sampler = MemorySampler::start();
 
-    for _ in 0..n_series { 
-        crossbeam::scope(|spawner| {
+    for series_idx in 0..n_series { 
+        if cancel::is_cancelled() {
+            break;
+        }
+        let series_watch = SystemTime::now();
+        let _series_zone = tracy::enter_series(n_tasks, series_idx);
+        thread::scope(|scope| {
             count_tasks_series = 0;
+            let mut handles = Vec::with_capacity(series_size);
             while task_idx < n_tasks && count_tasks_series < series_size {
-                handles.push(spawner.spawn(move || {standard_task(task_idx, n_cycles)}));
+                let task_seed = derive_task_seed(seed, n_tasks, task_idx);
+                let name = format_worker_name(n_tasks, task_idx);
+                handles.push((task_idx, platform::create_worker_builder(name).spawn_scoped(scope, move || {
+                    let _ = platform::prepare_worker_thread();
+                    let _ = placement::place_worker(task_idx);
+                    let mut task = {
+                        let _task_zone = tracy::enter_task(n_tasks, task_idx);
+                        workload(task_idx, observation_start, task_seed)
+                    };
+                    task.set_series(series_idx);
+                    task.set_thread(platform::get_thread_name(), platform::get_thread_id());
+                    hooks::notify_task_complete(n_tasks, observation_start, &task);
+                    task
+                })));
                 count_tasks_series += 1;
                 task_idx += 1;
             }
+            for (task_idx, handle) in handles {
+                let task = join_worker(task_idx, series_idx, handle);
+                trace!(n_tasks, task = task.get_idx() + 1, start = task.get_start().as_millis(), 
+                       duration = task.get_duration().as_millis(), status = format_task_status(&task), 
+                       "Task completed");
+                obs.register_task(task);
+            }
         });
+        debug!(n_tasks, series = series_idx + 1, tasks = count_tasks_series, 
+               duration = read_elapsed(&series_watch).as_millis(), "Series completed");
+    }
+
+    obs.set_cpu_utilization(cpu_sampler.finish());
+    obs.set_memory_usage(memory_sampler.finish());
+
+    if obs.is_timed_out() {
+        warn!("Observation of {} tasks timed out, {} tasks interrupted", 
+              n_tasks, obs.count_timed_out_tasks());
     }
 
-    let mut obs = Observation::create(n_tasks);
-    for handle in handles {
-        obs.register_task(handle.join());
+    if obs.count_failed_tasks() > 0 {
+        warn!("Observation of {} tasks has {} failed tasks", n_tasks, obs.count_failed_tasks());
     }
 
+    hooks::notify_observation_complete(&obs);
+
     obs
 }
 
+fn observe_with_retries(n_tasks: usize, config: &RunConfig) -> Observation {
 
-// Getting parameters of the current system
+    let mut retries = 0usize;
 
-fn count_cpus() -> usize {
-    num_cpus::get()
+    loop {
+        let (n_cycles, series_size, timeouts, seed) = 
+            (config.get_n_cycles(), config.get_series_size(), config.get_timeouts(), config.get_seed());
+        let mut obs = match (DagShape::parse(config.get_dag()), config.get_backend().as_str()) {
+            (Some(shape), _) => dag::observe(n_tasks, n_cycles, series_size, timeouts, seed, shape),
+            (None, simulate::BACKEND) => simulate::observe(n_tasks, n_cycles, series_size, timeouts, seed),
+            (None, fanout::BACKEND) => 
+                fanout::observe(n_tasks, n_cycles, series_size, timeouts, seed, config.get_n_processes()),
+            (None, _) => observe(n_tasks, n_cycles, series_size, timeouts, seed)
+        };
+        if aggregate::is_aggregate_only() {
+            obs.aggregate_tasks();
+        }
+        if !obs.needs_retry() || retries == config.get_retries_max() || config.is_cancelled() {
+            obs.set_retries(retries);
+            return obs;
+        }
+        retries += 1;
+        info!("Retrying observation of {} tasks ({} of {})", 
+              n_tasks, retries, config.get_retries_max());
+    }
 }
 
-fn count_cycles_per_sec() -> usize {
+/// The observation of the median total duration represents all the repeats.
+fn observe_repeatedly(n_tasks: usize, config: &RunConfig) -> Observation {
 
-    let mut duration: TimeMs = 0;    
-    let mut n_cycles: usize = 1; 
+    let repeats = config.get_repeats();
 
-    while duration < 1000 {
-        n_cycles *= 10;
-        let watch = SystemTime::now();
-        iterate(random_triplet(), n_cycles);
-        duration = duration_ms(&watch);
+    let mut observations: Vec<Observation> = (0..repeats.max(1))
+        .take_while(|repeat| *repeat == 0 || !config.is_cancelled())
+        .map(|repeat| {
+            if repeats > 1 {
+                debug!(n_tasks, repeat = repeat + 1, "Repeating observation");
+            }
+            observe_with_retries(n_tasks, config)
+        })
+        .collect();
+
+    let repeat_durations: Vec<TaskDuration> = 
+        observations.iter().map(|obs| obs.get_total_duration()).collect();
+
+    observations.sort_by_key(|obs| obs.get_total_duration());
+    let median_idx = observations.len()/2;
+    let mut obs = observations.swap_remove(median_idx);
+
+    if repeats > 1 {
+        obs.set_repeat_durations(repeat_durations);
     }
 
-    (1000*n_cycles as TimeCompatibleInt/duration) as usize
+    obs
 }
 
 
-// Printing messages to a console
+// Planning observations within a wall-clock budget
 
-fn print_salutation() {
-    println!("Testing concurrent code execution on Rust\n");
+struct RuntimeBudget {
+    limit: TaskDuration,
+    task_duration: TaskDuration,
+    watch: SystemTime
 }
 
-fn print_help() {
-    println!("Commands and arguments");
-    println!("Displaying system parameters:");
-    println!("s");
-    println!("Measuring profits of concurrency:");
-    println!("p <Number of tasks> <Cycles in a task> <Tasks in a series> [Output file]");
+impl RuntimeBudget {
+
+    fn estimate_observation(self: &Self, n_tasks: usize, series_size: usize) -> TaskDuration {
+
+        let n_cpus = count_cpus();
+        let mut estimate = TaskDuration::ZERO;
+        let mut n_tasks_left = n_tasks;
+
+        while n_tasks_left > 0 {
+            let n_tasks_series = n_tasks_left.min(series_size);
+            estimate += self.task_duration*count_series(n_tasks_series, n_cpus);
+            n_tasks_left -= n_tasks_series;
+        }
+
+        estimate
+    }
+
+    fn estimate_sweep(self: &Self, n_tasks_from: usize, tasks_max: usize, series_size: usize) -> TaskDuration {
+        (n_tasks_from..tasks_max + 1)
+            .map(|n_tasks| self.estimate_observation(n_tasks, series_size))
+            .sum()
+    }
+
+    /// Skips task counts that do not fit into the budget left and subsamples 
+    /// the rest of the sweep evenly when all of them would not fit. A single 
+    /// task is always observed since it is the baseline of profits.
+    fn allows(self: &Self, n_tasks: usize, tasks_max: usize, series_size: usize) -> bool {
+        self.allows_after(n_tasks, tasks_max, series_size, read_elapsed(&self.watch))
+    }
+
+    fn allows_after(self: &Self, n_tasks: usize, tasks_max: usize, series_size: usize, 
+                    time_spent: TaskDuration) -> bool {
+
+        if self.limit.is_zero() || n_tasks == 1 {
+            return true;
+        }
+
+        let time_left = self.limit - time_spent;
+
+        if self.estimate_observation(n_tasks, series_size) > time_left {
+            return false;
+        }
+
+        let sweep_estimate = self.estimate_sweep(n_tasks, tasks_max, series_size);
+
+        if sweep_estimate <= time_left {
+            return true;
+        }
+
+        let stride = sweep_estimate.as_millis().div_ceil(time_left.as_millis().max(1)) as usize;
+
+        (tasks_max - n_tasks).is_multiple_of(stride)
+    }
+
+    fn refine(self: &mut Self, task_duration: TaskDuration) {
+        self.task_duration = task_duration.max(TaskDuration::from_millis(1));
+    }
+
+    fn calibrate(n_cycles: usize) -> TaskDuration {
+        TaskDuration::from_millis((1000*n_cycles as u64/count_cycles_per_sec() as u64).max(1))
+    }
+
+    fn create_calibrated(limit: TaskDuration, n_cycles: usize) -> RuntimeBudget {
+        RuntimeBudget{limit, task_duration: Self::calibrate(n_cycles), watch: SystemTime::now()}
+    }
+
+    fn create(limit: TaskDuration, n_cycles: usize) -> RuntimeBudget {
+
+        let watch = SystemTime::now();
+
+        let task_duration = if !limit.is_zero() {
+            info!("Calibrating to fit into {} sec.", limit.as_duration().as_secs());
+            Self::calibrate(n_cycles)
+        } else {
+            TaskDuration::ZERO
+        };
+
+        RuntimeBudget{limit, task_duration, watch}
+    }
+}
+
+
+// Getting parameters of the current system
+
+const BUILD_PROFILE: &str = env!("CONCTEST_BUILD_PROFILE");
+const BUILD_OPT_LEVEL: &str = env!("CONCTEST_OPT_LEVEL");
+const BUILD_TARGET: &str = env!("CONCTEST_TARGET");
+const BUILD_LTO: &str = env!("CONCTEST_LTO");
+
+/// Debug builds run the workload many times slower, which makes their results meaningless.
+fn is_debug_build() -> bool {
+    cfg!(debug_assertions)
 }
 
-fn print_sysparams_header() {
-    println!("====================================");
-    println!("System parameter               Value");
-    println!("====================================");
+fn format_build_profile() -> String {
+    format!("{}, opt-level {}", BUILD_PROFILE, BUILD_OPT_LEVEL)
 }
 
-fn print_cpus(n_cpus: usize) {
-    println!("CPUs available {:>21}", n_cpus);
+static CPU_LIMITS: OnceLock<CpuLimits> = OnceLock::new();
+
+fn get_cpu_limits() -> &'static CpuLimits {
+    CPU_LIMITS.get_or_init(|| CpuLimits::read(num_cpus::get()))
 }
 
-fn print_cycles_per_sec(cycles_per_sec: usize) {
-    println!("Cycles per second {:>18}", cycles_per_sec.separate_with_commas());
+/// CPUs left to the process by cgroup quotas and cpusets, so containerized runs make sense.
+fn count_cpus() -> usize {
+    get_cpu_limits().count_effective()
 }
 
-fn print_sysparams_footer() {
-    println!("====================================");
+/// Physical cores of the host, no more than control groups leave to the process.
+fn count_physical_cpus() -> usize {
+    num_cpus::get_physical().min(count_cpus())
 }
 
-fn print_profit_header() {
-    println!("==================================================================");
-    println!("Tasks  Mean task duration  Std. dev.  Total duration  Cost  Profit");
-    println!("==================================================================");
+fn count_host_cpus() -> usize {
+    get_cpu_limits().host
 }
 
-fn print_profit_entry(obs: &Observation) {
-    println!("{:5} {:19} {:10} {:15} {:4.0}% {:6.0}%", 
-             obs.count_tasks(),
-             obs.get_mean_task_duration(),
-             obs.get_standard_deviation(), 
-             obs.get_total_duration(), 
-             obs.get_concurrency_cost()*100.0,
-             obs.get_concurrency_profit()*100.0);
+fn get_git_commit() -> String {
+    match process::Command::new("git").args(["rev-parse", "--short", "HEAD"]).output() {
+        Ok(output) if output.status.success() => 
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        _ => "unknown".to_string()
+    }
 }
 
-fn print_convergency(initial_triplet: Triplet, step: usize, member: f64) {
-    println!("The sequence has converged: {}, {}, and {} give {} since step {}.", 
-             initial_triplet.0, 
-             initial_triplet.1, 
-             initial_triplet.2, 
-             member, 
-             step.separate_with_commas());
+fn count_cycles_per_sec() -> usize {
+
+    let mut duration = TaskDuration::ZERO;    
+    let mut n_cycles: usize = 1; 
+
+    while duration < TaskDuration::from_millis(1000) {
+        n_cycles *= 10;
+        let watch = SystemTime::now();
+        iterate(random_triplet(&mut rand::thread_rng()), n_cycles);
+        duration = read_elapsed(&watch);
+    }
+
+    (1000*n_cycles as u64/duration.as_millis()) as usize
+}
+
+
+// Formatting numbers for humans
+
+#[derive(Copy, Clone, PartialEq)]
+enum NumberStyle {
+    Plain,
+    Grouped
 }
 
-fn print_profit_separator() {
-    println!("------------------------------------------------------------------");
+static NUMBER_STYLE: OnceLock<NumberStyle> = OnceLock::new();
+
+fn set_number_style(number_style: NumberStyle) {
+    let _ = NUMBER_STYLE.set(number_style);
 }
 
-fn print_profit_footer() {
-    println!("==================================================================");
+fn get_number_style() -> NumberStyle {
+    *NUMBER_STYLE.get().unwrap_or(&NumberStyle::Grouped)
 }
 
-fn print_profit_duration(duration_ms: TimeMs) {
-    println!("\nTotal duration: {} sec.", duration_ms/1000);
+fn format_count(n: usize) -> String {
+    match get_number_style() {
+        NumberStyle::Plain => n.to_string(),
+        NumberStyle::Grouped => n.separate_with_commas()
+    }
 }
 
 
-// Formatting and saving a report
+// Printing messages to a console
+
+fn print_salutation() {
+    info!("Testing concurrent code execution on Rust");
+}
 
-fn format_observation_totals_section_header() -> String {
-    "Tasks,Mean task duration,Std. dev.,Total duration,Cost,Profit\n".to_string()
+const PROGRAM_NAME: &str = "concrust";
+
+/// Lines of options in help, aligned as they are printed.
+const OPTION_HELP: &[(&str, &str)] = &[
+    (OPT_CHART, "--chart <Chart file>   Render speedup and total duration charts (PNG)"),
+    (OPT_SHOW_SCHEDULE, "--show-schedule        Display a timeline of tasks for each observation"),
+    (OPT_WORK_SPAN, "--work-span            Display work, span, and parallelism of each observation"),
+    (OPT_TUI, "--tui                  Display a live dashboard while measuring"),
+    (OPT_QUIET, "-q                     Display nothing but the resulting table"),
+    (OPT_VERBOSE, "-v, -vv                Display events of series, and of tasks as well"),
+    (OPT_LOG_JSON, "--log-json             Emit messages and events as JSON lines"),
+    (OPT_NO_COLOR, "--no-color             Display tables without colors (as does NO_COLOR)"),
+    (OPT_NUMBER_STYLE, "--number-style <Style> Display numbers as plain or grouped (default)"),
+    (OPT_HELP, "-h, --help             Display help of a command"),
+    (OPT_LABEL, "--label <Label>        Tag the run, e.g. \"rustc 1.79, turbo off\""),
+    (OPT_HISTORY, "--history <File>       Append a summary of the run to a history file"),
+    (OPT_TASK_TIMEOUT, "--task-timeout <Time>  Interrupt tasks running longer, e.g. 500ms, 10s, 2m"),
+    (OPT_OBSERVATION_TIMEOUT, "--observation-timeout <Time>  Interrupt observations running longer"),
+    (OPT_RETRIES, "--retries <Number>     Repeat failed, timed out, or outlying observations"),
+    (OPT_MAX_RUNTIME, "--max-runtime <Time>   Skip observations not fitting into a budget, e.g. 10m"),
+    (OPT_DRY_RUN, "--dry-run              Display the plan of observations without running them"),
+    (OPT_SEED, "--seed <Number>        Seed random data of tasks to reproduce a run"),
+    (OPT_RESUME, "--resume <File>        Continue an interrupted run saved to a file"),
+    (OPT_PRESET, "--preset <Preset>      Derive parameters from CPUs: quick, thorough, oversubscribe"),
+    (OPT_STRICT, "--strict               Fail if the system load or throttling disturbs measuring"),
+    (OPT_DOCTOR, "--doctor               Record pitfalls found by doctor in the output file"),
+    (OPT_BACKEND, "--backend <Backend>    Run tasks with a backend, see list backends"),
+    (OPT_WATCH, "--watch <Time>         Repeat the run periodically, keeping the latest in the output\n\
+                                       file and appending each to the history file, e.g. 10m"),
+    (OPT_DURATION, "--duration <Time>      How long to soak or stress, e.g. 2h"),
+    (OPT_RATE, "--rate <Number>        Tasks arriving per second in an open loop"),
+    (OPT_ARRIVALS, "--arrivals <Model>     Arrive at constant intervals (default) or as poisson"),
+    (OPT_STD_DEV, "--std-dev <Kind>       Compute sample (default) or population deviations"),
+    (OPT_REPEATS, "--repeats <Number>     Repeat each observation, adding 95% confidence intervals"),
+    (OPT_RNG, "--rng <Generator>      Seed tasks with the std (default), small, or xoshiro generator"),
+    (OPT_STOP_ON_CONVERGE, "--stop-on-converge     Stop tasks at convergence, so their work depends on data"),
+    (OPT_WORKLOAD, "--workload <Name>      Run the standard (default), branchy, syscalls, pagefaults, compress,\n\
+                \x20                      json, regex, stencil, gpu, or rwlock workload, see list workloads"),
+    (OPT_SYSCALLS, "--syscalls <Number>    Make this many system calls per cycle of the syscalls workload (default 1)"),
+    (OPT_MAP_SIZE, "--map-size <Size>      Map and touch this much memory per cycle of the pagefaults workload,\n\
+                \x20                      e.g. 64k (default), 2m"),
+    (OPT_BUFFER_SIZE, "--buffer-size <Size>   Deflate a buffer of this size per cycle of the compress workload,\n\
+                \x20                      e.g. 64k (default), 1m"),
+    (OPT_BUFFER_DATA, "--buffer-data <Data>   Fill buffers with random (default) or repetitive data"),
+    (OPT_IMAGE_SIZE, "--image-size <Size>    Blur an image of this width and height per cycle of the stencil\n\
+                \x20                      workload, e.g. 512x512 (default), 1920x1080"),
+    (OPT_WRITE_RATIO, "--write-ratio <Percent> Take the write lock in this percentage of cycles of the rwlock\n\
+                \x20                      workload, e.g. 10 (default), 50"),
+    (OPT_TRIPLETS, "--triplets <Number>    Advance a working set of triplets every cycle, e.g. 1 (default), 4k, 1m"),
+    (OPT_CLOCK, "--clock <Source>       Time tasks with the monotonic (default) clock or tsc"),
+    (OPT_PLACEMENT, "--placement <Placement> Pin tasks to CPUs compact, scatter, smt-pair, or random\n\
+                \x20                      (Linux, Windows)"),
+    (OPT_HIGH_PRIORITY, "--high-priority        Run in the high priority class (Windows)"),
+    (OPT_PIN_FREQUENCY, "--pin-frequency        Set the performance governor and turn turbo off while measuring,\n\
+                \x20                      restoring them afterwards (Linux, root)"),
+    (OPT_QOS, "--qos <Class>          Run tasks in a QoS class, e.g. utility (macOS)"),
+    (OPT_SCHED, "--sched <Policy>       Schedule workers in real time: fifo or rr (Linux, privileged)"),
+    (OPT_RT_PRIO, "--rt-prio <Number>     Real-time priority of workers, 1 (default) to 99"),
+    (OPT_NICE, "--nice <Number>        Nice value of workers, -20 to 19 (thread priority on Windows)"),
+    (OPT_STACK_SIZE, "--stack-size <Size>    Stack size of workers, at least 64k, e.g. 256k, 8m"),
+    (OPT_PROCESSES, "--processes <Number>   Worker processes of the processes backend, 2 by default"),
+    (OPT_DAG, "--dag <Shape>          Make tasks depend on each other as a chain, tree, or random\n\
+                \x20                      DAG, run by as many workers as tasks in a series"),
+    (OPT_FORMAT, "--format <Format>      Render as html, csv, md, svg (speedup chart), speedscope, or folded\n\
+                \x20                      (collapsed stacks of schedules), by default as the extension of\n\
+                \x20                      the output file tells, or md"),
+    (OPT_TEMPLATE, "--template <File>      Render with a Tera template given runs, report, and their\n\
+                \x20                      metadata, observations, and tasks, escaping .html ones"),
+    (OPT_OUTPUT, "-o <File>              Save merged runs to a file instead of printing them"),
+    (OPT_COMPRESS, "--compress <Format>    Compress saved reports with gz or zstd, appending .gz or .zst"),
+    (OPT_CAPTURE, "--capture              Save raw task records in a compact binary format, which render\n\
+                \x20                      --format csv converts to a report"),
+    (OPT_METRICS, "--metrics <Names>      Display and save these statistics only, e.g. mean,median,p99,speedup,\n\
+                \x20                      efficiency,profit, see list metrics"),
+    (OPT_AGGREGATE_ONLY, "--aggregate-only       Keep running totals of tasks instead of tasks, so huge observations fit\n\
+                \x20                      in memory, leaving schedules out of the output file"),
+    (OPT_TASKS, "--tasks <Numbers>      Keep rows of these numbers of tasks only, e.g. 4,8,16"),
+    (OPT_ONLY_TOTALS, "--only-totals          Keep totals of observations only"),
+    (OPT_SORT_BY, "--sort-by <Key>        Order rows best first by tasks, duration, cost, profit,\n\
+                \x20                      parallelism, or throughput"),
+    (OPT_SEPARATORS, "--separators <Numbers> Separate rows every so many tasks, e.g. 4,8, or none, by\n\
+                \x20                      default at physical and logical cores")
+];
+
+fn print_options(options: &[&str]) {
+    for (option, line) in OPTION_HELP {
+        if options.contains(option) {
+            println!("  {}", line.replace("\n", "\n  "));
+        }
+    }
 }
 
-fn format_observation_totals(obs: &Observation) -> String {
-    format!("{}, {}, {}, {}, {:.0}%, {:.0}%\n", 
-            obs.count_tasks(),
-            obs.get_mean_task_duration(),
-            obs.get_standard_deviation(),
-            obs.get_total_duration(), 
-            obs.get_concurrency_cost()*100.0,
-            obs.get_concurrency_profit()*100.0)
+fn print_help() {
+    println!("Usage: {} <Command> [Arguments] [Options]", PROGRAM_NAME);
+    println!("Commands:");
+    for subcommand in SUBCOMMANDS {
+        println!("  {:<12} {}", subcommand.name, subcommand.summary);
+    }
+    println!("Global options:");
+    print_options(GLOBAL_OPTIONS);
+    println!("Environment variables (arguments and options take precedence):");
+    println!("{}, {}, {}  Numbers of tasks, cycles, and tasks in a series", 
+             ENV_TASKS, ENV_CYCLES, ENV_SERIES);
+    println!("{}       Same as --backend", ENV_BACKEND);
+    println!("{}    Directory for relative output and chart files", ENV_OUTPUT_DIR);
+    println!("{}         always, never, or auto (default)", ENV_COLOR);
+    println!("{}          Same as --seed", ENV_SEED);
+    println!("Exit codes: 0 success, 2 invalid arguments, 3 measurements disturbed by");
+    println!("system noise, 4 regression detected");
+    println!("Run {} <Command> --help for arguments and options of a command", PROGRAM_NAME);
 }
 
-fn format_observation_totals_section_data(report: &Report) -> String {
+/// Help of a command tells its arguments and its own options, then the global ones.
+fn print_command_help(command: Command) {
 
-    let mut formatted_data: String = "".to_string();
+    let subcommand = match find_subcommand(command) {
+        Some(subcommand) if command != Command::Help => subcommand,
+        _ => return print_help()
+    };
 
-    for obs in &report.observations {
-        formatted_data += &format_observation_totals(obs);
+    println!("{}", subcommand.summary);
+    for (idx, usage) in subcommand.usage.iter().enumerate() {
+        let prefix = if idx == 0 {"Usage:"} else {"      "};
+        println!("{}", format!("{} {} {} {}", prefix, PROGRAM_NAME, subcommand.name, usage).trim_end());
     }
+    if command == Command::ListChoices {
+        println!("Kinds: {}", registry::REGISTRIES.iter()
+            .map(|(kind, _)| *kind)
+            .collect::<Vec<&str>>()
+            .join(", "));
+    }
+    if !subcommand.options.is_empty() {
+        println!("Options:");
+        print_options(subcommand.options);
+    }
+    println!("Global options:");
+    print_options(GLOBAL_OPTIONS);
+}
 
-    formatted_data
-} 
+fn create_sysparams_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("System parameter", 21, Align::Left, 0),
+        Column::create("Value", 24, Align::Right, 0)
+    ], colored)
+}
 
-fn format_observation_totals_section(report: &Report) -> String {
-    format_observation_totals_section_header() + 
-    &format_observation_totals_section_data(&report)
+fn print_sysparam(table: &ConsoleTable, title: &str, value: String) {
+    table.print_row(&vec![Cell::Plain(title.to_string()), Cell::Plain(value)]);
 }
 
-fn format_task(n_tasks: usize, task_idx: usize, task: &Task) -> String {
-    format!("{},{},{},{},{}\n", 
-            n_tasks,
-            task_idx, 
-            task.get_start(), 
-            task.get_finish(), 
-            task.get_duration())
+fn print_cpus(table: &ConsoleTable, cpu_limits: &CpuLimits) {
+    print_sysparam(table, "CPUs on the host", cpu_limits.host.to_string());
+    if let Some(quota) = cpu_limits.quota {
+        print_sysparam(table, "CPU quota", format!("{:.2}", quota));
+    }
+    if let Some(cpuset) = cpu_limits.cpuset {
+        print_sysparam(table, "CPUs in the cpuset", cpuset.to_string());
+    }
+    print_sysparam(table, "Effective CPUs", cpu_limits.count_effective().to_string());
 }
 
-fn format_tasks(obs: &Observation) -> String {
+fn print_power_settings(table: &ConsoleTable, power_settings: &PowerSettings) {
 
-    let mut schedule_text: String = "".to_string();
+    let turbo = power_settings.turbo.map(|turbo| if turbo {"on"} else {"off"});
+    let power_source = power_settings.on_battery.map(|on_battery| if on_battery {"battery"} else {"AC"});
 
-    let n_tasks: usize = obs.count_tasks();
-    let mut task_idx: usize = 1;
+    print_sysparam(table, "Frequency governor", 
+                   power_settings.governor.clone().unwrap_or("unknown".to_string()));
+    print_sysparam(table, "Turbo", turbo.unwrap_or("unknown").to_string());
+    print_sysparam(table, "Power source", power_source.unwrap_or("unknown").to_string());
+}
 
-    for task in &obs.tasks {
-        schedule_text += &format_task(n_tasks, task_idx, task);
-        task_idx += 1;
-    }
+fn print_build(table: &ConsoleTable) {
+    print_sysparam(table, "Build profile", format_build_profile());
+    print_sysparam(table, "LTO", BUILD_LTO.to_string());
+    print_sysparam(table, "Target", BUILD_TARGET.to_string());
+}
 
-    schedule_text
+fn print_hypervisor(table: &ConsoleTable, hypervisor: Option<String>) {
+    print_sysparam(table, "Hypervisor", hypervisor.unwrap_or("none".to_string()));
 }
 
-fn format_observation_schedule_header() -> String {
-    "Tasks,Task,Started,Finished,Duration\n".to_string()
+fn print_cycles_per_sec(table: &ConsoleTable, cycles_per_sec: usize) {
+    print_sysparam(table, "Cycles per second", format_count(cycles_per_sec));
 }
 
-fn format_observation_schedules_section(report: &Report) -> String {
+fn print_clock_resolution(table: &ConsoleTable, clock_source: ClockSource) {
+    print_sysparam(table, 
+                   &format!("Resolution, {}", clock_source.get_name()),
+                   match clock::measure_resolution(clock_source) {
+                       Some(resolution) => format!("{} ns", resolution),
+                       None => "unavailable".to_string()
+                   });
+}
 
-    let mut section_text: String = format_observation_schedule_header();
-    
-    for obs in &report.observations {
-        section_text += &format_tasks(obs);
+fn print_timer_overhead(table: &ConsoleTable, timer_overhead: u128) {
+    print_sysparam(table, "Timer overhead", format!("{} ns", timer_overhead));
+}
+
+fn create_profit_table(colored: bool, with_intervals: bool) -> ConsoleTable {
+
+    let mut columns = vec![
+        Column::create("Tasks", 5, Align::Right, 0),
+        Column::create("Mean task duration", 19, Align::Right, 3),
+        Column::create("Std. dev.", 10, Align::Right, 1),
+        Column::create("CV", 5, Align::Right, 3),
+        Column::create("Total duration", 15, Align::Right, 4),
+        Column::create("Bound", 6, Align::Right, 4),
+        Column::create("Quality", 7, Align::Right, 3),
+        Column::create("Parallelism", 11, Align::Right, 2),
+        Column::create("Max running", 11, Align::Right, 3),
+        Column::create("CPU util.", 9, Align::Right, 3),
+        Column::create("Start spread", 12, Align::Right, 3),
+        Column::create("Tasks/sec", 9, Align::Right, 5),
+        Column::create("Cycles/sec", 15, Align::Right, 2),
+        Column::create("Cost", 5, Align::Right, 2),
+        Column::create("Profit", 7, Align::Right, 0)
+    ];
+
+    if with_intervals {
+        columns.insert(5, Column::create("95% CI", 7, Align::Right, 4));
+        columns.push(Column::create("95% CI", 7, Align::Right, 4));
     }
 
-    section_text
+    ConsoleTable::create(columns, colored)
 }
 
-fn format_report(report: &Report) -> String {
-    format_observation_totals_section(&report) +
-    "\n" + 
-    &format_observation_schedules_section(&report)
+/// A line across the profit table, drawn after every so many tasks.
+struct Separator {
+    period: usize,
+    filler: &'static str,
+    label: String
 }
 
-fn save_text(out_file_path: &String, text: &String) {
+impl Separator {
 
-    if *out_file_path != "".to_string() {
-        match File::create(Path::new(out_file_path)) {
-            Ok(mut out_file) => {
-                out_file.write_all(&text.as_bytes()).unwrap();
-            }   
-            Err(e) => {
-                panic!("Error while opening an output file: {}", e);
-            }
+    fn format_label(self: &Self, n_tasks: usize) -> String {
+        match n_tasks/self.period {
+            1 => self.label.clone(),
+            multiple => format!("{} x {}", multiple, self.label)
         }
-    } 
+    }
+}
+
+fn format_cores(n_cores: usize, kind: &str) -> String {
+    format!("{} {} core{}", n_cores, kind, if n_cores == 1 {""} else {"s"})
 }
 
+/// Logical cores are marked with dashes, and physical ones with dots where SMT doubles them.
+/// A boundary of a single core would fall after every row, so it is not marked.
+fn create_core_separators(n_physical_cpus: usize, n_cpus: usize) -> Vec<Separator> {
 
-// Performing observations
+    let mut separators = vec![
+        Separator{period: n_cpus, filler: "-", label: format_cores(n_cpus, "logical")}
+    ];
 
-fn test_sysparams() {
-    print_sysparams_header();
-    print_cpus(count_cpus());
-    print_cycles_per_sec(count_cycles_per_sec());
-    print_sysparams_footer();
+    if n_physical_cpus < n_cpus {
+        separators.push(Separator{period: n_physical_cpus, filler: ".",
+                                  label: format_cores(n_physical_cpus, "physical")});
+    }
+
+    separators.retain(|separator| separator.period > 1);
+    separators
 }
 
-fn test_concurrency_profit(tasks_max: usize, n_cycles: usize, series_size: usize) -> Report {
+fn create_separators(periods: &[usize]) -> Vec<Separator> {
+    periods.iter()
+        .map(|period| Separator{period: *period, filler: "-", label: "".to_string()})
+        .collect()
+}
 
-    let mut report = Report::create(tasks_max);
+/// Separators go by precedence, so logical cores win where boundaries coincide.
+fn find_separator(separators: &[Separator], n_tasks: usize) -> Option<&Separator> {
+    separators.iter().find(|separator| n_tasks.is_multiple_of(separator.period))
+}
 
-    let watch = SystemTime::now();
-    
-    print_profit_header();
+fn print_profit_separator(table: &ConsoleTable, separator: &Separator, n_tasks: usize) {
+    if !separator.label.is_empty() {
+        table.print_labelled_separator(separator.filler, &separator.format_label(n_tasks));
+    } else {
+        table.print_separator();
+    }
+}
 
-    for n_tasks in 1..tasks_max + 1 {
+fn print_skipped_profit_entry(table: &ConsoleTable, n_tasks: usize, with_intervals: bool) {
 
-        let obs = observe(n_tasks, n_cycles, series_size);
+    let mut cells = vec![Cell::Plain(n_tasks.to_string()), Cell::Plain("skipped".to_string())];
+    let n_columns = if with_intervals {17} else {15};
 
-        report.register_observation(obs);
-        
-        print_profit_entry(report.get_observation(n_tasks - 1));
-        if n_tasks % count_cpus() == 0 && n_tasks != tasks_max {
-            print_profit_separator();
-        }    
-    } 
+    while cells.len() < n_columns {
+        cells.push(Cell::Plain("".to_string()));
+    }
 
-    print_profit_footer();
+    table.print_row(&cells);
+}
 
-    print_profit_duration(duration_ms(&watch));
+fn format_percent_cell(value: f64) -> String {
+    format!("{:.0}%", value*100.0)
+}
 
-    report
+fn format_interval_cell(interval: Option<(f64, f64)>, scale: f64, suffix: &str) -> String {
+    match interval {
+        Some(interval) => format!("±{:.0}{}", scale*stats::get_half_width(interval), suffix),
+        None => "".to_string()
+    }
 }
 
+/// Statistics aggregated observations lack show as not available, rather than as zeros.
+fn format_optional_cell<T: ToString>(value: Option<T>) -> String {
+    value.map_or("n/a".to_string(), |value| value.to_string())
+}
 
-// Accepting arguments
+fn print_profit_entry(table: &ConsoleTable, obs: &Observation, with_intervals: bool) {
+
+    let mut cells = vec![
+        Cell::Plain(obs.count_tasks().to_string()),
+        Cell::Plain(obs.get_mean_task_duration().to_string()),
+        Cell::Plain(obs.get_standard_deviation().to_string()),
+        Cell::Plain(format_percent_cell(obs.get_variation_coefficient())),
+        Cell::Plain(obs.get_total_duration().to_string()),
+        Cell::Plain(obs.get_duration_bound(count_cpus()).to_string()),
+        Cell::Plain(format_percent_cell(obs.get_scheduling_quality(count_cpus()))),
+        Cell::Plain(format!("{:.2}", obs.get_parallelism())),
+        Cell::Plain(format_optional_cell(obs.count_concurrent_tasks_max())),
+        Cell::Plain(obs.get_cpu_utilization()
+                       .map_or("".to_string(), |utilization| format_percent_cell(utilization.total))),
+        Cell::Plain(format_optional_cell(obs.get_start_spread())),
+        Cell::Plain(format!("{:.1}", obs.get_tasks_per_sec())),
+        Cell::Plain(format_count(obs.get_cycles_per_sec() as usize)),
+        Cell::Plain(format_percent_cell(obs.get_concurrency_cost())),
+        Cell::Signed(format_percent_cell(obs.get_concurrency_profit()), 
+                     obs.get_concurrency_profit())
+    ];
+
+    if with_intervals {
+        cells.insert(5, Cell::Plain(format_interval_cell(obs.get_duration_interval(), 1.0, "")));
+        cells.push(Cell::Plain(format_interval_cell(obs.get_profit_interval(), 100.0, "%")));
+    }
 
-fn validate_usize(s: &str) -> bool {   
-    Regex::new(r"^\d+$").unwrap().is_match(&s)
+    table.print_row(&cells);
 }
 
-fn parse_usize(s: &String) -> usize {
-    if validate_usize(s) {
-        return s.parse::<usize>().unwrap();
-    } else {
-        return 0;
-    }    
+fn print_convergency(initial_triplet: Triplet, step: usize, member: f64) {
+    info!(step, "The sequence has converged: {}, {}, and {} give {} since step {}.", 
+          initial_triplet.0, 
+          initial_triplet.1, 
+          initial_triplet.2, 
+          member, 
+          format_count(step));
 }
 
-type ArgsVec = Vec<String>;
+fn print_failure_summary(report: &Report) {
 
-#[derive(Copy, Clone, PartialEq)]
-enum Command {
-    Help,
-    RequestSysParams,
-    MeasureConcurrencyProfit,
-}
+    let failed_tasks: Vec<&Task> = report.observations.iter()
+        .flat_map(|obs| obs.tasks.iter())
+        .filter(|task| task.is_failed())
+        .collect();
 
-const ARG_IDX_COMMAND: usize = 1;
-const ARG_IDX_TASKS_MAX: usize = 2;
-const ARG_IDX_N_CYCLES: usize = 3;
-const ARG_IDX_SERIES_SIZE: usize = 4;
-const ARG_IDX_OUT_FILE_PATH: usize = 5;
+    if failed_tasks.is_empty() {
+        return;
+    }
 
-struct Args {
-    command: Command,
-    tasks_max: usize,
-    n_cycles: usize,
-    series_size: usize,
-    out_file_path: String
+    let n_failed_observations = 
+        report.observations.iter().filter(|obs| obs.count_failed_tasks() > 0).count();
+
+    warn!("{} tasks failed in {} observations:", failed_tasks.len(), n_failed_observations);
+
+    let mut errors: Vec<String> = failed_tasks.iter().map(|task| format_task_status(task)).collect();
+    errors.sort();
+    errors.dedup();
+
+    for error in errors {
+        warn!("  {}", error);
+    }
 }
 
-impl Args {
+fn print_profit_duration(duration: TaskDuration) {
+    info!("Total duration: {} sec.", duration.as_duration().as_secs());
+}
 
-    fn get_command(self: &Self) -> Command {
-        self.command
+const NOISE_SCORE_QUIET: f64 = 0.05;
+const NOISE_SCORE_NOISY: f64 = 0.15;
+
+fn assess_noise_score(noise_score: f64) -> &'static str {
+    if noise_score < NOISE_SCORE_QUIET {
+        "quiet"
+    } else if noise_score < NOISE_SCORE_NOISY {
+        "moderate"
+    } else {
+        "noisy, numbers are hardly trustworthy"
     }
+}
 
-    fn get_tasks_max(self: &Self) -> usize {
-        self.tasks_max
+fn print_noise_score(noise_score: f64) {
+    info!("Noise score: {} ({})", format_percent_cell(noise_score), assess_noise_score(noise_score));
+}
+
+/// Headline numbers of a run, those copied into slides.
+struct RunSummary {
+    best_speedup: Option<(usize, f64)>,
+    physical_efficiency: Option<(usize, f64)>,
+    serial_fraction: Option<f64>,
+    noise_score: f64
+}
+
+impl RunSummary {
+
+    /// Amdahl's law counts workers as the speedup of an ideal machine: tasks over the waves
+    /// they take in series of CPUs, so partial waves are not mistaken for serial work.
+    fn create(report: &Report, n_physical_cpus: usize, n_cpus: usize) -> RunSummary {
+
+        let series_size = report.get_metadata("Tasks in a series").map_or(0, |value| parse_usize(value));
+        let count_workers = |n_tasks: usize| {
+            let series_size = if series_size > 0 {series_size.min(n_tasks)} else {n_tasks};
+            let n_waves = (n_tasks/series_size)*count_series(series_size, n_cpus.max(1)) +
+                          count_series(n_tasks % series_size, n_cpus.max(1));
+            n_tasks as f64/n_waves as f64
+        };
+
+        let best_speedup = report.observations.iter()
+            .max_by(|a, b| a.get_speedup().total_cmp(&b.get_speedup()))
+            .map(|obs| (obs.count_tasks(), obs.get_speedup()));
+
+        let physical_efficiency = report.find_observation(n_physical_cpus)
+            .map(|obs| (n_physical_cpus, obs.get_speedup()/count_workers(n_physical_cpus)));
+
+        let points: Vec<(f64, f64)> = report.observations.iter()
+            .map(|obs| (count_workers(obs.count_tasks()), obs.get_speedup()))
+            .collect();
+
+        RunSummary {
+            best_speedup,
+            physical_efficiency,
+            serial_fraction: stats::fit_serial_fraction(&points),
+            noise_score: report.get_noise_score()
+        }
     }
+}
 
-    fn get_n_cycles(self: &Self) -> usize {
-        self.n_cycles
+fn print_run_summary(report: &Report) {
+
+    let summary = RunSummary::create(report, count_physical_cpus(), count_cpus());
+
+    if let Some((n_tasks, speedup)) = summary.best_speedup {
+        info!("Best speedup: {:.2} at {} tasks", speedup, n_tasks);
+    }
+    if let Some((n_tasks, efficiency)) = summary.physical_efficiency {
+        info!("Efficiency at {} tasks, one per physical core: {}", n_tasks, format_percent_cell(efficiency));
+    }
+    if let Some(serial_fraction) = summary.serial_fraction {
+        info!("Estimated serial fraction (Amdahl's law): {:.1}%", 100.0*serial_fraction);
     }
+    print_noise_score(summary.noise_score);
+    print_imbalance(report);
+    batch::print_working_set(report);
+    perf::print_branch_misses(report);
+    gpu::print_offload(report);
+    workload::print_lock_contention(report);
+    memory::print_memory_footprint(report);
+    memory::print_allocations(report);
+    dag::print_critical_paths(report);
+}
 
-    fn get_series_size(self: &Self) -> usize {
-        self.series_size
+/// Tasks stopping at convergence take as long as their data makes them, so runs in both modes
+/// tell the imbalance due to data from the one due to scheduling.
+fn print_imbalance(report: &Report) {
+    let mode = ConvergenceMode::parse(report.get_metadata("Convergence").map_or("", |mode| mode.as_str()));
+    if let Some(obs) = report.observations.last() {
+        info!("Imbalance of tasks {}: the longest task takes {:.2}x the mean at {} tasks",
+              mode.get_description(), obs.get_duration_imbalance(), obs.count_tasks());
     }
+}
 
-    fn get_out_file_path(self: &Self) -> String {
-        self.out_file_path.clone()
+const SCHEDULE_WIDTH: usize = 60;
+
+fn scale_to_schedule(moment: TaskDuration, total_duration: TaskDuration) -> usize {
+    if !total_duration.is_zero() {
+        (moment.as_millis()*SCHEDULE_WIDTH as u64/total_duration.as_millis().max(1)) as usize
+    } else {
+        0
     }
+}
 
-    fn parse_command(self: &Self, args: &ArgsVec) -> Command {
+fn format_schedule_bar(obs: &Observation, task: &Task) -> String {
 
-        let mut cmd: Command = Command::Help;
-    
-        if args.len() > 1 {
-            match &*args[ARG_IDX_COMMAND] {
-                "s" => {cmd = Command::RequestSysParams;}
-                "p" => {cmd = Command::MeasureConcurrencyProfit;}
-                _   => {cmd = Command::Help;}
-            }
-        } 
-    
-        cmd
+    let total_duration = obs.get_total_duration();
+    let bar_start = scale_to_schedule(obs.to_relative(task.get_start()), total_duration);
+    let bar_finish = scale_to_schedule(obs.to_relative(task.get_finish()), total_duration)
+        .max(bar_start + 1)
+        .min(SCHEDULE_WIDTH);
+
+    let bar_char = match task.get_status() {
+        TaskStatus::Completed => '█',
+        TaskStatus::TimedOut => '▒',
+        TaskStatus::Failed(_) => '×'
+    };
+
+    (0..SCHEDULE_WIDTH)
+        .map(|col| if col >= bar_start && col < bar_finish {bar_char} else {'·'})
+        .collect()
+}
+
+fn format_task_thread(task: &Task) -> String {
+    match (task.get_thread_name().is_empty(), task.get_thread_id()) {
+        (true, _) => "".to_string(),
+        (false, 0) => format!(", {}", task.get_thread_name()),
+        (false, thread_id) => format!(", {} #{}", task.get_thread_name(), thread_id)
     }
+}
 
-    fn parse_tasks_max(self: &Self, args: &ArgsVec) -> usize {
-        parse_usize(&args[ARG_IDX_TASKS_MAX])
+fn print_schedule(obs: &Observation) {
+
+    let total_duration = obs.get_total_duration();
+
+    println!("\nTasks: {}, total duration: {} ms, idle in series: {}", 
+             obs.count_tasks(), total_duration, 
+             format_optional_cell(obs.get_series_idle_share().map(format_percent_cell)));
+
+    for task in &obs.tasks {
+        println!("{:5} |{}| {:>6} ms{}{}", 
+                 task.get_idx() + 1, 
+                 format_schedule_bar(obs, task), 
+                 task.get_duration(),
+                 format_task_thread(task),
+                 match task.get_status() {
+                     TaskStatus::Completed => "".to_string(),
+                     _ => format!(", {}", format_task_status(task))
+                 });
     }
 
-    fn parse_n_cycles(self: &Self, args: &ArgsVec) -> usize {
-        parse_usize(&args[ARG_IDX_N_CYCLES])
+    print_series_spans(obs);
+}
+
+fn print_series_spans(obs: &Observation) {
+
+    let spans = obs.get_series_spans();
+    let gaps = obs.get_series_gaps().unwrap_or_default();
+
+    let delays = obs.get_start_delays();
+
+    for (idx, span) in spans.iter().enumerate() {
+        let start_spread = obs.tasks.iter().zip(&delays)
+            .filter(|(task, _)| task.get_series() == span.idx)
+            .map(|(_, delay)| *delay)
+            .max()
+            .unwrap_or_default();
+        println!("Series {}: {} tasks started within {} ms, {} ms, {} idle{}", 
+                 span.idx + 1,
+                 span.n_tasks,
+                 start_spread,
+                 span.get_duration(),
+                 format_percent_cell(span.get_idle_share()),
+                 match gaps.get(idx) {
+                     Some(gap) => format!(", then a gap of {} ms", gap),
+                     None => "".to_string()
+                 });
     }
-    
-    fn parse_series_size(self: &Self, args: &ArgsVec) -> usize {
-        parse_usize(&args[ARG_IDX_SERIES_SIZE])
+}
+
+fn print_schedules(report: &Report) {
+
+    let n_processes = match report.get_metadata("Backend") {
+        Some(backend) if backend == fanout::BACKEND => 
+            report.get_metadata("Processes").map_or(0, |value| parse_usize(value)),
+        _ => 0
+    };
+
+    for obs in &report.observations {
+        print_schedule(obs);
+        fanout::print_process_spans(obs, n_processes);
     }
-    
-    fn parse_out_file_path(self: &Self, args: &ArgsVec) -> String {
-        if args.len() == ARG_IDX_OUT_FILE_PATH + 1 {
-            return args[ARG_IDX_OUT_FILE_PATH].to_string(); 
-        } else {
-            return "".to_string();
-        }
+}
+
+
+// Formatting and saving a report
+
+fn quote_csv(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
     }
-    
-    fn parse(mut self: Self, args: &ArgsVec) -> Self {
+}
+
+fn split_csv_line(line: &str) -> Vec<String> {
+
+    let mut fields: Vec<String> = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
 
-        if args.len() >= 1 {
-            self.command = self.parse_command(args);
-            if args.len() >= 4 {
-                self.tasks_max = self.parse_tasks_max(args);
-                self.n_cycles = self.parse_n_cycles(args);
-                self.series_size = self.parse_series_size(args);
-                self.out_file_path = self.parse_out_file_path(args);
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
             }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c)
         }
+    }
 
-        self
+    fields.push(field);
+
+    fields
+}
+
+fn format_metadata_section(report: &Report) -> String {
+
+    let mut section_text: String = "Parameter,Value\n".to_string();
+
+    for (key, value) in &report.metadata {
+        section_text += &format!("{},{}\n", quote_csv(key), quote_csv(value));
     }
 
-    fn is_valid(self: &Self) -> bool {
-        self.get_tasks_max() > 0 &&
-        self.get_n_cycles() > 0 &&
-        self.get_series_size() > 0 && 
-        self.get_series_size() <= self.get_tasks_max()
+    section_text
+}
+
+/// Metrics chosen make up the section, nothing chosen gives the full one.
+fn format_observation_totals_section_header(metrics: &[Metric]) -> String {
+    if !metrics.is_empty() {
+        return metrics::format_totals_header(metrics);
     }
+    "Tasks,Mean task duration,Std. dev.,CV,Total duration,Duration CI low,Duration CI high,\
+     Parallelism,Max running,CPU utilization,CPU steal,Start spread,Tasks/sec,Cycles/sec,Cost,Profit,Profit CI low,Profit CI high,\
+     Duration bound,Scheduling quality,Timed out,Failed,Retries,Status\n"
+        .to_string()
 }
 
-fn accept_args(args: ArgsVec) -> Args {
-    Args{command: Command::Help, 
-         tasks_max: 0, 
-         n_cycles: 0, 
-         series_size: 0, 
-         out_file_path: "".to_string()}.parse(&args)
+/// Tells totals sections, of any metrics, from sections of tasks, series, and the like.
+fn is_observation_totals_header(header: &str) -> bool {
+    header.starts_with("Tasks,") && header.ends_with(",Status")
 }
 
+fn format_skipped_observation_totals(metrics: &[Metric], n_tasks: usize) -> String {
+    if !metrics.is_empty() {
+        return metrics::format_skipped_totals(metrics, n_tasks);
+    }
+    format!("{},,,,,,,,,,,,,,,,,,,,,,,skipped\n", n_tasks)
+}
 
-// Doing the job 
+fn format_interval_bounds(interval: Option<(f64, f64)>, precision: usize) -> (String, String) {
+    match interval {
+        Some((low, high)) => (format!("{:.*}", precision, low), format!("{:.*}", precision, high)),
+        None => ("".to_string(), "".to_string())
+    }
+}
 
-fn main() {
+fn format_observation_totals(metrics: &[Metric], obs: &Observation, n_cpus: usize) -> String {
 
-    print_salutation();
+    if !metrics.is_empty() {
+        return metrics::format_totals(metrics, obs, n_cpus);
+    }
 
-    let args: Args = accept_args(env::args().collect());
+    let (duration_low, duration_high) = format_interval_bounds(obs.get_duration_interval(), 1);
+    let (profit_low, profit_high) = format_interval_bounds(obs.get_profit_interval(), 4);
 
-    match args.get_command() {
-        Command::Help => {
-            print_help();
+    format!("{},{},{},{:.4},{},{},{},{:.3},{},{},{},{},{:.3},{:.0},{:.4},{:.4},{},{},{},{:.4},{},{},{},measured\n", 
+            obs.count_tasks(),
+            obs.get_mean_task_duration(),
+            obs.get_standard_deviation(),
+            obs.get_variation_coefficient(),
+            obs.get_total_duration(), 
+            duration_low,
+            duration_high,
+            obs.get_parallelism(),
+            obs.count_concurrent_tasks_max().map_or("".to_string(), |running| running.to_string()),
+            obs.get_cpu_utilization()
+               .map_or("".to_string(), |utilization| format!("{:.4}", utilization.total)),
+            obs.get_cpu_utilization()
+               .map_or("".to_string(), |utilization| format!("{:.4}", utilization.steal)),
+            obs.get_start_spread().map_or("".to_string(), |spread| spread.to_string()),
+            obs.get_tasks_per_sec(),
+            obs.get_cycles_per_sec(),
+            obs.get_concurrency_cost(),
+            obs.get_concurrency_profit(),
+            profit_low,
+            profit_high,
+            obs.get_duration_bound(n_cpus),
+            obs.get_scheduling_quality(n_cpus),
+            obs.count_timed_out_tasks(),
+            obs.count_failed_tasks(),
+            obs.get_retries())
+}
+
+/// Bounds are of the machine measuring, so saved reports keep them wherever rendered.
+fn count_report_cpus(report: &Report) -> usize {
+    match report.get_metadata("Effective CPUs").map(|value| parse_usize(value)) {
+        Some(n_cpus) if n_cpus > 0 => n_cpus,
+        _ => count_cpus()
+    }
+}
+
+fn format_observation_totals_section_data(report: &Report, metrics: &[Metric]) -> String {
+
+    let mut formatted_data: String = "".to_string();
+    let mut skipped = report.skipped.iter().peekable();
+    let n_cpus = count_report_cpus(report);
+
+    for obs in &report.observations {
+        while let Some(n_tasks) = skipped.next_if(|n_tasks| **n_tasks < obs.count_tasks()) {
+            formatted_data += &format_skipped_observation_totals(metrics, *n_tasks);
         }
-        Command::RequestSysParams => {
-            test_sysparams();
+        formatted_data += &format_observation_totals(metrics, obs, n_cpus);
+    }
+
+    for n_tasks in skipped {
+        formatted_data += &format_skipped_observation_totals(metrics, *n_tasks);
+    }
+
+    formatted_data
+} 
+
+fn format_observation_totals_section(report: &Report) -> String {
+    let metrics = metrics::get_report_metrics(report);
+    format_observation_totals_section_header(&metrics) + 
+    &format_observation_totals_section_data(report, &metrics)
+}
+
+fn format_task_status(task: &Task) -> String {
+    format_status(task.get_status())
+}
+
+fn format_status(status: &TaskStatus) -> String {
+    match status {
+        TaskStatus::Completed => "completed".to_string(),
+        TaskStatus::TimedOut => "timed out".to_string(),
+        TaskStatus::Failed(error) => format!("failed: {}", error)
+    }
+}
+
+fn format_task(obs: &Observation, task_idx: usize, task: &Task) -> String {
+    format!("{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n", 
+            obs.count_tasks(),
+            task_idx, 
+            obs.to_relative(task.get_start()), 
+            obs.to_relative(task.get_finish()), 
+            task.get_duration(),
+            quote_csv(&format_task_status(task)),
+            task.get_series() + 1,
+            task.get_start(),
+            task.get_finish(),
+            quote_csv(task.get_thread_name()),
+            if task.get_thread_id() > 0 {task.get_thread_id().to_string()} else {"".to_string()},
+            if task.get_seed() > 0 {task.get_seed().to_string()} else {"".to_string()},
+            task.get_branch_misses().map_or("".to_string(), |branch_misses| branch_misses.to_string()),
+            task.get_allocations().map_or("".to_string(), |allocations| allocations.allocated.to_string()),
+            task.get_allocations().map_or("".to_string(), |allocations| allocations.freed.to_string()))
+}
+
+fn format_tasks(obs: &Observation) -> String {
+
+    let mut schedule_text: String = "".to_string();
+
+    for (task_idx, task) in (1..).zip(&obs.tasks) {
+        schedule_text += &format_task(obs, task_idx, task);
+    }
+
+    schedule_text
+}
+
+fn format_observation_schedule_header() -> String {
+    "Tasks,Task,Started,Finished,Duration,Status,Series,Started at,Finished at,\
+     Thread,Thread ID,Seed,Branch misses,Allocated,Freed\n".to_string()
+}
+
+fn format_observation_schedules_section(report: &Report) -> String {
+
+    let mut section_text: String = format_observation_schedule_header();
+    
+    for obs in &report.observations {
+        section_text += &format_tasks(obs);
+    }
+
+    section_text
+}
+
+fn format_observation_series_section(report: &Report) -> String {
+
+    let mut section_text: String = "Tasks,Series,Tasks in the series,Started,Finished,Duration,\
+                                    Idle share,Gap\n".to_string();
+
+    for obs in &report.observations {
+        let gaps = obs.get_series_gaps().unwrap_or_default();
+        for (idx, span) in obs.get_series_spans().iter().enumerate() {
+            section_text += &format!("{},{},{},{},{},{},{:.4},{}\n",
+                                     obs.count_tasks(),
+                                     span.idx + 1,
+                                     span.n_tasks,
+                                     obs.to_relative(span.start),
+                                     obs.to_relative(span.finish),
+                                     span.get_duration(),
+                                     span.get_idle_share(),
+                                     gaps.get(idx).map_or("".to_string(), |gap| gap.to_string()));
         }
-        Command::MeasureConcurrencyProfit => {
-            if args.is_valid() {
-                let report = test_concurrency_profit(
-                    args.get_tasks_max(),
-                    args.get_n_cycles(), 
-                    args.get_series_size());
-                save_text(&args.get_out_file_path(), &format_report(&report));
-            } else {
-                print_help();
+    }
+
+    section_text
+}
+
+fn format_observation_start_delays_section(report: &Report) -> String {
+
+    let mut section_text: String = "Tasks,Percentile,Start delay\n".to_string();
+
+    for obs in &report.observations {
+        for percentile in PERCENTILES {
+            if let Some(delay) = obs.get_start_delay_percentile(percentile) {
+                section_text += &format!("{},{},{}\n", obs.count_tasks(), percentile, delay);
+            }
+        }
+    }
+
+    section_text
+}
+
+fn format_observation_utilization_section(report: &Report) -> String {
+
+    let mut section_text: String = "Tasks,CPU,Utilization\n".to_string();
+
+    for obs in &report.observations {
+        if let Some(utilization) = obs.get_cpu_utilization() {
+            for (cpu_idx, core_utilization) in utilization.per_core.iter().enumerate() {
+                section_text += &format!("{},{},{:.4}\n", obs.count_tasks(), cpu_idx, core_utilization);
             }
         }
     }
-}
\ No newline at end of file
+
+    section_text
+}
+
+fn format_observation_memory_section(report: &Report) -> String {
+
+    let mut section_text: String = "Tasks,RSS before,RSS after,Peak RSS\n".to_string();
+
+    for obs in &report.observations {
+        if let Some(memory_usage) = obs.get_memory_usage() {
+            section_text += &format!("{},{},{},{}\n", obs.count_tasks(),
+                                     memory_usage.before, memory_usage.after, memory_usage.peak);
+        }
+    }
+
+    section_text
+}
+
+fn format_observation_allocations_section(report: &Report) -> String {
+
+    let mut section_text: String = "Tasks,Allocated,Freed\n".to_string();
+
+    for obs in &report.observations {
+        if let Some(allocations) = obs.sum_allocations() {
+            section_text += &format!("{},{},{}\n", obs.count_tasks(), allocations.allocated, allocations.freed);
+        }
+    }
+
+    section_text
+}
+
+fn format_observation_repeats_section(report: &Report) -> String {
+
+    let mut section_text: String = "Tasks,Repeat,Total duration\n".to_string();
+
+    for obs in &report.observations {
+        for (idx, duration) in obs.get_repeat_durations().iter().enumerate() {
+            section_text += &format!("{},{},{}\n", obs.count_tasks(), idx + 1, duration);
+        }
+    }
+
+    section_text
+}
+
+fn format_report(report: &Report) -> String {
+
+    let mut report_text = format_metadata_section(report) +
+        "\n" +
+        &format_observation_totals_section(report) +
+        "\n" + 
+        &format_observation_schedules_section(report) +
+        "\n" + 
+        &format_observation_series_section(report) +
+        "\n" + 
+        &format_observation_start_delays_section(report) +
+        "\n" + 
+        &format_observation_utilization_section(report) +
+        "\n" + 
+        &format_observation_memory_section(report);
+
+    if report.observations.iter().any(|obs| obs.sum_allocations().is_some()) {
+        report_text += &("\n".to_string() + &format_observation_allocations_section(report));
+    }
+    if report.observations.iter().any(|obs| !obs.get_repeat_durations().is_empty()) {
+        report_text += &("\n".to_string() + &format_observation_repeats_section(report));
+    }
+
+    report_text
+}
+
+const PERCENTILES: [f64; 4] = [50.0, 99.0, 99.9, 100.0];
+const HISTOGRAM_SIGNIFICANT_DIGITS: u8 = 3;
+
+fn create_histogram() -> Histogram<u64> {
+    Histogram::new(HISTOGRAM_SIGNIFICANT_DIGITS).unwrap()
+}
+
+fn format_percentiles_section(titles: &[&str], histograms: &[&Histogram<u64>]) -> String {
+
+    let mut section_text = format!("Percentile,{}\n", titles.join(","));
+
+    for percentile in PERCENTILES {
+        let values: Vec<String> = histograms.iter()
+            .map(|histogram| histogram.value_at_quantile(percentile/100.0).to_string())
+            .collect();
+        section_text += &format!("p{},{}\n", percentile, values.join(","));
+    }
+
+    section_text
+}
+
+fn format_chart_data(report: &Report) -> String {
+
+    let mut formatted_data: String = "".to_string();
+
+    for obs in &report.observations {
+        let total_duration = obs.get_total_duration().as_millis_f64();
+        let (duration_low, duration_high) = 
+            obs.get_duration_interval().unwrap_or((total_duration, total_duration));
+        let profit = obs.get_concurrency_profit();
+        let (profit_low, profit_high) = obs.get_profit_interval().unwrap_or((profit, profit));
+        formatted_data += &format!("{} {:.3} {} {:.3} {:.3} {:.1} {:.1}\n",
+                                   obs.count_tasks(),
+                                   obs.get_speedup(),
+                                   obs.get_total_duration(),
+                                   1.0/(1.0 - profit_low),
+                                   1.0/(1.0 - profit_high),
+                                   duration_low,
+                                   duration_high);
+    }
+
+    formatted_data
+}
+
+fn format_chart_script(chart_file_path: &String, report: &Report) -> String {
+    format!("set terminal pngcairo size 800,1000\n\
+             set output \"{}\"\n\
+             $data << EOD\n{}EOD\n\
+             set multiplot layout 2,1\n\
+             set grid\n\
+             set xlabel \"Tasks\"\n\
+             set title \"Speedup vs. tasks\"\n\
+             set ylabel \"Speedup\"\n\
+             plot $data using 1:2:4:5 with yerrorlines title \"Speedup\"\n\
+             set title \"Total duration vs. tasks\"\n\
+             set ylabel \"Total duration, ms\"\n\
+             plot $data using 1:3:6:7 with yerrorlines title \"Total duration\"\n\
+             unset multiplot\n",
+            chart_file_path,
+            format_chart_data(report))
+}
+
+fn get_chart_script_path(chart_file_path: &String) -> String {
+    format!("{}.gp", chart_file_path)
+}
+
+fn render_chart(chart_script_path: &String) {
+    match process::Command::new("gnuplot").arg(chart_script_path).status() {
+        Ok(status) if status.success() => {}
+        _ => {
+            warn!("Cannot run gnuplot, render the chart manually: gnuplot {}", 
+                  chart_script_path);
+        }
+    }
+}
+
+fn save_chart(chart_file_path: &String, report: &Report) {
+
+    if !chart_file_path.is_empty() {
+        let chart_script_path = get_chart_script_path(chart_file_path);
+        save_text(&chart_script_path, &format_chart_script(chart_file_path, report));
+        render_chart(&chart_script_path);
+    }
+}
+
+fn save_text(out_file_path: &String, text: &String) {
+    save_bytes(out_file_path, text.as_bytes());
+}
+
+/// Reports are written through the compression chosen, under its extension.
+fn save_report_bytes(out_file_path: &String, bytes: &[u8]) {
+    match compression::get_compression() {
+        Compression::Off => save_bytes(out_file_path, bytes),
+        compression if !out_file_path.is_empty() =>
+            save_bytes(&compression.append_extension(out_file_path), &compression.compress(bytes)),
+        _ => {}
+    }
+}
+
+fn save_report_text(out_file_path: &String, text: &String) {
+    save_report_bytes(out_file_path, text.as_bytes());
+}
+
+/// Captures skip formatting every section of the report, which is what huge runs spend on saving.
+fn save_report(out_file_path: &String, report: &Report) {
+    if capture::get_capture() {
+        save_report_bytes(out_file_path, &capture::format_capture(report));
+    } else {
+        save_report_text(out_file_path, &format_report(report));
+    }
+}
+
+fn save_bytes(out_file_path: &String, bytes: &[u8]) {
+
+    if !out_file_path.is_empty() {
+        if let Some(out_dir) = Path::new(out_file_path).parent() {
+            let _ = std::fs::create_dir_all(out_dir);
+        }
+        match File::create(Path::new(out_file_path)) {
+            Ok(mut out_file) => {
+                out_file.write_all(bytes).unwrap();
+            }   
+            Err(e) => {
+                panic!("Error while opening an output file: {}", e);
+            }
+        }
+    } 
+}
+
+
+// Performing observations
+
+fn test_sysparams(colored: bool) {
+    let table = create_sysparams_table(colored);
+    table.print_header();
+    print_build(&table);
+    print_cpus(&table, get_cpu_limits());
+    print_hypervisor(&table, environment::detect_hypervisor());
+    print_power_settings(&table, &PowerSettings::read());
+    print_cycles_per_sec(&table, count_cycles_per_sec());
+    print_clock_resolution(&table, ClockSource::Monotonic);
+    print_clock_resolution(&table, ClockSource::Tsc);
+    print_timer_overhead(&table, get_timer_overhead());
+    table.print_footer();
+}
+
+fn create_plan_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Tasks", 5, Align::Right, 0),
+        Column::create("Series", 6, Align::Right, 1),
+        Column::create("Estimated duration", 18, Align::Right, 0),
+        Column::create("Status", 8, Align::Left, 0)
+    ], colored)
+}
+
+fn print_plan_output(title: &str, file_path: &String) {
+    if !file_path.is_empty() {
+        println!("{}: {}", title, file_path);
+    }
+}
+
+fn preview_concurrency_profit(args: &Args) {
+
+    let (tasks_max, series_size) = (args.get_tasks_max(), args.get_series_size());
+
+    info!("Calibrating to estimate durations");
+    let budget = RuntimeBudget::create_calibrated(args.get_max_runtime(), 
+                                                  args.get_n_cycles()*args.get_repeats());
+
+    let table = create_plan_table(args.get_colored());
+    table.print_header();
+
+    let mut time_spent = TaskDuration::ZERO;
+
+    for n_tasks in 1..tasks_max + 1 {
+        let estimate = budget.estimate_observation(n_tasks, series_size);
+        let planned = budget.allows_after(n_tasks, tasks_max, series_size, time_spent);
+        if planned {
+            time_spent += estimate;
+        }
+        table.print_row(&vec![
+            Cell::Plain(n_tasks.to_string()),
+            Cell::Plain(count_series(n_tasks, series_size).to_string()),
+            Cell::Plain(format!("{} ms", format_count(estimate.as_millis() as usize))),
+            Cell::Plain(if planned {"planned"} else {"skipped"}.to_string())
+        ]);
+    }
+
+    table.print_footer();
+
+    println!("Cycles in a task: {}", format_count(args.get_n_cycles()));
+    println!("Estimated task duration: {} ms", format_count(budget.task_duration.as_millis() as usize));
+    println!("Estimated total duration: {} sec.", time_spent.as_duration().as_secs());
+    print_plan_output("Report file", &args.get_out_file_path());
+    if args.get_chart_file_path() != "" {
+        print_plan_output("Chart script", &get_chart_script_path(&args.get_chart_file_path()));
+        print_plan_output("Chart file", &args.get_chart_file_path());
+    }
+    print_plan_output("History file", &args.get_history_file_path());
+}
+
+#[cfg(feature = "tui")]
+fn test_concurrency_profit_live(args: &Args, report: Report) -> Report {
+    tui::test_concurrency_profit(args, report)
+}
+
+#[cfg(not(feature = "tui"))]
+fn test_concurrency_profit_live(args: &Args, report: Report) -> Report {
+    warn!("The live dashboard is unavailable, rebuild with --features tui");
+    test_concurrency_profit(args, report)
+}
+
+fn test_concurrency_profit(args: &Args, mut report: Report) -> Report {
+
+    let config = args.get_run_config();
+    let (tasks_max, n_cycles, series_size) = 
+        (config.get_tasks_max(), config.get_n_cycles(), config.get_series_size());
+
+    let watch = SystemTime::now();
+
+    let repeats = config.get_repeats();
+    let mut budget = RuntimeBudget::create(args.get_max_runtime(), n_cycles*repeats);
+    if report.count_observations() > 0 {
+        budget.refine(report.get_task_duration_min()*repeats);
+    }
+    
+    let metrics = args.get_metrics();
+    let table = if metrics.is_empty() {
+        create_profit_table(args.get_colored(), repeats > 1)
+    } else {
+        metrics::create_table(&metrics, args.get_colored())
+    };
+    let separators = args.get_separators();
+    table.print_header();
+
+    for n_tasks in config.get_tasks() {
+
+        if report.find_observation(n_tasks).is_none() {
+            if budget.allows(n_tasks, tasks_max, series_size) {
+                let obs = observe_repeatedly(n_tasks, &config);
+                if cancel::is_cancelled() {
+                    break;
+                }
+                report.register_observation(obs);
+                tracy::plot_observation(report.find_observation(n_tasks).unwrap());
+                budget.refine(report.get_task_duration_min()*repeats);
+            } else {
+                report.register_skipped(n_tasks);
+            }
+            save_report(&args.get_out_file_path(), &report);
+        }
+
+        match report.find_observation(n_tasks) {
+            Some(obs) if !metrics.is_empty() => metrics::print_entry(&table, &metrics, obs),
+            Some(obs) => print_profit_entry(&table, obs, repeats > 1),
+            None if !metrics.is_empty() => metrics::print_skipped_entry(&table, &metrics, n_tasks),
+            None => print_skipped_profit_entry(&table, n_tasks, repeats > 1)
+        }
+        
+        match find_separator(&separators, n_tasks) {
+            Some(separator) if n_tasks != tasks_max => print_profit_separator(&table, separator, n_tasks),
+            _ => {}
+        }
+    } 
+
+    table.print_footer();
+
+    if cancel::is_cancelled() {
+        warn!("Measuring cancelled, {} observations kept", report.count_observations());
+    }
+    print_failure_summary(&report);
+    if !report.skipped.is_empty() {
+        warn!("{} observations skipped to fit into the runtime budget", report.skipped.len());
+    }
+    print_profit_duration(read_elapsed(&watch));
+    print_run_summary(&report);
+
+    report
+}
+
+
+// Logging messages and events
+
+#[derive(Copy, Clone, PartialEq)]
+enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+    VeryVerbose
+}
+
+fn get_log_level(verbosity: Verbosity) -> LevelFilter {
+    match verbosity {
+        Verbosity::Quiet => LevelFilter::ERROR,
+        Verbosity::Normal => LevelFilter::INFO,
+        Verbosity::Verbose => LevelFilter::DEBUG,
+        Verbosity::VeryVerbose => LevelFilter::TRACE
+    }
+}
+
+fn init_logging(verbosity: Verbosity, log_json: bool, tui: bool) {
+
+    let log_level = if tui {LevelFilter::OFF} else {get_log_level(verbosity)};
+
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(log_level)
+        .with_writer(std::io::stderr);
+
+    if log_json {
+        subscriber.json().init();
+    } else {
+        subscriber.without_time().with_target(false).with_level(false).init();
+    }
+}
+
+
+// Expanding presets of common scenarios
+
+#[derive(Copy, Clone, PartialEq)]
+enum Preset {
+    Custom,
+    Quick,
+    Thorough,
+    Oversubscribe
+}
+
+struct PresetParams {
+    tasks_per_cpu: usize,
+    series_per_cpu: usize,
+    task_duration: TaskDuration,
+    retries_max: usize
+}
+
+impl Preset {
+
+    fn parse(s: &str) -> Option<Preset> {
+        match s {
+            "" => Some(Preset::Custom),
+            "quick" => Some(Preset::Quick),
+            "thorough" => Some(Preset::Thorough),
+            "oversubscribe" => Some(Preset::Oversubscribe),
+            _ => None
+        }
+    }
+
+    fn get_params(self: &Self) -> Option<PresetParams> {
+        match self {
+            Preset::Custom => None,
+            Preset::Quick => Some(PresetParams {
+                tasks_per_cpu: 1, series_per_cpu: 1, task_duration: TaskDuration::from_millis(50), retries_max: 0
+            }),
+            Preset::Thorough => Some(PresetParams {
+                tasks_per_cpu: 2, series_per_cpu: 2, task_duration: TaskDuration::from_millis(1000), retries_max: 2
+            }),
+            Preset::Oversubscribe => Some(PresetParams {
+                tasks_per_cpu: 4, series_per_cpu: 4, task_duration: TaskDuration::from_millis(200), retries_max: 1
+            })
+        }
+    }
+}
+
+// Accepting arguments
+
+fn validate_usize(s: &str) -> bool {   
+    Regex::new(r"^\d+$").unwrap().is_match(s)
+}
+
+fn parse_usize(s: &str) -> usize {
+    if validate_usize(s) {
+        s.parse::<usize>().unwrap()
+    } else {
+        0
+    }    
+}
+
+fn parse_size_bytes(s: &str) -> usize {
+
+    let units: [(&str, usize); 6] = 
+        [("k", 1024), ("K", 1024), ("m", 1024*1024), ("M", 1024*1024), 
+         ("g", 1024*1024*1024), ("G", 1024*1024*1024)];
+
+    for (suffix, unit) in units {
+        if let Some(number) = s.strip_suffix(suffix) {
+            if validate_usize(number) {
+                return number.parse::<usize>().unwrap()*unit;
+            }
+        }
+    }
+
+    parse_usize(s)
+}
+
+/// Nothing given is no duration, anything not a duration is nothing valid, so a typo
+/// never lifts a limit.
+fn parse_duration_ms(s: &str) -> Option<TaskDuration> {
+
+    let units: [(&str, u64); 4] = [("ms", 1), ("s", 1000), ("m", 60000), ("h", 3600000)];
+
+    for (suffix, unit_ms) in units {
+        if let Some(number) = s.strip_suffix(suffix) {
+            if validate_usize(number) {
+                return Some(TaskDuration::from_millis(number.parse::<u64>().unwrap()*unit_ms));
+            }
+        }
+    }
+
+    match s {
+        "" => Some(TaskDuration::ZERO),
+        _ if validate_usize(s) => Some(TaskDuration::from_millis(parse_usize(s) as u64)),
+        _ => None
+    }
+}
+
+type ArgsVec = Vec<String>;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Command {
+    Help,
+    Invalid,
+    RequestSysParams,
+    MeasureConcurrencyProfit,
+    RenderTrend,
+    RunSuite,
+    PrintCompletions,
+    ListChoices,
+    RunSelfTest,
+    RunDoctor,
+    RunSoak,
+    RunStress,
+    RunInversion,
+    CompareSharing,
+    StudyGranularity,
+    ComparePlacements,
+    CompareSmt,
+    RunOpenLoop,
+    ReplayTask,
+    CompareRuns,
+    RenderReport,
+    MergeReports,
+}
+
+/// A command as typed, with what its help tells.
+struct Subcommand {
+    name: &'static str,
+    command: Command,
+    summary: &'static str,
+    usage: &'static [&'static str],
+    options: &'static [&'static str]
+}
+
+const SUBCOMMANDS: &[Subcommand] = &[
+    Subcommand {
+        name: "sys",
+        command: Command::RequestSysParams,
+        summary: "Display system parameters",
+        usage: &[""],
+        options: &[]
+    },
+    Subcommand {
+        name: "profit",
+        command: Command::MeasureConcurrencyProfit,
+        summary: "Measure profits of concurrency",
+        usage: &["<Number of tasks> <Cycles in a task> <Tasks in a series> [Output file] [Options]",
+                 "--resume <File> [Options]",
+                 "--preset <Preset> [Output file] [Options]"],
+        options: &[OPT_CHART, OPT_SHOW_SCHEDULE, OPT_WORK_SPAN, OPT_TUI, OPT_LABEL, OPT_HISTORY, OPT_TASK_TIMEOUT,
+                   OPT_OBSERVATION_TIMEOUT, OPT_RETRIES, OPT_MAX_RUNTIME, OPT_DRY_RUN, OPT_SEED,
+                   OPT_RESUME, OPT_PRESET, OPT_STRICT, OPT_DOCTOR, OPT_BACKEND, OPT_WATCH,
+                   OPT_STD_DEV, OPT_REPEATS, OPT_CLOCK, OPT_HIGH_PRIORITY, OPT_PIN_FREQUENCY,
+                   OPT_QOS, OPT_SCHED,
+                   OPT_RT_PRIO, OPT_NICE, OPT_STACK_SIZE, OPT_SEPARATORS, OPT_PROCESSES, OPT_DAG,
+                   OPT_RNG, OPT_STOP_ON_CONVERGE, OPT_TRIPLETS, OPT_WORKLOAD, OPT_SYSCALLS,
+                   OPT_MAP_SIZE, OPT_BUFFER_SIZE, OPT_BUFFER_DATA, OPT_IMAGE_SIZE, OPT_WRITE_RATIO,
+                   OPT_PLACEMENT, OPT_COMPRESS, OPT_CAPTURE, OPT_AGGREGATE_ONLY, OPT_METRICS]
+    },
+    Subcommand {
+        name: "trend",
+        command: Command::RenderTrend,
+        summary: "Render trends from a history file",
+        usage: &["<History file> [Numbers of tasks, e.g. 1,4,8]"],
+        options: &[]
+    },
+    Subcommand {
+        name: "suite",
+        command: Command::RunSuite,
+        summary: "Run named configurations from a suite file (TOML)",
+        usage: &["<Suite file> [Output file] [Options]"],
+        options: &[OPT_SHOW_SCHEDULE, OPT_WORK_SPAN, OPT_HISTORY, OPT_TASK_TIMEOUT, OPT_OBSERVATION_TIMEOUT,
+                   OPT_RETRIES, OPT_MAX_RUNTIME, OPT_SEED, OPT_BACKEND, OPT_STD_DEV, OPT_CLOCK,
+                   OPT_HIGH_PRIORITY, OPT_QOS, OPT_SCHED, OPT_RT_PRIO, OPT_NICE, OPT_STACK_SIZE,
+                   OPT_SEPARATORS, OPT_PROCESSES, OPT_DAG, OPT_RNG, OPT_STOP_ON_CONVERGE,
+                   OPT_TRIPLETS, OPT_WORKLOAD, OPT_SYSCALLS, OPT_MAP_SIZE, OPT_BUFFER_SIZE,
+                   OPT_BUFFER_DATA, OPT_IMAGE_SIZE, OPT_WRITE_RATIO, OPT_PLACEMENT, OPT_COMPRESS]
+    },
+    Subcommand {
+        name: "compare",
+        command: Command::CompareRuns,
+        summary: "Compare runs saved with --repeats, testing significance of differences",
+        usage: &["<Baseline file> <Candidate file> [Options]",
+                 "<Merged file> [Report or merged file] [Options]"],
+        options: &[OPT_TASKS, OPT_ONLY_TOTALS, OPT_SORT_BY]
+    },
+    Subcommand {
+        name: "render",
+        command: Command::RenderReport,
+        summary: "Render a saved report in another format without measuring again",
+        usage: &["<Report file> [Output file] [--format <Format>] [Options]",
+                 "<Report file> [Output file] --template <File> [Options]"],
+        options: &[OPT_FORMAT, OPT_TEMPLATE, OPT_TASKS, OPT_ONLY_TOTALS, OPT_SORT_BY]
+    },
+    Subcommand {
+        name: "merge",
+        command: Command::MergeReports,
+        summary: "Merge runs of several reports into one file, labelled by run",
+        usage: &["<Report or merged file>... [-o <Merged file>]"],
+        options: &[OPT_OUTPUT, OPT_COMPRESS]
+    },
+    Subcommand {
+        name: "soak",
+        command: Command::RunSoak,
+        summary: "Repeat an observation of a number of tasks to detect drift",
+        usage: &["<Number of tasks> <Cycles in a task> <Tasks in a series> [Output file] \
+                  --duration <Time> [Options]"],
+        options: &[OPT_DURATION, OPT_TASK_TIMEOUT, OPT_OBSERVATION_TIMEOUT, OPT_RETRIES, OPT_SEED,
+                   OPT_BACKEND, OPT_STD_DEV, OPT_REPEATS, OPT_CLOCK, OPT_HIGH_PRIORITY, OPT_QOS,
+                   OPT_SCHED, OPT_RT_PRIO, OPT_NICE, OPT_STACK_SIZE, OPT_COMPRESS]
+    },
+    Subcommand {
+        name: "open",
+        command: Command::RunOpenLoop,
+        summary: "Serve tasks arriving at a rate with a number of workers",
+        usage: &["<Number of tasks> <Cycles in a task> <Workers> [Output file] \
+                  --rate <Tasks per sec> [Options]"],
+        options: &[OPT_RATE, OPT_ARRIVALS, OPT_TASK_TIMEOUT, OPT_OBSERVATION_TIMEOUT, OPT_SEED,
+                   OPT_CLOCK, OPT_HIGH_PRIORITY, OPT_QOS, OPT_SCHED, OPT_RT_PRIO, OPT_NICE,
+                   OPT_STACK_SIZE, OPT_COMPRESS]
+    },
+    Subcommand {
+        name: "stress",
+        command: Command::RunStress,
+        summary: "Keep threads busy, one per CPU by default",
+        usage: &["[Number of threads] --duration <Time> [Options]"],
+        options: &[OPT_DURATION, OPT_SEED, OPT_HIGH_PRIORITY, OPT_QOS, OPT_SCHED, OPT_RT_PRIO,
+                   OPT_NICE, OPT_STACK_SIZE]
+    },
+    Subcommand {
+        name: "inversion",
+        command: Command::RunInversion,
+        summary: "Show a low priority task holding up high priority ones",
+        usage: &["<Cycles in a task> [Medium priority tasks, one per CPU by default]"],
+        options: &[]
+    },
+    Subcommand {
+        name: "sharing",
+        command: Command::CompareSharing,
+        summary: "Compare accumulating into a shared total with thread-locals",
+        usage: &["<Max number of tasks> <Cycles in a task> [Options]"],
+        options: &[OPT_SEED, OPT_RNG]
+    },
+    Subcommand {
+        name: "granularity",
+        command: Command::StudyGranularity,
+        summary: "Split a fixed total of cycles into 1 to 2^k chunks on a rayon pool",
+        usage: &["<Cycles in total> [Max power of two of chunks, 16 by default]"],
+        options: &[OPT_SEED, OPT_RNG]
+    },
+    Subcommand {
+        name: "placement",
+        command: Command::ComparePlacements,
+        summary: "Run the same tasks pinned by every placement and unpinned",
+        usage: &["<Number of tasks> <Cycles in a task> [Options]"],
+        options: &[OPT_SEED, OPT_RNG, OPT_TASK_TIMEOUT]
+    },
+    Subcommand {
+        name: "smt",
+        command: Command::CompareSmt,
+        summary: "Run the sweep on one logical CPU per core and on all of them",
+        usage: &["<Max number of tasks> <Cycles in a task> <Series size> [Options]"],
+        options: &[OPT_SEED, OPT_RNG, OPT_TASK_TIMEOUT, OPT_TRIPLETS, OPT_WORKLOAD, OPT_SYSCALLS,
+                   OPT_MAP_SIZE, OPT_BUFFER_SIZE, OPT_BUFFER_DATA, OPT_IMAGE_SIZE, OPT_WRITE_RATIO]
+    },
+    Subcommand {
+        name: "replay-task",
+        command: Command::ReplayTask,
+        summary: "Replay a single task by the seed its schedule row records",
+        usage: &["<Cycles in a task> --seed <Seed of the task> [Options]"],
+        options: &[OPT_SEED, OPT_WORKLOAD, OPT_RNG, OPT_TRIPLETS, OPT_STOP_ON_CONVERGE, OPT_SYSCALLS,
+                   OPT_MAP_SIZE, OPT_BUFFER_SIZE, OPT_BUFFER_DATA, OPT_IMAGE_SIZE, OPT_WRITE_RATIO,
+                   OPT_TASK_TIMEOUT]
+    },
+    Subcommand {
+        name: "selftest",
+        command: Command::RunSelfTest,
+        summary: "Check the measurement machinery",
+        usage: &[""],
+        options: &[]
+    },
+    Subcommand {
+        name: "doctor",
+        command: Command::RunDoctor,
+        summary: "Check the system for common pitfalls of benchmarking",
+        usage: &[""],
+        options: &[]
+    },
+    Subcommand {
+        name: "list",
+        command: Command::ListChoices,
+        summary: "List available choices",
+        usage: &["<Kind>"],
+        options: &[]
+    },
+    Subcommand {
+        name: "completions",
+        command: Command::PrintCompletions,
+        summary: "Generate a shell completion script",
+        usage: &["<bash|zsh|fish|powershell>"],
+        options: &[]
+    },
+    Subcommand {
+        name: "help",
+        command: Command::Help,
+        summary: "Display help, of a command if given",
+        usage: &["[Command]"],
+        options: &[]
+    }
+];
+
+/// Options every command takes.
+const GLOBAL_OPTIONS: &[&str] = &[OPT_QUIET, OPT_VERBOSE, OPT_LOG_JSON, OPT_NO_COLOR, 
+                                  OPT_NUMBER_STYLE, OPT_HELP];
+
+fn list_commands() -> Vec<&'static str> {
+    SUBCOMMANDS.iter().map(|subcommand| subcommand.name).collect()
+}
+
+fn find_subcommand(command: Command) -> Option<&'static Subcommand> {
+    SUBCOMMANDS.iter().find(|subcommand| subcommand.command == command)
+}
+
+fn find_command(name: &str) -> Command {
+    SUBCOMMANDS.iter()
+        .find(|subcommand| subcommand.name == name)
+        .map_or(Command::Invalid, |subcommand| subcommand.command)
+}
+
+const ARG_IDX_COMMAND: usize = 1;
+const ARG_IDX_HELP_COMMAND: usize = 2;
+const ARG_IDX_TASKS_MAX: usize = 2;
+const ARG_IDX_N_CYCLES: usize = 3;
+const ARG_IDX_SERIES_SIZE: usize = 4;
+const ARG_IDX_OUT_FILE_PATH: usize = 5;
+const ARG_IDX_HISTORY_FILE_PATH: usize = 2;
+const ARG_IDX_TREND_TASKS: usize = 3;
+const ARG_IDX_PRESET_OUT_FILE_PATH: usize = 2;
+const ARG_IDX_SUITE_FILE_PATH: usize = 2;
+const ARG_IDX_SUITE_OUT_FILE_PATH: usize = 3;
+const ARG_IDX_COMPLETIONS_SHELL: usize = 2;
+const ARG_IDX_LIST_KIND: usize = 2;
+const ARG_IDX_BASELINE_FILE_PATH: usize = 2;
+const ARG_IDX_CANDIDATE_FILE_PATH: usize = 3;
+const ARG_IDX_REPORT_FILE_PATH: usize = 2;
+const ARG_IDX_RENDER_OUT_FILE_PATH: usize = 3;
+const ARG_IDX_MERGED_FILE_PATHS: usize = 2;
+const ARG_IDX_REPLAY_N_CYCLES: usize = 2;
+const ARG_IDX_INVERSION_N_CYCLES: usize = 2;
+const ARG_IDX_INVERSION_MEDIUM_TASKS: usize = 3;
+const ARG_IDX_GRANULARITY_N_CYCLES: usize = 2;
+const ARG_IDX_GRANULARITY_POWER_MAX: usize = 3;
+
+const OPT_CHART: &str = "--chart";
+const OPT_SHOW_SCHEDULE: &str = "--show-schedule";
+const OPT_WORK_SPAN: &str = "--work-span";
+const OPT_TUI: &str = "--tui";
+const OPT_QUIET: &str = "-q";
+const OPT_VERBOSE: &str = "-v";
+const OPT_VERY_VERBOSE: &str = "-vv";
+const OPT_LOG_JSON: &str = "--log-json";
+const OPT_NO_COLOR: &str = "--no-color";
+const OPT_NUMBER_STYLE: &str = "--number-style";
+const OPT_LABEL: &str = "--label";
+const OPT_HISTORY: &str = "--history";
+const OPT_TASK_TIMEOUT: &str = "--task-timeout";
+const OPT_OBSERVATION_TIMEOUT: &str = "--observation-timeout";
+const OPT_RETRIES: &str = "--retries";
+const OPT_MAX_RUNTIME: &str = "--max-runtime";
+const OPT_DRY_RUN: &str = "--dry-run";
+const OPT_SEED: &str = "--seed";
+const OPT_PRESET: &str = "--preset";
+const OPT_STRICT: &str = "--strict";
+const OPT_DOCTOR: &str = "--doctor";
+const OPT_BACKEND: &str = "--backend";
+const OPT_WATCH: &str = "--watch";
+const OPT_DURATION: &str = "--duration";
+const OPT_RATE: &str = "--rate";
+const OPT_ARRIVALS: &str = "--arrivals";
+const OPT_STD_DEV: &str = "--std-dev";
+const OPT_REPEATS: &str = "--repeats";
+const OPT_CLOCK: &str = "--clock";
+const OPT_RNG: &str = "--rng";
+const OPT_STOP_ON_CONVERGE: &str = "--stop-on-converge";
+const OPT_TRIPLETS: &str = "--triplets";
+const OPT_WORKLOAD: &str = "--workload";
+const OPT_SYSCALLS: &str = "--syscalls";
+const OPT_MAP_SIZE: &str = "--map-size";
+const OPT_BUFFER_SIZE: &str = "--buffer-size";
+const OPT_BUFFER_DATA: &str = "--buffer-data";
+const OPT_IMAGE_SIZE: &str = "--image-size";
+const OPT_WRITE_RATIO: &str = "--write-ratio";
+const OPT_PLACEMENT: &str = "--placement";
+const OPT_HIGH_PRIORITY: &str = "--high-priority";
+const OPT_PIN_FREQUENCY: &str = "--pin-frequency";
+const OPT_QOS: &str = "--qos";
+const OPT_SCHED: &str = "--sched";
+const OPT_RT_PRIO: &str = "--rt-prio";
+const OPT_NICE: &str = "--nice";
+const OPT_STACK_SIZE: &str = "--stack-size";
+const OPT_PROCESSES: &str = "--processes";
+const OPT_DAG: &str = "--dag";
+const OPT_RESUME: &str = "--resume";
+const OPT_FORMAT: &str = "--format";
+const OPT_TEMPLATE: &str = "--template";
+const OPT_OUTPUT: &str = "-o";
+const OPT_COMPRESS: &str = "--compress";
+const OPT_CAPTURE: &str = "--capture";
+const OPT_AGGREGATE_ONLY: &str = "--aggregate-only";
+const OPT_METRICS: &str = "--metrics";
+const OPT_TASKS: &str = "--tasks";
+const OPT_ONLY_TOTALS: &str = "--only-totals";
+const OPT_SORT_BY: &str = "--sort-by";
+const OPT_SEPARATORS: &str = "--separators";
+const OPT_HELP: &str = "--help";
+const OPT_HELP_SHORT: &str = "-h";
+const OPTIONS: &[&str] = &[OPT_CHART, OPT_SHOW_SCHEDULE, OPT_TUI, OPT_QUIET, OPT_VERBOSE, 
+                           OPT_VERY_VERBOSE, OPT_LOG_JSON, OPT_NO_COLOR, OPT_NUMBER_STYLE, 
+                           OPT_LABEL, OPT_HISTORY, OPT_TASK_TIMEOUT, OPT_OBSERVATION_TIMEOUT, 
+                           OPT_RETRIES, OPT_MAX_RUNTIME, OPT_DRY_RUN, OPT_SEED, OPT_PRESET, 
+                           OPT_RESUME, OPT_STRICT, OPT_DOCTOR, OPT_BACKEND, OPT_WATCH,
+                           OPT_DURATION, OPT_RATE, OPT_ARRIVALS, OPT_STD_DEV,
+                           OPT_REPEATS, OPT_CLOCK, OPT_HIGH_PRIORITY, OPT_PIN_FREQUENCY, OPT_QOS,
+                           OPT_SCHED, OPT_RT_PRIO, OPT_NICE, OPT_STACK_SIZE, OPT_FORMAT, 
+                           OPT_OUTPUT, OPT_TASKS, OPT_ONLY_TOTALS, OPT_SORT_BY,
+                           OPT_SEPARATORS, OPT_PROCESSES, OPT_DAG, OPT_WORK_SPAN, OPT_RNG,
+                           OPT_STOP_ON_CONVERGE, OPT_TRIPLETS, OPT_WORKLOAD,
+                           OPT_SYSCALLS, OPT_MAP_SIZE, OPT_BUFFER_SIZE, OPT_BUFFER_DATA,
+                           OPT_IMAGE_SIZE, OPT_WRITE_RATIO, OPT_PLACEMENT, OPT_COMPRESS, OPT_CAPTURE,
+                           OPT_AGGREGATE_ONLY, OPT_METRICS, OPT_TEMPLATE, OPT_HELP, OPT_HELP_SHORT];
+const OPTIONS_WITH_VALUES: &[&str] = &[OPT_CHART, OPT_NUMBER_STYLE, OPT_LABEL, OPT_HISTORY,
+                                       OPT_TASK_TIMEOUT, OPT_OBSERVATION_TIMEOUT, OPT_RETRIES,
+                                       OPT_MAX_RUNTIME, OPT_SEED, OPT_RESUME,
+                                       OPT_PRESET, OPT_BACKEND, OPT_WATCH, OPT_DURATION,
+                                       OPT_RATE, OPT_ARRIVALS, OPT_STD_DEV, OPT_REPEATS,
+                                       OPT_CLOCK, OPT_QOS, OPT_SCHED, OPT_RT_PRIO,
+                                       OPT_NICE, OPT_STACK_SIZE, OPT_FORMAT, OPT_OUTPUT,
+                                       OPT_TASKS, OPT_SORT_BY, OPT_SEPARATORS, OPT_PROCESSES,
+                                       OPT_DAG, OPT_RNG, OPT_TRIPLETS, OPT_WORKLOAD, OPT_SYSCALLS,
+                                       OPT_MAP_SIZE, OPT_BUFFER_SIZE, OPT_BUFFER_DATA, OPT_IMAGE_SIZE,
+                                       OPT_WRITE_RATIO, OPT_PLACEMENT, OPT_COMPRESS, OPT_METRICS,
+                                       OPT_TEMPLATE];
+
+const ENV_TASKS: &str = "CONCTEST_TASKS";
+const ENV_CYCLES: &str = "CONCTEST_CYCLES";
+const ENV_SERIES: &str = "CONCTEST_SERIES";
+const ENV_BACKEND: &str = "CONCTEST_BACKEND";
+const ENV_OUTPUT_DIR: &str = "CONCTEST_OUTPUT_DIR";
+const ENV_COLOR: &str = "CONCTEST_COLOR";
+const ENV_SEED: &str = "CONCTEST_SEED";
+const DEFAULT_BACKEND: &str = "threads";
+
+fn read_env(name: &str) -> String {
+    env::var(name).unwrap_or_default()
+}
+
+/// Values given in arguments take precedence over environment variables.
+fn find_option_or_env_value(args: &ArgsVec, option: &str, env_name: &str) -> String {
+    let value = find_option_value(args, option);
+    if value.is_empty() {read_env(env_name)} else {value}
+}
+
+fn place_in_output_dir(file_path: String) -> String {
+    let output_dir = read_env(ENV_OUTPUT_DIR);
+    if file_path.is_empty() || output_dir.is_empty() || Path::new(&file_path).is_absolute() {
+        file_path
+    } else {
+        Path::new(&output_dir).join(file_path).to_string_lossy().to_string()
+    }
+}
+
+fn is_option(arg: &str) -> bool {
+    arg.starts_with("-")
+}
+
+fn extract_positional_args(args: &ArgsVec) -> ArgsVec {
+
+    let mut positional_args: ArgsVec = Vec::with_capacity(args.len());
+    let mut idx = 0usize;
+
+    while idx < args.len() {
+        if is_option(&args[idx]) {
+            if OPTIONS_WITH_VALUES.contains(&&*args[idx]) {
+                idx += 1;
+            }
+        } else {
+            positional_args.push(args[idx].to_string());
+        }
+        idx += 1;
+    }
+
+    positional_args
+}
+
+fn has_option(args: &ArgsVec, option: &str) -> bool {
+    args.iter().any(|arg| arg == option)
+}
+
+fn find_option_value(args: &ArgsVec, option: &str) -> String {
+    match args.iter().position(|arg| arg == option) {
+        Some(idx) if idx + 1 < args.len() => args[idx + 1].to_string(),
+        _ => "".to_string()
+    }
+}
+
+#[derive(Clone)]
+struct Args {
+    command: Command,
+    help: bool,
+    tasks_max: usize,
+    n_cycles: usize,
+    series_size: usize,
+    out_file_path: String,
+    chart_file_path: String,
+    show_schedule: bool,
+    work_span: bool,
+    tui: bool,
+    verbosity: Verbosity,
+    log_json: bool,
+    colored: bool,
+    number_style: Option<NumberStyle>,
+    label: String,
+    history_file_path: String,
+    trend_tasks: Vec<usize>,
+    suite_file_path: String,
+    baseline_file_path: String,
+    candidate_file_path: String,
+    report_file_path: String,
+    render_format: String,
+    template_file_path: String,
+    merged_file_paths: Vec<String>,
+    selected_tasks: Vec<usize>,
+    only_totals: bool,
+    sort_by: String,
+    separators: String,
+    completions_shell: String,
+    list_kind: String,
+    task_seed: String,
+    timeouts: Option<Timeouts>,
+    retries_max: usize,
+    max_runtime: Option<TaskDuration>,
+    dry_run: bool,
+    seed: Option<u64>,
+    resume_file_path: String,
+    preset: Option<Preset>,
+    strict: bool,
+    doctor: bool,
+    backend: String,
+    watch_interval: Option<TaskDuration>,
+    duration: Option<TaskDuration>,
+    arrival_rate: f64,
+    arrivals: Option<Arrivals>,
+    deviation_kind: Option<DeviationKind>,
+    rng_kind: Option<RngKind>,
+    convergence_mode: ConvergenceMode,
+    n_triplets: usize,
+    workload: String,
+    n_syscalls: usize,
+    map_size: usize,
+    buffer_size: usize,
+    buffer_data: String,
+    image_size: String,
+    write_percent: usize,
+    placement: String,
+    compression: String,
+    capture: bool,
+    aggregate_only: bool,
+    metrics: String,
+    chunk_power_max: usize,
+    n_processes: usize,
+    dag: String,
+    repeats: usize,
+    clock_source: Option<ClockSource>,
+    high_priority: bool,
+    pin_frequency: bool,
+    qos_class: Option<QosClass>,
+    sched_policy: Option<SchedPolicy>,
+    rt_priority: Option<i32>,
+    nice: Option<i32>,
+    stack_size: usize
+}
+
+impl Args {
+
+    fn get_command(self: &Self) -> Command {
+        self.command
+    }
+
+    fn get_help(self: &Self) -> bool {
+        self.help
+    }
+
+    fn get_tasks_max(self: &Self) -> usize {
+        self.tasks_max
+    }
+
+    fn get_n_cycles(self: &Self) -> usize {
+        self.n_cycles
+    }
+
+    fn get_series_size(self: &Self) -> usize {
+        self.series_size
+    }
+
+    fn get_out_file_path(self: &Self) -> String {
+        self.out_file_path.clone()
+    }
+
+    fn get_chart_file_path(self: &Self) -> String {
+        self.chart_file_path.clone()
+    }
+
+    fn get_show_schedule(self: &Self) -> bool {
+        self.show_schedule
+    }
+
+    fn get_work_span(self: &Self) -> bool {
+        self.work_span
+    }
+
+    fn get_tui(self: &Self) -> bool {
+        self.tui
+    }
+
+    fn get_verbosity(self: &Self) -> Verbosity {
+        self.verbosity
+    }
+
+    fn get_log_json(self: &Self) -> bool {
+        self.log_json
+    }
+
+    fn get_colored(self: &Self) -> bool {
+        self.colored
+    }
+
+    fn get_label(self: &Self) -> String {
+        self.label.clone()
+    }
+
+    fn get_history_file_path(self: &Self) -> String {
+        self.history_file_path.clone()
+    }
+
+    fn get_suite_file_path(self: &Self) -> String {
+        self.suite_file_path.clone()
+    }
+
+    fn get_baseline_file_path(self: &Self) -> String {
+        self.baseline_file_path.clone()
+    }
+
+    fn get_candidate_file_path(self: &Self) -> String {
+        self.candidate_file_path.clone()
+    }
+
+    fn get_report_file_path(self: &Self) -> String {
+        self.report_file_path.clone()
+    }
+
+    fn get_render_format(self: &Self) -> String {
+        self.render_format.clone()
+    }
+
+    fn get_template_file_path(self: &Self) -> String {
+        self.template_file_path.clone()
+    }
+
+    fn get_merged_file_paths(self: &Self) -> Vec<String> {
+        self.merged_file_paths.clone()
+    }
+
+    fn get_report_query(self: &Self) -> ReportQuery {
+        ReportQuery::create(self.selected_tasks.clone(), self.only_totals, SortKey::parse(&self.sort_by))
+    }
+
+    /// Without numbers given, rows are separated at physical and logical cores.
+    fn get_separators(self: &Self) -> Vec<Separator> {
+        match &*self.separators {
+            "" => create_core_separators(count_physical_cpus(), count_cpus()),
+            "none" => Vec::new(),
+            separators => create_separators(&separators.split(',')
+                .map(parse_usize)
+                .collect::<Vec<usize>>())
+        }
+    }
+
+    fn get_completions_shell(self: &Self) -> String {
+        self.completions_shell.clone()
+    }
+
+    fn get_list_kind(self: &Self) -> String {
+        self.list_kind.clone()
+    }
+
+    /// The seed of a single task to replay, as a schedule records it.
+    fn get_task_seed(self: &Self) -> Option<u64> {
+        self.task_seed.parse::<u64>().ok()
+    }
+
+    fn get_trend_tasks(self: &Self) -> Vec<usize> {
+        self.trend_tasks.clone()
+    }
+
+    fn get_timeouts(self: &Self) -> Timeouts {
+        self.timeouts.unwrap_or(Timeouts::create(TaskDuration::ZERO, TaskDuration::ZERO))
+    }
+
+    fn get_retries_max(self: &Self) -> usize {
+        self.retries_max
+    }
+
+    fn get_max_runtime(self: &Self) -> TaskDuration {
+        self.max_runtime.unwrap_or(TaskDuration::ZERO)
+    }
+
+    fn get_dry_run(self: &Self) -> bool {
+        self.dry_run
+    }
+
+    fn get_seed(self: &Self) -> u64 {
+        self.seed.unwrap_or(0)
+    }
+
+    fn get_resume_file_path(self: &Self) -> String {
+        self.resume_file_path.clone()
+    }
+
+    fn get_preset(self: &Self) -> Preset {
+        self.preset.unwrap_or(Preset::Custom)
+    }
+
+    fn get_strict(self: &Self) -> bool {
+        self.strict
+    }
+
+    fn get_doctor(self: &Self) -> bool {
+        self.doctor
+    }
+
+    fn get_backend(self: &Self) -> String {
+        self.backend.clone()
+    }
+
+    fn get_watch_interval(self: &Self) -> TaskDuration {
+        self.watch_interval.unwrap_or(TaskDuration::ZERO)
+    }
+
+    fn get_duration(self: &Self) -> TaskDuration {
+        self.duration.unwrap_or(TaskDuration::ZERO)
+    }
+
+    /// Durations given are all valid.
+    fn are_durations_valid(self: &Self) -> bool {
+        self.timeouts.is_some() && self.max_runtime.is_some() &&
+            self.watch_interval.is_some() && self.duration.is_some()
+    }
+
+    fn get_arrival_rate(self: &Self) -> f64 {
+        self.arrival_rate
+    }
+
+    fn get_arrivals(self: &Self) -> Arrivals {
+        self.arrivals.unwrap_or(Arrivals::Constant)
+    }
+
+    fn get_deviation_kind(self: &Self) -> DeviationKind {
+        self.deviation_kind.unwrap_or(DeviationKind::Sample)
+    }
+
+    fn get_rng_kind(self: &Self) -> RngKind {
+        self.rng_kind.unwrap_or(RngKind::Std)
+    }
+
+    fn get_convergence_mode(self: &Self) -> ConvergenceMode {
+        self.convergence_mode
+    }
+
+    fn get_n_triplets(self: &Self) -> usize {
+        self.n_triplets
+    }
+
+    fn get_workload_kind(self: &Self) -> Option<WorkloadKind> {
+        WorkloadKind::parse(&self.workload)
+    }
+
+    fn get_n_syscalls(self: &Self) -> usize {
+        self.n_syscalls
+    }
+
+    fn get_map_size(self: &Self) -> usize {
+        self.map_size
+    }
+
+    fn get_buffer_size(self: &Self) -> usize {
+        self.buffer_size
+    }
+
+    fn get_buffer_data(self: &Self) -> Option<BufferData> {
+        BufferData::parse(&self.buffer_data)
+    }
+
+    fn get_image_size(self: &Self) -> Option<(usize, usize)> {
+        workload::parse_image_size(&self.image_size)
+    }
+
+    fn get_write_percent(self: &Self) -> usize {
+        self.write_percent
+    }
+
+    fn get_placement(self: &Self) -> Option<Placement> {
+        Placement::parse(&self.placement)
+    }
+
+    fn get_compression(self: &Self) -> Option<Compression> {
+        Compression::parse(&self.compression)
+    }
+
+    fn get_capture(self: &Self) -> bool {
+        self.capture
+    }
+
+    fn get_aggregate_only(self: &Self) -> bool {
+        self.aggregate_only
+    }
+
+    /// Nothing for the default columns.
+    fn get_metrics(self: &Self) -> Vec<Metric> {
+        metrics::parse_metrics(&self.metrics).unwrap_or_default()
+    }
+
+    /// Granularity studies split work into up to 2 to this power chunks.
+    fn get_chunk_power_max(self: &Self) -> usize {
+        self.chunk_power_max
+    }
+
+    fn get_n_processes(self: &Self) -> usize {
+        self.n_processes
+    }
+
+    fn get_dag(self: &Self) -> String {
+        self.dag.clone()
+    }
+
+    fn get_repeats(self: &Self) -> usize {
+        self.repeats
+    }
+
+    fn get_clock_source(self: &Self) -> ClockSource {
+        self.clock_source.unwrap_or(ClockSource::Monotonic)
+    }
+
+    fn get_high_priority(self: &Self) -> bool {
+        self.high_priority
+    }
+
+    fn get_pin_frequency(self: &Self) -> bool {
+        self.pin_frequency
+    }
+
+    fn get_qos_class(self: &Self) -> QosClass {
+        self.qos_class.unwrap_or(QosClass::Unspecified)
+    }
+
+    fn get_sched_policy(self: &Self) -> SchedPolicy {
+        self.sched_policy.unwrap_or(SchedPolicy::Other)
+    }
+
+    fn get_rt_priority(self: &Self) -> i32 {
+        self.rt_priority.unwrap_or(platform::RT_PRIORITY_MIN)
+    }
+
+    fn get_nice(self: &Self) -> i32 {
+        self.nice.unwrap_or(0)
+    }
+
+    fn get_stack_size(self: &Self) -> usize {
+        self.stack_size
+    }
+
+    fn get_worker_settings(self: &Self) -> WorkerSettings {
+        WorkerSettings {
+            qos_class: self.get_qos_class(),
+            sched_policy: self.get_sched_policy(),
+            rt_priority: self.get_rt_priority(),
+            nice: self.get_nice(),
+            stack_size: self.get_stack_size()
+        }
+    }
+
+    fn get_number_style(self: &Self) -> NumberStyle {
+        if self.log_json {NumberStyle::Plain} else {self.number_style.unwrap_or(NumberStyle::Grouped)}
+    }
+
+    /// Global options are checked for every command, not only measuring ones.
+    fn is_global_valid(self: &Self) -> bool {
+        self.number_style.is_some()
+    }
+
+    fn parse_command(self: &Self, args: &ArgsVec) -> Command {
+        if args.len() > ARG_IDX_COMMAND {
+            find_command(&args[ARG_IDX_COMMAND])
+        } else {
+            Command::Help
+        }
+    }
+
+    /// Both `help <Command>` and `<Command> --help` display help of the command.
+    fn parse_help(self: &Self, args: &ArgsVec, positional_args: &ArgsVec) -> bool {
+        has_option(args, OPT_HELP) || has_option(args, OPT_HELP_SHORT) ||
+            (self.command == Command::Help && positional_args.len() > ARG_IDX_HELP_COMMAND)
+    }
+
+    fn parse_tasks_max(self: &Self, args: &ArgsVec) -> usize {
+        parse_usize(&args[ARG_IDX_TASKS_MAX])
+    }
+
+    fn parse_n_cycles(self: &Self, args: &ArgsVec) -> usize {
+        parse_usize(&args[ARG_IDX_N_CYCLES])
+    }
+    
+    fn parse_series_size(self: &Self, args: &ArgsVec) -> usize {
+        parse_usize(&args[ARG_IDX_SERIES_SIZE])
+    }
+    
+    fn parse_out_file_path(self: &Self, args: &ArgsVec) -> String {
+        if args.len() == ARG_IDX_OUT_FILE_PATH + 1 {
+            args[ARG_IDX_OUT_FILE_PATH].to_string()
+        } else {
+            "".to_string()
+        }
+    }
+    
+    fn parse_chart_file_path(self: &Self, args: &ArgsVec) -> String {
+        find_option_value(args, OPT_CHART)
+    }
+
+    fn parse_work_span(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_WORK_SPAN)
+    }
+
+    fn parse_show_schedule(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_SHOW_SCHEDULE)
+    }
+
+    fn parse_tui(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_TUI)
+    }
+
+    fn parse_verbosity(self: &Self, args: &ArgsVec) -> Verbosity {
+        if has_option(args, OPT_QUIET) {
+            Verbosity::Quiet
+        } else if has_option(args, OPT_VERY_VERBOSE) {
+            Verbosity::VeryVerbose
+        } else if has_option(args, OPT_VERBOSE) {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+
+    fn parse_log_json(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_LOG_JSON)
+    }
+
+    fn parse_colored(self: &Self, args: &ArgsVec) -> bool {
+        match &*read_env(ENV_COLOR) {
+            _ if has_option(args, OPT_NO_COLOR) => false,
+            "always" => true,
+            "never" => false,
+            _ => table::is_color_enabled(false)
+        }
+    }
+
+    fn parse_label(self: &Self, args: &ArgsVec) -> String {
+        find_option_value(args, OPT_LABEL)
+    }
+
+    fn parse_render_format(self: &Self, args: &ArgsVec) -> String {
+        find_option_value(args, OPT_FORMAT)
+    }
+
+    fn parse_template_file_path(self: &Self, args: &ArgsVec) -> String {
+        find_option_value(args, OPT_TEMPLATE)
+    }
+
+    fn parse_selected_tasks(self: &Self, args: &ArgsVec) -> Vec<usize> {
+        match &*find_option_value(args, OPT_TASKS) {
+            "" => Vec::new(),
+            tasks => tasks.split(',').map(parse_usize).collect()
+        }
+    }
+
+    fn parse_only_totals(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_ONLY_TOTALS)
+    }
+
+    fn parse_sort_by(self: &Self, args: &ArgsVec) -> String {
+        find_option_value(args, OPT_SORT_BY)
+    }
+
+    fn parse_separators(self: &Self, args: &ArgsVec) -> String {
+        find_option_value(args, OPT_SEPARATORS)
+    }
+
+    fn parse_history_file_path(self: &Self, args: &ArgsVec, positional_args: &ArgsVec) -> String {
+        if self.command == Command::RenderTrend && positional_args.len() > ARG_IDX_HISTORY_FILE_PATH {
+            positional_args[ARG_IDX_HISTORY_FILE_PATH].to_string()
+        } else {
+            find_option_value(args, OPT_HISTORY)
+        }
+    }
+
+    fn parse_timeouts(self: &Self, args: &ArgsVec) -> Option<Timeouts> {
+        Some(Timeouts::create(parse_duration_ms(&find_option_value(args, OPT_TASK_TIMEOUT))?,
+                              parse_duration_ms(&find_option_value(args, OPT_OBSERVATION_TIMEOUT))?))
+    }
+
+    fn parse_retries_max(self: &Self, args: &ArgsVec) -> usize {
+        parse_usize(&find_option_value(args, OPT_RETRIES))
+    }
+
+    fn parse_max_runtime(self: &Self, args: &ArgsVec) -> Option<TaskDuration> {
+        parse_duration_ms(&find_option_value(args, OPT_MAX_RUNTIME))
+    }
+
+    fn parse_dry_run(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_DRY_RUN)
+    }
+
+    fn parse_seed(self: &Self, args: &ArgsVec) -> Option<u64> {
+        match &*find_option_or_env_value(args, OPT_SEED, ENV_SEED) {
+            "" => Some(rand::random()),
+            seed => seed.parse::<u64>().ok()
+        }
+    }
+
+    fn parse_sched_policy(self: &Self, args: &ArgsVec) -> Option<SchedPolicy> {
+        SchedPolicy::parse(&find_option_value(args, OPT_SCHED))
+    }
+
+    fn parse_rt_priority(self: &Self, args: &ArgsVec) -> Option<i32> {
+        match &*find_option_value(args, OPT_RT_PRIO) {
+            "" => Some(platform::RT_PRIORITY_MIN),
+            rt_priority => rt_priority.parse::<i32>().ok()
+        }
+    }
+
+    fn parse_stack_size(self: &Self, args: &ArgsVec) -> usize {
+        parse_size_bytes(&find_option_value(args, OPT_STACK_SIZE))
+    }
+
+    fn parse_nice(self: &Self, args: &ArgsVec) -> Option<i32> {
+        match &*find_option_value(args, OPT_NICE) {
+            "" => Some(0),
+            nice => nice.parse::<i32>().ok()
+        }
+    }
+
+    fn parse_qos_class(self: &Self, args: &ArgsVec) -> Option<QosClass> {
+        QosClass::parse(&find_option_value(args, OPT_QOS))
+    }
+
+    fn parse_high_priority(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_HIGH_PRIORITY)
+    }
+
+    fn parse_pin_frequency(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_PIN_FREQUENCY)
+    }
+
+    fn parse_clock_source(self: &Self, args: &ArgsVec) -> Option<ClockSource> {
+        ClockSource::parse(&find_option_value(args, OPT_CLOCK))
+    }
+
+    fn parse_n_processes(self: &Self, args: &ArgsVec) -> usize {
+        match &*find_option_value(args, OPT_PROCESSES) {
+            "" => fanout::DEFAULT_PROCESSES,
+            n_processes => parse_usize(n_processes)
+        }
+    }
+
+    fn parse_dag(self: &Self, args: &ArgsVec) -> String {
+        find_option_value(args, OPT_DAG)
+    }
+
+    fn parse_repeats(self: &Self, args: &ArgsVec) -> usize {
+        parse_usize(&find_option_value(args, OPT_REPEATS)).max(1)
+    }
+
+    fn parse_deviation_kind(self: &Self, args: &ArgsVec) -> Option<DeviationKind> {
+        DeviationKind::parse(&find_option_value(args, OPT_STD_DEV))
+    }
+
+    fn parse_rng_kind(self: &Self, args: &ArgsVec) -> Option<RngKind> {
+        match &*find_option_value(args, OPT_RNG) {
+            "" => Some(RngKind::Std),
+            rng_kind => RngKind::parse(rng_kind)
+        }
+    }
+
+    fn parse_convergence_mode(self: &Self, args: &ArgsVec) -> ConvergenceMode {
+        if has_option(args, OPT_STOP_ON_CONVERGE) {ConvergenceMode::Stop} else {ConvergenceMode::AllCycles}
+    }
+
+    fn parse_n_triplets(self: &Self, args: &ArgsVec) -> usize {
+        let n_triplets = find_option_value(args, OPT_TRIPLETS);
+        if n_triplets.is_empty() {batch::DEFAULT_TRIPLETS} else {parse_size_bytes(&n_triplets)}
+    }
+
+    fn parse_workload(self: &Self, args: &ArgsVec) -> String {
+        let workload = find_option_value(args, OPT_WORKLOAD);
+        if workload.is_empty() {workload::DEFAULT_WORKLOAD.to_string()} else {workload}
+    }
+
+    fn parse_n_syscalls(self: &Self, args: &ArgsVec) -> usize {
+        let n_syscalls = find_option_value(args, OPT_SYSCALLS);
+        if n_syscalls.is_empty() {workload::DEFAULT_SYSCALLS} else {parse_usize(&n_syscalls)}
+    }
+
+    fn parse_map_size(self: &Self, args: &ArgsVec) -> usize {
+        let map_size = find_option_value(args, OPT_MAP_SIZE);
+        if map_size.is_empty() {workload::DEFAULT_MAP_SIZE} else {parse_size_bytes(&map_size)}
+    }
+
+    fn parse_buffer_size(self: &Self, args: &ArgsVec) -> usize {
+        let buffer_size = find_option_value(args, OPT_BUFFER_SIZE);
+        if buffer_size.is_empty() {workload::DEFAULT_BUFFER_SIZE} else {parse_size_bytes(&buffer_size)}
+    }
+
+    fn parse_buffer_data(self: &Self, args: &ArgsVec) -> String {
+        let buffer_data = find_option_value(args, OPT_BUFFER_DATA);
+        if buffer_data.is_empty() {BufferData::Random.get_name().to_string()} else {buffer_data}
+    }
+
+    fn parse_image_size(self: &Self, args: &ArgsVec) -> String {
+        let image_size = find_option_value(args, OPT_IMAGE_SIZE);
+        if image_size.is_empty() {workload::DEFAULT_IMAGE_SIZE.to_string()} else {image_size}
+    }
+
+    fn parse_placement(self: &Self, args: &ArgsVec) -> String {
+        find_option_value(args, OPT_PLACEMENT)
+    }
+
+    fn parse_compression(self: &Self, args: &ArgsVec) -> String {
+        find_option_value(args, OPT_COMPRESS)
+    }
+
+    fn parse_capture(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_CAPTURE)
+    }
+
+    fn parse_aggregate_only(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_AGGREGATE_ONLY)
+    }
+
+    fn parse_metrics(self: &Self, args: &ArgsVec) -> String {
+        find_option_value(args, OPT_METRICS)
+    }
+
+    fn parse_write_percent(self: &Self, args: &ArgsVec) -> usize {
+        let write_percent = find_option_value(args, OPT_WRITE_RATIO);
+        if write_percent.is_empty() {workload::DEFAULT_WRITE_PERCENT} else {parse_usize(&write_percent)}
+    }
+
+    fn parse_arrival_rate(self: &Self, args: &ArgsVec) -> f64 {
+        find_option_value(args, OPT_RATE).parse::<f64>().unwrap_or(0.0)
+    }
+
+    fn parse_arrivals(self: &Self, args: &ArgsVec) -> Option<Arrivals> {
+        Arrivals::parse(&find_option_value(args, OPT_ARRIVALS))
+    }
+
+    fn parse_duration(self: &Self, args: &ArgsVec) -> Option<TaskDuration> {
+        parse_duration_ms(&find_option_value(args, OPT_DURATION))
+    }
+
+    fn parse_watch_interval(self: &Self, args: &ArgsVec) -> Option<TaskDuration> {
+        parse_duration_ms(&find_option_value(args, OPT_WATCH))
+    }
+
+    fn parse_backend(self: &Self, args: &ArgsVec) -> String {
+        let backend = find_option_or_env_value(args, OPT_BACKEND, ENV_BACKEND);
+        if backend.is_empty() {DEFAULT_BACKEND.to_string()} else {backend}
+    }
+
+    fn parse_strict(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_STRICT)
+    }
+
+    fn parse_doctor(self: &Self, args: &ArgsVec) -> bool {
+        has_option(args, OPT_DOCTOR)
+    }
+
+    fn parse_preset(self: &Self, args: &ArgsVec) -> Option<Preset> {
+        Preset::parse(&find_option_value(args, OPT_PRESET))
+    }
+
+    fn parse_resume_file_path(self: &Self, args: &ArgsVec) -> String {
+        find_option_value(args, OPT_RESUME)
+    }
+
+    fn parse_trend_tasks(self: &Self, args: &ArgsVec) -> Vec<usize> {
+        if args.len() > ARG_IDX_TREND_TASKS {
+            args[ARG_IDX_TREND_TASKS].split(',').map(parse_usize).collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn parse_number_style(self: &Self, args: &ArgsVec) -> Option<NumberStyle> {
+        match &*find_option_value(args, OPT_NUMBER_STYLE) {
+            "plain" => Some(NumberStyle::Plain),
+            "grouped" | "" => Some(NumberStyle::Grouped),
+            _ => None
+        }
+    }
+
+    fn parse(mut self: Self, args: &ArgsVec) -> Self {
+
+        let positional_args = extract_positional_args(args);
+
+        if !positional_args.is_empty() {
+            self.command = self.parse_command(&positional_args);
+            match self.command {
+                Command::MeasureConcurrencyProfit | Command::RunSoak | Command::RunOpenLoop
+                    if positional_args.len() >= 4 => {
+                    self.tasks_max = self.parse_tasks_max(&positional_args);
+                    self.n_cycles = self.parse_n_cycles(&positional_args);
+                    self.series_size = self.parse_series_size(&positional_args);
+                    self.out_file_path = self.parse_out_file_path(&positional_args);
+                }
+                Command::MeasureConcurrencyProfit 
+                    if positional_args.len() > ARG_IDX_PRESET_OUT_FILE_PATH => {
+                    self.out_file_path = 
+                        positional_args[ARG_IDX_PRESET_OUT_FILE_PATH].to_string();
+                }
+                Command::RenderTrend => {
+                    self.trend_tasks = self.parse_trend_tasks(&positional_args);
+                }
+                Command::RunSuite if positional_args.len() > ARG_IDX_SUITE_FILE_PATH => {
+                    self.suite_file_path = positional_args[ARG_IDX_SUITE_FILE_PATH].to_string();
+                    if positional_args.len() > ARG_IDX_SUITE_OUT_FILE_PATH {
+                        self.out_file_path = 
+                            positional_args[ARG_IDX_SUITE_OUT_FILE_PATH].to_string();
+                    }
+                }
+                Command::PrintCompletions if positional_args.len() > ARG_IDX_COMPLETIONS_SHELL => {
+                    self.completions_shell = 
+                        positional_args[ARG_IDX_COMPLETIONS_SHELL].to_string();
+                }
+                Command::RunStress if positional_args.len() > ARG_IDX_TASKS_MAX => {
+                    self.tasks_max = self.parse_tasks_max(&positional_args);
+                }
+                Command::CompareRuns if positional_args.len() > ARG_IDX_BASELINE_FILE_PATH => {
+                    self.baseline_file_path = 
+                        positional_args[ARG_IDX_BASELINE_FILE_PATH].to_string();
+                    if positional_args.len() > ARG_IDX_CANDIDATE_FILE_PATH {
+                        self.candidate_file_path = 
+                            positional_args[ARG_IDX_CANDIDATE_FILE_PATH].to_string();
+                    }
+                }
+                Command::MergeReports => {
+                    self.merged_file_paths = positional_args.iter()
+                        .skip(ARG_IDX_MERGED_FILE_PATHS)
+                        .cloned()
+                        .collect();
+                    self.out_file_path = find_option_value(args, OPT_OUTPUT);
+                }
+                Command::RenderReport if positional_args.len() > ARG_IDX_REPORT_FILE_PATH => {
+                    self.report_file_path = positional_args[ARG_IDX_REPORT_FILE_PATH].to_string();
+                    if positional_args.len() > ARG_IDX_RENDER_OUT_FILE_PATH {
+                        self.out_file_path = 
+                            positional_args[ARG_IDX_RENDER_OUT_FILE_PATH].to_string();
+                    }
+                }
+                Command::ReplayTask => {
+                    if positional_args.len() > ARG_IDX_REPLAY_N_CYCLES {
+                        self.n_cycles = parse_usize(&positional_args[ARG_IDX_REPLAY_N_CYCLES]);
+                    }
+                    self.task_seed = find_option_value(args, OPT_SEED);
+                }
+                Command::CompareSharing if positional_args.len() > ARG_IDX_N_CYCLES => {
+                    self.tasks_max = self.parse_tasks_max(&positional_args);
+                    self.n_cycles = self.parse_n_cycles(&positional_args);
+                }
+                Command::ComparePlacements if positional_args.len() > ARG_IDX_N_CYCLES => {
+                    self.tasks_max = self.parse_tasks_max(&positional_args);
+                    self.n_cycles = self.parse_n_cycles(&positional_args);
+                }
+                Command::CompareSmt if positional_args.len() > ARG_IDX_SERIES_SIZE => {
+                    self.tasks_max = self.parse_tasks_max(&positional_args);
+                    self.n_cycles = self.parse_n_cycles(&positional_args);
+                    self.series_size = self.parse_series_size(&positional_args);
+                }
+                Command::StudyGranularity if positional_args.len() > ARG_IDX_GRANULARITY_N_CYCLES => {
+                    self.n_cycles = parse_usize(&positional_args[ARG_IDX_GRANULARITY_N_CYCLES]);
+                    if positional_args.len() > ARG_IDX_GRANULARITY_POWER_MAX {
+                        self.chunk_power_max = parse_usize(&positional_args[ARG_IDX_GRANULARITY_POWER_MAX]);
+                    }
+                }
+                Command::RunInversion if positional_args.len() > ARG_IDX_INVERSION_N_CYCLES => {
+                    self.n_cycles = parse_usize(&positional_args[ARG_IDX_INVERSION_N_CYCLES]);
+                    if positional_args.len() > ARG_IDX_INVERSION_MEDIUM_TASKS {
+                        self.tasks_max = parse_usize(&positional_args[ARG_IDX_INVERSION_MEDIUM_TASKS]);
+                    }
+                }
+                Command::ListChoices if positional_args.len() > ARG_IDX_LIST_KIND => {
+                    self.list_kind = positional_args[ARG_IDX_LIST_KIND].to_string();
+                }
+                _ => {}
+            }
+        }
+
+        self.render_format = self.parse_render_format(args);
+        self.template_file_path = self.parse_template_file_path(args);
+        self.selected_tasks = self.parse_selected_tasks(args);
+        self.only_totals = self.parse_only_totals(args);
+        self.sort_by = self.parse_sort_by(args);
+        self.separators = self.parse_separators(args);
+        self.help = self.parse_help(args, &positional_args);
+        if self.command == Command::Help && positional_args.len() > ARG_IDX_HELP_COMMAND {
+            self.command = find_command(&positional_args[ARG_IDX_HELP_COMMAND]);
+        }
+
+        self.history_file_path = self.parse_history_file_path(args, &positional_args);
+        self.timeouts = self.parse_timeouts(args);
+        self.retries_max = self.parse_retries_max(args);
+        self.max_runtime = self.parse_max_runtime(args);
+        self.dry_run = self.parse_dry_run(args);
+        self.seed = self.parse_seed(args);
+        self.resume_file_path = self.parse_resume_file_path(args);
+        self.preset = self.parse_preset(args);
+        self.strict = self.parse_strict(args);
+        self.doctor = self.parse_doctor(args);
+        self.backend = self.parse_backend(args);
+        self.watch_interval = self.parse_watch_interval(args);
+        self.duration = self.parse_duration(args);
+        self.arrival_rate = self.parse_arrival_rate(args);
+        self.arrivals = self.parse_arrivals(args);
+        self.deviation_kind = self.parse_deviation_kind(args);
+        self.rng_kind = self.parse_rng_kind(args);
+        self.convergence_mode = self.parse_convergence_mode(args);
+        self.n_triplets = self.parse_n_triplets(args);
+        self.workload = self.parse_workload(args);
+        self.n_syscalls = self.parse_n_syscalls(args);
+        self.map_size = self.parse_map_size(args);
+        self.buffer_size = self.parse_buffer_size(args);
+        self.buffer_data = self.parse_buffer_data(args);
+        self.image_size = self.parse_image_size(args);
+        self.write_percent = self.parse_write_percent(args);
+        self.placement = self.parse_placement(args);
+        self.compression = self.parse_compression(args);
+        self.capture = self.parse_capture(args);
+        self.aggregate_only = self.parse_aggregate_only(args);
+        self.metrics = self.parse_metrics(args);
+        self.n_processes = self.parse_n_processes(args);
+        self.dag = self.parse_dag(args);
+        self.repeats = self.parse_repeats(args);
+        self.clock_source = self.parse_clock_source(args);
+        self.high_priority = self.parse_high_priority(args);
+        self.pin_frequency = self.parse_pin_frequency(args);
+        self.qos_class = self.parse_qos_class(args);
+        self.sched_policy = self.parse_sched_policy(args);
+        self.rt_priority = self.parse_rt_priority(args);
+        self.nice = self.parse_nice(args);
+        self.stack_size = self.parse_stack_size(args);
+
+        self.chart_file_path = self.parse_chart_file_path(args);
+        self.show_schedule = self.parse_show_schedule(args);
+        self.work_span = self.parse_work_span(args);
+        self.tui = self.parse_tui(args);
+        self.verbosity = self.parse_verbosity(args);
+        self.log_json = self.parse_log_json(args);
+        self.colored = self.parse_colored(args);
+        self.number_style = self.parse_number_style(args);
+        self.label = self.parse_label(args);
+
+        self
+    }
+
+    fn fill_from_env(mut self: Self) -> Self {
+
+        if self.tasks_max == 0 {
+            self.tasks_max = parse_usize(&read_env(ENV_TASKS));
+        }
+
+        if self.n_cycles == 0 {
+            self.n_cycles = parse_usize(&read_env(ENV_CYCLES));
+        }
+
+        if self.series_size == 0 {
+            self.series_size = parse_usize(&read_env(ENV_SERIES));
+        }
+
+        self.out_file_path = place_in_output_dir(self.out_file_path);
+        self.chart_file_path = place_in_output_dir(self.chart_file_path);
+
+        self
+    }
+
+    /// Continuing a saved sweep with its original parameters.
+    fn adopt_resumed(mut self: Self, report: &Report) -> Self {
+
+        let get_usize = |key: &str| report.get_metadata(key).map_or(0, |value| parse_usize(value));
+
+        self.tasks_max = get_usize("Tasks");
+        self.n_cycles = get_usize("Cycles in a task");
+        self.series_size = get_usize("Tasks in a series");
+        self.seed = report.get_metadata("Seed").and_then(|seed| seed.parse::<u64>().ok());
+
+        if let Some(backend) = report.get_metadata("Backend") {
+            self.backend = backend.clone();
+        }
+
+        if let Some(n_processes) = report.get_metadata("Processes") {
+            self.n_processes = parse_usize(n_processes);
+        }
+
+        if let Some(dag) = report.get_metadata("Dependencies") {
+            self.dag = dag.clone();
+        }
+
+        if self.label.is_empty() {
+            self.label = report.get_metadata("Label").cloned().unwrap_or_default();
+        }
+
+        if self.out_file_path.is_empty() {
+            self.out_file_path = self.resume_file_path.clone();
+        }
+
+        self
+    }
+
+    /// Parameters given explicitly take precedence over the ones of a preset.
+    fn expand_preset(mut self: Self) -> Self {
+
+        let params = match self.get_preset().get_params() {
+            Some(params) => params,
+            None => return self
+        };
+
+        let n_cpus = count_cpus();
+
+        if self.tasks_max == 0 {
+            self.tasks_max = params.tasks_per_cpu*n_cpus;
+        }
+
+        if self.series_size == 0 {
+            self.series_size = params.series_per_cpu*n_cpus;
+        }
+
+        if self.n_cycles == 0 {
+            info!("Calibrating a task to last about {} ms", params.task_duration);
+            let n_cycles = count_cycles_per_sec() as u64*params.task_duration.as_millis()/1000;
+            self.n_cycles = (n_cycles as usize).max(1);
+        }
+
+        if self.retries_max == 0 {
+            self.retries_max = params.retries_max;
+        }
+
+        self
+    }
+
+    fn is_trend_valid(self: &Self) -> bool {
+        self.get_history_file_path() != "" &&
+        self.get_trend_tasks().iter().all(|n_tasks| *n_tasks > 0)
+    }
+
+    fn is_open_loop_valid(self: &Self) -> bool {
+        self.is_valid() && self.get_arrival_rate() > 0.0 && self.arrivals.is_some()
+    }
+
+    /// Seeds of tasks are random, so replaying one takes its seed.
+    fn is_replay_valid(self: &Self) -> bool {
+        self.get_n_cycles() > 0 && self.get_task_seed().is_some() && self.get_n_triplets() > 0 &&
+        self.get_workload_kind().is_some() && self.get_map_size() > 0 && self.get_buffer_data().is_some() &&
+        self.get_image_size().is_some() && self.get_write_percent() <= 100 && self.rng_kind.is_some()
+    }
+
+    fn is_soak_valid(self: &Self) -> bool {
+        self.is_valid() && !self.get_duration().is_zero()
+    }
+
+    fn is_comparison_valid(self: &Self) -> bool {
+        self.get_baseline_file_path() != "" && self.is_query_valid()
+    }
+
+    /// Numbers of tasks given are all valid, and so is a key given.
+    fn is_query_valid(self: &Self) -> bool {
+        !self.selected_tasks.contains(&0) && (self.sort_by.is_empty() || SortKey::parse(&self.sort_by).is_some())
+    }
+
+    fn is_merge_valid(self: &Self) -> bool {
+        !self.get_merged_file_paths().is_empty() && self.get_compression().is_some()
+    }
+
+    fn is_render_valid(self: &Self) -> bool {
+        self.get_report_file_path() != "" && self.is_query_valid() &&
+            (self.get_render_format() == "" || 
+             (render::RENDER_FORMATS.contains(&&*self.get_render_format()) &&
+              self.get_template_file_path() == ""))
+    }
+
+    fn is_suite_valid(self: &Self) -> bool {
+        self.get_suite_file_path() != "" && self.get_compression().is_some()
+    }
+
+    /// The measurement engine gets its configuration built as embedders build theirs.
+    fn get_run_config(self: &Self) -> RunConfig {
+        RunConfig::new()
+            .tasks(1..=self.get_tasks_max())
+            .cycles(self.get_n_cycles())
+            .series_size(self.get_series_size())
+            .backend(&self.get_backend())
+            .processes(self.get_n_processes())
+            .dag(&self.get_dag())
+            .repeats(self.get_repeats())
+            .timeouts(self.get_timeouts())
+            .retries(self.get_retries_max())
+            .seed(self.get_seed())
+    }
+
+    /// Options given are all valid whatever the command, so none of them falls back to its default.
+    fn are_options_valid(self: &Self) -> bool {
+        self.rt_priority.is_some_and(|rt_priority| self.get_sched_policy() == SchedPolicy::Other ||
+            (platform::RT_PRIORITY_MIN..=platform::RT_PRIORITY_MAX).contains(&rt_priority)) &&
+        self.nice.is_some_and(|nice| (platform::NICE_MIN..=platform::NICE_MAX).contains(&nice)) &&
+        (self.get_stack_size() == 0 || self.get_stack_size() >= platform::STACK_SIZE_MIN) &&
+        self.get_separators().iter().all(|separator| separator.period > 0) &&
+        self.get_n_triplets() > 0 &&
+        self.get_workload_kind().is_some() &&
+        self.get_map_size() > 0 &&
+        self.get_buffer_data().is_some() &&
+        self.get_image_size().is_some() &&
+        self.get_write_percent() <= 100 &&
+        self.get_placement().is_some() &&
+        self.are_durations_valid() &&
+        self.get_compression().is_some() &&
+        self.clock_source.is_some() &&
+        self.qos_class.is_some() &&
+        self.sched_policy.is_some() &&
+        self.rng_kind.is_some() &&
+        self.deviation_kind.is_some() &&
+        self.preset.is_some() &&
+        self.seed.is_some() &&
+        !(self.get_capture() && self.get_aggregate_only()) &&
+        metrics::parse_metrics(&self.metrics).is_some()
+    }
+
+    fn is_valid(self: &Self) -> bool {
+        self.are_options_valid() &&
+        self.get_run_config().is_valid() &&
+        self.get_series_size() <= self.get_tasks_max()
+    }
+
+    fn create() -> Args {
+        Args{command: Command::Help, 
+             help: false,
+             tasks_max: 0, 
+             n_cycles: 0, 
+             series_size: 0, 
+             out_file_path: "".to_string(),
+             chart_file_path: "".to_string(),
+             show_schedule: false,
+             work_span: false,
+             tui: false,
+             verbosity: Verbosity::Normal,
+             log_json: false,
+             colored: false,
+             number_style: Some(NumberStyle::Grouped),
+             label: "".to_string(),
+             history_file_path: "".to_string(),
+             trend_tasks: Vec::new(),
+             suite_file_path: "".to_string(),
+             baseline_file_path: "".to_string(),
+             candidate_file_path: "".to_string(),
+             report_file_path: "".to_string(),
+             render_format: "".to_string(),
+             template_file_path: "".to_string(),
+             merged_file_paths: Vec::new(),
+             selected_tasks: Vec::new(),
+             only_totals: false,
+             sort_by: "".to_string(),
+             separators: "".to_string(),
+             completions_shell: "".to_string(),
+             list_kind: "".to_string(),
+             task_seed: "".to_string(),
+             timeouts: Some(Timeouts::create(TaskDuration::ZERO, TaskDuration::ZERO)),
+             retries_max: 0,
+             max_runtime: Some(TaskDuration::ZERO),
+             dry_run: false,
+             seed: Some(0),
+             resume_file_path: "".to_string(),
+             preset: Some(Preset::Custom),
+             strict: false,
+             doctor: false,
+             backend: DEFAULT_BACKEND.to_string(),
+             watch_interval: Some(TaskDuration::ZERO),
+             duration: Some(TaskDuration::ZERO),
+             arrival_rate: 0.0,
+             arrivals: Some(Arrivals::Constant),
+             deviation_kind: Some(DeviationKind::Sample),
+             rng_kind: Some(RngKind::Std),
+             convergence_mode: ConvergenceMode::AllCycles,
+             n_triplets: batch::DEFAULT_TRIPLETS,
+             workload: workload::DEFAULT_WORKLOAD.to_string(),
+             n_syscalls: workload::DEFAULT_SYSCALLS,
+             map_size: workload::DEFAULT_MAP_SIZE,
+             buffer_size: workload::DEFAULT_BUFFER_SIZE,
+             buffer_data: BufferData::Random.get_name().to_string(),
+             image_size: workload::DEFAULT_IMAGE_SIZE.to_string(),
+             write_percent: workload::DEFAULT_WRITE_PERCENT,
+             placement: "".to_string(),
+             compression: "".to_string(),
+             capture: false,
+             aggregate_only: false,
+             metrics: "".to_string(),
+             chunk_power_max: granularity::DEFAULT_CHUNK_POWER_MAX,
+             n_processes: fanout::DEFAULT_PROCESSES,
+             dag: "".to_string(),
+             repeats: 1,
+             clock_source: Some(ClockSource::Monotonic),
+             high_priority: false,
+             pin_frequency: false,
+             qos_class: Some(QosClass::Unspecified),
+             sched_policy: Some(SchedPolicy::Other),
+             rt_priority: Some(0),
+             nice: Some(0),
+             stack_size: 0}
+    }
+}
+
+/// Environment variables only fill in what the arguments leave unset.
+fn accept_args(args: ArgsVec) -> Args {
+    Args::create().parse(&args).fill_from_env()
+}
+
+
+// Doing the job 
+
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_INVALID_ARGS: i32 = 2;
+const EXIT_NOISE: i32 = 3;
+const EXIT_REGRESSION: i32 = 4;
+
+/// Invalid arguments of a command bring up its help.
+fn reject_args(command: Command) -> i32 {
+    print_command_help(command);
+    EXIT_INVALID_ARGS
+}
+
+/// Warnings only fail a run in strict mode.
+fn assess_measuring(args: &Args, report: &Report, before: &EnvironmentSnapshot) -> i32 {
+
+    let mut warnings = environment::find_environment_warnings(before, 
+                                                              &EnvironmentSnapshot::capture());
+
+    let stolen = environment::find_stolen_observations(&report.observations);
+    if !stolen.is_empty() {
+        warnings.push(format!("The hypervisor stole CPU time from observations of {} tasks", 
+                              stolen.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", ")));
+    }
+
+    let n_outliers = report.observations.iter().filter(|obs| obs.is_outlier()).count();
+    if n_outliers > 0 {
+        warnings.push(format!("{} observations remain outlying", n_outliers));
+    }
+
+    let task_duration_min = report.observations.iter()
+        .flat_map(|obs| obs.tasks.iter().map(|task| task.get_duration()))
+        .min();
+    if let Some(task_duration_min) = task_duration_min {
+        let timer_overhead = get_timer_overhead() as f64;
+        if timer_overhead > TIMER_OVERHEAD_SHARE_MAX*1e6*task_duration_min.as_millis_f64() {
+            warnings.push(format!("The timer overhead of {} ns is significant for tasks of {} ms",
+                                  timer_overhead, task_duration_min));
+        }
+    }
+
+    let clock_skew = measure_clock_skew(args.get_series_size());
+    debug!(clock_skew = clock_skew.as_millis(), "Clock skew measured");
+    if clock_skew > CLOCK_SKEW_MAX {
+        warnings.push(format!("System clocks of threads disagree by up to {} ms", clock_skew));
+    }
+
+    for warning in &warnings {
+        warn!("{}", warning);
+    }
+
+    if args.get_strict() && !warnings.is_empty() {
+        warn!("Measurements are disturbed by system noise");
+        EXIT_NOISE
+    } else {
+        EXIT_SUCCESS
+    }
+}
+
+fn register_run_metadata(report: &mut Report, args: &Args, start: Timestamp) {
+    if args.get_label() != "" {
+        report.set_metadata("Label", &args.get_label());
+    }
+    report.set_metadata("Started", &format_utc_datetime(start));
+    report.set_metadata("Commit", &get_git_commit());
+    report.set_metadata("Build profile", &format_build_profile());
+    report.set_metadata("Debug assertions", if is_debug_build() {"on"} else {"off"});
+    report.set_metadata("LTO", BUILD_LTO);
+    report.set_metadata("Target", BUILD_TARGET);
+    report.set_metadata("Tasks", &args.get_tasks_max().to_string());
+    report.set_metadata("Cycles in a task", &args.get_n_cycles().to_string());
+    report.set_metadata("Tasks in a series", &args.get_series_size().to_string());
+    report.set_metadata("Seed", &args.get_seed().to_string());
+    report.set_metadata("Backend", &args.get_backend());
+    if args.get_backend() == fanout::BACKEND {
+        report.set_metadata("Processes", &args.get_n_processes().to_string());
+    }
+    if args.get_dag() != "" {
+        report.set_metadata("Dependencies", &args.get_dag());
+    }
+    report.set_metadata("Std. dev.", args.get_deviation_kind().get_name());
+    report.set_metadata("Repeats", &args.get_repeats().to_string());
+    if aggregate::is_aggregate_only() {
+        report.set_metadata("Tasks kept", "aggregates only");
+    }
+    if !args.get_metrics().is_empty() {
+        report.set_metadata("Metrics", &metrics::format_metrics(&args.get_metrics()));
+    }
+    report.set_metadata("Clock", clock::get_clock_source().get_name());
+    report.set_metadata("RNG", rng::get_rng_kind().get_name());
+    report.set_metadata("Workload", workload::get_workload_kind().get_name());
+    if workload::get_workload_kind() == WorkloadKind::Syscalls {
+        report.set_metadata("Syscalls per cycle", &workload::get_n_syscalls().to_string());
+    }
+    if workload::get_workload_kind() == WorkloadKind::Pagefaults {
+        report.set_metadata("Mapped per cycle", &workload::get_map_size().to_string());
+    }
+    if workload::get_workload_kind() == WorkloadKind::Compress {
+        let (buffer_size, buffer_data) = workload::get_buffer();
+        report.set_metadata("Buffer size", &buffer_size.to_string());
+        report.set_metadata("Buffer data", buffer_data.get_name());
+    }
+    if workload::get_workload_kind() == WorkloadKind::Stencil {
+        report.set_metadata("Image size", &workload::format_image_size(workload::get_image_size()));
+    }
+    if workload::get_workload_kind() == WorkloadKind::Rwlock {
+        report.set_metadata("Write ratio", &workload::get_write_percent().to_string());
+    }
+    if placement::get_placement() != Placement::Unpinned {
+        report.set_metadata("Placement", placement::get_placement().get_name());
+    }
+    report.set_metadata("Convergence", get_convergence_mode().get_name());
+    report.set_metadata("Triplets", &batch::get_n_triplets().to_string());
+    report.set_metadata("Working set", &batch::format_working_set(batch::get_n_triplets()));
+    report.set_metadata("RNG cost", &format!("{:.0} ns", rng::measure_rng_cost(rng::get_rng_kind())));
+    if platform::is_process_priority_raised() {
+        report.set_metadata("Process priority", "high");
+    }
+    if args.get_qos_class() != QosClass::Unspecified {
+        report.set_metadata("QoS class", args.get_qos_class().get_name());
+    }
+    if args.get_sched_policy() != SchedPolicy::Other {
+        report.set_metadata("Scheduling policy", &format!("{}, priority {}", 
+                                                          args.get_sched_policy().get_name(),
+                                                          args.get_rt_priority()));
+    }
+    if args.get_nice() != 0 {
+        report.set_metadata("Nice", &args.get_nice().to_string());
+    }
+    if args.get_stack_size() > 0 {
+        report.set_metadata("Stack size", &args.get_stack_size().to_string());
+    }
+    report.set_metadata("Effective CPUs", &count_cpus().to_string());
+    if args.get_doctor() {
+        report.set_metadata("Doctor", &doctor::format_findings(&doctor::find_findings()));
+    }
+    if let Some(hypervisor) = environment::detect_hypervisor() {
+        report.set_metadata("Hypervisor", &hypervisor);
+    }
+    let power_settings = PowerSettings::read();
+    if let Some(governor) = &power_settings.governor {
+        report.set_metadata("Frequency governor", governor);
+    }
+    if let Some(turbo) = power_settings.turbo {
+        report.set_metadata("Turbo", if turbo {"on"} else {"off"});
+    }
+    report.set_metadata("Timer overhead, ns", &get_timer_overhead().to_string());
+}
+
+fn load_resumed_report(args: &Args) -> Option<Report> {
+    if args.get_resume_file_path() != "" {
+        Some(saved::load_report(&args.get_resume_file_path()))
+    } else {
+        None
+    }
+}
+
+fn prepare_report(args: &Args, resumed_report: Option<Report>) -> Report {
+
+    let start = read_timestamp(&SystemTime::now());
+
+    match resumed_report {
+        Some(mut report) => {
+            info!("Resuming {} observations from {}", 
+                  report.count_observations(), args.get_resume_file_path());
+            report.set_metadata("Resumed", &format_utc_datetime(start));
+            report
+        }
+        None => {
+            let mut report = Report::create(args.get_tasks_max());
+            register_run_metadata(&mut report, args, start);
+            report
+        }
+    }
+}
+
+fn measure_concurrency_profit(args: &Args, resumed_report: Option<Report>) -> i32 {
+
+    if args.get_label() != "" {
+        info!("Label: {}", args.get_label());
+    }
+
+    if is_debug_build() && args.get_backend() != simulate::BACKEND {
+        warn!("MEASURING WITH A DEBUG BUILD, results are meaningless, build with --release");
+    }
+    // Held until measuring ends, an interrupt exiting at once leaves the settings pinned
+    let frequency_pin = match args.get_pin_frequency() {
+        true => match environment::pin_frequency() {
+            Ok(frequency_pin) => Some(frequency_pin),
+            Err(error) => {
+                warn!("{}, frequencies are not pinned", error);
+                None
+            }
+        },
+        false => None
+    };
+    for warning in PowerSettings::read().find_warnings() {
+        warn!("{}", warning);
+    }
+
+    cancel::cancel_on_interrupt();
+    let environment_before = EnvironmentSnapshot::capture();
+    let report = prepare_report(args, resumed_report);
+    let mut report = if args.get_tui() {
+        test_concurrency_profit_live(args, report)
+    } else {
+        test_concurrency_profit(args, report)
+    };
+    report.set_metadata("Noise score", &format!("{:.4}", report.get_noise_score()));
+    if let Some(frequency_pin) = &frequency_pin {
+        report.set_metadata("Frequency pinning", &frequency_pin.describe());
+    }
+
+    if args.get_show_schedule() {
+        print_schedules(&report);
+    }
+    if args.get_work_span() {
+        workspan::print_work_span(&report, args.get_colored());
+    }
+
+    save_report(&args.get_out_file_path(), &report);
+    save_chart(&args.get_chart_file_path(), &report);
+    history::append_history(&args.get_history_file_path(), &report);
+
+    assess_measuring(args, &report, &environment_before)
+}
+
+/// Rounds are appended to a history file, so trend renders how they change over time.
+/// Waiting for the next round wakes up this often to notice an interrupt.
+const WATCH_TICK: Duration = Duration::from_millis(100);
+
+/// Rounds keep going until interrupted, or until a strict one finds the measurements disturbed.
+fn watch_concurrency_profit(args: &Args) -> i32 {
+
+    let interval = args.get_watch_interval().as_duration();
+
+    cancel::cancel_on_interrupt();
+
+    let mut round = 1usize;
+
+    loop {
+
+        let watch = SystemTime::now();
+        info!("Round {} at {}", round, format_utc_datetime(read_timestamp(&watch)));
+
+        let environment_before = EnvironmentSnapshot::capture();
+        let report = prepare_report(args, None);
+        let report = test_concurrency_profit(args, report);
+        if cancel::is_cancelled() {
+            return EXIT_SUCCESS;
+        }
+        save_report(&args.get_out_file_path(), &report);
+        history::append_history(&args.get_history_file_path(), &report);
+
+        let exit_code = assess_measuring(args, &report, &environment_before);
+        if exit_code != EXIT_SUCCESS {
+            return exit_code;
+        }
+
+        while watch.elapsed().unwrap_or(Duration::ZERO) < interval {
+            if cancel::is_cancelled() {
+                return EXIT_SUCCESS;
+            }
+            thread::sleep(WATCH_TICK);
+        }
+
+        round += 1;
+    }
+}
+
+fn main() {
+
+    let args: Args = accept_args(env::args().collect());
+
+    init_logging(args.get_verbosity(), args.get_log_json(), args.get_tui());
+    tracy::start();
+    set_number_style(args.get_number_style());
+    set_deviation_kind(args.get_deviation_kind());
+    rng::set_rng_kind(args.get_rng_kind());
+    set_convergence_mode(args.get_convergence_mode());
+    batch::set_n_triplets(args.get_n_triplets());
+    workload::set_workload_kind(args.get_workload_kind().unwrap_or(WorkloadKind::Standard));
+    workload::set_n_syscalls(args.get_n_syscalls());
+    workload::set_map_size(args.get_map_size());
+    workload::set_buffer(args.get_buffer_size(), args.get_buffer_data().unwrap_or(BufferData::Random));
+    workload::set_write_percent(args.get_write_percent());
+    workload::set_image_size(args.get_image_size().unwrap_or(workload::get_image_size()));
+    if clock::set_clock_source(args.get_clock_source()) != args.get_clock_source() {
+        warn!("No invariant TSC found, timing with the monotonic clock");
+    }
+    platform::set_worker_settings(args.get_worker_settings());
+    if let Err(error) = platform::try_worker_settings() {
+        warn!("{}, workers run with default settings", error);
+    }
+    compression::set_compression(args.get_compression().unwrap_or(Compression::Off));
+    capture::set_capture(args.get_capture());
+    aggregate::set_aggregate_only(args.get_aggregate_only());
+    placement::set_placement(args.get_placement().unwrap_or(Placement::Unpinned), args.get_seed());
+    if let Err(error) = placement::try_placement() {
+        warn!("{}, tasks run unpinned", error);
+    }
+    if args.get_high_priority() && !platform::raise_process_priority() {
+        warn!("The process priority is only raised on Windows, with enough rights");
+    }
+    if let Some(exit_code) = fanout::serve_share() {
+        process::exit(exit_code);
+    }
+    if get_cpu_limits().is_limited() {
+        info!("Control groups leave {} of {} CPUs to the process", count_cpus(), count_host_cpus());
+    }
+    debug!(timer_overhead = get_timer_overhead() as u64, "Timer overhead calibrated");
+
+    print_salutation();
+
+    let exit_code = match args.get_command() {
+        Command::Invalid => {
+            reject_args(args.get_command())
+        }
+        command if !args.is_global_valid() || !args.are_options_valid() => {
+            reject_args(command)
+        }
+        command if args.get_help() => {
+            print_command_help(command);
+            EXIT_SUCCESS
+        }
+        Command::Help => {
+            print_help();
+            EXIT_SUCCESS
+        }
+        Command::RequestSysParams => {
+            test_sysparams(args.get_colored());
+            EXIT_SUCCESS
+        }
+        Command::MeasureConcurrencyProfit => {
+            let resumed_report = load_resumed_report(&args);
+            let args = match &resumed_report {
+                Some(report) => args.adopt_resumed(report),
+                None => args.expand_preset()
+            };
+            if args.is_valid() && args.get_dry_run() {
+                preview_concurrency_profit(&args);
+                EXIT_SUCCESS
+            } else if args.is_valid() && !args.get_watch_interval().is_zero() {
+                watch_concurrency_profit(&args)
+            } else if args.is_valid() {
+                measure_concurrency_profit(&args, resumed_report)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::RenderTrend => {
+            if !args.is_trend_valid() {
+                reject_args(args.get_command())
+            } else if history::render_trend(&args.get_history_file_path(), 
+                                             &args.get_trend_tasks(), 
+                                             args.get_colored()) {
+                warn!("The latest run regressed");
+                EXIT_REGRESSION
+            } else {
+                EXIT_SUCCESS
+            }
+        }
+        Command::RunSuite => {
+            if args.is_suite_valid() {
+                suite::run_suite(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::CompareRuns => {
+            if args.is_comparison_valid() {
+                compare::run_comparison(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::MergeReports => {
+            if args.is_merge_valid() {
+                merge::run_merge(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::RenderReport => {
+            if args.is_render_valid() {
+                render::run_render(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::RunSoak => {
+            if args.is_soak_valid() {
+                soak::run_soak(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::RunOpenLoop => {
+            if args.is_open_loop_valid() {
+                openloop::run_open_loop(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::RunStress => {
+            if !args.get_duration().is_zero() {
+                stress::run_stress(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::RunInversion => {
+            if args.get_n_cycles() > 0 {
+                inversion::run_inversion(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::CompareSharing => {
+            if args.get_tasks_max() > 0 && args.get_n_cycles() > 0 {
+                sharing::run_sharing(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::ComparePlacements => {
+            if args.get_tasks_max() > 0 && args.get_n_cycles() > 0 {
+                placement::run_placements(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::CompareSmt => {
+            if args.is_valid() {
+                smt::run_smt(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::StudyGranularity => {
+            if args.get_n_cycles() > 0 && args.get_chunk_power_max() <= granularity::CHUNK_POWER_LIMIT {
+                granularity::run_granularity(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::ReplayTask => {
+            if args.is_replay_valid() {
+                replay::run_replay(&args)
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::RunSelfTest => {
+            if selftest::run_selftest(args.get_colored()) {
+                EXIT_SUCCESS
+            } else {
+                warn!("Self-test failed, results of measurements may be misleading");
+                EXIT_NOISE
+            }
+        }
+        Command::RunDoctor => {
+            let n_pitfalls = doctor::run_doctor(args.get_colored());
+            if n_pitfalls > 0 {
+                warn!("{} pitfalls found, see the suggestions", n_pitfalls);
+            }
+            EXIT_SUCCESS
+        }
+        Command::ListChoices => {
+            if registry::print_registry(&args.get_list_kind(), args.get_colored()) {
+                EXIT_SUCCESS
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+        Command::PrintCompletions => {
+            if completions::print_completions(&args.get_completions_shell()) {
+                EXIT_SUCCESS
+            } else {
+                reject_args(args.get_command())
+            }
+        }
+    };
+
+    process::exit(exit_code);
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use proptest::prelude::*;
+    use testing::{assert_golden, FakeWorkload};
+
+    const DATASET: [u64; 8] = [2, 4, 4, 4, 5, 5, 7, 9];
+
+    fn to_durations(ms: &[u64]) -> Vec<TaskDuration> {
+        ms.iter().map(|ms| TaskDuration::from_millis(*ms)).collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} is not {}", actual, expected);
+    }
+
+    #[test]
+    fn population_deviation_of_known_dataset() {
+        assert_close(calc_standard_deviation(&to_durations(&DATASET), DeviationKind::Population), 2.0);
+    }
+
+    #[test]
+    fn sample_deviation_of_known_dataset() {
+        assert_close(calc_standard_deviation(&to_durations(&DATASET), DeviationKind::Sample), 
+                     (32.0f64/7.0).sqrt());
+    }
+
+    #[test]
+    fn deviation_of_constant_durations_is_zero() {
+        let durations = to_durations(&[7; 5]);
+        assert_close(calc_standard_deviation(&durations, DeviationKind::Sample), 0.0);
+        assert_close(calc_standard_deviation(&durations, DeviationKind::Population), 0.0);
+    }
+
+    #[test]
+    fn sample_deviation_of_single_duration_is_zero() {
+        assert_close(calc_standard_deviation(&to_durations(&[42]), DeviationKind::Sample), 0.0);
+        assert_close(calc_standard_deviation(&to_durations(&[42]), DeviationKind::Population), 0.0);
+    }
+
+    #[test]
+    fn deviation_of_no_durations_is_zero() {
+        assert_close(calc_standard_deviation(&[], DeviationKind::Sample), 0.0);
+    }
+
+    #[test]
+    fn deviation_uses_exact_mean() {
+        // The mean of 1 and 2 is 1.5, not the truncated 1
+        assert_close(calc_standard_deviation(&to_durations(&[1, 2]), DeviationKind::Population), 0.5);
+    }
+
+    // Series
+
+    #[test]
+    fn series_hold_remaining_tasks() {
+        assert_eq!(count_series(8, 4), 2);
+        assert_eq!(count_series(9, 4), 3);
+        assert_eq!(count_series(3, 4), 1);
+        assert_eq!(count_series(1, 1), 1);
+    }
+
+    // Profits
+
+    #[test]
+    fn serial_duration_runs_tasks_one_by_one() {
+        assert_eq!(calc_serial_duration(4, TaskDuration::from_millis(250)), 
+                   TaskDuration::from_millis(1000));
+    }
+
+    #[test]
+    fn profit_of_perfect_parallelism() {
+        let serial_duration = calc_serial_duration(4, TaskDuration::from_millis(100));
+        assert_close(calc_profit(TaskDuration::from_millis(100), serial_duration), 0.75);
+        assert_close(calc_profit(serial_duration, serial_duration), 0.0);
+    }
+
+    #[test]
+    fn profit_of_oversubscription_is_negative() {
+        let serial_duration = calc_serial_duration(2, TaskDuration::from_millis(100));
+        assert_close(calc_profit(TaskDuration::from_millis(250), serial_duration), -0.25);
+    }
+
+    #[test]
+    fn cost_of_slowed_down_tasks() {
+        let serial_duration = calc_serial_duration(2, TaskDuration::from_millis(100));
+        assert_close(calc_cost(TaskDuration::from_millis(400), serial_duration), 0.5);
+        assert_close(calc_cost(serial_duration, serial_duration), 0.0);
+    }
+
+    // Observations of a fake workload
+
+    fn create_fake_workload() -> FakeWorkload {
+        FakeWorkload::create(2, 4, 100)
+    }
+
+    #[test]
+    fn engine_places_tasks_in_series() {
+
+        let obs = create_fake_workload().observe(6);
+
+        assert_eq!(obs.count_tasks(), 6);
+        for (idx, task) in obs.tasks.iter().enumerate() {
+            assert_eq!(task.get_idx(), idx);
+            assert_eq!(task.get_series(), idx/4);
+            assert_eq!(task.get_thread_name(), &format_worker_name(6, idx));
+        }
+        assert_eq!(obs.count_failed_tasks(), 0);
+        assert!(!obs.needs_retry());
+    }
+
+    #[test]
+    fn fake_observation_has_expected_timing() {
+
+        let obs = create_fake_workload().observe(4);
+
+        assert_eq!(obs.get_total_duration(), TaskDuration::from_millis(201));
+        assert_eq!(obs.sum_duration(), TaskDuration::from_millis(400));
+        assert_eq!(obs.get_mean_task_duration(), TaskDuration::from_millis(100));
+        assert_eq!(obs.get_standard_deviation(), TaskDuration::ZERO);
+        assert_eq!(obs.count_concurrent_tasks_max(), Some(2));
+        assert_eq!(obs.get_start_spread(), Some(TaskDuration::from_millis(101)));
+        assert_close(obs.get_parallelism(), 400.0/201.0);
+    }
+
+    #[test]
+    fn series_of_fake_observation_are_separated_by_gaps() {
+
+        let obs = create_fake_workload().observe(6);
+        let spans = obs.get_series_spans();
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].n_tasks, 4);
+        assert_eq!(spans[1].n_tasks, 2);
+        assert_eq!(obs.get_series_gaps(), Some(vec![TaskDuration::from_millis(2)]));
+    }
+
+    #[test]
+    fn report_computes_profits_against_single_task() {
+
+        let report = create_fake_workload().create_report(1..=4);
+
+        assert_eq!(report.get_task_duration_min(), TaskDuration::from_millis(100));
+        assert_close(report.find_observation(1).unwrap().get_concurrency_profit(), 0.0);
+        assert_close(report.find_observation(2).unwrap().get_concurrency_profit(), 1.0 - 101.0/200.0);
+        assert_close(report.find_observation(4).unwrap().get_concurrency_profit(), 1.0 - 201.0/400.0);
+        assert_close(report.find_observation(4).unwrap().get_concurrency_cost(), 0.0);
+    }
+
+    #[test]
+    fn slow_task_makes_outlier() {
+
+        let mut obs = create_fake_workload().observe(4);
+        let mut task = Task::create(3, obs.tasks[3].get_start(), TaskDuration::from_millis(400));
+        task.set_series(0);
+        obs.register_task(task);
+
+        assert!(obs.is_outlier());
+        assert!(obs.needs_retry());
+    }
+
+    #[test]
+    fn stopping_at_convergence_keeps_the_member() {
+        let triplet = (0.5, 0.25, 0.75);
+        let member = iterate_until(triplet, 1000000, None, ConvergenceMode::AllCycles).unwrap();
+        assert_close(iterate_until(triplet, 1000000, None, ConvergenceMode::Stop).unwrap(), member);
+        assert!(ConvergenceMode::parse(ConvergenceMode::Stop.get_name()) == ConvergenceMode::Stop);
+        assert!(parse_args("concrust profit 4 1000 2 --stop-on-converge").get_convergence_mode() == ConvergenceMode::Stop);
+        assert!(parse_args("concrust profit 4 1000 2").get_convergence_mode() == ConvergenceMode::AllCycles);
+    }
+
+    #[test]
+    fn workloads_are_chosen_by_name() {
+        assert!(parse_args("concrust profit 4 1000 2").get_workload_kind() == Some(WorkloadKind::Standard));
+        assert!(parse_args("concrust profit 4 1000 2 --workload branchy").get_workload_kind() == Some(WorkloadKind::Branchy));
+        assert!(!parse_args("concrust profit 4 1000 2 --workload idle").is_valid());
+        assert_eq!(parse_args("concrust profit 4 1000 2 --workload syscalls --syscalls 8").get_n_syscalls(), 8);
+        assert_eq!(parse_args("concrust profit 4 1000 2 --workload pagefaults --map-size 2m").get_map_size(), 2*1024*1024);
+        assert!(!parse_args("concrust profit 4 1000 2 --workload pagefaults --map-size 0").is_valid());
+        let args = parse_args("concrust profit 4 1000 2 --workload compress --buffer-size 1m --buffer-data repetitive");
+        assert_eq!((args.get_buffer_size(), args.get_buffer_data()), (1024*1024, Some(BufferData::Repetitive)));
+        assert!(!parse_args("concrust profit 4 1000 2 --buffer-data zeros").is_valid());
+        assert!(parse_args("concrust profit 4 1000 2 --workload json").get_workload_kind() == Some(WorkloadKind::Json));
+        assert!(parse_args("concrust profit 4 1000 2 --workload regex").get_workload_kind() == Some(WorkloadKind::Regex));
+        assert_eq!(parse_args("concrust profit 4 1000 2 --workload stencil --image-size 640x480").get_image_size(), Some((640, 480)));
+        assert!(!parse_args("concrust profit 4 1000 2 --image-size 640").is_valid());
+        assert!(parse_args("concrust profit 4 1000 2 --workload gpu").get_workload_kind() == Some(WorkloadKind::Gpu));
+        assert_eq!(parse_args("concrust profit 4 1000 2 --workload rwlock --write-ratio 50").get_write_percent(), 50);
+        assert!(!parse_args("concrust profit 4 1000 2 --workload rwlock --write-ratio 101").is_valid());
+    }
+
+    #[test]
+    fn working_sets_take_counts_of_triplets() {
+        assert_eq!(parse_args("concrust profit 4 1000 2").get_n_triplets(), 1);
+        assert_eq!(parse_args("concrust profit 4 1000 2 --triplets 4k").get_n_triplets(), 4096);
+        assert!(!parse_args("concrust profit 4 1000 2 --triplets 0").is_valid());
+    }
+
+    #[test]
+    fn imbalance_compares_the_longest_task_with_the_mean() {
+        let mut obs = Observation::create(3, 1000);
+        for (task_idx, duration) in [100, 100, 400].iter().enumerate() {
+            obs.register_task(Task::create(task_idx, Timestamp::EPOCH, TaskDuration::from_millis(*duration)));
+        }
+        assert_close(obs.get_duration_imbalance(), 2.0);
+    }
+
+    #[test]
+    fn task_seeds_are_reproducible_and_distinct() {
+        assert_eq!(derive_task_seed(7, 4, 2), derive_task_seed(7, 4, 2));
+        assert!(derive_task_seed(7, 4, 2) != derive_task_seed(7, 4, 3));
+        assert!(derive_task_seed(7, 4, 2) != derive_task_seed(7, 5, 2));
+        assert!(derive_task_seed(7, 4, 2) != derive_task_seed(8, 4, 2));
+    }
+
+    #[test]
+    fn earlier_deadline_wins() {
+
+        let start = Timestamp::from_millis(1000);
+        let timeouts = Timeouts::create(TaskDuration::from_millis(50), TaskDuration::from_millis(30));
+        assert_eq!(timeouts.get_task_deadline(start, start), Some(Timestamp::from_millis(1030)));
+
+        let no_timeouts = Timeouts::create(TaskDuration::ZERO, TaskDuration::ZERO);
+        assert_eq!(no_timeouts.get_task_deadline(start, start), None);
+    }
+
+    // Formatting
+
+    #[test]
+    fn percent_cells_are_rounded() {
+        assert_eq!(format_percent_cell(0.4975), "50%");
+        assert_eq!(format_percent_cell(-0.25), "-25%");
+    }
+
+    #[test]
+    fn csv_fields_survive_quoting() {
+
+        let fields = ["plain", "with, comma", "with \"quotes\"", ""];
+        let line = fields.iter().map(|field| quote_csv(field)).collect::<Vec<String>>().join(",");
+
+        assert_eq!(line, "plain,\"with, comma\",\"with \"\"quotes\"\"\",");
+        assert_eq!(split_csv_line(&line), fields);
+    }
+
+    #[test]
+    fn schedule_bars_scale_to_width() {
+        let total_duration = TaskDuration::from_millis(200);
+        assert_eq!(scale_to_schedule(TaskDuration::ZERO, total_duration), 0);
+        assert_eq!(scale_to_schedule(TaskDuration::from_millis(100), total_duration), SCHEDULE_WIDTH/2);
+        assert_eq!(scale_to_schedule(total_duration, total_duration), SCHEDULE_WIDTH);
+        assert_eq!(scale_to_schedule(total_duration, TaskDuration::ZERO), 0);
+    }
+
+    fn create_golden_report() -> Report {
+
+        let workload = create_fake_workload();
+        let mut report = workload.create_report(1..=4);
+
+        let mut obs = workload.observe(6);
+        obs.set_repeat_durations(to_durations(&[301, 305, 303]));
+        report.register_observation(obs);
+        report.register_skipped(5);
+
+        report
+    }
+
+    #[test]
+    fn report_matches_golden_file() {
+        assert_golden("report.csv", &format_report(&create_golden_report()));
+    }
+
+    #[test]
+    fn chart_script_matches_golden_file() {
+        assert_golden("chart.gp", &format_chart_script(&"chart.png".to_string(), &create_golden_report()));
+    }
+
+    // Parsing arguments
+
+    fn parse_args(line: &str) -> Args {
+        let args: ArgsVec = line.split_whitespace().map(|arg| arg.to_string()).collect();
+        Args::create().parse(&args)
+    }
+
+    #[test]
+    fn sizes_and_durations_take_suffixes() {
+        assert_eq!(parse_size_bytes("512"), 512);
+        assert_eq!(parse_size_bytes("64k"), 65536);
+        assert_eq!(parse_size_bytes("2M"), 2*1024*1024);
+        assert_eq!(parse_duration_ms("250"), Some(TaskDuration::from_millis(250)));
+        assert_eq!(parse_duration_ms("250ms"), Some(TaskDuration::from_millis(250)));
+        assert_eq!(parse_duration_ms("2s"), Some(TaskDuration::from_millis(2000)));
+        assert_eq!(parse_duration_ms("3m"), Some(TaskDuration::from_millis(180000)));
+        assert_eq!(parse_duration_ms("1h"), Some(TaskDuration::from_millis(3600000)));
+        assert_eq!(parse_duration_ms(""), Some(TaskDuration::ZERO));
+        assert_eq!(parse_duration_ms("soon"), None);
+        assert!(!parse_args("concrust profit 4 1000 2 --max-runtime 10mn").is_valid());
+    }
+
+    #[test]
+    fn measuring_arguments_are_parsed() {
+
+        let args = parse_args("concrust profit 8 1000 4 out.csv --retries 2 --task-timeout 5s --seed 9");
+
+        assert!(args.get_command() == Command::MeasureConcurrencyProfit);
+        assert_eq!(args.get_tasks_max(), 8);
+        assert_eq!(args.get_n_cycles(), 1000);
+        assert_eq!(args.get_series_size(), 4);
+        assert_eq!(args.get_out_file_path(), "out.csv");
+        assert_eq!(args.get_retries_max(), 2);
+        assert_eq!(args.get_seed(), 9);
+        assert_eq!(args.get_timeouts().task, TaskDuration::from_millis(5000));
+        assert!(args.is_valid());
+    }
+
+    #[test]
+    fn unknown_command_is_invalid() {
+        assert!(parse_args("concrust q").get_command() == Command::Invalid);
+        assert!(parse_args("concrust p 8 1000 4").get_command() == Command::Invalid);
+        assert!(parse_args("concrust").get_command() == Command::Help);
+    }
+
+    #[test]
+    fn help_of_commands_is_requested_either_way() {
+        for line in ["concrust help sys", "concrust sys --help", "concrust -h sys"] {
+            let args = parse_args(line);
+            assert!(args.get_command() == Command::RequestSysParams && args.get_help(), "{}", line);
+        }
+        assert!(parse_args("concrust help nothing").get_command() == Command::Invalid);
+        assert!(!parse_args("concrust help").get_help());
+    }
+
+    #[test]
+    fn global_options_go_before_commands() {
+        let args = parse_args("concrust -v --number-style plain profit 4 1000 2");
+        assert!(args.get_command() == Command::MeasureConcurrencyProfit);
+        assert!(args.get_verbosity() == Verbosity::Verbose);
+        assert_eq!(args.get_tasks_max(), 4);
+        assert!(parse_args("concrust --number-style grouped render report.csv").is_global_valid());
+        assert!(!parse_args("concrust --number-style plian render report.csv").is_global_valid());
+    }
+
+    #[test]
+    fn every_command_has_help() {
+        for subcommand in SUBCOMMANDS {
+            assert!(find_command(subcommand.name) == subcommand.command);
+            assert!(subcommand.options.iter().all(|option| OPTION_HELP.iter().any(|(o, _)| o == option)));
+        }
+    }
+
+    #[test]
+    fn render_arguments_are_parsed() {
+        let args = parse_args("concrust render raw.json page.html --format html");
+        assert_eq!((args.get_report_file_path(), args.get_out_file_path()), 
+                   ("raw.json".to_string(), "page.html".to_string()));
+        assert!(args.is_render_valid());
+        assert!(!parse_args("concrust render raw.json --format pdf").is_render_valid());
+        assert!(!parse_args("concrust render").is_render_valid());
+    }
+
+    #[test]
+    fn replay_takes_cycles_and_the_seed_of_a_task() {
+        let args = parse_args("concrust replay-task 1000 --seed 18446744073709551615 --rng small");
+        assert!(args.get_command() == Command::ReplayTask);
+        assert_eq!((args.get_n_cycles(), args.get_task_seed()), (1000, Some(u64::MAX)));
+        assert!(args.is_replay_valid());
+        assert!(!parse_args("concrust replay-task 1000").is_replay_valid());
+        assert!(!parse_args("concrust replay-task --seed 7").is_replay_valid());
+    }
+
+    #[test]
+    fn inversion_takes_cycles_and_medium_tasks() {
+        let args = parse_args("concrust inversion 1000000 3");
+        assert!(args.get_command() == Command::RunInversion);
+        assert_eq!((args.get_n_cycles(), args.get_tasks_max()), (1000000, 3));
+        assert_eq!(parse_args("concrust inversion 1000").get_tasks_max(), 0);
+    }
+
+    #[test]
+    fn sharing_takes_tasks_and_cycles() {
+        let args = parse_args("concrust sharing 4 1000000 --seed 7");
+        assert!(args.get_command() == Command::CompareSharing);
+        assert_eq!((args.get_tasks_max(), args.get_n_cycles(), args.get_seed()), (4, 1000000, 7));
+    }
+
+    #[test]
+    fn placement_takes_tasks_and_cycles() {
+        let args = parse_args("concrust placement 4 1000000 --seed 7");
+        assert!(args.get_command() == Command::ComparePlacements);
+        assert_eq!((args.get_tasks_max(), args.get_n_cycles(), args.get_seed()), (4, 1000000, 7));
+        assert_eq!(parse_args("concrust profit 4 1000 2 --placement scatter").get_placement(), Some(Placement::Scatter));
+        assert_eq!(parse_args("concrust profit 4 1000 2").get_placement(), Some(Placement::Unpinned));
+        assert!(!parse_args("concrust profit 4 1000 2 --placement spread").is_valid());
+    }
+
+    #[test]
+    fn frequencies_are_pinned_on_request() {
+        assert!(parse_args("concrust profit 4 1000 2 --pin-frequency").get_pin_frequency());
+        assert!(!parse_args("concrust profit 4 1000 2").get_pin_frequency());
+    }
+
+    #[test]
+    fn clock_sources_are_validated() {
+        assert!(parse_args("concrust profit 4 1000 2 --clock tsc").get_clock_source() == ClockSource::Tsc);
+        assert!(parse_args("concrust profit 4 1000 2").get_clock_source() == ClockSource::Monotonic);
+        assert!(!parse_args("concrust profit 4 1000 2 --clock rdtsc").is_valid());
+    }
+
+    #[test]
+    fn qos_classes_are_validated() {
+        assert!(parse_args("concrust profit 4 1000 2 --qos utility").get_qos_class() == QosClass::Utility);
+        assert!(parse_args("concrust profit 4 1000 2").get_qos_class() == QosClass::Unspecified);
+        assert!(!parse_args("concrust profit 4 1000 2 --qos utilty").is_valid());
+    }
+
+    #[test]
+    fn sched_policies_are_validated() {
+        assert!(parse_args("concrust profit 4 1000 2 --sched rr").get_sched_policy() == SchedPolicy::RoundRobin);
+        assert!(parse_args("concrust profit 4 1000 2").get_sched_policy() == SchedPolicy::Other);
+        assert!(!parse_args("concrust profit 4 1000 2 --sched deadline").is_valid());
+    }
+
+    #[test]
+    fn nice_values_are_validated() {
+        assert_eq!(parse_args("concrust profit 4 1000 2 --nice -5").get_nice(), -5);
+        assert_eq!(parse_args("concrust profit 4 1000 2").get_nice(), 0);
+        assert!(!parse_args("concrust profit 4 1000 2 --nice 20").is_valid());
+        assert!(!parse_args("concrust profit 4 1000 2 --nice low").is_valid());
+    }
+
+    #[test]
+    fn rng_kinds_are_validated() {
+        assert!(parse_args("concrust profit 4 1000 2 --rng xoshiro").get_rng_kind() == RngKind::Xoshiro);
+        assert!(parse_args("concrust profit 4 1000 2").get_rng_kind() == RngKind::Std);
+        assert!(!parse_args("concrust profit 4 1000 2 --rng mt").is_valid());
+        assert!(!parse_args("concrust replay-task 1000 --seed 7 --rng mt").is_replay_valid());
+    }
+
+    #[test]
+    fn deviation_kinds_are_validated() {
+        assert!(parse_args("concrust profit 4 1000 2 --std-dev population").get_deviation_kind() ==
+                DeviationKind::Population);
+        assert!(parse_args("concrust profit 4 1000 2").get_deviation_kind() == DeviationKind::Sample);
+        assert!(!parse_args("concrust profit 4 1000 2 --std-dev populaton").is_valid());
+    }
+
+    #[test]
+    fn presets_are_validated() {
+        assert!(parse_args("concrust profit --preset thorough").get_preset() == Preset::Thorough);
+        assert!(parse_args("concrust profit 4 1000 2").get_preset() == Preset::Custom);
+        assert!(!parse_args("concrust profit 4 1000 2 --preset thoro").is_valid());
+    }
+
+    #[test]
+    fn arrival_models_are_validated() {
+        assert!(parse_args("concrust open 100 1000 4 --rate 50 --arrivals poisson").get_arrivals() ==
+                Arrivals::Poisson);
+        assert!(parse_args("concrust open 100 1000 4 --rate 50").is_open_loop_valid());
+        assert!(!parse_args("concrust open 100 1000 4 --rate 50 --arrivals bursty").is_open_loop_valid());
+    }
+
+    #[test]
+    fn seeds_and_real_time_priorities_are_validated() {
+        assert_eq!(parse_args("concrust profit 4 1000 2 --seed 18446744073709551615").get_seed(), u64::MAX);
+        assert!(!parse_args("concrust profit 4 1000 2 --seed abc").is_valid());
+        assert_eq!(parse_args("concrust profit 4 1000 2 --sched fifo --rt-prio 50").get_rt_priority(), 50);
+        assert!(!parse_args("concrust profit 4 1000 2 --sched fifo --rt-prio high").is_valid());
+        assert!(!parse_args("concrust profit 4 1000 2 --rt-prio high").is_valid());
+    }
+
+    #[test]
+    fn options_are_validated_whatever_the_command() {
+        assert!(parse_args("concrust inversion 1000").are_options_valid());
+        assert!(!parse_args("concrust stress 4 --duration 10s --nice low").are_options_valid());
+        assert!(!parse_args("concrust inversion 1000 --qos utilty").are_options_valid());
+        assert!(!parse_args("concrust sharing 4 1000 --seed abc").are_options_valid());
+        assert!(!parse_args("concrust placement 4 1000 --task-timeout soon").are_options_valid());
+        assert!(!parse_args("concrust granularity 1000 --rng mt").are_options_valid());
+    }
+
+    #[test]
+    fn reports_are_compressed_on_request() {
+        assert_eq!(parse_args("concrust profit 4 1000 2 out.csv --compress zstd").get_compression(),
+                   Some(Compression::Zstd));
+        assert_eq!(parse_args("concrust merge a.csv b.csv -o all.json --compress gz").get_compression(),
+                   Some(Compression::Gzip));
+        assert!(!parse_args("concrust profit 4 1000 2 --compress bz2").is_valid());
+        assert!(!parse_args("concrust merge a.csv --compress bz2").is_merge_valid());
+    }
+
+    #[test]
+    fn raw_records_are_captured_on_request() {
+        assert!(parse_args("concrust profit 4 1000 2 out.cap --capture").get_capture());
+        assert!(!parse_args("concrust profit 4 1000 2 out.csv").get_capture());
+    }
+
+    #[test]
+    fn metrics_are_chosen_by_name() {
+        assert_eq!(parse_args("concrust profit 4 1000 2 --metrics mean,p99,profit").get_metrics(),
+                   vec![Metric::Mean, Metric::P99, Metric::Profit]);
+        assert!(parse_args("concrust profit 4 1000 2").get_metrics().is_empty());
+        assert!(!parse_args("concrust profit 4 1000 2 --metrics mean,p95").is_valid());
+    }
+
+    #[test]
+    fn aggregates_leave_nothing_to_capture() {
+        assert!(parse_args("concrust profit 4 1000 2 --aggregate-only").get_aggregate_only());
+        assert!(parse_args("concrust profit 4 1000 2 --aggregate-only").is_valid());
+        assert!(!parse_args("concrust profit 4 1000 2 --aggregate-only --capture").is_valid());
+    }
+
+    #[test]
+    fn smt_takes_a_sweep() {
+        let args = parse_args("concrust smt 8 1000000 4 --workload branchy");
+        assert!(args.get_command() == Command::CompareSmt);
+        assert_eq!((args.get_tasks_max(), args.get_n_cycles(), args.get_series_size()), (8, 1000000, 4));
+        assert!(args.is_valid());
+        assert!(!parse_args("concrust smt 8 1000000").is_valid());
+    }
+
+    #[test]
+    fn granularity_takes_cycles_and_a_power_of_chunks() {
+        let args = parse_args("concrust granularity 100000000 10");
+        assert!(args.get_command() == Command::StudyGranularity);
+        assert_eq!((args.get_n_cycles(), args.get_chunk_power_max()), (100000000, 10));
+        assert_eq!(parse_args("concrust granularity 1000").get_chunk_power_max(), granularity::DEFAULT_CHUNK_POWER_MAX);
+    }
+
+    #[test]
+    fn merge_arguments_are_parsed() {
+        let args = parse_args("concrust merge a.json b.csv -o combined.json c.json");
+        assert_eq!(args.get_merged_file_paths(), vec!["a.json", "b.csv", "c.json"]);
+        assert_eq!(args.get_out_file_path(), "combined.json");
+        assert!(args.is_merge_valid());
+        assert!(!parse_args("concrust merge -o combined.json").is_merge_valid());
+    }
+
+    #[test]
+    fn queries_are_parsed() {
+        let args = parse_args("concrust render raw.csv --tasks 4,8,16 --only-totals --sort-by profit");
+        let query = args.get_report_query();
+        assert_eq!(query.tasks, vec![4, 8, 16]);
+        assert!(query.only_totals && query.sort_key == Some(SortKey::Profit));
+        assert!(args.is_render_valid());
+        assert!(!parse_args("concrust render raw.csv --sort-by speed").is_render_valid());
+        assert!(!parse_args("concrust compare a.csv b.csv --tasks 4,x").is_comparison_valid());
+    }
+
+    #[test]
+    fn summary_tells_headline_numbers() {
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=4);
+        let summary = RunSummary::create(&report, 2, 2);
+        let (n_tasks, speedup) = summary.best_speedup.unwrap();
+        assert_eq!(n_tasks, 4);
+        assert!((speedup - 400.0/201.0).abs() < 1e-9);
+        let (n_tasks, efficiency) = summary.physical_efficiency.unwrap();
+        assert_eq!(n_tasks, 2);
+        assert!((efficiency - 100.0/101.0).abs() < 1e-9);
+        assert!(summary.serial_fraction.unwrap() < 0.02);
+        assert!(RunSummary::create(&report, 8, 8).physical_efficiency.is_none());
+    }
+
+    #[test]
+    fn separators_mark_physical_and_logical_cores() {
+        let separators = create_core_separators(4, 8);
+        assert_eq!(find_separator(&separators, 4).unwrap().format_label(4), "4 physical cores");
+        assert_eq!(find_separator(&separators, 8).unwrap().format_label(8), "8 logical cores");
+        assert_eq!(find_separator(&separators, 12).unwrap().filler, ".");
+        assert_eq!(find_separator(&separators, 16).unwrap().format_label(16), "2 x 8 logical cores");
+        assert!(find_separator(&separators, 6).is_none());
+        assert_eq!(create_core_separators(8, 8).len(), 1);
+    }
+
+    #[test]
+    fn single_cores_are_not_separated() {
+        assert!(create_core_separators(1, 1).is_empty());
+        let separators = create_core_separators(1, 2);
+        assert_eq!(separators.len(), 1);
+        assert_eq!(find_separator(&separators, 1).map(|separator| separator.filler), None);
+    }
+
+    #[test]
+    fn separators_are_configurable() {
+        assert!(parse_args("concrust profit 16 1000 4 --separators none").get_separators().is_empty());
+        let separators = parse_args("concrust profit 16 1000 4 --separators 3,5").get_separators();
+        assert!(find_separator(&separators, 10).is_some() && find_separator(&separators, 7).is_none());
+        assert!(!parse_args("concrust profit 16 1000 4 --separators 0").is_valid());
+        assert!(!parse_args("concrust profit 16 1000 4 --separators few").is_valid());
+    }
+
+    #[test]
+    fn timer_overhead_is_taken_off_to_the_nanosecond() {
+        assert_eq!(compensate_timer_overhead(1000450, 450), TaskDuration::from_nanos(1000000));
+        assert_eq!(compensate_timer_overhead(999999, 120), TaskDuration::from_nanos(999879));
+        assert_eq!(compensate_timer_overhead(100, 450), TaskDuration::ZERO);
+    }
+
+    #[test]
+    fn statistics_keep_fractions_of_milliseconds() {
+        let mut obs = Observation::create(2, 1000);
+        for (idx, nanos) in [(0, 1250000), (1, 1750000)] {
+            obs.register_task(Task::create(idx, Timestamp::from_millis(EPOCH_MS), TaskDuration::from_nanos(nanos)));
+        }
+        assert_eq!(obs.get_mean_task_duration(), TaskDuration::from_nanos(1500000));
+        assert_eq!(obs.get_standard_deviation(), TaskDuration::from_nanos(353553));
+    }
+
+    #[test]
+    fn bounds_take_the_longest_task_or_the_work_spread() {
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=4);
+        let (single, four) = (report.find_observation(1).unwrap(), report.find_observation(4).unwrap());
+        assert_eq!(single.get_duration_bound(2).as_millis(), 100);
+        assert_eq!(single.get_scheduling_quality(2), 1.0);
+        assert_eq!(four.get_duration_bound(2).as_millis(), 200);
+        assert!((four.get_scheduling_quality(2) - 200.0/201.0).abs() < 1e-9);
+        assert_eq!(four.get_duration_bound(8).as_millis(), 100);
+        let time_sliced = create_observation(1, &[(0, 10); 4]);
+        assert_eq!(time_sliced.get_duration_bound(1).as_millis(), 40);
+        assert_eq!(time_sliced.get_scheduling_quality(1), 1.0);
+    }
+
+    #[test]
+    fn dependencies_are_waited_for_by_threads_only() {
+        assert!(parse_args("concrust profit 8 1000 4 --dag tree").is_valid());
+        assert!(!parse_args("concrust profit 8 1000 4 --dag star").is_valid());
+        assert!(!parse_args("concrust profit 8 1000 4 --dag chain --backend simulated").is_valid());
+    }
+
+    #[test]
+    fn series_larger_than_tasks_are_invalid() {
+        assert!(!parse_args("concrust profit 2 1000 4").is_valid());
+        assert!(!parse_args("concrust profit 4 0 2").is_valid());
+        assert!(!parse_args("concrust profit 4 1000 2 --backend fibers").is_valid());
+    }
+
+    // Properties of arbitrary schedules
+
+    const EPOCH_MS: u64 = 1700000000000;
+
+    /// Tasks of arbitrary start offsets and durations, taking turns in series.
+    fn create_observation(n_series: usize, timings: &[(u64, u64)]) -> Observation {
+        let mut obs = Observation::create(timings.len(), 1000);
+        for (idx, (offset, duration)) in timings.iter().enumerate() {
+            let mut task = Task::create(idx, Timestamp::from_millis(EPOCH_MS + offset), 
+                                        TaskDuration::from_millis(*duration));
+            task.set_series(idx % n_series);
+            obs.register_task(task);
+        }
+        obs
+    }
+
+    /// Tasks placed back to back on lanes starting together, each on the least busy lane,
+    /// so the busiest lane keeps working from the first start to the last finish.
+    fn create_gapless_observation(n_lanes: usize, durations: &[u64]) -> Observation {
+        let mut lane_ends = vec![0u64; n_lanes];
+        let timings: Vec<(u64, u64)> = durations.iter()
+            .map(|duration| {
+                let lane = (0..n_lanes).min_by_key(|lane| lane_ends[*lane]).unwrap();
+                let start = lane_ends[lane];
+                lane_ends[lane] += duration;
+                (start, *duration)
+            })
+            .collect();
+        create_observation(1, &timings)
+    }
+
+    fn arb_timings() -> impl Strategy<Value = Vec<(u64, u64)>> {
+        prop::collection::vec((0u64..500, 0u64..1000), 1..24)
+    }
+
+    proptest! {
+
+        #[test]
+        fn series_hold_all_tasks(n_tasks in 1usize..1000, series_size in 1usize..100) {
+            let n_series = count_series(n_tasks, series_size);
+            prop_assert!(n_series*series_size >= n_tasks);
+            prop_assert!((n_series - 1)*series_size < n_tasks);
+        }
+
+        #[test]
+        fn total_duration_covers_every_task(n_series in 1usize..4, timings in arb_timings()) {
+
+            let obs = create_observation(n_series, &timings);
+            let total_duration = obs.get_total_duration();
+
+            prop_assert!(total_duration >= obs.get_max_task_duration());
+            for task in &obs.tasks {
+                prop_assert!(obs.to_relative(task.get_start()) <= total_duration);
+                prop_assert!(obs.to_relative(task.get_finish()) <= total_duration);
+            }
+            prop_assert!(obs.get_start_spread().unwrap() <= total_duration);
+        }
+
+        #[test]
+        fn earliest_task_starts_at_zero(n_series in 1usize..4, timings in arb_timings()) {
+            let obs = create_observation(n_series, &timings);
+            let first_start = obs.tasks.iter().map(|task| obs.to_relative(task.get_start())).min();
+            prop_assert_eq!(first_start, Some(TaskDuration::ZERO));
+        }
+
+        #[test]
+        fn sum_of_gapless_durations_covers_total(n_lanes in 1usize..8, 
+                                                 durations in prop::collection::vec(1u64..1000, 1..24)) {
+            let obs = create_gapless_observation(n_lanes, &durations);
+            prop_assert!(obs.sum_duration() >= obs.get_total_duration());
+            prop_assert!(obs.count_concurrent_tasks_max().unwrap() <= n_lanes);
+        }
+
+        #[test]
+        fn parallelism_stays_within_concurrency(n_series in 1usize..4, timings in arb_timings()) {
+            let obs = create_observation(n_series, &timings);
+            let concurrent_tasks_max = obs.count_concurrent_tasks_max().unwrap();
+            prop_assert!(concurrent_tasks_max <= obs.count_tasks());
+            prop_assert!(obs.get_parallelism() <= concurrent_tasks_max as f64 + 1e-9);
+        }
+
+        #[test]
+        fn shares_stay_within_bounds(n_series in 1usize..4, timings in arb_timings()) {
+
+            let obs = create_observation(n_series, &timings);
+
+            let idle_share = obs.get_series_idle_share().unwrap();
+            prop_assert!((0.0..=1.0).contains(&idle_share), "idle share {}", idle_share);
+            for span in obs.get_series_spans() {
+                prop_assert!((0.0..=1.0).contains(&span.get_idle_share()));
+            }
+            prop_assert!(obs.get_variation_coefficient() >= 0.0);
+            prop_assert_eq!(obs.get_series_gaps().unwrap().len(), obs.get_series_spans().len() - 1);
+        }
+
+        #[test]
+        fn start_delays_grow_with_percentiles(n_series in 1usize..4, timings in arb_timings()) {
+            let obs = create_observation(n_series, &timings);
+            let delays: Vec<TaskDuration> = PERCENTILES.iter()
+                .map(|percentile| obs.get_start_delay_percentile(*percentile).unwrap())
+                .collect();
+            prop_assert!(delays.windows(2).all(|pair| pair[0] <= pair[1]));
+            prop_assert_eq!(delays.last().copied(), obs.get_start_spread());
+        }
+
+        #[test]
+        fn population_deviation_never_exceeds_sample(durations in prop::collection::vec(0u64..1000, 0..32)) {
+            let durations = to_durations(&durations);
+            let population = calc_standard_deviation(&durations, DeviationKind::Population);
+            let sample = calc_standard_deviation(&durations, DeviationKind::Sample);
+            prop_assert!(population >= 0.0);
+            prop_assert!(population <= sample + 1e-9 || durations.len() < 2);
+        }
+
+        #[test]
+        fn profit_never_exceeds_one(task_duration_min in 1u64..1000, n_series in 1usize..4, 
+                                    timings in arb_timings()) {
+
+            let mut report = Report::create(timings.len());
+            report.register_observation(create_observation(1, &[(0, task_duration_min)]));
+
+            report.register_observation(create_observation(n_series, &timings));
+
+            for obs in &report.observations {
+                prop_assert!(obs.get_concurrency_profit() <= 1.0);
+                prop_assert!(obs.get_concurrency_cost() <= 1.0);
+            }
+        }
+
+        #[test]
+        fn profit_of_any_durations_never_exceeds_one(total in 0u64..100000, serial in 1u64..100000) {
+            let (total, serial) = (TaskDuration::from_millis(total), TaskDuration::from_millis(serial));
+            prop_assert!(calc_profit(total, serial) <= 1.0);
+            prop_assert!(calc_cost(serial + total, serial) <= 1.0);
+        }
+    }
+}
@@ -0,0 +1,273 @@
+// * * ** *** ***** ******** ************* *********************
+// Sampling the memory footprint while observing
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+
+
+// Reading resident set sizes
+
+#[cfg(target_os = "linux")]
+const STATUS_PATH: &str = "/proc/self/status";
+#[cfg(target_os = "linux")]
+const CLEAR_REFS_PATH: &str = "/proc/self/clear_refs";
+#[cfg(target_os = "linux")]
+const RESET_PEAK_RSS: &str = "5";
+
+/// Fields of the status file are sizes in KiB, e.g. "VmRSS:     2048 kB".
+#[cfg(target_os = "linux")]
+fn read_status_bytes(field: &str) -> Option<u64> {
+    std::fs::read_to_string(STATUS_PATH).ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix(field)?.strip_prefix(':'))?
+        .split_whitespace()
+        .next()?
+        .parse::<u64>()
+        .ok()
+        .map(|kib| 1024*kib)
+}
+
+/// Current and peak resident set sizes of the process in bytes.
+#[cfg(target_os = "linux")]
+fn read_rss() -> Option<(u64, u64)> {
+    Some((read_status_bytes("VmRSS")?, read_status_bytes("VmHWM")?))
+}
+
+/// Linux lets the peak start over from the current size, Windows keeps the peak of the whole process.
+#[cfg(target_os = "linux")]
+fn reset_peak_rss() {
+    let _ = std::fs::write(CLEAR_REFS_PATH, RESET_PEAK_RSS);
+}
+
+#[cfg(windows)]
+fn read_rss() -> Option<(u64, u64)> {
+    use windows_sys::Win32::System::ProcessStatus::*;
+    use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+    let mut counters: PROCESS_MEMORY_COUNTERS = unsafe {std::mem::zeroed()};
+    let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+
+    match unsafe {GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size)} {
+        0 => None,
+        _ => Some((counters.WorkingSetSize as u64, counters.PeakWorkingSetSize as u64))
+    }
+}
+
+#[cfg(windows)]
+fn reset_peak_rss() {}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn read_rss() -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn reset_peak_rss() {}
+
+
+// Comparing the footprint before and after an observation
+
+/// Resident set sizes of the process in bytes, the peak being the largest while observing.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct MemoryUsage {
+    pub before: u64,
+    pub after: u64,
+    pub peak: u64
+}
+
+impl MemoryUsage {
+
+    pub fn get_growth(self: &Self) -> u64 {
+        self.peak.saturating_sub(self.before)
+    }
+}
+
+pub struct MemorySampler {
+    before: Option<u64>
+}
+
+impl MemorySampler {
+
+    pub fn start() -> MemorySampler {
+        reset_peak_rss();
+        MemorySampler {before: read_rss().map(|(rss, _)| rss)}
+    }
+
+    /// Gives nothing where resident set sizes are unavailable, e.g. on macOS.
+    pub fn finish(self: &Self) -> Option<MemoryUsage> {
+        let before = self.before?;
+        let (after, peak) = read_rss()?;
+        Some(MemoryUsage {before, after, peak: peak.max(before).max(after)})
+    }
+}
+
+
+// Counting allocations of tasks
+
+/// Bytes allocated and freed by a task, reallocations counting as both.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct Allocations {
+    pub allocated: u64,
+    pub freed: u64
+}
+
+/// A global allocator that counts in thread-locals, which need no allocation themselves,
+/// so every task counts what its own thread allocates.
+#[cfg(feature = "alloc-count")]
+mod counting {
+
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    thread_local! {
+        static ALLOCATED: Cell<u64> = const {Cell::new(0)};
+        static FREED: Cell<u64> = const {Cell::new(0)};
+    }
+
+    /// Threads being torn down have no thread-locals left, and count nothing.
+    fn count(counter: &'static std::thread::LocalKey<Cell<u64>>, size: usize) {
+        let _ = counter.try_with(|counter| counter.set(counter.get() + size as u64));
+    }
+
+    pub fn read_counts() -> (u64, u64) {
+        (ALLOCATED.try_with(Cell::get).unwrap_or(0), FREED.try_with(Cell::get).unwrap_or(0))
+    }
+
+    struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count(&ALLOCATED, layout.size());
+            unsafe {System.alloc(layout)}
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            count(&ALLOCATED, layout.size());
+            unsafe {System.alloc_zeroed(layout)}
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            count(&FREED, layout.size());
+            unsafe {System.dealloc(ptr, layout)}
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count(&FREED, layout.size());
+            count(&ALLOCATED, new_size);
+            unsafe {System.realloc(ptr, layout, new_size)}
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+}
+
+/// Allocations of the calling thread, counted in builds with the alloc-count feature,
+/// and by nothing elsewhere.
+pub struct AllocationCounter {
+    #[cfg(feature = "alloc-count")]
+    start: (u64, u64)
+}
+
+impl AllocationCounter {
+
+    #[cfg(feature = "alloc-count")]
+    pub fn start() -> Option<AllocationCounter> {
+        Some(AllocationCounter{start: counting::read_counts()})
+    }
+
+    #[cfg(not(feature = "alloc-count"))]
+    pub fn start() -> Option<AllocationCounter> {
+        None
+    }
+
+    #[cfg(feature = "alloc-count")]
+    pub fn finish(self: Self) -> Option<Allocations> {
+        let (allocated, freed) = counting::read_counts();
+        Some(Allocations {allocated: allocated - self.start.0, freed: freed - self.start.1})
+    }
+
+    #[cfg(not(feature = "alloc-count"))]
+    pub fn finish(self: Self) -> Option<Allocations> {
+        None
+    }
+}
+
+
+// Displaying the footprint
+
+pub fn format_bytes(n_bytes: u64) -> String {
+    if n_bytes >= 1024*1024 {
+        format!("{:.1} MiB", n_bytes as f64/(1024.0*1024.0))
+    } else if n_bytes >= 1024 {
+        format!("{:.1} KiB", n_bytes as f64/1024.0)
+    } else {
+        format!("{} bytes", n_bytes)
+    }
+}
+
+/// The observation growing the most tells what tasks, their stacks and data included, cost in memory.
+pub fn print_memory_footprint(report: &Report) {
+
+    let largest = report.observations.iter()
+        .filter_map(|obs| obs.get_memory_usage().map(|memory_usage| (obs.count_tasks(), memory_usage)))
+        .max_by_key(|(_, memory_usage)| memory_usage.get_growth());
+
+    if let Some((n_tasks, memory_usage)) = largest {
+        info!("Peak RSS: {} at {} tasks, {} over the {} before observing",
+              format_bytes(memory_usage.peak), n_tasks,
+              format_bytes(memory_usage.get_growth()), format_bytes(memory_usage.before));
+    }
+}
+
+pub fn print_allocations(report: &Report) {
+    for obs in &report.observations {
+        if let Some(allocations) = obs.sum_allocations() {
+            info!("Allocations of {} tasks: {} allocated, {} freed, {} per task",
+                  obs.count_tasks(), format_bytes(allocations.allocated), format_bytes(allocations.freed),
+                  format_bytes(allocations.allocated/obs.count_tasks().max(1) as u64));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn peaks_are_sampled_where_supported() {
+        let sampler = MemorySampler::start();
+        let buffer = vec![1u8; 16*1024*1024];
+        let memory_usage = sampler.finish();
+        if cfg!(any(target_os = "linux", windows)) {
+            let memory_usage = memory_usage.unwrap();
+            assert!(memory_usage.peak >= memory_usage.before.max(memory_usage.after));
+            assert!(memory_usage.after > 0);
+        }
+        assert_eq!(buffer.len(), 16*1024*1024);
+    }
+
+    #[test]
+    fn allocations_are_counted_with_the_feature() {
+        let counter = AllocationCounter::start();
+        let buffer = vec![1u8; 4096];
+        let allocations = counter.and_then(|counter| counter.finish());
+        if cfg!(feature = "alloc-count") {
+            assert!(allocations.unwrap().allocated >= 4096);
+        } else {
+            assert_eq!(allocations, None);
+        }
+        assert_eq!(buffer.len(), 4096);
+    }
+
+    #[test]
+    fn growth_is_measured_from_before() {
+        let memory_usage = MemoryUsage {before: 4096, after: 2048, peak: 8192};
+        assert_eq!(memory_usage.get_growth(), 4096);
+        assert_eq!(format_bytes(memory_usage.peak), "8.0 KiB");
+        assert_eq!(format_bytes(512), "512 bytes");
+    }
+}
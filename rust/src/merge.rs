@@ -0,0 +1,155 @@
+// * * ** *** ***** ******** ************* *********************
+// Merging reports of several runs
+// * * ** *** ***** ******** ************* *********************
+
+use serde::{Deserialize, Serialize};
+
+use embed::ReportRecords;
+
+use super::*;
+
+
+// Merged files
+
+/// A run of a merged file, labelled after the machine or backend it comes from.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LabelledRun {
+    pub label: String,
+    #[serde(flatten)]
+    pub records: ReportRecords
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct MergedRuns {
+    pub runs: Vec<LabelledRun>
+}
+
+fn is_merged(text: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(text)
+        .is_ok_and(|value| value.get("runs").is_some())
+}
+
+
+// Labelling runs
+
+/// Runs are labelled as they were tagged, or after the file they come from.
+fn label_run(report: &Report, report_file_path: &str) -> String {
+    match report.get_metadata("Label") {
+        Some(label) if !label.is_empty() => label.clone(),
+        _ => Path::new(compression::strip_extension(report_file_path)).file_stem()
+            .map_or(report_file_path.to_string(), |stem| stem.to_string_lossy().to_string())
+    }
+}
+
+/// Labels stay apart, so runs of the same tag keep separate lines on charts.
+fn add_run(runs: &mut Vec<(String, Report)>, label: String, report: Report) {
+    let mut unique_label = label.clone();
+    let mut n = 1;
+    while runs.iter().any(|(run_label, _)| *run_label == unique_label) {
+        n += 1;
+        unique_label = format!("{} #{}", label, n);
+    }
+    runs.push((unique_label, report));
+}
+
+
+// Loading runs
+
+/// Reports read as a single run, and merged files as all of theirs.
+pub fn load_runs(report_file_path: &str) -> Vec<(String, Report)> {
+
+    let mut runs: Vec<(String, Report)> = Vec::new();
+
+    match compression::read_bytes(report_file_path) {
+        Ok(bytes) if std::str::from_utf8(&bytes).is_ok_and(is_merged) => {
+            let merged: MergedRuns = serde_json::from_slice(&bytes)
+                .unwrap_or_else(|e| panic!("Error while parsing merged runs: {}", e));
+            for run in merged.runs {
+                add_run(&mut runs, run.label, saved::convert_records(&run.records));
+            }
+        }
+        Ok(_) => {
+            let report = saved::load_report(report_file_path);
+            add_run(&mut runs, label_run(&report, report_file_path), report);
+        }
+        Err(e) => {
+            panic!("Error while reading a report file: {}", e);
+        }
+    }
+
+    runs
+}
+
+pub fn format_merged_runs(runs: &[(String, Report)]) -> String {
+    let merged = MergedRuns {
+        runs: runs.iter()
+            .map(|(label, report)| LabelledRun{label: label.clone(), records: ReportRecords::create(report)})
+            .collect()
+    };
+    serde_json::to_string_pretty(&merged).unwrap() + "\n"
+}
+
+
+// Merging
+
+pub fn run_merge(args: &Args) -> i32 {
+
+    let mut runs: Vec<(String, Report)> = Vec::new();
+
+    for report_file_path in args.get_merged_file_paths() {
+        for (label, report) in load_runs(&report_file_path) {
+            add_run(&mut runs, label, report);
+        }
+    }
+
+    let text = format_merged_runs(&runs);
+    if args.get_out_file_path() != "" {
+        save_report_text(&args.get_out_file_path(), &text);
+        info!("{} runs merged into {}",
+              runs.len(), compression::get_compression().append_extension(&args.get_out_file_path()));
+    } else {
+        print!("{}", text);
+    }
+
+    EXIT_SUCCESS
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::FakeWorkload;
+
+    #[test]
+    fn labels_stay_apart() {
+        let mut runs: Vec<(String, Report)> = Vec::new();
+        for _ in 0..3 {
+            add_run(&mut runs, "fake".to_string(), FakeWorkload::create(2, 2, 100).create_report(1..=2));
+        }
+        let labels: Vec<&str> = runs.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(labels, vec!["fake", "fake #2", "fake #3"]);
+    }
+
+    #[test]
+    fn untagged_runs_are_labelled_after_files() {
+        let mut report = FakeWorkload::create(2, 2, 100).create_report(1..=2);
+        assert_eq!(label_run(&report, "out/linux.csv"), "fake");
+        report.set_metadata("Label", "");
+        assert_eq!(label_run(&report, "out/linux.csv"), "linux");
+    }
+
+    #[test]
+    fn merged_runs_read_back() {
+        let runs = vec![
+            ("a".to_string(), FakeWorkload::create(2, 2, 100).create_report(1..=2)),
+            ("b".to_string(), FakeWorkload::create(1, 2, 200).create_report(1..=3))
+        ];
+        let text = format_merged_runs(&runs);
+        assert!(is_merged(&text));
+        let merged: MergedRuns = serde_json::from_str(&text).unwrap();
+        assert_eq!(merged.runs.len(), 2);
+        assert_eq!(merged.runs[1].label, "b");
+        assert_eq!(merged.runs[1].records.observations.len(), 3);
+    }
+}
@@ -0,0 +1,254 @@
+// * * ** *** ***** ******** ************* *********************
+// Choosing statistics of observations to display and save
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+
+
+// Metrics
+
+pub const METRIC_NAMES: &[&str] = &["mean", "median", "p99", "std-dev", "cv", "total", "bound", "quality",
+                                    "parallelism", "running", "cpu", "spread", "throughput", "cycles",
+                                    "cost", "speedup", "efficiency", "profit"];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Metric {
+    Mean,
+    Median,
+    P99,
+    StdDev,
+    Cv,
+    Total,
+    Bound,
+    Quality,
+    Parallelism,
+    Running,
+    Cpu,
+    Spread,
+    Throughput,
+    Cycles,
+    Cost,
+    Speedup,
+    Efficiency,
+    Profit
+}
+
+impl Metric {
+
+    pub fn parse(s: &str) -> Option<Metric> {
+        match s {
+            "mean" => Some(Metric::Mean),
+            "median" => Some(Metric::Median),
+            "p99" => Some(Metric::P99),
+            "std-dev" => Some(Metric::StdDev),
+            "cv" => Some(Metric::Cv),
+            "total" => Some(Metric::Total),
+            "bound" => Some(Metric::Bound),
+            "quality" => Some(Metric::Quality),
+            "parallelism" => Some(Metric::Parallelism),
+            "running" => Some(Metric::Running),
+            "cpu" => Some(Metric::Cpu),
+            "spread" => Some(Metric::Spread),
+            "throughput" => Some(Metric::Throughput),
+            "cycles" => Some(Metric::Cycles),
+            "cost" => Some(Metric::Cost),
+            "speedup" => Some(Metric::Speedup),
+            "efficiency" => Some(Metric::Efficiency),
+            "profit" => Some(Metric::Profit),
+            _ => None
+        }
+    }
+
+    pub fn get_name(self: &Self) -> &'static str {
+        METRIC_NAMES[self.get_idx()]
+    }
+
+    fn get_idx(self: &Self) -> usize {
+        *self as usize
+    }
+
+    /// Titles are those of the full totals section, so sorting and reading back find them.
+    fn get_title(self: &Self) -> &'static str {
+        match self {
+            Metric::Mean => "Mean task duration",
+            Metric::Median => "Median task duration",
+            Metric::P99 => "P99 task duration",
+            Metric::StdDev => "Std. dev.",
+            Metric::Cv => "CV",
+            Metric::Total => "Total duration",
+            Metric::Bound => "Duration bound",
+            Metric::Quality => "Scheduling quality",
+            Metric::Parallelism => "Parallelism",
+            Metric::Running => "Max running",
+            Metric::Cpu => "CPU utilization",
+            Metric::Spread => "Start spread",
+            Metric::Throughput => "Tasks/sec",
+            Metric::Cycles => "Cycles/sec",
+            Metric::Cost => "Cost",
+            Metric::Speedup => "Speedup",
+            Metric::Efficiency => "Efficiency",
+            Metric::Profit => "Profit"
+        }
+    }
+
+    /// Columns of the profit table hide as the default table hides them on narrow terminals.
+    fn get_priority(self: &Self) -> usize {
+        match self {
+            Metric::Profit => 0,
+            Metric::Parallelism | Metric::Cycles | Metric::Cost | Metric::Speedup => 2,
+            Metric::Mean | Metric::Median | Metric::Cv | Metric::Quality | Metric::Running |
+            Metric::Cpu | Metric::Spread | Metric::Efficiency => 3,
+            Metric::P99 | Metric::Total | Metric::Bound => 4,
+            Metric::Throughput => 5,
+            Metric::StdDev => 1
+        }
+    }
+
+    fn create_column(self: &Self) -> Column {
+        let width = if *self == Metric::Cycles {15} else {self.get_title().len().max(7)};
+        Column::create(self.get_title(), width, Align::Right, self.get_priority())
+    }
+
+    /// As the full totals section saves the statistic.
+    fn format_value(self: &Self, obs: &Observation, n_cpus: usize) -> String {
+        match self {
+            Metric::Mean => obs.get_mean_task_duration().to_string(),
+            Metric::Median => obs.get_median_task_duration().to_string(),
+            Metric::P99 => obs.get_task_duration_percentile(99.0).to_string(),
+            Metric::StdDev => obs.get_standard_deviation().to_string(),
+            Metric::Cv => format!("{:.4}", obs.get_variation_coefficient()),
+            Metric::Total => obs.get_total_duration().to_string(),
+            Metric::Bound => obs.get_duration_bound(n_cpus).to_string(),
+            Metric::Quality => format!("{:.4}", obs.get_scheduling_quality(n_cpus)),
+            Metric::Parallelism => format!("{:.3}", obs.get_parallelism()),
+            Metric::Running => obs.count_concurrent_tasks_max().map_or("".to_string(), |running| running.to_string()),
+            Metric::Cpu => obs.get_cpu_utilization()
+                .map_or("".to_string(), |utilization| format!("{:.4}", utilization.total)),
+            Metric::Spread => obs.get_start_spread().map_or("".to_string(), |spread| spread.to_string()),
+            Metric::Throughput => format!("{:.3}", obs.get_tasks_per_sec()),
+            Metric::Cycles => format!("{:.0}", obs.get_cycles_per_sec()),
+            Metric::Cost => format!("{:.4}", obs.get_concurrency_cost()),
+            Metric::Speedup => format!("{:.3}", obs.get_speedup()),
+            Metric::Efficiency => format!("{:.4}", obs.get_efficiency(n_cpus)),
+            Metric::Profit => format!("{:.4}", obs.get_concurrency_profit())
+        }
+    }
+
+    /// As the default profit table displays the statistic.
+    fn create_cell(self: &Self, obs: &Observation, n_cpus: usize) -> Cell {
+        match self {
+            Metric::Cv => Cell::Plain(format_percent_cell(obs.get_variation_coefficient())),
+            Metric::Quality => Cell::Plain(format_percent_cell(obs.get_scheduling_quality(n_cpus))),
+            Metric::Parallelism => Cell::Plain(format!("{:.2}", obs.get_parallelism())),
+            Metric::Running => Cell::Plain(format_optional_cell(obs.count_concurrent_tasks_max())),
+            Metric::Spread => Cell::Plain(format_optional_cell(obs.get_start_spread())),
+            Metric::Cpu => Cell::Plain(obs.get_cpu_utilization()
+                .map_or("".to_string(), |utilization| format_percent_cell(utilization.total))),
+            Metric::Throughput => Cell::Plain(format!("{:.1}", obs.get_tasks_per_sec())),
+            Metric::Cycles => Cell::Plain(format_count(obs.get_cycles_per_sec() as usize)),
+            Metric::Cost => Cell::Plain(format_percent_cell(obs.get_concurrency_cost())),
+            Metric::Speedup => Cell::Plain(format!("{:.2}x", obs.get_speedup())),
+            Metric::Efficiency => Cell::Plain(format_percent_cell(obs.get_efficiency(n_cpus))),
+            Metric::Profit => Cell::Signed(format_percent_cell(obs.get_concurrency_profit()),
+                                           obs.get_concurrency_profit()),
+            _ => Cell::Plain(self.format_value(obs, n_cpus))
+        }
+    }
+}
+
+/// Nothing chosen is the default set of columns, a name not known is nothing valid.
+pub fn parse_metrics(s: &str) -> Option<Vec<Metric>> {
+    if s.is_empty() {
+        return Some(Vec::new());
+    }
+    s.split(',').map(|name| Metric::parse(name.trim())).collect()
+}
+
+pub fn format_metrics(metrics: &[Metric]) -> String {
+    metrics.iter().map(|metric| metric.get_name()).collect::<Vec<&str>>().join(",")
+}
+
+/// Reports keep the metrics chosen in their metadata, so rendering them keeps the same columns.
+pub fn get_report_metrics(report: &Report) -> Vec<Metric> {
+    report.get_metadata("Metrics")
+        .and_then(|value| parse_metrics(value))
+        .unwrap_or_default()
+}
+
+
+// Totals of chosen metrics
+
+/// Retries and statuses stay, so reports read back and resume as ones of the default columns.
+pub fn format_totals_header(metrics: &[Metric]) -> String {
+    let titles: Vec<&str> = metrics.iter().map(|metric| metric.get_title()).collect();
+    format!("Tasks,{},Retries,Status\n", titles.join(","))
+}
+
+pub fn format_skipped_totals(metrics: &[Metric], n_tasks: usize) -> String {
+    format!("{},{},,skipped\n", n_tasks, ",".repeat(metrics.len().saturating_sub(1)))
+}
+
+pub fn format_totals(metrics: &[Metric], obs: &Observation, n_cpus: usize) -> String {
+    let values: Vec<String> = metrics.iter().map(|metric| metric.format_value(obs, n_cpus)).collect();
+    format!("{},{},{},measured\n", obs.count_tasks(), values.join(","), obs.get_retries())
+}
+
+
+// Profit tables of chosen metrics
+
+pub fn create_table(metrics: &[Metric], colored: bool) -> ConsoleTable {
+    let mut columns = vec![Column::create("Tasks", 5, Align::Right, 0)];
+    columns.extend(metrics.iter().map(|metric| metric.create_column()));
+    ConsoleTable::create(columns, colored)
+}
+
+pub fn print_entry(table: &ConsoleTable, metrics: &[Metric], obs: &Observation) {
+    let mut cells = vec![Cell::Plain(obs.count_tasks().to_string())];
+    cells.extend(metrics.iter().map(|metric| metric.create_cell(obs, count_cpus())));
+    table.print_row(&cells);
+}
+
+pub fn print_skipped_entry(table: &ConsoleTable, metrics: &[Metric], n_tasks: usize) {
+    let mut cells = vec![Cell::Plain(n_tasks.to_string()), Cell::Plain("skipped".to_string())];
+    while cells.len() < metrics.len() + 1 {
+        cells.push(Cell::Plain("".to_string()));
+    }
+    table.print_row(&cells);
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::FakeWorkload;
+
+    #[test]
+    fn metrics_parse_by_name() {
+        assert_eq!(parse_metrics("mean, p99,profit"), Some(vec![Metric::Mean, Metric::P99, Metric::Profit]));
+        assert_eq!(parse_metrics(""), Some(Vec::new()));
+        assert_eq!(parse_metrics("mean,p95"), None);
+    }
+
+    #[test]
+    fn metrics_are_named_as_registered() {
+        assert_eq!(registry::METRICS.len(), METRIC_NAMES.len());
+        for entry in registry::METRICS {
+            assert_eq!(Metric::parse(entry.name).map(|metric| metric.get_name()), Some(entry.name));
+        }
+    }
+
+    #[test]
+    fn chosen_metrics_make_the_totals_section() {
+
+        let mut report = FakeWorkload::create(2, 4, 100).create_report(1..=4);
+        report.set_metadata("Metrics", "speedup,efficiency");
+        report.register_skipped(5);
+
+        let section = format_observation_totals_section(&report);
+        let rows: Vec<Vec<String>> = section.lines().map(split_csv_line).collect();
+        assert_eq!(rows[0], vec!["Tasks", "Speedup", "Efficiency", "Retries", "Status"]);
+        assert_eq!(rows[2], vec!["2", "1.980", "0.9901", "0", "measured"]);
+        assert_eq!(rows[5], vec!["5", "", "", "", "skipped"]);
+    }
+}
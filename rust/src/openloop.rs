@@ -0,0 +1,261 @@
+// * * ** *** ***** ******** ************* *********************
+// Generating open-loop load with tasks arriving at a rate
+// * * ** *** ***** ******** ************* *********************
+
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Instant;
+use hdrhistogram::Histogram;
+
+use super::*;
+
+
+// Scheduling arrivals
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Arrivals {
+    Constant,
+    Poisson
+}
+
+impl Arrivals {
+
+    pub fn parse(s: &str) -> Option<Arrivals> {
+        match s {
+            "" | "constant" => Some(Arrivals::Constant),
+            "poisson" => Some(Arrivals::Poisson),
+            _ => None
+        }
+    }
+
+    fn get_name(self: &Self) -> &'static str {
+        match self {
+            Arrivals::Constant => "constant",
+            Arrivals::Poisson => "poisson"
+        }
+    }
+}
+
+/// Offsets of arrivals from the start, in microseconds.
+fn schedule_arrivals(n_tasks: usize, rate: f64, arrivals: Arrivals, seed: u64) -> Vec<u64> {
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mean_interval = 1e6/rate;
+    let mut offset = 0.0f64;
+
+    (0..n_tasks).map(|_| {
+        let arrival = offset as u64;
+        offset += match arrivals {
+            Arrivals::Constant => mean_interval,
+            Arrivals::Poisson => -mean_interval*(1.0 - rng.gen::<f64>()).ln()
+        };
+        arrival
+    }).collect()
+}
+
+
+// Serving arrived tasks
+
+struct ServedTask {
+    arrival: u64,
+    start: u64,
+    finish: u64,
+    status: TaskStatus
+}
+
+impl ServedTask {
+
+    fn get_queueing_delay(self: &Self) -> u64 {
+        self.start - self.arrival
+    }
+
+    fn get_latency(self: &Self) -> u64 {
+        self.finish - self.arrival
+    }
+}
+
+fn micros_since(origin: Instant) -> u64 {
+    origin.elapsed().as_micros() as u64
+}
+
+fn serve(args: &Args, arrivals: &[u64]) -> Vec<ServedTask> {
+
+    let (sender, receiver) = mpsc::channel::<(usize, u64)>();
+    let receiver = Mutex::new(receiver);
+    let served: Mutex<Vec<Option<ServedTask>>> = Mutex::new((0..arrivals.len()).map(|_| None).collect());
+    let origin = Instant::now();
+    let observation_start = read_run_clock();
+    let n_tasks = arrivals.len();
+    let _timer_period = platform::TimerPeriodGuard::acquire();
+
+    thread::scope(|scope| {
+
+        for worker_idx in 0..args.get_series_size() {
+            let (receiver, served) = (&receiver, &served);
+            let name = format!("conctest-open-{}", worker_idx + 1);
+            platform::create_worker_builder(name).spawn_scoped(scope, move || {
+                let _ = platform::prepare_worker_thread();
+                loop {
+                    let next = receiver.lock().unwrap().recv();
+                    let (task_idx, arrival) = match next {
+                        Ok(next) => next,
+                        Err(_) => return
+                    };
+                    let start = micros_since(origin);
+                    let task = standard_task(task_idx, args.get_n_cycles(), args.get_timeouts(),
+                                             observation_start,
+                                             derive_task_seed(args.get_seed(), n_tasks, task_idx));
+                    let finish = micros_since(origin);
+                    served.lock().unwrap()[task_idx] = Some(ServedTask {
+                        arrival, start, finish, status: task.get_status().clone()
+                    });
+                }
+            }).expect("Spawning a worker failed");
+        }
+
+        for (task_idx, arrival) in arrivals.iter().enumerate() {
+            let now = micros_since(origin);
+            if *arrival > now {
+                thread::sleep(Duration::from_micros(arrival - now));
+            }
+            if sender.send((task_idx, *arrival)).is_err() {
+                break;
+            }
+        }
+
+        drop(sender);
+    });
+
+    served.into_inner().unwrap().into_iter().flatten().collect()
+}
+
+
+// Reporting distributions
+
+/// Latencies count from intended arrivals, which keeps them free of coordinated omission.
+fn record_distributions(served: &Vec<ServedTask>) -> (Histogram<u64>, Histogram<u64>) {
+
+    let mut delays = create_histogram();
+    let mut latencies = create_histogram();
+
+    // Saturating records would clamp to the initial range instead of resizing histograms
+    let mut n_unrecorded = 0usize;
+    for task in served {
+        n_unrecorded += delays.record(task.get_queueing_delay()).is_err() as usize;
+        n_unrecorded += latencies.record(task.get_latency()).is_err() as usize;
+    }
+
+    if n_unrecorded > 0 {
+        warn!("{} queueing delays and latencies out of the range of histograms were left out", n_unrecorded);
+    }
+
+    (delays, latencies)
+}
+
+fn create_distribution_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Percentile", 10, Align::Right, 0),
+        Column::create("Queueing delay, us", 18, Align::Right, 0),
+        Column::create("Latency, us", 14, Align::Right, 0)
+    ], colored)
+}
+
+fn print_distributions(colored: bool, delays: &Histogram<u64>, latencies: &Histogram<u64>) {
+
+    let table = create_distribution_table(colored);
+    table.print_header();
+
+    for percentile in PERCENTILES {
+        table.print_row(&vec![
+            Cell::Plain(format!("p{}", percentile)),
+            Cell::Plain(format_count(delays.value_at_quantile(percentile/100.0) as usize)),
+            Cell::Plain(format_count(latencies.value_at_quantile(percentile/100.0) as usize))
+        ]);
+    }
+
+    table.print_footer();
+}
+
+fn format_open_loop_report(report: &Report, served: &[ServedTask], 
+                           delays: &Histogram<u64>, latencies: &Histogram<u64>) -> String {
+
+    let percentiles_text = format_percentiles_section(&["Queueing delay", "Latency"],
+                                                      &[delays, latencies]);
+
+    let mut tasks_text = "Task,Arrival,Started,Finished,Queueing delay,Latency,Status\n".to_string();
+
+    for (idx, task) in served.iter().enumerate() {
+        tasks_text += &format!("{},{},{},{},{},{},{}\n", idx + 1, task.arrival, task.start,
+                               task.finish, task.get_queueing_delay(), task.get_latency(),
+                               quote_csv(&format_status(&task.status)));
+    }
+
+    format_metadata_section(report) + "\n" + &percentiles_text + "\n" + &tasks_text
+}
+
+
+// Running an open loop
+
+pub fn run_open_loop(args: &Args) -> i32 {
+
+    let mut report = Report::create(args.get_tasks_max());
+    register_run_metadata(&mut report, args, read_timestamp(&SystemTime::now()));
+    report.set_metadata("Arrival rate, tasks per sec", &args.get_arrival_rate().to_string());
+    report.set_metadata("Arrivals", args.get_arrivals().get_name());
+
+    info!("Serving {} tasks arriving at {} per sec. ({}) with {} workers",
+          args.get_tasks_max(), args.get_arrival_rate(), args.get_arrivals().get_name(),
+          args.get_series_size());
+
+    let environment_before = EnvironmentSnapshot::capture();
+    let arrivals = schedule_arrivals(args.get_tasks_max(), args.get_arrival_rate(),
+                                     args.get_arrivals(), args.get_seed());
+    let served = serve(args, &arrivals);
+
+    let (delays, latencies) = record_distributions(&served);
+    print_distributions(args.get_colored(), &delays, &latencies);
+    save_report_text(&args.get_out_file_path(), 
+                     &format_open_loop_report(&report, &served, &delays, &latencies));
+
+    assess_measuring(args, &report, &environment_before)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::{assert_golden, FakeWorkload};
+
+    /// Tasks start when they arrive or when the fake machine gets to them, whichever is later.
+    fn serve_fake(arrivals: &[u64]) -> Vec<ServedTask> {
+        let obs = FakeWorkload::create(2, arrivals.len(), 100).observe(arrivals.len());
+        obs.tasks.iter().zip(arrivals)
+            .map(|(task, arrival)| {
+                let start = (*arrival).max(1000*obs.to_relative(task.get_start()).as_millis());
+                let finish = start + 1000*task.get_duration().as_millis();
+                ServedTask {arrival: *arrival, start, finish, status: task.get_status().clone()}
+            })
+            .collect()
+    }
+
+    #[test]
+    fn constant_arrivals_are_evenly_spaced() {
+        assert_eq!(schedule_arrivals(4, 20.0, Arrivals::Constant, 0), vec![0, 50000, 100000, 150000]);
+    }
+
+    #[test]
+    fn poisson_arrivals_are_reproducible() {
+        let arrivals = schedule_arrivals(100, 50.0, Arrivals::Poisson, 7);
+        assert_eq!(arrivals, schedule_arrivals(100, 50.0, Arrivals::Poisson, 7));
+        assert!(arrivals.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn open_loop_report_matches_golden_file() {
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=1);
+        let served = serve_fake(&schedule_arrivals(6, 25.0, Arrivals::Constant, 0));
+        let (delays, latencies) = record_distributions(&served);
+        assert_golden("openloop.csv", &format_open_loop_report(&report, &served, &delays, &latencies));
+    }
+}
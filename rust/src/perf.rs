@@ -0,0 +1,127 @@
+// * * ** *** ***** ******** ************* *********************
+// Counting hardware events of tasks
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+
+
+// Counting branch misses
+
+/// Branch misses of the calling thread, counted by Linux perf events in builds
+/// with the perf feature, and by nothing elsewhere.
+pub struct BranchMissCounter {
+    #[cfg(all(feature = "perf", target_os = "linux"))]
+    fd: libc::c_int
+}
+
+#[cfg(all(feature = "perf", target_os = "linux"))]
+mod events {
+
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_COUNT_HW_BRANCH_MISSES: u64 = 5;
+    const PERF_ATTR_SIZE_VER0: u32 = 64;
+    const EXCLUDE_KERNEL: u64 = 1 << 5;
+    const EXCLUDE_HV: u64 = 1 << 6;
+
+    /// The first version of perf_event_attr, which every kernel accepts.
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        kind: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64
+    }
+
+    /// Counting starts at once, for the calling thread on any CPU, in user space.
+    pub fn open_branch_misses() -> Option<libc::c_int> {
+
+        let attr = PerfEventAttr {
+            kind: PERF_TYPE_HARDWARE,
+            size: PERF_ATTR_SIZE_VER0,
+            config: PERF_COUNT_HW_BRANCH_MISSES,
+            flags: EXCLUDE_KERNEL | EXCLUDE_HV,
+            ..Default::default()
+        };
+
+        let fd = unsafe {libc::syscall(libc::SYS_perf_event_open, &attr as *const PerfEventAttr, 0, -1, -1, 0)};
+
+        if fd >= 0 {Some(fd as libc::c_int)} else {None}
+    }
+
+    pub fn read_and_close(fd: libc::c_int) -> Option<u64> {
+
+        let mut count = 0u64;
+        let n_bytes = unsafe {libc::read(fd, &mut count as *mut u64 as *mut libc::c_void, 8)};
+        unsafe {libc::close(fd);}
+
+        if n_bytes == 8 {Some(count)} else {None}
+    }
+}
+
+impl BranchMissCounter {
+
+    /// Nothing when perf events are unavailable, e.g. denied by perf_event_paranoid.
+    #[cfg(all(feature = "perf", target_os = "linux"))]
+    pub fn start() -> Option<BranchMissCounter> {
+        events::open_branch_misses().map(|fd| BranchMissCounter{fd})
+    }
+
+    #[cfg(not(all(feature = "perf", target_os = "linux")))]
+    pub fn start() -> Option<BranchMissCounter> {
+        None
+    }
+
+    #[cfg(all(feature = "perf", target_os = "linux"))]
+    pub fn finish(self: Self) -> Option<u64> {
+        events::read_and_close(self.fd)
+    }
+
+    #[cfg(not(all(feature = "perf", target_os = "linux")))]
+    pub fn finish(self: Self) -> Option<u64> {
+        None
+    }
+}
+
+
+// Displaying counts
+
+/// Misses per thousand cycles, comparable across numbers of cycles.
+fn get_misses_per_kilocycle(obs: &Observation) -> Option<f64> {
+    let branch_misses = obs.sum_branch_misses()?;
+    let n_cycles = obs.count_tasks()*obs.n_cycles;
+    if n_cycles > 0 {Some(1000.0*branch_misses as f64/n_cycles as f64)} else {None}
+}
+
+pub fn print_branch_misses(report: &Report) {
+    for obs in &report.observations {
+        if let Some(misses) = get_misses_per_kilocycle(obs) {
+            info!("Branch misses of {} tasks: {:.1} per 1000 cycles", obs.count_tasks(), misses);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn misses_are_counted_per_thousand_cycles() {
+        let mut obs = Observation::create(2, 1000);
+        for task_idx in 0..2 {
+            let mut task = Task::create(task_idx, Timestamp::EPOCH, TaskDuration::from_millis(1));
+            task.set_branch_misses(Some(250));
+            obs.register_task(task);
+        }
+        assert_eq!(get_misses_per_kilocycle(&obs), Some(250.0));
+        assert_eq!(get_misses_per_kilocycle(&Observation::create(2, 1000)), None);
+    }
+}
@@ -0,0 +1,235 @@
+// * * ** *** ***** ******** ************* *********************
+// Placing tasks on CPUs
+// * * ** *** ***** ******** ************* *********************
+
+use rand::seq::SliceRandom;
+
+use platform::LogicalCpu;
+
+use super::*;
+
+
+// Placements
+
+pub const PLACEMENT_NAMES: [&str; 4] = ["compact", "scatter", "smt-pair", "random"];
+
+/// Compact fills the cores of one cache before the next, scatter spreads tasks over caches
+/// and packages, and smt-pair puts pairs of tasks on sibling threads of a core. Only after
+/// every core has a task do compact and scatter put tasks on siblings.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Placement {
+    Unpinned,
+    Compact,
+    Scatter,
+    SmtPair,
+    Random
+}
+
+impl Placement {
+
+    pub fn parse(s: &str) -> Option<Placement> {
+        match s {
+            "" => Some(Placement::Unpinned),
+            "compact" => Some(Placement::Compact),
+            "scatter" => Some(Placement::Scatter),
+            "smt-pair" => Some(Placement::SmtPair),
+            "random" => Some(Placement::Random),
+            _ => None
+        }
+    }
+
+    pub fn get_name(self: &Self) -> &'static str {
+        match self {
+            Placement::Unpinned => "unpinned",
+            Placement::Compact => "compact",
+            Placement::Scatter => "scatter",
+            Placement::SmtPair => "smt-pair",
+            Placement::Random => "random"
+        }
+    }
+}
+
+/// Rank of every CPU among the threads of its core, and of its core among the cores of its cache.
+fn rank_cpus(cpus: &[LogicalCpu]) -> Vec<(usize, usize)> {
+    cpus.iter().map(|cpu| {
+        let thread_rank = cpus.iter()
+            .filter(|other| (other.package, other.core) == (cpu.package, cpu.core) && other.idx < cpu.idx)
+            .count();
+        let mut cores: Vec<usize> = cpus.iter()
+            .filter(|other| (other.package, other.cache) == (cpu.package, cpu.cache) && other.core < cpu.core)
+            .map(|other| other.core)
+            .collect();
+        cores.sort_unstable();
+        cores.dedup();
+        (thread_rank, cores.len())
+    }).collect()
+}
+
+/// CPUs in the order tasks take them, the task of an index the CPU of the index modulo their number.
+pub fn order_cpus(placement: Placement, cpus: &[LogicalCpu], seed: u64) -> Vec<usize> {
+
+    let ranks = rank_cpus(cpus);
+    let mut order: Vec<(LogicalCpu, (usize, usize))> = cpus.iter().copied().zip(ranks).collect();
+
+    match placement {
+        Placement::Unpinned => return Vec::new(),
+        Placement::Compact => order.sort_by_key(|(cpu, (thread_rank, _))|
+            (*thread_rank, cpu.package, cpu.cache, cpu.core, cpu.idx)),
+        Placement::Scatter => order.sort_by_key(|(cpu, (thread_rank, core_rank))|
+            (*thread_rank, *core_rank, cpu.package, cpu.cache, cpu.idx)),
+        Placement::SmtPair => order.sort_by_key(|(cpu, (thread_rank, _))|
+            (cpu.package, cpu.cache, cpu.core, *thread_rank)),
+        Placement::Random =>
+            order.shuffle(&mut StdRng::seed_from_u64(seed))
+    }
+
+    order.iter().map(|(cpu, _)| cpu.idx).collect()
+}
+
+static CPU_ORDER: OnceLock<(Placement, Vec<usize>)> = OnceLock::new();
+
+pub fn set_placement(placement: Placement, seed: u64) {
+    let _ = CPU_ORDER.set((placement, order_cpus(placement, &platform::read_cpu_topology(), seed)));
+}
+
+pub fn get_placement() -> Placement {
+    CPU_ORDER.get().map_or(Placement::Unpinned, |(placement, _)| *placement)
+}
+
+/// Pins the calling worker to the CPU its task takes, if tasks are placed at all.
+pub fn place_worker(task_idx: usize) -> Result<(), String> {
+    match CPU_ORDER.get() {
+        Some((_, order)) if !order.is_empty() => platform::pin_thread(order[task_idx % order.len()]),
+        _ => Ok(())
+    }
+}
+
+/// Pinning fails alike for every worker, so a trial thread finds it out beforehand.
+pub fn try_placement() -> Result<(), String> {
+    std::thread::spawn(|| place_worker(0))
+        .join()
+        .unwrap_or(Err("Placing a trial worker panicked".to_string()))
+}
+
+
+// Comparing placements
+
+const ORDER_PREVIEW: usize = 8;
+
+const PLACEMENTS: [Placement; 5] =
+    [Placement::Unpinned, Placement::Compact, Placement::Scatter, Placement::SmtPair, Placement::Random];
+
+fn format_cpu_order(order: &[usize], n_tasks: usize) -> String {
+    let shown: Vec<String> = order.iter()
+        .cycle()
+        .take(n_tasks.min(ORDER_PREVIEW))
+        .map(|idx| idx.to_string())
+        .collect();
+    match (order.is_empty(), n_tasks > ORDER_PREVIEW) {
+        (true, _) => "any".to_string(),
+        (false, true) => shown.join(",") + ",...",
+        (false, false) => shown.join(",")
+    }
+}
+
+fn create_placement_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Placement", 9, Align::Left, 0),
+        Column::create("CPUs of tasks", 20, Align::Left, 0),
+        Column::create("Total, ms", 10, Align::Right, 0),
+        Column::create("Mean task, ms", 13, Align::Right, 0),
+        Column::create("Relative", 8, Align::Right, 0)
+    ], colored)
+}
+
+/// The same tasks, from the same seeds, under every placement and unpinned.
+pub fn run_placements(args: &Args) -> i32 {
+
+    let (n_tasks, n_cycles, seed) = (args.get_tasks_max(), args.get_n_cycles(), args.get_seed());
+    let timeouts = args.get_timeouts();
+    let cpus = platform::read_cpu_topology();
+
+    info!("Placing {} tasks of {} cycles on {} CPUs", n_tasks, format_count(n_cycles), cpus.len());
+
+    let table = create_placement_table(args.get_colored());
+    table.print_header();
+
+    let mut unpinned_duration = TaskDuration::ZERO;
+
+    for placement in PLACEMENTS {
+
+        if cancel::is_cancelled() {
+            break;
+        }
+
+        let order = order_cpus(placement, &cpus, seed);
+        let obs = observe_workload(&RunClock, n_tasks, n_cycles, n_tasks, seed,
+                                   &|task_idx, observation_start, task_seed| {
+            if let Some(cpu_idx) = order.get(task_idx % order.len().max(1)) {
+                if let Err(error) = platform::pin_thread(*cpu_idx) {
+                    debug!(task = task_idx + 1, error, "Task runs unpinned");
+                }
+            }
+            standard_task(task_idx, n_cycles, timeouts, observation_start, task_seed)
+        });
+
+        if placement == Placement::Unpinned {
+            unpinned_duration = obs.get_total_duration();
+        }
+
+        table.print_row(&vec![
+            Cell::Plain(placement.get_name().to_string()),
+            Cell::Plain(format_cpu_order(&order, n_tasks)),
+            Cell::Plain(obs.get_total_duration().to_string()),
+            Cell::Plain(obs.get_mean_task_duration().to_string()),
+            Cell::Plain(format!("{:.2}",
+                                obs.get_total_duration().as_millis_f64()/unpinned_duration.as_millis_f64().max(1.0)))
+        ]);
+    }
+
+    table.print_footer();
+
+    EXIT_SUCCESS
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Two packages of one cache with two cores of two threads each, numbered as Linux does,
+    /// first threads of all cores, then their siblings.
+    fn create_topology() -> Vec<LogicalCpu> {
+        (0..8).map(|idx| LogicalCpu{idx, core: idx % 2, cache: (idx/2) % 2, package: (idx/2) % 2}).collect()
+    }
+
+    #[test]
+    fn placements_order_cpus_by_topology() {
+        let cpus = create_topology();
+        assert_eq!(order_cpus(Placement::Compact, &cpus, 0), vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(order_cpus(Placement::Scatter, &cpus, 0), vec![0, 2, 1, 3, 4, 6, 5, 7]);
+        assert_eq!(order_cpus(Placement::SmtPair, &cpus, 0), vec![0, 4, 1, 5, 2, 6, 3, 7]);
+        assert!(order_cpus(Placement::Unpinned, &cpus, 0).is_empty());
+        let mut random = order_cpus(Placement::Random, &cpus, 7);
+        assert_eq!(random, order_cpus(Placement::Random, &cpus, 7));
+        random.sort();
+        assert_eq!(random, (0..8).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn placements_are_named() {
+        for name in PLACEMENT_NAMES {
+            assert_eq!(Placement::parse(name).map(|placement| placement.get_name()), Some(name));
+        }
+        assert_eq!(Placement::parse(""), Some(Placement::Unpinned));
+        assert_eq!(Placement::parse("spread"), None);
+    }
+
+    #[test]
+    fn cpu_orders_are_previewed() {
+        assert_eq!(format_cpu_order(&[], 4), "any");
+        assert_eq!(format_cpu_order(&[0, 2], 3), "0,2,0");
+        assert_eq!(format_cpu_order(&(0..16).collect::<Vec<usize>>(), 16), "0,1,2,3,4,5,6,7,...");
+    }
+}
@@ -0,0 +1,550 @@
+// * * ** *** ***** ******** ************* *********************
+// Adjusting the process to the operating system
+// * * ** *** ***** ******** ************* *********************
+
+
+use std::cell::Cell;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+
+// Raising the priority of the process
+
+static PROCESS_PRIORITY_RAISED: AtomicBool = AtomicBool::new(false);
+
+/// Windows schedules a process of the high priority class ahead of normal ones,
+/// which makes its results comparable to those of a quiet Linux machine.
+#[cfg(windows)]
+pub fn raise_process_priority() -> bool {
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, SetPriorityClass,
+                                                HIGH_PRIORITY_CLASS};
+
+    let raised = unsafe {SetPriorityClass(GetCurrentProcess(), HIGH_PRIORITY_CLASS) != 0};
+    PROCESS_PRIORITY_RAISED.store(raised, Ordering::Relaxed);
+    raised
+}
+
+#[cfg(not(windows))]
+pub fn raise_process_priority() -> bool {
+    false
+}
+
+pub fn is_process_priority_raised() -> bool {
+    PROCESS_PRIORITY_RAISED.load(Ordering::Relaxed)
+}
+
+
+// Sleeping precisely
+
+#[cfg(windows)]
+const TIMER_PERIOD_MS: u32 = 1;
+
+/// Windows wakes sleeping threads at ticks of a system timer, 15.6 ms apart by default.
+/// The period is shortened while the guard lives, so sleeps last about as long as on Linux.
+pub struct TimerPeriodGuard;
+
+impl TimerPeriodGuard {
+
+    #[cfg(windows)]
+    pub fn acquire() -> TimerPeriodGuard {
+        unsafe {windows_sys::Win32::Media::timeBeginPeriod(TIMER_PERIOD_MS);}
+        TimerPeriodGuard
+    }
+
+    #[cfg(not(windows))]
+    pub fn acquire() -> TimerPeriodGuard {
+        TimerPeriodGuard
+    }
+}
+
+#[cfg(windows)]
+impl Drop for TimerPeriodGuard {
+    fn drop(self: &mut Self) {
+        unsafe {windows_sys::Win32::Media::timeEndPeriod(TIMER_PERIOD_MS);}
+    }
+}
+
+
+// Classifying the quality of service of workers
+
+/// Apple's scheduler places threads on performance or efficiency cores by their QoS class.
+#[derive(Copy, Clone, PartialEq)]
+pub enum QosClass {
+    Unspecified,
+    UserInteractive,
+    UserInitiated,
+    Default,
+    Utility,
+    Background
+}
+
+pub const QOS_CLASS_NAMES: [&str; 5] = 
+    ["user-interactive", "user-initiated", "default", "utility", "background"];
+
+impl QosClass {
+
+    pub fn parse(s: &str) -> Option<QosClass> {
+        match s {
+            "" => Some(QosClass::Unspecified),
+            "user-interactive" => Some(QosClass::UserInteractive),
+            "user-initiated" => Some(QosClass::UserInitiated),
+            "default" => Some(QosClass::Default),
+            "utility" => Some(QosClass::Utility),
+            "background" => Some(QosClass::Background),
+            _ => None
+        }
+    }
+
+    pub fn get_name(self: &Self) -> &'static str {
+        match self {
+            QosClass::Unspecified => "unspecified",
+            QosClass::UserInteractive => "user-interactive",
+            QosClass::UserInitiated => "user-initiated",
+            QosClass::Default => "default",
+            QosClass::Utility => "utility",
+            QosClass::Background => "background"
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn apply_qos_class(qos_class: QosClass) -> Result<(), String> {
+    use libc::qos_class_t::*;
+
+    let class = match qos_class {
+        QosClass::Unspecified => return Ok(()),
+        QosClass::UserInteractive => QOS_CLASS_USER_INTERACTIVE,
+        QosClass::UserInitiated => QOS_CLASS_USER_INITIATED,
+        QosClass::Default => QOS_CLASS_DEFAULT,
+        QosClass::Utility => QOS_CLASS_UTILITY,
+        QosClass::Background => QOS_CLASS_BACKGROUND
+    };
+
+    match unsafe {libc::pthread_set_qos_class_self_np(class, 0)} {
+        0 => Ok(()),
+        code => Err(format!("Setting the QoS class failed with error {}", code))
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn apply_qos_class(qos_class: QosClass) -> Result<(), String> {
+    match qos_class {
+        QosClass::Unspecified => Ok(()),
+        _ => Err("QoS classes are only supported on macOS".to_string())
+    }
+}
+
+
+// Scheduling workers in real time
+
+/// Real-time threads preempt any thread of the default policy, so CFS cannot disturb them.
+#[derive(Copy, Clone, PartialEq)]
+pub enum SchedPolicy {
+    Other,
+    Fifo,
+    RoundRobin
+}
+
+pub const SCHED_POLICY_NAMES: [&str; 2] = ["fifo", "rr"];
+pub const RT_PRIORITY_MIN: i32 = 1;
+pub const RT_PRIORITY_MAX: i32 = 99;
+
+impl SchedPolicy {
+
+    pub fn parse(s: &str) -> Option<SchedPolicy> {
+        match s {
+            "" => Some(SchedPolicy::Other),
+            "fifo" => Some(SchedPolicy::Fifo),
+            "rr" => Some(SchedPolicy::RoundRobin),
+            _ => None
+        }
+    }
+
+    pub fn get_name(self: &Self) -> &'static str {
+        match self {
+            SchedPolicy::Other => "other",
+            SchedPolicy::Fifo => "fifo",
+            SchedPolicy::RoundRobin => "rr"
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn apply_sched_policy(sched_policy: SchedPolicy, rt_priority: i32) -> Result<(), String> {
+
+    let policy = match sched_policy {
+        SchedPolicy::Other => return Ok(()),
+        SchedPolicy::Fifo => libc::SCHED_FIFO,
+        SchedPolicy::RoundRobin => libc::SCHED_RR
+    };
+    let param = libc::sched_param {sched_priority: rt_priority};
+
+    match unsafe {libc::pthread_setschedparam(libc::pthread_self(), policy, &param)} {
+        0 => Ok(()),
+        libc::EPERM => Err("Real-time scheduling requires root or CAP_SYS_NICE".to_string()),
+        code => Err(format!("Setting the scheduling policy failed with error {}", code))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn apply_sched_policy(sched_policy: SchedPolicy, _rt_priority: i32) -> Result<(), String> {
+    match sched_policy {
+        SchedPolicy::Other => Ok(()),
+        _ => Err("Real-time scheduling policies are only supported on Linux".to_string())
+    }
+}
+
+
+// Prioritizing workers
+
+pub const NICE_MIN: i32 = -20;
+pub const NICE_MAX: i32 = 19;
+
+/// Linux keeps a nice value per thread, other Unix systems per process.
+#[cfg(unix)]
+pub fn apply_nice(nice: i32) -> Result<(), String> {
+
+    if nice == 0 {
+        return Ok(());
+    }
+
+    #[cfg(target_os = "linux")]
+    let who = unsafe {libc::gettid()} as libc::id_t;
+    #[cfg(not(target_os = "linux"))]
+    let who = 0;
+
+    match unsafe {libc::setpriority(libc::PRIO_PROCESS, who, nice)} {
+        0 => Ok(()),
+        _ if nice < 0 => Err("Negative nice values require root or CAP_SYS_NICE".to_string()),
+        _ => Err(format!("Setting the nice value failed: {}", std::io::Error::last_os_error()))
+    }
+}
+
+/// Windows has no nice values, so they are mapped to the closest thread priorities.
+#[cfg(windows)]
+pub fn apply_nice(nice: i32) -> Result<(), String> {
+    use windows_sys::Win32::System::Threading::*;
+
+    let priority = match nice {
+        0 => return Ok(()),
+        NICE_MIN..=-15 => THREAD_PRIORITY_HIGHEST,
+        -14..=-1 => THREAD_PRIORITY_ABOVE_NORMAL,
+        1..=14 => THREAD_PRIORITY_BELOW_NORMAL,
+        _ => THREAD_PRIORITY_LOWEST
+    };
+
+    match unsafe {SetThreadPriority(GetCurrentThread(), priority)} {
+        0 => Err("Setting the thread priority failed".to_string()),
+        _ => Ok(())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn apply_nice(nice: i32) -> Result<(), String> {
+    match nice {
+        0 => Ok(()),
+        _ => Err("Nice values are only supported on Unix and Windows".to_string())
+    }
+}
+
+
+// Pinning workers to CPUs
+
+/// A logical CPU with the core, last-level cache, and package it belongs to,
+/// by ids unique within the package.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LogicalCpu {
+    pub idx: usize,
+    pub core: usize,
+    pub cache: usize,
+    pub package: usize
+}
+
+#[cfg(target_os = "linux")]
+fn read_topology_id(cpu_idx: usize, file: &str) -> Option<usize> {
+    std::fs::read_to_string(format!("/sys/devices/system/cpu/cpu{}/{}", cpu_idx, file)).ok()?
+        .trim()
+        .parse::<usize>()
+        .ok()
+}
+
+/// CPUs the process may run on, as sysfs describes them.
+#[cfg(target_os = "linux")]
+pub fn read_cpu_topology() -> Vec<LogicalCpu> {
+
+    let mut set: libc::cpu_set_t = unsafe {std::mem::zeroed()};
+    if unsafe {libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set)} != 0 {
+        return Vec::new();
+    }
+
+    (0..libc::CPU_SETSIZE as usize)
+        .filter(|idx| unsafe {libc::CPU_ISSET(*idx, &set)})
+        .map(|idx| {
+            let package = read_topology_id(idx, "topology/physical_package_id").unwrap_or(0);
+            LogicalCpu {
+                idx,
+                core: read_topology_id(idx, "topology/core_id").unwrap_or(idx),
+                cache: read_topology_id(idx, "cache/index3/id").unwrap_or(package),
+                package
+            }
+        })
+        .collect()
+}
+
+/// Without a topology to read, every logical CPU counts as a core of its own.
+#[cfg(not(target_os = "linux"))]
+pub fn read_cpu_topology() -> Vec<LogicalCpu> {
+    (0..num_cpus::get()).map(|idx| LogicalCpu{idx, core: idx, cache: 0, package: 0}).collect()
+}
+
+pub fn pin_thread(cpu_idx: usize) -> Result<(), String> {
+    restrict_thread(&[cpu_idx])
+}
+
+/// Lets the calling thread run on any of the CPUs, and only on them.
+#[cfg(target_os = "linux")]
+pub fn restrict_thread(cpu_idxs: &[usize]) -> Result<(), String> {
+
+    let mut set: libc::cpu_set_t = unsafe {std::mem::zeroed()};
+    for cpu_idx in cpu_idxs {
+        unsafe {libc::CPU_SET(*cpu_idx, &mut set)};
+    }
+
+    match unsafe {libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set)} {
+        0 => Ok(()),
+        _ => Err(format!("Pinning to CPUs {:?} failed: {}", cpu_idxs, std::io::Error::last_os_error()))
+    }
+}
+
+/// Affinity masks only reach the first 64 CPUs, the processor group of the thread.
+#[cfg(windows)]
+pub fn restrict_thread(cpu_idxs: &[usize]) -> Result<(), String> {
+    use windows_sys::Win32::System::Threading::*;
+
+    if let Some(cpu_idx) = cpu_idxs.iter().find(|cpu_idx| **cpu_idx >= usize::BITS as usize) {
+        return Err(format!("Pinning to CPU {} needs processor groups", cpu_idx));
+    }
+
+    let mask = cpu_idxs.iter().fold(0usize, |mask, cpu_idx| mask | 1 << cpu_idx);
+    match unsafe {SetThreadAffinityMask(GetCurrentThread(), mask)} {
+        0 => Err(format!("Pinning to CPUs {:?} failed", cpu_idxs)),
+        _ => Ok(())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn restrict_thread(_cpu_idxs: &[usize]) -> Result<(), String> {
+    Err("Pinning threads is only supported on Linux and Windows".to_string())
+}
+
+
+// Identifying worker threads
+
+/// Profilers and debuggers list threads by the ids of the operating system, not those of Rust.
+#[cfg(target_os = "linux")]
+pub fn get_thread_id() -> u64 {
+    unsafe {libc::gettid() as u64}
+}
+
+#[cfg(target_os = "macos")]
+pub fn get_thread_id() -> u64 {
+    let mut thread_id = 0u64;
+    unsafe {libc::pthread_threadid_np(0, &mut thread_id);}
+    thread_id
+}
+
+#[cfg(windows)]
+pub fn get_thread_id() -> u64 {
+    unsafe {windows_sys::Win32::System::Threading::GetCurrentThreadId() as u64}
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub fn get_thread_id() -> u64 {
+    0
+}
+
+pub fn get_thread_name() -> String {
+    thread::current().name().unwrap_or("").to_string()
+}
+
+
+// Entering the kernel
+
+/// A raw system call, so no C library answers it from a cache and every call enters the kernel.
+#[cfg(target_os = "linux")]
+pub fn enter_kernel() -> u64 {
+    unsafe {libc::syscall(libc::SYS_getppid) as u64}
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn enter_kernel() -> u64 {
+    unsafe {libc::getppid() as u64}
+}
+
+/// Yielding enters the kernel even when no other thread is ready to run.
+#[cfg(windows)]
+pub fn enter_kernel() -> u64 {
+    unsafe {windows_sys::Win32::System::Threading::SwitchToThread() as u64}
+}
+
+#[cfg(not(any(unix, windows)))]
+pub fn enter_kernel() -> u64 {
+    0
+}
+
+
+// Faulting pages in
+
+const PAGE_SIZE_DEFAULT: usize = 4096;
+
+#[cfg(unix)]
+fn get_page_size() -> usize {
+    match unsafe {libc::sysconf(libc::_SC_PAGESIZE)} {
+        page_size if page_size > 0 => page_size as usize,
+        _ => PAGE_SIZE_DEFAULT
+    }
+}
+
+#[cfg(not(unix))]
+fn get_page_size() -> usize {
+    PAGE_SIZE_DEFAULT
+}
+
+/// Writes a byte to every page, so each of them faults in a zeroed page from the kernel.
+fn touch_pages(memory: *mut u8, n_bytes: usize) -> u64 {
+    let mut sum = 0u64;
+    for offset in (0..n_bytes).step_by(get_page_size()) {
+        unsafe {
+            memory.add(offset).write_volatile(1);
+            sum += memory.add(offset).read_volatile() as u64;
+        }
+    }
+    sum
+}
+
+/// Maps fresh anonymous memory, touches its pages, and unmaps it, telling the pages touched.
+#[cfg(unix)]
+pub fn map_and_touch(n_bytes: usize) -> u64 {
+
+    let memory = unsafe {
+        libc::mmap(std::ptr::null_mut(), n_bytes, libc::PROT_READ | libc::PROT_WRITE,
+                   libc::MAP_PRIVATE | libc::MAP_ANONYMOUS, -1, 0)
+    };
+    if memory == libc::MAP_FAILED {
+        panic!("Mapping {} bytes failed", n_bytes);
+    }
+
+    let sum = touch_pages(memory as *mut u8, n_bytes);
+    unsafe {libc::munmap(memory, n_bytes);}
+
+    sum
+}
+
+#[cfg(windows)]
+pub fn map_and_touch(n_bytes: usize) -> u64 {
+    use windows_sys::Win32::System::Memory::{VirtualAlloc, VirtualFree, MEM_COMMIT, MEM_RELEASE,
+                                            MEM_RESERVE, PAGE_READWRITE};
+
+    let memory = unsafe {VirtualAlloc(std::ptr::null(), n_bytes, MEM_COMMIT | MEM_RESERVE, PAGE_READWRITE)};
+    if memory.is_null() {
+        panic!("Mapping {} bytes failed", n_bytes);
+    }
+
+    let sum = touch_pages(memory as *mut u8, n_bytes);
+    unsafe {VirtualFree(memory, 0, MEM_RELEASE);}
+
+    sum
+}
+
+/// Without virtual memory to map, fresh memory comes from the allocator.
+#[cfg(not(any(unix, windows)))]
+pub fn map_and_touch(n_bytes: usize) -> u64 {
+    let mut memory = vec![0u8; n_bytes];
+    touch_pages(memory.as_mut_ptr(), n_bytes)
+}
+
+
+// Guarding stacks of workers
+
+pub const STACK_SIZE_MIN: usize = 64*1024;
+const STACK_GUARD_SIZE: usize = 16*1024;
+
+thread_local! {
+    static STACK_BASE: Cell<usize> = const {Cell::new(0)};
+}
+
+fn read_stack_pointer() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+/// Overflowing a stack aborts the whole process, so deep workloads check the stack left
+/// and panic instead, which fails only their task.
+pub fn check_stack() {
+
+    let stack_size = get_worker_settings().stack_size;
+    let base = STACK_BASE.with(|base| base.get());
+
+    if stack_size > 0 && base > 0 {
+        let used = base.saturating_sub(read_stack_pointer());
+        if used + STACK_GUARD_SIZE > stack_size {
+            panic!("Stack overflow averted, {} of {} bytes used", used, stack_size);
+        }
+    }
+}
+
+pub fn create_worker_builder(name: String) -> thread::Builder {
+    let builder = thread::Builder::new().name(name);
+    match get_worker_settings().stack_size {
+        0 => builder,
+        stack_size => builder.stack_size(stack_size)
+    }
+}
+
+
+// Preparing worker threads
+
+/// Settings every worker thread applies to itself before running a task.
+#[derive(Copy, Clone)]
+pub struct WorkerSettings {
+    pub qos_class: QosClass,
+    pub sched_policy: SchedPolicy,
+    pub rt_priority: i32,
+    pub nice: i32,
+    pub stack_size: usize
+}
+
+const DEFAULT_WORKER_SETTINGS: WorkerSettings = WorkerSettings {
+    qos_class: QosClass::Unspecified,
+    sched_policy: SchedPolicy::Other,
+    rt_priority: 0,
+    nice: 0,
+    stack_size: 0
+};
+
+static WORKER_SETTINGS: OnceLock<WorkerSettings> = OnceLock::new();
+
+pub fn set_worker_settings(settings: WorkerSettings) {
+    let _ = WORKER_SETTINGS.set(settings);
+}
+
+pub fn get_worker_settings() -> WorkerSettings {
+    *WORKER_SETTINGS.get().unwrap_or(&DEFAULT_WORKER_SETTINGS)
+}
+
+pub fn prepare_worker_thread() -> Result<(), String> {
+    STACK_BASE.with(|base| base.set(read_stack_pointer()));
+    let settings = get_worker_settings();
+    apply_qos_class(settings.qos_class)?;
+    apply_sched_policy(settings.sched_policy, settings.rt_priority)?;
+    apply_nice(settings.nice)
+}
+
+/// Workers go on with failed settings, so failures are found out by a trial thread beforehand.
+pub fn try_worker_settings() -> Result<(), String> {
+    create_worker_builder("conctest-trial".to_string()).spawn(prepare_worker_thread)
+        .map_err(|error| format!("Spawning a trial worker failed: {}", error))?
+        .join()
+        .unwrap_or(Err("Preparing a trial worker panicked".to_string()))
+}
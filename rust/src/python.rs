@@ -0,0 +1,144 @@
+// * * ** *** ***** ******** ************* *********************
+// Driving the measurement engine from Python
+// * * ** *** ***** ******** ************* *********************
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use super::embed::{self, ObservationRecord, ReportRecords, RunConfig, TaskRecord};
+
+
+// Configurations
+
+/// `concrust.RunConfig(tasks=16, cycles=10_000_000, series_size=8)`, the rest defaults as in the CLI.
+#[pyclass(name = "RunConfig")]
+#[derive(Clone)]
+struct PyRunConfig {
+    config: RunConfig
+}
+
+#[pymethods]
+impl PyRunConfig {
+
+    #[new]
+    #[pyo3(signature = (tasks, cycles, series_size=1, backend="threads", repeats=1, retries=0, seed=0))]
+    fn new(tasks: usize, cycles: usize, series_size: usize, backend: &str, repeats: usize,
+           retries: usize, seed: u64) -> PyResult<PyRunConfig> {
+
+        let config = RunConfig::new()
+            .tasks(1..=tasks)
+            .cycles(cycles)
+            .series_size(series_size)
+            .backend(backend)
+            .repeats(repeats)
+            .retries(retries)
+            .seed(seed);
+
+        if !config.is_valid() {
+            return Err(PyValueError::new_err("The run configuration is invalid"));
+        }
+
+        Ok(PyRunConfig{config})
+    }
+}
+
+
+// Reports
+
+fn create_observation_dict<'py>(py: Python<'py>, record: &ObservationRecord) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("n_tasks", record.n_tasks)?;
+    dict.set_item("mean_task_duration_ms", record.mean_task_duration_ms)?;
+    dict.set_item("std_dev_ms", record.std_dev_ms)?;
+    dict.set_item("cv", record.cv)?;
+    dict.set_item("total_duration_ms", record.total_duration_ms)?;
+    dict.set_item("parallelism", record.parallelism)?;
+    dict.set_item("max_running", record.max_running)?;
+    dict.set_item("start_spread_ms", record.start_spread_ms)?;
+    dict.set_item("tasks_per_sec", record.tasks_per_sec)?;
+    dict.set_item("cycles_per_sec", record.cycles_per_sec)?;
+    dict.set_item("speedup", record.speedup)?;
+    dict.set_item("cost", record.cost)?;
+    dict.set_item("profit", record.profit)?;
+    dict.set_item("profit_ci_low", record.profit_ci_low)?;
+    dict.set_item("profit_ci_high", record.profit_ci_high)?;
+    dict.set_item("timed_out", record.timed_out)?;
+    dict.set_item("failed", record.failed)?;
+    dict.set_item("retries", record.retries)?;
+    dict.set_item("repeat_durations_ms", &record.repeat_durations_ms)?;
+    Ok(dict)
+}
+
+fn create_task_dict<'py>(py: Python<'py>, record: &TaskRecord) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("n_tasks", record.n_tasks)?;
+    dict.set_item("task", record.task)?;
+    dict.set_item("series", record.series)?;
+    dict.set_item("started_ms", record.started_ms)?;
+    dict.set_item("finished_ms", record.finished_ms)?;
+    dict.set_item("duration_ms", record.duration_ms)?;
+    dict.set_item("status", &record.status)?;
+    dict.set_item("thread", &record.thread)?;
+    dict.set_item("seed", record.seed)?;
+    Ok(dict)
+}
+
+/// Rows come as lists of dicts, so `pandas.DataFrame(report.observations)` just works.
+#[pyclass(name = "Report")]
+struct PyReport {
+    records: ReportRecords
+}
+
+// PyO3 only takes methods on a plain `&self`
+#[pymethods]
+impl PyReport {
+
+    #[getter]
+    fn metadata<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for (key, value) in &self.records.metadata {
+            dict.set_item(key, value)?;
+        }
+        Ok(dict)
+    }
+
+    #[getter]
+    fn observations<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for record in &self.records.observations {
+            list.append(create_observation_dict(py, record)?)?;
+        }
+        Ok(list)
+    }
+
+    /// Schedules of all observations, told apart by the number of tasks.
+    #[getter]
+    fn tasks<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let list = PyList::empty(py);
+        for record in &self.records.tasks {
+            list.append(create_task_dict(py, record)?)?;
+        }
+        Ok(list)
+    }
+}
+
+
+// Module
+
+/// Measuring takes long, so other Python threads run meanwhile.
+#[pyfunction]
+fn measure(py: Python<'_>, config: &PyRunConfig) -> PyResult<PyReport> {
+    let config = config.config.clone();
+    py.allow_threads(move || embed::measure(&config))
+        .map(|records| PyReport{records})
+        .map_err(PyValueError::new_err)
+}
+
+#[pymodule]
+fn concrust(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyRunConfig>()?;
+    module.add_class::<PyReport>()?;
+    module.add_function(wrap_pyfunction!(measure, module)?)?;
+    Ok(())
+}
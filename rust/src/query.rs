@@ -0,0 +1,173 @@
+// * * ** *** ***** ******** ************* *********************
+// Selecting rows of saved reports
+// * * ** *** ***** ******** ************* *********************
+
+use std::cmp::Ordering;
+
+use super::*;
+
+
+// Sorting
+
+pub const SORT_KEYS: &[&str] = &["tasks", "duration", "cost", "profit", "parallelism", "throughput"];
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum SortKey {
+    Tasks,
+    Duration,
+    Cost,
+    Profit,
+    Parallelism,
+    Throughput
+}
+
+impl SortKey {
+
+    pub fn parse(s: &str) -> Option<SortKey> {
+        match s {
+            "tasks" => Some(SortKey::Tasks),
+            "duration" => Some(SortKey::Duration),
+            "cost" => Some(SortKey::Cost),
+            "profit" => Some(SortKey::Profit),
+            "parallelism" => Some(SortKey::Parallelism),
+            "throughput" => Some(SortKey::Throughput),
+            _ => None
+        }
+    }
+
+    /// The column of totals sections holding the value.
+    fn get_column(self: &Self) -> &'static str {
+        match self {
+            SortKey::Tasks => "Tasks",
+            SortKey::Duration => "Total duration",
+            SortKey::Cost => "Cost",
+            SortKey::Profit => "Profit",
+            SortKey::Parallelism => "Parallelism",
+            SortKey::Throughput => "Tasks/sec"
+        }
+    }
+
+    /// Rows go best first: the shortest and cheapest, or the most profitable and parallel.
+    fn is_ascending(self: &Self) -> bool {
+        matches!(self, SortKey::Tasks | SortKey::Duration | SortKey::Cost)
+    }
+
+    fn get_value(self: &Self, obs: &Observation) -> f64 {
+        match self {
+            SortKey::Tasks => obs.count_tasks() as f64,
+            SortKey::Duration => obs.get_total_duration().as_millis_f64(),
+            SortKey::Cost => obs.get_concurrency_cost(),
+            SortKey::Profit => obs.get_concurrency_profit(),
+            SortKey::Parallelism => obs.get_parallelism(),
+            SortKey::Throughput => obs.get_tasks_per_sec()
+        }
+    }
+
+    /// Values missing, as of skipped observations, go last whichever the order.
+    fn compare_values(self: &Self, a: Option<f64>, b: Option<f64>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) if self.is_ascending() => a.total_cmp(&b),
+            (Some(a), Some(b)) => b.total_cmp(&a),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal
+        }
+    }
+}
+
+
+// Queries
+
+/// Which numbers of tasks to keep, whether to keep anything but totals, and how to order rows.
+#[derive(Clone)]
+pub struct ReportQuery {
+    pub tasks: Vec<usize>,
+    pub only_totals: bool,
+    pub sort_key: Option<SortKey>
+}
+
+impl ReportQuery {
+
+    pub fn create(tasks: Vec<usize>, only_totals: bool, sort_key: Option<SortKey>) -> ReportQuery {
+        ReportQuery{tasks, only_totals, sort_key}
+    }
+
+    pub fn is_selected(self: &Self, n_tasks: usize) -> bool {
+        self.tasks.is_empty() || self.tasks.contains(&n_tasks)
+    }
+
+    pub fn select(self: &Self, mut report: Report) -> Report {
+        report.observations.retain(|obs| self.is_selected(obs.count_tasks()));
+        report.skipped.retain(|n_tasks| self.is_selected(*n_tasks));
+        report
+    }
+
+    /// Rows come as split sections, the header first.
+    pub fn sort_rows(self: &Self, rows: &mut [Vec<String>]) {
+
+        let key = match self.sort_key {
+            Some(key) => key,
+            None => return
+        };
+
+        let column_idx = match rows.first().and_then(|header| header.iter().position(|title| title == key.get_column())) {
+            Some(column_idx) => column_idx,
+            None => return
+        };
+
+        let value = |row: &Vec<String>| row.get(column_idx).and_then(|cell| cell.parse::<f64>().ok());
+        rows[1..].sort_by(|a, b| key.compare_values(value(a), value(b)));
+    }
+
+    pub fn compare_observations(self: &Self, a: &Observation, b: &Observation) -> Ordering {
+        match self.sort_key {
+            Some(key) => key.compare_values(Some(key.get_value(a)), Some(key.get_value(b))),
+            None => Ordering::Equal
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::FakeWorkload;
+
+    fn split_rows(text: &str) -> Vec<Vec<String>> {
+        text.lines().map(split_csv_line).collect()
+    }
+
+    #[test]
+    fn only_selected_tasks_are_kept() {
+        let query = ReportQuery::create(vec![2, 4], false, None);
+        let mut report = FakeWorkload::create(2, 4, 100).create_report(1..=4);
+        report.skipped = vec![4, 5];
+        let report = query.select(report);
+        assert_eq!(report.observations.iter().map(|obs| obs.count_tasks()).collect::<Vec<_>>(), vec![2, 4]);
+        assert_eq!(report.skipped, vec![4]);
+    }
+
+    #[test]
+    fn rows_go_best_first() {
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=4);
+        let mut rows = split_rows(&format_observation_totals_section(&report));
+        ReportQuery::create(Vec::new(), false, SortKey::parse("profit")).sort_rows(&mut rows);
+        let order: Vec<&str> = rows[1..].iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(order, vec!["4", "2", "3", "1"]);
+    }
+
+    #[test]
+    fn skipped_rows_go_last() {
+        let mut rows = split_rows("Tasks,Total duration\n1,100\n2,\n3,50\n");
+        ReportQuery::create(Vec::new(), false, Some(SortKey::Duration)).sort_rows(&mut rows);
+        let order: Vec<&str> = rows[1..].iter().map(|row| row[0].as_str()).collect();
+        assert_eq!(order, vec!["3", "1", "2"]);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(SortKey::parse("speed").is_none());
+        assert!(SORT_KEYS.iter().all(|key| SortKey::parse(key).is_some()));
+    }
+}
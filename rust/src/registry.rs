@@ -0,0 +1,296 @@
+// * * ** *** ***** ******** ************* *********************
+// Registering workloads, backends, kernels, and formats
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+
+
+// Describing available choices
+
+pub struct RegistryEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub knobs: &'static [&'static str]
+}
+
+pub const WORKLOADS: &[RegistryEntry] = &[
+    RegistryEntry {
+        name: "standard",
+        description: "Iterating the triplet kernel from a random seed",
+        knobs: &["<Cycles in a task>", OPT_SEED, OPT_TASK_TIMEOUT, OPT_DAG]
+    },
+    RegistryEntry {
+        name: "branchy",
+        description: "Stepping a state machine on random integers",
+        knobs: &["<Cycles in a task>", OPT_WORKLOAD, OPT_SEED, OPT_TASK_TIMEOUT]
+    },
+    RegistryEntry {
+        name: "syscalls",
+        description: "System calls entering the kernel for nothing",
+        knobs: &["<Cycles in a task>", OPT_WORKLOAD, OPT_SYSCALLS, OPT_TASK_TIMEOUT]
+    },
+    RegistryEntry {
+        name: "pagefaults",
+        description: "Mapping and touching fresh anonymous memory",
+        knobs: &["<Cycles in a task>", OPT_WORKLOAD, OPT_MAP_SIZE, OPT_TASK_TIMEOUT]
+    },
+    RegistryEntry {
+        name: "compress",
+        description: "Deflating a buffer of random or repetitive data",
+        knobs: &["<Cycles in a task>", OPT_WORKLOAD, OPT_BUFFER_SIZE, OPT_BUFFER_DATA, OPT_SEED]
+    },
+    RegistryEntry {
+        name: "json",
+        description: "Parsing a bundled page of orders with serde_json",
+        knobs: &["<Cycles in a task>", OPT_WORKLOAD, OPT_TASK_TIMEOUT]
+    },
+    RegistryEntry {
+        name: "regex",
+        description: "Matching six regexes over 16 KiB of access log",
+        knobs: &["<Cycles in a task>", OPT_WORKLOAD, OPT_SEED]
+    },
+    RegistryEntry {
+        name: "stencil",
+        description: "Blurring an image with a 3x3 stencil",
+        knobs: &["<Cycles in a task>", OPT_WORKLOAD, OPT_IMAGE_SIZE, OPT_SEED]
+    },
+    RegistryEntry {
+        name: "gpu",
+        description: "The standard one, then offloaded in f32 to wgpu",
+        knobs: &["<Cycles in a task>", OPT_WORKLOAD, OPT_TRIPLETS, OPT_SEED]
+    },
+    RegistryEntry {
+        name: "rwlock",
+        description: "Reading and writing a table behind an RwLock",
+        knobs: &["<Cycles in a task>", OPT_WORKLOAD, OPT_WRITE_RATIO, OPT_SEED]
+    }
+];
+
+pub const BACKENDS: &[RegistryEntry] = &[
+    RegistryEntry {
+        name: "threads",
+        description: "Scoped OS threads spawned series by series",
+        knobs: &["<Tasks in a series>", OPT_OBSERVATION_TIMEOUT, OPT_RETRIES]
+    },
+    RegistryEntry {
+        name: "processes",
+        description: "Worker processes, each spawning threads for its share of tasks",
+        knobs: &["<Tasks in a series>", OPT_PROCESSES, OPT_RETRIES]
+    },
+    RegistryEntry {
+        name: "simulated",
+        description: "Virtual time from a model of an ideal machine",
+        knobs: &["<Tasks in a series>", OPT_SEED, OPT_TASK_TIMEOUT]
+    }
+];
+
+pub const KERNELS: &[RegistryEntry] = &[
+    RegistryEntry {
+        name: "triplet",
+        description: "Mixing three floats until they converge",
+        knobs: &["<Cycles in a task>"]
+    },
+    RegistryEntry {
+        name: "state",
+        description: "Branching on each of random integers, mispredicted about half the time",
+        knobs: &["<Cycles in a task>"]
+    }
+];
+
+pub const FORMATS: &[RegistryEntry] = &[
+    RegistryEntry {
+        name: "csv",
+        description: "Report of metadata, totals, and schedules",
+        knobs: &["[Output file]", OPT_RESUME]
+    },
+    RegistryEntry {
+        name: "json",
+        description: "Records of a report saved by bindings, rendered like reports",
+        knobs: &["render <Report file>"]
+    },
+    RegistryEntry {
+        name: "merged",
+        description: "Labelled runs of several reports, rendered and compared together",
+        knobs: &["merge", OPT_OUTPUT]
+    },
+    RegistryEntry {
+        name: "md",
+        description: "Metadata and totals as Markdown tables",
+        knobs: &[OPT_FORMAT]
+    },
+    RegistryEntry {
+        name: "html",
+        description: "A page of tables, the speedup chart, and a schedule",
+        knobs: &[OPT_FORMAT]
+    },
+    RegistryEntry {
+        name: "svg",
+        description: "Speedup chart with numbers on hover",
+        knobs: &[OPT_FORMAT]
+    },
+    RegistryEntry {
+        name: "speedscope",
+        description: "Schedules as evented profiles, a lane of tasks each, for speedscope.app",
+        knobs: &[OPT_FORMAT]
+    },
+    RegistryEntry {
+        name: "folded",
+        description: "Schedules as collapsed stacks for flamegraph.pl and inferno",
+        knobs: &[OPT_FORMAT]
+    },
+    RegistryEntry {
+        name: "template",
+        description: "Text or HTML of a Tera template given the records of runs",
+        knobs: &[OPT_TEMPLATE]
+    },
+    RegistryEntry {
+        name: "png",
+        description: "Speedup and duration charts rendered by gnuplot",
+        knobs: &[OPT_CHART]
+    },
+    RegistryEntry {
+        name: "history",
+        description: "Summaries of runs appended to a CSV file",
+        knobs: &[OPT_HISTORY]
+    },
+    RegistryEntry {
+        name: "json-log",
+        description: "Messages and events as JSON lines",
+        knobs: &[OPT_LOG_JSON]
+    }
+];
+
+pub const METRICS: &[RegistryEntry] = &[
+    RegistryEntry {
+        name: "mean",
+        description: "Mean duration of a task",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "median",
+        description: "Median duration of a task",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "p99",
+        description: "Duration 99% of tasks finish within",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "std-dev",
+        description: "Standard deviation of task durations",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "cv",
+        description: "Standard deviation relative to the mean",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "total",
+        description: "Duration of the observation",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "bound",
+        description: "Estimated shortest duration of a schedule on the CPUs",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "quality",
+        description: "The estimated bound over the duration achieved, 100% at most",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "parallelism",
+        description: "Tasks running at the same time on average",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "running",
+        description: "Tasks running at the same time at most",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "cpu",
+        description: "CPU utilization while observing",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "spread",
+        description: "Delay of the latest task starting in a series",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "throughput",
+        description: "Tasks finished per second",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "cycles",
+        description: "Cycles of tasks per second",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "cost",
+        description: "Share of thread time lost to concurrency",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "speedup",
+        description: "Serial time over the duration",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "efficiency",
+        description: "Speedup per CPU the tasks could occupy",
+        knobs: &[OPT_METRICS]
+    },
+    RegistryEntry {
+        name: "profit",
+        description: "Share of wall-clock time saved by concurrency",
+        knobs: &[OPT_METRICS]
+    }
+];
+
+pub const REGISTRIES: &[(&str, &[RegistryEntry])] = &[
+    ("workloads", WORKLOADS),
+    ("backends", BACKENDS),
+    ("kernels", KERNELS),
+    ("formats", FORMATS),
+    ("metrics", METRICS)
+];
+
+
+// Listing choices in a console
+
+fn create_registry_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Name", 10, Align::Left, 0),
+        Column::create("Description", 48, Align::Left, 0),
+        Column::create("Knobs", 44, Align::Left, 1)
+    ], colored)
+}
+
+pub fn print_registry(kind: &String, colored: bool) -> bool {
+
+    let entries = match REGISTRIES.iter().find(|(name, _)| name == kind) {
+        Some((_, entries)) => entries,
+        None => return false
+    };
+
+    let table = create_registry_table(colored);
+    table.print_header();
+
+    for entry in entries.iter() {
+        table.print_row(&vec![
+            Cell::Plain(entry.name.to_string()),
+            Cell::Plain(entry.description.to_string()),
+            Cell::Plain(entry.knobs.join(", "))
+        ]);
+    }
+
+    table.print_footer();
+
+    true
+}
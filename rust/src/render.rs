@@ -0,0 +1,346 @@
+// * * ** *** ***** ******** ************* *********************
+// Rendering saved reports in other formats
+// * * ** *** ***** ******** ************* *********************
+
+use embed::ReportRecords;
+
+use super::*;
+
+
+// Choosing a format
+
+pub const RENDER_FORMATS: &[&str] = &["html", "csv", "md", "svg", "speedscope", "folded"];
+
+/// Speedscope files are JSON, named as speedscope.app suggests.
+const SPEEDSCOPE_SUFFIX: &str = ".speedscope.json";
+
+/// Without a format given, the extension of the output file tells it, and the console gets Markdown.
+pub fn choose_format(format: &str, out_file_path: &str) -> String {
+    if !format.is_empty() {
+        return format.to_string();
+    }
+    if out_file_path.ends_with(SPEEDSCOPE_SUFFIX) {
+        return "speedscope".to_string();
+    }
+    match Path::new(out_file_path).extension().and_then(|extension| extension.to_str()) {
+        Some(extension) if RENDER_FORMATS.contains(&extension) => extension.to_string(),
+        _ => "md".to_string()
+    }
+}
+
+
+// Tables
+
+/// Sections of the CSV report are what tables show, so every format has the same columns.
+fn split_section(section_text: &str) -> Vec<Vec<String>> {
+    section_text.lines().map(split_csv_line).collect()
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+fn format_markdown_table(rows: &[Vec<String>]) -> String {
+
+    let mut table_text: String = "".to_string();
+
+    for (idx, row) in rows.iter().enumerate() {
+        let cells: Vec<String> = row.iter().map(|cell| escape_markdown(cell)).collect();
+        table_text += &format!("| {} |\n", cells.join(" | "));
+        if idx == 0 {
+            table_text += &format!("|{}\n", "---|".repeat(row.len()));
+        }
+    }
+
+    table_text
+}
+
+fn format_html_table(rows: &[Vec<String>]) -> String {
+
+    let mut table_text: String = "<table>\n".to_string();
+
+    for (idx, row) in rows.iter().enumerate() {
+        let tag = if idx == 0 {"th"} else {"td"};
+        let cells: Vec<String> = row.iter()
+            .map(|cell| format!("<{}>{}</{}>", tag, svg::escape_xml(cell), tag))
+            .collect();
+        table_text += &format!("<tr>{}</tr>\n", cells.join(""));
+    }
+
+    table_text + "</table>\n"
+}
+
+
+// Documents
+
+/// Totals as rows, in the order the query asks.
+fn query_totals(section_text: &str, query: &ReportQuery) -> Vec<Vec<String>> {
+    let mut rows = split_section(section_text);
+    query.sort_rows(&mut rows);
+    rows
+}
+
+fn join_rows(rows: &[Vec<String>]) -> String {
+    rows.iter()
+        .map(|row| row.iter().map(|cell| quote_csv(cell)).collect::<Vec<String>>().join(",") + "\n")
+        .collect()
+}
+
+/// The full report keeps reading back as a report, its totals sorted.
+fn format_csv_report(report: &Report, query: &ReportQuery) -> String {
+
+    let totals = join_rows(&query_totals(&format_observation_totals_section(report), query));
+    if query.only_totals {
+        return totals;
+    }
+
+    format_report(report).split("\n\n")
+        .map(|section| match section.lines().next() {
+            Some(header) if is_observation_totals_header(header) => totals.trim_end(),
+            _ => section
+        })
+        .collect::<Vec<&str>>()
+        .join("\n\n")
+}
+
+fn format_markdown_report(report: &Report, query: &ReportQuery) -> String {
+
+    let parameters = if query.only_totals {"".to_string()} else {
+        format!("## Parameters\n\n{}\n", 
+                format_markdown_table(&split_section(&format_metadata_section(report))))
+    };
+
+    format!("# Concurrency profit\n\n{}## Observations\n\n{}", parameters,
+            format_markdown_table(&query_totals(&format_observation_totals_section(report), query)))
+}
+
+fn format_html_page(body: &str) -> String {
+    format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Concurrency profit</title>\n\
+             <style>table {{border-collapse: collapse}} th, td {{border: 1px solid #ccc; \
+             padding: 2px 6px; text-align: right}}</style>\n\
+             </head>\n<body>\n<h1>Concurrency profit</h1>\n{}</body>\n</html>\n", body)
+}
+
+/// A page standing alone, the chart and the schedule of the most tasks drawn inline.
+fn format_html_report(report: &Report, query: &ReportQuery) -> String {
+
+    let totals = format!("<h2>Observations</h2>\n{}", 
+                         format_html_table(&query_totals(&format_observation_totals_section(report), query)));
+    if query.only_totals {
+        return format_html_page(&totals);
+    }
+
+    let records = ReportRecords::create(report);
+    let n_tasks_max = records.observations.iter().map(|obs| obs.n_tasks).max().unwrap_or(0);
+
+    format_html_page(&format!("<h2>Parameters</h2>\n{}{}<h2>Speedup</h2>\n{}{}",
+                              format_html_table(&split_section(&format_metadata_section(report))),
+                              totals,
+                              svg::render_speedup_chart(&records),
+                              svg::render_schedule(&records, n_tasks_max)
+                                  .map_or("".to_string(), |schedule| format!("<h2>Schedule</h2>\n{}", schedule))))
+}
+
+/// Totals of all runs in one section, told apart by the leading column, of the metrics
+/// the first run was saved with.
+fn format_runs_totals_section(runs: &[(String, Report)]) -> String {
+
+    let metrics = runs.first().map_or(Vec::new(), |(_, report)| metrics::get_report_metrics(report));
+    let mut section_text: String = "Run,".to_string() + &format_observation_totals_section_header(&metrics);
+
+    for (label, report) in runs {
+        for line in format_observation_totals_section_data(report, &metrics).lines() {
+            section_text += &format!("{},{}\n", quote_csv(label), line);
+        }
+    }
+
+    section_text
+}
+
+fn format_markdown_runs(runs: &[(String, Report)], query: &ReportQuery) -> String {
+
+    let mut text = format!("# Concurrency profit\n\n## Observations\n\n{}",
+                           format_markdown_table(&query_totals(&format_runs_totals_section(runs), query)));
+
+    if !query.only_totals {
+        for (label, report) in runs {
+            text += &format!("\n## Parameters of {}\n\n{}", escape_markdown(label),
+                             format_markdown_table(&split_section(&format_metadata_section(report))));
+        }
+    }
+
+    text
+}
+
+fn render_runs_chart(runs: &[(String, Report)]) -> String {
+    let records: Vec<ReportRecords> = runs.iter().map(|(_, report)| ReportRecords::create(report)).collect();
+    let chart_runs: Vec<(&str, &ReportRecords)> = runs.iter()
+        .zip(&records)
+        .map(|((label, _), records)| (label.as_str(), records))
+        .collect();
+    svg::render_speedup_comparison(&chart_runs)
+}
+
+fn format_html_runs(runs: &[(String, Report)], query: &ReportQuery) -> String {
+
+    let totals = format!("<h2>Observations</h2>\n{}",
+                         format_html_table(&query_totals(&format_runs_totals_section(runs), query)));
+    if query.only_totals {
+        return format_html_page(&totals);
+    }
+
+    let mut parameters: String = "".to_string();
+    for (label, report) in runs {
+        parameters += &format!("<h2>Parameters of {}</h2>\n{}", svg::escape_xml(label),
+                               format_html_table(&split_section(&format_metadata_section(report))));
+    }
+
+    format_html_page(&format!("<h2>Speedup</h2>\n{}{}{}", render_runs_chart(runs), totals, parameters))
+}
+
+fn label_runs(runs: &[(String, Report)]) -> Vec<(&str, &Report)> {
+    runs.iter().map(|(label, report)| (label.as_str(), report)).collect()
+}
+
+/// Runs merged from several reports share tables and a chart, a single run renders as its report.
+pub fn format_rendered_runs(runs: &[(String, Report)], format: &str, query: &ReportQuery) -> Option<String> {
+
+    if let [(_, report)] = runs {
+        return format_rendered_report(report, format, query);
+    }
+
+    match format {
+        "csv" => Some(join_rows(&query_totals(&format_runs_totals_section(runs), query))),
+        "md" => Some(format_markdown_runs(runs, query)),
+        "html" => Some(format_html_runs(runs, query)),
+        "svg" => Some(render_runs_chart(runs)),
+        "speedscope" => Some(speedscope::format_speedscope(&label_runs(runs))),
+        "folded" => Some(speedscope::format_collapsed_stacks(&label_runs(runs))),
+        _ => None
+    }
+}
+
+pub fn format_rendered_report(report: &Report, format: &str, query: &ReportQuery) -> Option<String> {
+    match format {
+        "csv" => Some(format_csv_report(report, query)),
+        "md" => Some(format_markdown_report(report, query)),
+        "html" => Some(format_html_report(report, query)),
+        "svg" => Some(svg::render_speedup_chart(&ReportRecords::create(report))),
+        "speedscope" => Some(speedscope::format_speedscope(&[("", report)])),
+        "folded" => Some(speedscope::format_collapsed_stacks(&[("", report)])),
+        _ => None
+    }
+}
+
+
+// Rendering
+
+/// Measurements are not repeated, so styling a report takes no more than reading it.
+pub fn run_render(args: &Args) -> i32 {
+
+    let query = args.get_report_query();
+    let runs: Vec<(String, Report)> = merge::load_runs(&args.get_report_file_path()).into_iter()
+        .map(|(label, report)| (label, query.select(report)))
+        .collect();
+    let out_file_path = args.get_out_file_path();
+
+    let rendered = if args.get_template_file_path() != "" {
+        match template::render_template(&runs, &args.get_template_file_path()) {
+            Ok(text) => Some(text),
+            Err(message) => {
+                panic!("{}", message);
+            }
+        }
+    } else {
+        format_rendered_runs(&runs, &choose_format(&args.get_render_format(), &out_file_path), &query)
+    };
+
+    match rendered {
+        Some(text) if !out_file_path.is_empty() => {
+            save_text(&out_file_path, &text);
+            EXIT_SUCCESS
+        }
+        Some(text) => {
+            print!("{}", text);
+            EXIT_SUCCESS
+        }
+        None => reject_args(args.get_command())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::{assert_golden, FakeWorkload};
+
+    fn query_all() -> ReportQuery {
+        ReportQuery::create(Vec::new(), false, None)
+    }
+
+    #[test]
+    fn formats_follow_output_files() {
+        assert_eq!(choose_format("", "out/report.html"), "html");
+        assert_eq!(choose_format("csv", "report.html"), "csv");
+        assert_eq!(choose_format("", "report.txt"), "md");
+        assert_eq!(choose_format("", ""), "md");
+        assert_eq!(choose_format("", "report.speedscope.json"), "speedscope");
+        assert_eq!(choose_format("", "report.folded"), "folded");
+    }
+
+    #[test]
+    fn markdown_report_matches_golden_file() {
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=4);
+        assert_golden("report.md", &format_rendered_report(&report, "md", &query_all()).unwrap());
+    }
+
+    #[test]
+    fn every_format_renders() {
+        let report = FakeWorkload::create(2, 2, 100).create_report(1..=2);
+        for format in RENDER_FORMATS {
+            assert!(format_rendered_report(&report, format, &query_all()).is_some(), "{}", format);
+        }
+        assert!(format_rendered_report(&report, "pdf", &query_all()).is_none());
+    }
+
+    #[test]
+    fn merged_runs_share_tables() {
+        let runs = vec![
+            ("linux".to_string(), FakeWorkload::create(2, 2, 100).create_report(1..=2)),
+            ("macos".to_string(), FakeWorkload::create(1, 2, 100).create_report(1..=2))
+        ];
+        let csv = format_rendered_runs(&runs, "csv", &query_all()).unwrap();
+        assert!(csv.starts_with("Run,Tasks,"));
+        assert_eq!(csv.lines().filter(|line| line.starts_with("macos,")).count(), 2);
+        assert!(format_rendered_runs(&runs, "md", &query_all()).unwrap().contains("## Parameters of macos"));
+        assert_eq!(format_rendered_runs(&runs, "svg", &query_all()).unwrap().matches("<polyline").count(), 2);
+        assert_eq!(format_rendered_runs(&runs[..1], "csv", &query_all()), format_rendered_report(&runs[0].1, "csv", &query_all()));
+    }
+
+    #[test]
+    fn csv_reports_keep_reading_back_when_sorted() {
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=4);
+        let query = ReportQuery::create(Vec::new(), false, SortKey::parse("profit"));
+        let text = format_rendered_report(&report, "csv", &query).unwrap();
+        assert_eq!(text.len(), format_report(&report).len());
+        assert!(text.find("\n4,100,").unwrap() < text.find("\n1,100,").unwrap());
+        let totals_only = ReportQuery::create(vec![3], true, None);
+        let text = format_rendered_report(&totals_only.select(report), "csv", &totals_only).unwrap();
+        assert_eq!(text.lines().count(), 2);
+    }
+
+    #[test]
+    fn totals_stand_alone() {
+        let report = FakeWorkload::create(2, 2, 100).create_report(1..=2);
+        let text = format_rendered_report(&report, "md", &ReportQuery::create(Vec::new(), true, None)).unwrap();
+        assert!(!text.contains("Parameters") && text.contains("## Observations"));
+    }
+
+    #[test]
+    fn html_cells_are_escaped() {
+        let rows = vec![vec!["Label".to_string()], vec!["<b>".to_string()]];
+        assert_eq!(format_html_table(&rows), "<table>\n<tr><th>Label</th></tr>\n<tr><td>&lt;b&gt;</td></tr>\n</table>\n");
+    }
+}
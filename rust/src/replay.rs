@@ -0,0 +1,62 @@
+// * * ** *** ***** ******** ************* *********************
+// Replaying a single task by its seed
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+
+
+// Replaying
+
+/// Runs a task alone on the calling thread, from the triplet its seed gives,
+/// so a task standing out in a schedule runs again as it did.
+fn replay_task(n_cycles: usize, task_seed: u64, timeouts: Timeouts) -> Task {
+    standard_task(0, n_cycles, timeouts, read_run_clock(), task_seed)
+}
+
+pub fn run_replay(args: &Args) -> i32 {
+
+    let task_seed = args.get_task_seed().unwrap_or_default();
+    let workload_kind = workload::get_workload_kind();
+    let rng_kind = rng::get_rng_kind();
+
+    info!("Replaying a task of {} cycles of the {} workload seeded with {}",
+          format_count(args.get_n_cycles()), workload_kind.get_name(), task_seed);
+
+    if workload_kind == WorkloadKind::Standard {
+        let triplet = rng::seed_triplet(rng_kind, task_seed);
+        info!("Initial triplet by the {} generator: {}, {}, {}", rng_kind.get_name(), triplet.0, triplet.1, triplet.2);
+        if batch::get_n_triplets() > batch::DEFAULT_TRIPLETS {
+            info!("Working set: {} triplets ({}), the first one given",
+                  format_count(batch::get_n_triplets()), batch::format_working_set(batch::get_n_triplets()));
+        }
+    }
+
+    let task = replay_task(args.get_n_cycles(), task_seed, args.get_timeouts());
+
+    info!("Duration: {} ms", task.get_duration());
+    info!("Status: {}", format_task_status(&task));
+    if let Some(branch_misses) = task.get_branch_misses() {
+        info!("Branch misses: {}", format_count(branch_misses as usize));
+    }
+    if let Some(allocations) = task.get_allocations() {
+        info!("Allocated: {}, freed: {}",
+              memory::format_bytes(allocations.allocated), memory::format_bytes(allocations.freed));
+    }
+
+    EXIT_SUCCESS
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn replayed_tasks_keep_their_seed() {
+        let timeouts = Timeouts::create(TaskDuration::ZERO, TaskDuration::ZERO);
+        let task = replay_task(1000, 42, timeouts);
+        assert_eq!(task.get_seed(), 42);
+        assert!(*task.get_status() == TaskStatus::Completed);
+    }
+}
@@ -0,0 +1,180 @@
+// * * ** *** ***** ******** ************* *********************
+// Choosing random number generators of workloads
+// * * ** *** ***** ******** ************* *********************
+
+use rand::RngCore;
+use rand::rngs::SmallRng;
+
+use super::*;
+
+
+// Generators
+
+pub const RNG_NAMES: &[&str] = &["std", "small", "xoshiro"];
+
+/// StdRng is ChaCha12, cryptographically strong and the slowest to seed, SmallRng is what rand
+/// deems fastest on the platform, and xoshiro256** is the same everywhere.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RngKind {
+    Std,
+    Small,
+    Xoshiro
+}
+
+impl RngKind {
+
+    pub fn parse(s: &str) -> Option<RngKind> {
+        match s {
+            "std" => Some(RngKind::Std),
+            "small" => Some(RngKind::Small),
+            "xoshiro" => Some(RngKind::Xoshiro),
+            _ => None
+        }
+    }
+
+    pub fn get_name(self: &Self) -> &'static str {
+        match self {
+            RngKind::Std => "std",
+            RngKind::Small => "small",
+            RngKind::Xoshiro => "xoshiro"
+        }
+    }
+}
+
+/// Xoshiro256** by Blackman and Vigna, seeded from a u64 by SplitMix64 as rand does.
+pub struct Xoshiro256StarStar {
+    state: [u64; 4]
+}
+
+impl RngCore for Xoshiro256StarStar {
+
+    fn next_u32(self: &mut Self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(self: &mut Self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    fn fill_bytes(self: &mut Self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(self: &mut Self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for Xoshiro256StarStar {
+
+    type Seed = [u8; 32];
+
+    /// The all-zero state never leaves zero, so it is replaced.
+    fn from_seed(seed: [u8; 32]) -> Xoshiro256StarStar {
+        let mut state = [0u64; 4];
+        for (word, bytes) in state.iter_mut().zip(seed.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        if state == [0; 4] {
+            return Xoshiro256StarStar::seed_from_u64(0);
+        }
+        Xoshiro256StarStar{state}
+    }
+}
+
+
+// Seeding tasks
+
+static RNG_KIND: OnceLock<RngKind> = OnceLock::new();
+
+pub fn set_rng_kind(rng_kind: RngKind) {
+    let _ = RNG_KIND.set(rng_kind);
+}
+
+pub fn get_rng_kind() -> RngKind {
+    *RNG_KIND.get().unwrap_or(&RngKind::Std)
+}
+
+/// The initial triplet of a task, the same for a seed and a generator.
+pub fn seed_triplet(rng_kind: RngKind, task_seed: u64) -> Triplet {
+    match rng_kind {
+        RngKind::Std => random_triplet(&mut StdRng::seed_from_u64(task_seed)),
+        RngKind::Small => random_triplet(&mut SmallRng::seed_from_u64(task_seed)),
+        RngKind::Xoshiro => random_triplet(&mut Xoshiro256StarStar::seed_from_u64(task_seed))
+    }
+}
+
+fn draw_triplets<R: Rng>(rng: &mut R, n_triplets: usize) -> Vec<Triplet> {
+    (0..n_triplets).map(|_| random_triplet(rng)).collect()
+}
+
+/// A working set of triplets drawn in turn, its first triplet being the one of a single triplet.
+pub fn seed_triplets(rng_kind: RngKind, task_seed: u64, n_triplets: usize) -> Vec<Triplet> {
+    match rng_kind {
+        RngKind::Std => draw_triplets(&mut StdRng::seed_from_u64(task_seed), n_triplets),
+        RngKind::Small => draw_triplets(&mut SmallRng::seed_from_u64(task_seed), n_triplets),
+        RngKind::Xoshiro => draw_triplets(&mut Xoshiro256StarStar::seed_from_u64(task_seed), n_triplets)
+    }
+}
+
+const RNG_COST_SAMPLES: usize = 10000;
+
+/// Nanoseconds a task spends seeding its generator and drawing its triplet.
+pub fn measure_rng_cost(rng_kind: RngKind) -> f64 {
+
+    let watch = Instant::now();
+
+    for idx in 0..RNG_COST_SAMPLES {
+        std::hint::black_box(seed_triplet(rng_kind, std::hint::black_box(idx as u64)));
+    }
+
+    watch.elapsed().as_nanos() as f64/RNG_COST_SAMPLES as f64
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn xoshiro_follows_the_reference() {
+        // The first outputs of the reference implementation from the state 1, 2, 3, 4
+        let mut rng = Xoshiro256StarStar{state: [1, 2, 3, 4]};
+        let outputs: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+        assert_eq!(outputs, vec![11520, 0, 1509978240]);
+    }
+
+    #[test]
+    fn triplets_depend_on_seed_and_generator() {
+        for name in RNG_NAMES {
+            let rng_kind = RngKind::parse(name).unwrap();
+            assert_eq!(rng_kind.get_name(), *name);
+            assert_eq!(seed_triplet(rng_kind, 7), seed_triplet(rng_kind, 7));
+            assert_ne!(seed_triplet(rng_kind, 7), seed_triplet(rng_kind, 8));
+        }
+        assert_ne!(seed_triplet(RngKind::Std, 7), seed_triplet(RngKind::Xoshiro, 7));
+        assert!(RngKind::parse("mt").is_none());
+    }
+
+    #[test]
+    fn working_sets_start_with_the_single_triplet() {
+        let triplets = seed_triplets(RngKind::Xoshiro, 7, 3);
+        assert_eq!(triplets.len(), 3);
+        assert_eq!(triplets[0], seed_triplet(RngKind::Xoshiro, 7));
+        assert_ne!(triplets[1], triplets[0]);
+    }
+}
@@ -0,0 +1,252 @@
+// * * ** *** ***** ******** ************* *********************
+// Loading reports saved by earlier runs
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+use embed::ReportRecords;
+
+
+// Reading sections of a report
+
+pub fn parse_task_status(text: &str) -> TaskStatus {
+    match text {
+        "timed out" => TaskStatus::TimedOut,
+        _ => match text.strip_prefix("failed: ") {
+            Some(error) => TaskStatus::Failed(error.to_string()),
+            None => TaskStatus::Completed
+        }
+    }
+}
+
+fn parse_metadata(report: &mut Report, lines: &[&str]) {
+    for line in lines {
+        let fields = split_csv_line(line);
+        if fields.len() >= 2 {
+            report.set_metadata(&fields[0], &fields[1]);
+        }
+    }
+}
+
+fn find_column(header: &str, title: &str) -> Option<usize> {
+    split_csv_line(header).iter().position(|column| column == title)
+}
+
+/// Skipped observations are left out, so a resumed run gets another chance to measure them.
+fn parse_retries(header: &str, lines: &[&str]) -> Vec<(usize, usize)> {
+
+    let (retries_idx, status_idx) = match (find_column(header, "Retries"), 
+                                           find_column(header, "Status")) {
+        (Some(retries_idx), Some(status_idx)) => (retries_idx, status_idx),
+        _ => return Vec::new()
+    };
+
+    lines.iter()
+        .map(|line| split_csv_line(line))
+        .filter(|fields| fields.len() > status_idx && fields[status_idx] == "measured")
+        .map(|fields| (parse_usize(&fields[0]), parse_usize(&fields[retries_idx])))
+        .collect()
+}
+
+/// Reports saved before epoch timestamps were kept give relative starts, which still add up.
+fn parse_schedules(header: &str, lines: &[&str], n_cycles: usize) -> Vec<Observation> {
+
+    let mut observations: Vec<Observation> = Vec::new();
+    let start_idx = find_column(header, "Started at").unwrap_or(2);
+    let thread_name_idx = find_column(header, "Thread");
+    let thread_id_idx = find_column(header, "Thread ID");
+    let seed_idx = find_column(header, "Seed");
+    let branch_misses_idx = find_column(header, "Branch misses");
+    let allocated_idx = find_column(header, "Allocated");
+    let freed_idx = find_column(header, "Freed");
+
+    for line in lines {
+
+        let fields = split_csv_line(line);
+        if fields.len() < 6 {
+            continue;
+        }
+
+        let n_tasks = parse_usize(&fields[0]);
+        let task_idx = parse_usize(&fields[1]);
+        if n_tasks == 0 || task_idx == 0 || task_idx > n_tasks {
+            continue;
+        }
+
+        if observations.last().is_none_or(|obs| obs.count_tasks() != n_tasks) {
+            observations.push(Observation::create(n_tasks, n_cycles));
+        }
+
+        let start = fields.get(start_idx).unwrap_or(&fields[2]);
+        let mut task = Task::create(task_idx - 1,
+                                    Timestamp::from_millis(parse_usize(start) as u64),
+                                    TaskDuration::from_millis(parse_usize(&fields[4]) as u64));
+        task.set_status(parse_task_status(&fields[5]));
+        if fields.len() > 6 {
+            task.set_series(parse_usize(&fields[6]).saturating_sub(1));
+        }
+        if let Some(thread_name) = thread_name_idx.and_then(|idx| fields.get(idx)) {
+            let thread_id = thread_id_idx.and_then(|idx| fields.get(idx))
+                .map_or(0, |thread_id| parse_usize(thread_id) as u64);
+            task.set_thread(thread_name.clone(), thread_id);
+        }
+        if let Some(seed) = seed_idx.and_then(|idx| fields.get(idx)) {
+            task.set_seed(seed.parse::<u64>().unwrap_or(0));
+        }
+        if let Some(branch_misses) = branch_misses_idx.and_then(|idx| fields.get(idx)) {
+            task.set_branch_misses(branch_misses.parse::<u64>().ok());
+        }
+        if let (Some(allocated), Some(freed)) = (allocated_idx.and_then(|idx| fields.get(idx)),
+                                                 freed_idx.and_then(|idx| fields.get(idx))) {
+            task.set_allocations(allocated.parse::<u64>().ok()
+                .zip(freed.parse::<u64>().ok())
+                .map(|(allocated, freed)| Allocations {allocated, freed}));
+        }
+        observations.last_mut().unwrap().register_task(task);
+    }
+
+    observations
+}
+
+fn parse_repeats(lines: &[&str]) -> Vec<(usize, TaskDuration)> {
+    lines.iter()
+        .map(|line| split_csv_line(line))
+        .filter(|fields| fields.len() >= 3)
+        .map(|fields| (parse_usize(&fields[0]), TaskDuration::from_millis(parse_usize(&fields[2]) as u64)))
+        .collect()
+}
+
+
+// Assembling a report
+
+fn parse_report(text: &str) -> Report {
+
+    let mut report = Report::create(0);
+    let mut retries: Vec<(usize, usize)> = Vec::new();
+    let mut schedule_header: &str = "";
+    let mut schedule_lines: Vec<&str> = Vec::new();
+    let mut repeats: Vec<(usize, TaskDuration)> = Vec::new();
+
+    for section in text.split("\n\n") {
+
+        let lines: Vec<&str> = section.lines().collect();
+        if lines.is_empty() {
+            continue;
+        }
+
+        if lines[0].starts_with("Parameter,") {
+            parse_metadata(&mut report, &lines[1..]);
+        } else if lines[0].starts_with("Tasks,Task,") {
+            schedule_header = lines[0];
+            schedule_lines = lines[1..].to_vec();
+        } else if is_observation_totals_header(lines[0]) {
+            retries = parse_retries(lines[0], &lines[1..]);
+        } else if lines[0].starts_with("Tasks,Repeat,") {
+            repeats = parse_repeats(&lines[1..]);
+        }
+    }
+
+    let n_cycles = report.get_metadata("Cycles in a task").map_or(0, |value| parse_usize(value));
+
+    for mut obs in parse_schedules(schedule_header, &schedule_lines, n_cycles) {
+        let n_tasks = obs.count_tasks();
+        if let Some((_, n_retries)) = retries.iter().find(|(n, _)| *n == n_tasks) {
+            obs.set_retries(*n_retries);
+        }
+        obs.set_repeat_durations(repeats.iter()
+            .filter(|(n, _)| *n == n_tasks)
+            .map(|(_, duration)| *duration)
+            .collect());
+        report.register_observation(obs);
+    }
+
+    report
+}
+
+/// Records keep relative starts only, as do reports saved before epoch timestamps were kept.
+pub fn convert_records(records: &ReportRecords) -> Report {
+
+    let mut report = Report::create(0);
+    for (key, value) in &records.metadata {
+        report.set_metadata(key, value);
+    }
+
+    let n_cycles = report.get_metadata("Cycles in a task").map_or(0, |value| parse_usize(value));
+
+    for totals in &records.observations {
+        let mut obs = Observation::create(totals.n_tasks, n_cycles);
+        for record in records.tasks.iter().filter(|record| record.n_tasks == totals.n_tasks) {
+            let mut task = Task::create(record.task - 1,
+                                        Timestamp::from_millis(record.started_ms),
+                                        TaskDuration::from_millis(record.duration_ms));
+            task.set_status(parse_task_status(&record.status));
+            task.set_series(record.series.saturating_sub(1));
+            task.set_thread(record.thread.clone(), 0);
+            obs.register_task(task);
+        }
+        obs.set_retries(totals.retries);
+        obs.set_repeat_durations(totals.repeat_durations_ms.iter()
+            .map(|duration_ms| TaskDuration::from_millis(*duration_ms))
+            .collect());
+        report.register_observation(obs);
+    }
+
+    report
+}
+
+/// Reports saved as JSON records by bindings read as well as the CSV ones.
+fn parse_any_report(text: &str) -> Result<Report, String> {
+    if text.trim_start().starts_with('{') {
+        embed::parse_records(text).map(|records| convert_records(&records))
+    } else {
+        Ok(parse_report(text))
+    }
+}
+
+/// Binary captures read as well as text reports, compressed or not.
+pub fn load_report(report_file_path: &str) -> Report {
+    match compression::read_bytes(report_file_path) {
+        Ok(bytes) if capture::is_capture(&bytes) =>
+            capture::parse_capture(&bytes).unwrap_or_else(|e| panic!("{}", e)),
+        Ok(bytes) => String::from_utf8(bytes)
+            .map_err(|e| format!("Error while reading a report file: {}", e))
+            .and_then(|text| parse_any_report(&text))
+            .unwrap_or_else(|e| panic!("{}", e)),
+        Err(e) => {
+            panic!("Error while reading a report file: {}", e);
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::FakeWorkload;
+
+    #[test]
+    fn saved_report_reads_back_as_written() {
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=6);
+        let text = format_report(&report);
+        assert_eq!(format_report(&parse_report(&text)), text);
+    }
+
+    #[test]
+    fn allocations_read_back_as_written() {
+        let mut report = FakeWorkload::create(2, 4, 100).create_report(1..=2);
+        report.observations[1].tasks[0].set_allocations(Some(Allocations {allocated: 4096, freed: 1024}));
+        let report = parse_report(&format_report(&report));
+        assert_eq!(report.observations[0].sum_allocations(), None);
+        assert_eq!(report.observations[1].sum_allocations(), Some(Allocations {allocated: 4096, freed: 1024}));
+    }
+
+    #[test]
+    fn json_records_read_as_reports() {
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=4);
+        let json = serde_json::to_string(&ReportRecords::create(&report)).unwrap();
+        let text = format_report(&parse_any_report(&json).unwrap());
+        assert_eq!(format_observation_totals_section(&parse_report(&text)), 
+                   format_observation_totals_section(&report));
+        assert!(parse_any_report("{\"observations\": 1}").is_err());
+    }
+}
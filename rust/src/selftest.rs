@@ -0,0 +1,207 @@
+// * * ** *** ***** ******** ************* *********************
+// Checking the measurement machinery before trusting results
+// * * ** *** ***** ******** ************* *********************
+
+use std::thread;
+
+use super::*;
+
+
+// Outcomes of checks
+
+struct CheckOutcome {
+    title: &'static str,
+    passed: bool,
+    details: String
+}
+
+impl CheckOutcome {
+
+    fn create(title: &'static str, passed: bool, details: String) -> CheckOutcome {
+        CheckOutcome{title, passed, details}
+    }
+}
+
+
+// Timers
+
+const TIMER_SAMPLES: usize = 100000;
+const TIMER_RESOLUTION_MAX: Duration = Duration::from_millis(1);
+
+/// Results are reported in milliseconds, so a coarser clock would make them meaningless.
+fn check_timer() -> CheckOutcome {
+
+    let mut resolution = Duration::MAX;
+    let mut n_backsteps = 0usize;
+    let mut previous = SystemTime::now();
+
+    for _ in 0..TIMER_SAMPLES {
+        let current = SystemTime::now();
+        match current.duration_since(previous) {
+            Ok(step) if step > Duration::ZERO => resolution = resolution.min(step),
+            Ok(_) => {}
+            Err(_) => n_backsteps += 1
+        }
+        previous = current;
+    }
+
+    CheckOutcome::create(
+        "Timer resolution and monotonicity",
+        n_backsteps == 0 && resolution <= TIMER_RESOLUTION_MAX,
+        format!("resolution {} ns, {} steps back", resolution.as_nanos(), n_backsteps))
+}
+
+const CLOCK_SKEW_THREADS: usize = 4;
+
+fn check_clock_skew() -> CheckOutcome {
+
+    let clock_skew = measure_clock_skew(CLOCK_SKEW_THREADS);
+
+    CheckOutcome::create(
+        "Clock skew across threads",
+        clock_skew <= CLOCK_SKEW_MAX,
+        format!("up to {} ms in {} threads", clock_skew, CLOCK_SKEW_THREADS))
+}
+
+
+// Calibration
+
+const CALIBRATION_DURATION_MIN: TaskDuration = TaskDuration::from_millis(100);
+const CALIBRATION_RUNS: usize = 5;
+const CALIBRATION_SPREAD_MAX: f64 = 0.2;
+
+fn find_calibration_cycles(triplet: Triplet) -> usize {
+
+    let mut n_cycles: usize = 1000;
+
+    loop {
+        let watch = SystemTime::now();
+        iterate(triplet, n_cycles);
+        if read_elapsed(&watch) >= CALIBRATION_DURATION_MIN {
+            return n_cycles;
+        }
+        n_cycles *= 2;
+    }
+}
+
+fn check_calibration() -> CheckOutcome {
+
+    let triplet = random_triplet(&mut StdRng::seed_from_u64(SELFTEST_SEED));
+    let n_cycles = find_calibration_cycles(triplet);
+
+    let mut durations: Vec<TaskDuration> = (0..CALIBRATION_RUNS).map(|_| {
+        let watch = SystemTime::now();
+        iterate(triplet, n_cycles);
+        read_elapsed(&watch)
+    }).collect();
+    durations.sort();
+
+    let median = durations[durations.len()/2].max(TaskDuration::from_millis(1));
+    let spread = (durations[durations.len() - 1] - durations[0]).ratio(median).unwrap_or(0.0);
+
+    CheckOutcome::create(
+        "Calibration stability",
+        spread <= CALIBRATION_SPREAD_MAX,
+        format!("{} runs of {} cycles, spread {:.1}%",
+                CALIBRATION_RUNS, format_count(n_cycles), 100.0*spread))
+}
+
+
+// Workload
+
+const SELFTEST_SEED: u64 = 42;
+const CHECKSUM_CYCLES: usize = 100000;
+
+/// Bits of the result of the workload seeded with SELFTEST_SEED, which changes only if
+/// the arithmetic or the seeding of tasks does.
+const SELFTEST_CHECKSUM: u64 = 0x3fea8241f02d0deb;
+
+fn compute_checksum(seed: u64) -> u64 {
+    let task_seed = derive_task_seed(seed, 1, 0);
+    iterate(random_triplet(&mut StdRng::seed_from_u64(task_seed)), CHECKSUM_CYCLES).to_bits()
+}
+
+fn check_checksum() -> CheckOutcome {
+
+    let checksum = compute_checksum(SELFTEST_SEED);
+    let thread_checksum = thread::spawn(|| compute_checksum(SELFTEST_SEED)).join().ok();
+
+    let details = if checksum == SELFTEST_CHECKSUM {
+        format!("{:016x} with seed {}", checksum, SELFTEST_SEED)
+    } else {
+        format!("{:016x}, {:016x} expected", checksum, SELFTEST_CHECKSUM)
+    };
+
+    CheckOutcome::create(
+        "Deterministic workload checksum",
+        checksum == SELFTEST_CHECKSUM && thread_checksum == Some(checksum),
+        details)
+}
+
+
+// Spawning and joining
+
+const SPAWN_TASKS: usize = 8;
+const SPAWN_SERIES_SIZE: usize = 3;
+const SPAWN_CYCLES: usize = 10000;
+
+fn check_spawn_join() -> CheckOutcome {
+
+    let obs = observe(SPAWN_TASKS, SPAWN_CYCLES, SPAWN_SERIES_SIZE, Timeouts::create(TaskDuration::ZERO, TaskDuration::ZERO),
+                      SELFTEST_SEED);
+
+    // Slots of tasks never joined keep their placeholders, started at the epoch
+    let n_unjoined = obs.tasks.iter()
+        .filter(|task| task.get_start() == Timestamp::EPOCH)
+        .count();
+    let n_incomplete = obs.tasks.iter()
+        .filter(|task| *task.get_status() != TaskStatus::Completed)
+        .count();
+
+    CheckOutcome::create(
+        "Spawning and joining tasks",
+        obs.count_tasks() == SPAWN_TASKS && n_unjoined == 0 && n_incomplete == 0,
+        format!("{} of {} tasks joined, {} incomplete",
+                SPAWN_TASKS - n_unjoined, SPAWN_TASKS, n_incomplete))
+}
+
+
+// Running all checks
+
+fn create_selftest_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Check", 34, Align::Left, 0),
+        Column::create("Result", 6, Align::Left, 0),
+        Column::create("Details", 44, Align::Left, 1)
+    ], colored)
+}
+
+fn print_outcome(table: &ConsoleTable, outcome: &CheckOutcome) {
+    let (result, sign) = if outcome.passed {("pass", 1.0)} else {("fail", -1.0)};
+    table.print_row(&vec![
+        Cell::Plain(outcome.title.to_string()),
+        Cell::Signed(result.to_string(), sign),
+        Cell::Plain(outcome.details.clone())
+    ]);
+}
+
+pub fn run_selftest(colored: bool) -> bool {
+
+    let checks: [fn() -> CheckOutcome; 5] =
+        [check_timer, check_clock_skew, check_calibration, check_checksum, check_spawn_join];
+
+    let table = create_selftest_table(colored);
+    table.print_header();
+
+    let mut passed = true;
+
+    for check in checks {
+        let outcome = check();
+        print_outcome(&table, &outcome);
+        passed &= outcome.passed;
+    }
+
+    table.print_footer();
+
+    passed
+}
@@ -0,0 +1,126 @@
+// * * ** *** ***** ******** ************* *********************
+// Comparing shared and thread-local accumulation
+// * * ** *** ***** ******** ************* *********************
+
+use std::sync::Mutex;
+
+use super::*;
+
+
+// Accumulating
+
+/// Shared tasks lock the total every cycle, local ones add up on their own and lock it once.
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Accumulation {
+    Shared,
+    Local
+}
+
+fn accumulate(triplet: Triplet, n_cycles: usize, accumulation: Accumulation, total: &Mutex<f64>) {
+
+    let mut triplet = triplet;
+    let mut sum = 0f64;
+
+    for _ in 0..n_cycles {
+        triplet = get_next_triplet(triplet);
+        match accumulation {
+            Accumulation::Shared => *total.lock().unwrap() += triplet.2,
+            Accumulation::Local => sum += triplet.2
+        }
+    }
+
+    if accumulation == Accumulation::Local {
+        *total.lock().unwrap() += sum;
+    }
+}
+
+/// All tasks run at once, from the same seeds in both modes, so only accumulating differs.
+fn observe_accumulation(n_tasks: usize, n_cycles: usize, seed: u64,
+                        accumulation: Accumulation) -> (Observation, f64) {
+
+    let total = Mutex::new(0f64);
+
+    let obs = observe_workload(&RunClock, n_tasks, n_cycles, n_tasks, seed, &|task_idx, _, task_seed| {
+        let triplet = rng::seed_triplet(rng::get_rng_kind(), task_seed);
+        let watch = Stopwatch::start();
+        let start = read_run_clock();
+        accumulate(triplet, n_cycles, accumulation, &total);
+        let duration = TaskDuration::from_nanos(watch.elapsed_nanos());
+        let mut task = Task::create(task_idx, start, duration);
+        task.set_seed(task_seed);
+        task
+    });
+
+    let total = *total.lock().unwrap();
+    (obs, total)
+}
+
+
+// Comparing
+
+/// How much longer sharing takes than accumulating locally, e.g. 0.5 for half as long again.
+fn get_sharing_cost(shared: TaskDuration, local: TaskDuration) -> f64 {
+    shared.as_millis_f64()/local.as_millis_f64().max(1.0) - 1.0
+}
+
+fn create_sharing_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Tasks", 5, Align::Right, 0),
+        Column::create("Shared, ms", 12, Align::Right, 0),
+        Column::create("Thread-local, ms", 16, Align::Right, 0),
+        Column::create("Cost of sharing", 15, Align::Right, 0)
+    ], colored)
+}
+
+pub fn run_sharing(args: &Args) -> i32 {
+
+    let (tasks_max, n_cycles, seed) = (args.get_tasks_max(), args.get_n_cycles(), args.get_seed());
+
+    info!("Accumulating {} cycles per task into a shared total or thread-locals", format_count(n_cycles));
+
+    let table = create_sharing_table(args.get_colored());
+    table.print_header();
+
+    for n_tasks in 1..tasks_max + 1 {
+
+        if cancel::is_cancelled() {
+            break;
+        }
+
+        let (shared, shared_total) = observe_accumulation(n_tasks, n_cycles, seed, Accumulation::Shared);
+        let (local, local_total) = observe_accumulation(n_tasks, n_cycles, seed, Accumulation::Local);
+        debug!(n_tasks, shared_total, local_total, "Totals accumulated");
+
+        table.print_row(&vec![
+            Cell::Plain(n_tasks.to_string()),
+            Cell::Plain(shared.get_total_duration().to_string()),
+            Cell::Plain(local.get_total_duration().to_string()),
+            Cell::Plain(format_percent_cell(get_sharing_cost(shared.get_total_duration(), local.get_total_duration())))
+        ]);
+    }
+
+    table.print_footer();
+
+    EXIT_SUCCESS
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn both_accumulations_add_up_the_same() {
+        let (shared, local) = (Mutex::new(0f64), Mutex::new(0f64));
+        accumulate((0.5, 0.25, 0.75), 1000, Accumulation::Shared, &shared);
+        accumulate((0.5, 0.25, 0.75), 1000, Accumulation::Local, &local);
+        assert!((*shared.lock().unwrap() - *local.lock().unwrap()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sharing_costs_relative_to_local_accumulation() {
+        assert_eq!(get_sharing_cost(TaskDuration::from_millis(150), TaskDuration::from_millis(100)), 0.5);
+        assert_eq!(get_sharing_cost(TaskDuration::from_millis(100), TaskDuration::from_millis(100)), 0.0);
+    }
+}
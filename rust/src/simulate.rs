@@ -0,0 +1,179 @@
+// * * ** *** ***** ******** ************* *********************
+// Simulating observations on virtual time
+// * * ** *** ***** ******** ************* *********************
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::*;
+
+
+// Virtual clocks
+
+/// A clock standing still until moved, so observations on it take no real time.
+pub struct SimulatedClock {
+    now_ms: AtomicU64
+}
+
+impl SimulatedClock {
+
+    pub fn create(start: Timestamp) -> SimulatedClock {
+        SimulatedClock{now_ms: AtomicU64::new(start.as_millis())}
+    }
+
+    /// The clock never moves back.
+    pub fn advance_to(self: &Self, moment: Timestamp) {
+        self.now_ms.fetch_max(moment.as_millis(), Ordering::Relaxed);
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(self: &Self) -> Timestamp {
+        Timestamp::from_millis(self.now_ms.load(Ordering::Relaxed))
+    }
+}
+
+static SIMULATED_CLOCK: OnceLock<SimulatedClock> = OnceLock::new();
+
+/// Virtual time starts with the run and goes on from observation to observation.
+fn get_simulated_clock() -> &'static SimulatedClock {
+    SIMULATED_CLOCK.get_or_init(|| SimulatedClock::create(read_run_clock()))
+}
+
+
+// Modelling machines
+
+/// An ideal machine running as many tasks at a time as it has CPUs. Series run in waves,
+/// tasks of a wave spawned one by one, and series are separated by a gap.
+#[derive(Copy, Clone)]
+pub struct MachineModel {
+    pub n_cpus: usize,
+    pub spawn_delay: TaskDuration,
+    pub series_gap: TaskDuration
+}
+
+impl MachineModel {
+
+    pub fn create(n_cpus: usize) -> MachineModel {
+        MachineModel {
+            n_cpus: n_cpus.max(1),
+            spawn_delay: TaskDuration::from_millis(1),
+            series_gap: TaskDuration::from_millis(2)
+        }
+    }
+
+    fn get_series_period(self: &Self, series_size: usize, task_duration: TaskDuration) -> TaskDuration {
+        let n_waves = count_series(series_size, self.n_cpus);
+        let wave_size = series_size.min(self.n_cpus);
+        task_duration*n_waves + self.spawn_delay*(wave_size - 1) + self.series_gap
+    }
+
+    /// The start of a task from the start of its observation, when tasks take the same duration.
+    pub fn place_task(self: &Self, task_idx: usize, series_size: usize,
+                      task_duration: TaskDuration) -> TaskDuration {
+        let (series_idx, position) = (task_idx/series_size, task_idx % series_size);
+        self.get_series_period(series_size, task_duration)*series_idx +
+            task_duration*(position/self.n_cpus) +
+            self.spawn_delay*(position % self.n_cpus)
+    }
+}
+
+
+// Synthesizing tasks
+
+pub const BACKEND: &str = "simulated";
+
+/// A nominal rate of the triplet kernel, about what a desktop CPU does.
+const SIMULATED_CYCLES_PER_SEC: usize = 10000000;
+
+/// Durations of tasks vary by up to this share either way, as they do on real machines.
+const SIMULATED_JITTER: f64 = 0.05;
+
+fn get_nominal_duration(n_cycles: usize) -> TaskDuration {
+    TaskDuration::from_millis((1000*n_cycles as u64/SIMULATED_CYCLES_PER_SEC as u64).max(1))
+}
+
+/// Tasks are placed in slots fitting the longest jitter, so a CPU never runs two at a time,
+/// and vary by a jitter derived from their seed. Tasks running past a deadline are cut at it,
+/// as real ones are interrupted.
+fn simulate_task(model: &MachineModel, task_idx: usize, n_cycles: usize, series_size: usize,
+                 timeouts: Timeouts, observation_start: Timestamp, task_seed: u64) -> Task {
+
+    let nominal_duration = get_nominal_duration(n_cycles);
+    let slot_ms = (nominal_duration.as_millis_f64()*(1.0 + SIMULATED_JITTER)).ceil() as u64;
+    let start = observation_start + model.place_task(task_idx, series_size, TaskDuration::from_millis(slot_ms));
+
+    let jitter = StdRng::seed_from_u64(task_seed).gen_range(-SIMULATED_JITTER..=SIMULATED_JITTER);
+    let duration_ms = (nominal_duration.as_millis_f64()*(1.0 + jitter)).round() as u64;
+    let mut task = Task::create(task_idx, start, TaskDuration::from_millis(duration_ms));
+
+    if let Some(deadline) = timeouts.get_task_deadline(start, observation_start) {
+        if task.get_finish() > deadline {
+            task = Task::create(task_idx, start, deadline - start);
+            task.set_status(TaskStatus::TimedOut);
+        }
+    }
+
+    task
+}
+
+/// Observes through the measurement engine with nothing run, so reports, statistics, and charts
+/// come out at once and the same on every run of a seed.
+pub fn observe(n_tasks: usize, n_cycles: usize, series_size: usize, timeouts: Timeouts,
+               seed: u64) -> Observation {
+
+    let clock = get_simulated_clock();
+    let model = MachineModel::create(count_cpus());
+
+    let obs = observe_workload(clock, n_tasks, n_cycles, series_size, seed,
+                               &|task_idx, observation_start, task_seed|
+                                   simulate_task(&model, task_idx, n_cycles, series_size,
+                                                 timeouts, observation_start, task_seed));
+
+    if obs.count_tasks() > 0 {
+        clock.advance_to(obs.get_latest_finish() + model.series_gap);
+    }
+
+    obs
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn clock_never_moves_back() {
+        let clock = SimulatedClock::create(Timestamp::from_millis(1000));
+        clock.advance_to(Timestamp::from_millis(1500));
+        clock.advance_to(Timestamp::from_millis(1200));
+        assert_eq!(clock.now(), Timestamp::from_millis(1500));
+    }
+
+    #[test]
+    fn tasks_run_in_waves_of_cpus() {
+        let model = MachineModel::create(2);
+        let duration = TaskDuration::from_millis(100);
+        let offsets: Vec<u64> = (0..6).map(|idx| model.place_task(idx, 3, duration).as_millis()).collect();
+        assert_eq!(offsets, vec![0, 1, 100, 203, 204, 303]);
+    }
+
+    #[test]
+    fn jitter_depends_on_seed_only() {
+        let model = MachineModel::create(4);
+        let timeouts = Timeouts::create(TaskDuration::ZERO, TaskDuration::ZERO);
+        let task = |seed| simulate_task(&model, 0, 10000000, 4, timeouts, Timestamp::EPOCH, seed);
+        assert_eq!(task(7).get_duration(), task(7).get_duration());
+        let duration = task(7).get_duration().as_millis();
+        assert!((950..=1050).contains(&duration));
+    }
+
+    #[test]
+    fn tasks_are_cut_at_deadlines() {
+        let model = MachineModel::create(1);
+        let timeouts = Timeouts::create(TaskDuration::from_millis(500), TaskDuration::ZERO);
+        let task = simulate_task(&model, 0, 10000000, 1, timeouts, Timestamp::EPOCH, 1);
+        assert_eq!(task.get_duration(), TaskDuration::from_millis(500));
+        assert!(task.is_timed_out());
+    }
+}
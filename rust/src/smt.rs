@@ -0,0 +1,162 @@
+// * * ** *** ***** ******** ************* *********************
+// Comparing sweeps with and without SMT
+// * * ** *** ***** ******** ************* *********************
+
+use std::thread;
+
+use platform::LogicalCpu;
+
+use super::*;
+
+
+// Restricting tasks to CPUs
+
+/// The first logical CPU of every physical core, so that SMT siblings stay idle.
+fn find_core_cpus(cpus: &[LogicalCpu]) -> Vec<usize> {
+    cpus.iter()
+        .filter(|cpu| !cpus.iter().any(|other| (other.package, other.core) == (cpu.package, cpu.core) &&
+                                               other.idx < cpu.idx))
+        .map(|cpu| cpu.idx)
+        .collect()
+}
+
+/// Restricting fails alike for every worker, so a trial thread finds it out beforehand.
+fn try_restriction(cpu_idxs: &[usize]) -> Result<(), String> {
+    thread::scope(|scope| {
+        scope.spawn(|| platform::restrict_thread(cpu_idxs))
+            .join()
+            .unwrap_or(Err("Restricting a trial worker panicked".to_string()))
+    })
+}
+
+/// Tasks may run on any of the CPUs, the scheduler balancing them within.
+fn observe_on_cpus(cpu_idxs: &[usize], n_tasks: usize, n_cycles: usize, series_size: usize,
+                   timeouts: Timeouts, seed: u64) -> Observation {
+    observe_workload(&RunClock, n_tasks, n_cycles, series_size, seed, &|task_idx, observation_start, task_seed| {
+        if let Err(error) = platform::restrict_thread(cpu_idxs) {
+            debug!(task = task_idx + 1, error, "Task runs unrestricted");
+        }
+        standard_task(task_idx, n_cycles, timeouts, observation_start, task_seed)
+    })
+}
+
+
+// Comparing
+
+/// How much faster all logical CPUs run the tasks than one per core, e.g. 0.25 for a quarter.
+fn get_smt_gain(core_duration: TaskDuration, logical_duration: TaskDuration) -> f64 {
+    core_duration.as_millis_f64()/logical_duration.as_millis_f64().max(1.0) - 1.0
+}
+
+fn create_smt_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Tasks", 5, Align::Right, 0),
+        Column::create("One per core, ms", 16, Align::Right, 0),
+        Column::create("Speedup", 7, Align::Right, 0),
+        Column::create("All logical, ms", 15, Align::Right, 0),
+        Column::create("Speedup", 7, Align::Right, 0),
+        Column::create("SMT gain", 8, Align::Right, 0)
+    ], colored)
+}
+
+fn print_smt_summary(core_report: &Report, logical_report: &Report) {
+
+    let best_speedup = |report: &Report| report.observations.iter()
+        .map(|obs| obs.get_speedup())
+        .fold(1.0, f64::max);
+
+    info!("Best speedup: {:.2} on one logical CPU per core, {:.2} on all logical CPUs",
+          best_speedup(core_report), best_speedup(logical_report));
+
+    let gains = core_report.observations.iter().filter_map(|core_obs| {
+        logical_report.find_observation(core_obs.count_tasks()).map(|logical_obs| {
+            (core_obs.count_tasks(), get_smt_gain(core_obs.get_total_duration(), logical_obs.get_total_duration()))
+        })
+    });
+
+    if let Some((n_tasks, gain)) = gains.max_by(|(_, gain1), (_, gain2)| gain1.total_cmp(gain2)) {
+        info!("SMT buys the most at {} tasks: {} faster than one logical CPU per core", n_tasks, format_percent_cell(gain));
+    }
+}
+
+/// The same sweep, from the same seeds, restricted to one logical CPU per core and then on all of them.
+pub fn run_smt(args: &Args) -> i32 {
+
+    let config = args.get_run_config();
+    let (n_cycles, series_size, seed) = (config.get_n_cycles(), config.get_series_size(), args.get_seed());
+    let timeouts = args.get_timeouts();
+
+    let cpus = platform::read_cpu_topology();
+    let logical_cpus: Vec<usize> = cpus.iter().map(|cpu| cpu.idx).collect();
+    let core_cpus = find_core_cpus(&cpus);
+
+    info!("Running tasks on one logical CPU per core ({}) and on all logical CPUs ({})",
+          core_cpus.len(), logical_cpus.len());
+
+    if let Err(error) = try_restriction(&core_cpus) {
+        warn!("{}, SMT cannot be compared", error);
+        return EXIT_SUCCESS;
+    }
+    if core_cpus.len() == logical_cpus.len() {
+        warn!("No SMT siblings found, both sweeps run on the same CPUs");
+    }
+
+    let table = create_smt_table(args.get_colored());
+    table.print_header();
+
+    let mut core_report = Report::create(config.get_tasks_max());
+    let mut logical_report = Report::create(config.get_tasks_max());
+
+    for n_tasks in config.get_tasks() {
+
+        core_report.register_observation(observe_on_cpus(&core_cpus, n_tasks, n_cycles, series_size, timeouts, seed));
+        logical_report.register_observation(observe_on_cpus(&logical_cpus, n_tasks, n_cycles, series_size,
+                                                            timeouts, seed));
+        if cancel::is_cancelled() {
+            break;
+        }
+
+        if let (Some(core_obs), Some(logical_obs)) =
+            (core_report.find_observation(n_tasks), logical_report.find_observation(n_tasks)) {
+            table.print_row(&vec![
+                Cell::Plain(n_tasks.to_string()),
+                Cell::Plain(core_obs.get_total_duration().to_string()),
+                Cell::Plain(format!("{:.2}", core_obs.get_speedup())),
+                Cell::Plain(logical_obs.get_total_duration().to_string()),
+                Cell::Plain(format!("{:.2}", logical_obs.get_speedup())),
+                Cell::Plain(format_percent_cell(get_smt_gain(core_obs.get_total_duration(),
+                                                             logical_obs.get_total_duration())))
+            ]);
+        }
+    }
+
+    table.print_footer();
+    print_smt_summary(&core_report, &logical_report);
+
+    EXIT_SUCCESS
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn one_cpu_of_every_core_is_kept() {
+        let cpus: Vec<LogicalCpu> = (0..8)
+            .map(|idx| LogicalCpu{idx, core: idx % 4, cache: 0, package: 0})
+            .collect();
+        assert_eq!(find_core_cpus(&cpus), vec![0, 1, 2, 3]);
+        let cpus: Vec<LogicalCpu> = (0..4)
+            .map(|idx| LogicalCpu{idx, core: 0, cache: 0, package: idx/2})
+            .collect();
+        assert_eq!(find_core_cpus(&cpus), vec![0, 2]);
+    }
+
+    #[test]
+    fn smt_gains_relative_to_one_cpu_per_core() {
+        assert_eq!(get_smt_gain(TaskDuration::from_millis(125), TaskDuration::from_millis(100)), 0.25);
+        assert_eq!(get_smt_gain(TaskDuration::from_millis(100), TaskDuration::from_millis(100)), 0.0);
+    }
+}
@@ -0,0 +1,229 @@
+// * * ** *** ***** ******** ************* *********************
+// Soaking a machine with a fixed observation to detect drift
+// * * ** *** ***** ******** ************* *********************
+
+use hdrhistogram::Histogram;
+
+use super::*;
+
+
+// Keeping samples of a soak
+
+const ROLLING_WINDOW: usize = 10;
+const MS_PER_HOUR: f64 = 3600000.0;
+
+struct SoakSample {
+    elapsed: TaskDuration,
+    total_duration: TaskDuration,
+    rolling_median: TaskDuration
+}
+
+fn find_median(durations: &[TaskDuration]) -> TaskDuration {
+    let mut sorted = durations.to_vec();
+    sorted.sort();
+    sorted[sorted.len()/2]
+}
+
+/// A trend line fitted by least squares, in milliseconds of total duration per hour.
+fn find_trend_slope(samples: &[SoakSample]) -> f64 {
+
+    let n = samples.len() as f64;
+    if samples.len() < 2 {
+        return 0.0;
+    }
+
+    let mean_x = samples.iter().map(|s| s.elapsed.as_millis_f64()).sum::<f64>()/n;
+    let mean_y = samples.iter().map(|s| s.total_duration.as_millis_f64()).sum::<f64>()/n;
+
+    let (covariance, variance) = samples.iter().fold((0.0, 0.0), |(cov, var), s| {
+        let dx = s.elapsed.as_millis_f64() - mean_x;
+        (cov + dx*(s.total_duration.as_millis_f64() - mean_y), var + dx*dx)
+    });
+
+    if variance > 0.0 {MS_PER_HOUR*covariance/variance} else {0.0}
+}
+
+
+// Reporting drift
+
+fn create_soak_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Sample", 6, Align::Right, 0),
+        Column::create("Elapsed, sec.", 13, Align::Right, 1),
+        Column::create("Total duration", 15, Align::Right, 0),
+        Column::create("Rolling median", 15, Align::Right, 0),
+        Column::create("Drift", 7, Align::Right, 2)
+    ], colored)
+}
+
+fn find_drift(sample: &SoakSample, first: &SoakSample) -> f64 {
+    sample.rolling_median.ratio(first.rolling_median).map_or(0.0, |ratio| ratio - 1.0)
+}
+
+fn print_soak_sample(table: &ConsoleTable, idx: usize, sample: &SoakSample, first: &SoakSample) {
+    let drift = find_drift(sample, first);
+    table.print_row(&vec![
+        Cell::Plain((idx + 1).to_string()),
+        Cell::Plain(sample.elapsed.as_duration().as_secs().to_string()),
+        Cell::Plain(sample.total_duration.to_string()),
+        Cell::Plain(sample.rolling_median.to_string()),
+        Cell::Signed(format_percent_cell(drift), -drift)
+    ]);
+}
+
+/// Each sample starts as the previous one ends, with no rate to keep, so no sample is
+/// omitted for a stall and durations are recorded as measured.
+fn record_total_durations(samples: &Vec<SoakSample>) -> Histogram<u64> {
+
+    let mut histogram = create_histogram();
+
+    let mut n_unrecorded = 0usize;
+    for sample in samples {
+        n_unrecorded += histogram.record(sample.total_duration.as_millis()).is_err() as usize;
+    }
+
+    if n_unrecorded > 0 {
+        warn!("{} total durations out of the range of the histogram were left out", n_unrecorded);
+    }
+
+    histogram
+}
+
+fn print_percentiles(histogram: &Histogram<u64>) {
+    for percentile in PERCENTILES {
+        info!("p{} total duration: {} ms", percentile, histogram.value_at_quantile(percentile/100.0));
+    }
+}
+
+fn format_soak_report(report: &Report, samples: &Vec<SoakSample>) -> String {
+
+    let mut samples_text = "Sample,Elapsed,Total duration,Rolling median\n".to_string();
+
+    for (idx, sample) in samples.iter().enumerate() {
+        samples_text += &format!("{},{},{},{}\n", idx + 1, sample.elapsed,
+                                 sample.total_duration, sample.rolling_median);
+    }
+
+    let percentiles_text = format_percentiles_section(&["Total duration"],
+                                                      &[&record_total_durations(samples)]);
+
+    format_metadata_section(report) + "\n" + &percentiles_text + "\n" + &samples_text
+}
+
+
+// Running a soak
+
+pub fn run_soak(args: &Args) -> i32 {
+
+    let config = args.get_run_config();
+    let n_tasks = config.get_tasks_max();
+
+    let mut report = Report::create(n_tasks);
+    let start = read_timestamp(&SystemTime::now());
+    register_run_metadata(&mut report, args, start);
+    report.set_metadata("Soak duration", &args.get_duration().to_string());
+
+    cancel::cancel_on_interrupt();
+    let environment_before = EnvironmentSnapshot::capture();
+    let watch = SystemTime::now();
+    let mut samples: Vec<SoakSample> = Vec::new();
+    let mut durations: Vec<TaskDuration> = Vec::new();
+
+    let table = create_soak_table(args.get_colored());
+    table.print_header();
+
+    while read_elapsed(&watch) < args.get_duration() {
+
+        let obs = observe_with_retries(n_tasks, &config);
+        if cancel::is_cancelled() {
+            warn!("Soak cancelled after {} samples", samples.len());
+            break;
+        }
+        durations.push(obs.get_total_duration());
+
+        let window_start = durations.len().saturating_sub(ROLLING_WINDOW);
+        samples.push(SoakSample {
+            elapsed: read_elapsed(&watch),
+            total_duration: obs.get_total_duration(),
+            rolling_median: find_median(&durations[window_start..])
+        });
+
+        print_soak_sample(&table, samples.len() - 1, samples.last().unwrap(), &samples[0]);
+        if samples.len().is_multiple_of(ROLLING_WINDOW) {
+            save_report_text(&args.get_out_file_path(), &format_soak_report(&report, &samples));
+        }
+    }
+
+    table.print_footer();
+
+    let slope = find_trend_slope(&samples);
+    report.set_metadata("Trend, ms per hour", &format!("{:.3}", slope));
+    save_report_text(&args.get_out_file_path(), &format_soak_report(&report, &samples));
+
+    info!("Samples: {}", samples.len());
+    print_percentiles(&record_total_durations(&samples));
+    info!("Trend: {:+.3} ms of total duration per hour", slope);
+    if let (Some(first), Some(last)) = (samples.first(), samples.last()) {
+        info!("Drift of the rolling median: {}", format_percent_cell(find_drift(last, first)));
+    }
+
+    assess_measuring(args, &report, &environment_before)
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::{assert_golden, FakeWorkload};
+
+    /// Samples as a soak takes them, the fake machine slowing down as it goes.
+    fn create_fake_samples() -> Vec<SoakSample> {
+
+        let mut samples: Vec<SoakSample> = Vec::new();
+        let mut durations: Vec<TaskDuration> = Vec::new();
+        let mut elapsed = TaskDuration::ZERO;
+
+        for idx in 0..12 {
+            let total_duration = FakeWorkload::create(2, 4, 100 + idx).observe(4).get_total_duration();
+            elapsed += total_duration;
+            durations.push(total_duration);
+            let window_start = durations.len().saturating_sub(ROLLING_WINDOW);
+            samples.push(SoakSample {
+                elapsed,
+                total_duration,
+                rolling_median: find_median(&durations[window_start..])
+            });
+        }
+
+        samples
+    }
+
+    #[test]
+    fn soak_report_matches_golden_file() {
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=1);
+        assert_golden("soak.csv", &format_soak_report(&report, &create_fake_samples()));
+    }
+
+    #[test]
+    fn slowing_down_makes_positive_trend_and_drift() {
+        let samples = create_fake_samples();
+        assert!(find_trend_slope(&samples) > 0.0);
+        assert!(find_drift(samples.last().unwrap(), &samples[0]) > 0.0);
+    }
+
+    #[test]
+    fn stalls_add_no_samples_to_percentiles() {
+        let mut samples = create_fake_samples();
+        samples[5].total_duration = TaskDuration::from_millis(2000);
+        let histogram = record_total_durations(&samples);
+        assert_eq!(histogram.len(), 12);
+        assert_eq!(histogram.max(), 2000);
+    }
+
+    #[test]
+    fn trend_of_single_sample_is_flat() {
+        let samples: Vec<SoakSample> = create_fake_samples().into_iter().take(1).collect();
+        assert_eq!(find_trend_slope(&samples), 0.0);
+    }
+}
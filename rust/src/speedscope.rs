@@ -0,0 +1,220 @@
+// * * ** *** ***** ******** ************* *********************
+// Exporting schedules to flame graph viewers
+// * * ** *** ***** ******** ************* *********************
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::*;
+
+
+// Lanes
+
+/// Workers are spawned for every task, so tasks are packed into lanes instead: a task takes
+/// the first lane free by its start, and lanes of an observation never overlap.
+fn pack_lanes(obs: &Observation) -> Vec<Vec<&Task>> {
+
+    let mut tasks: Vec<&Task> = obs.tasks.iter().collect();
+    tasks.sort_by_key(|task| (task.get_start(), task.get_idx()));
+
+    let mut lanes: Vec<Vec<&Task>> = Vec::new();
+    for task in tasks {
+        match lanes.iter_mut().find(|lane| lane.last().unwrap().get_finish() <= task.get_start()) {
+            Some(lane) => lane.push(task),
+            None => lanes.push(vec![task])
+        }
+    }
+
+    lanes
+}
+
+/// Runs merged from several reports are told apart by their labels, a single report needs none.
+fn prefix_label(label: &str, text: &str) -> String {
+    if label.is_empty() {text.to_string()} else {format!("{}, {}", label, text)}
+}
+
+
+// Speedscope
+
+const SPEEDSCOPE_SCHEMA: &str = "https://www.speedscope.app/file-format-schema.json";
+
+#[derive(Serialize)]
+struct Frame {
+    name: String
+}
+
+#[derive(Serialize)]
+struct Event {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    frame: usize,
+    at: f64
+}
+
+/// An evented profile of a lane, timed in milliseconds from the start of its observation.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Profile {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    name: String,
+    unit: &'static str,
+    start_value: f64,
+    end_value: f64,
+    events: Vec<Event>
+}
+
+#[derive(Serialize)]
+struct Shared {
+    frames: Vec<Frame>
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Speedscope {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    shared: Shared,
+    profiles: Vec<Profile>,
+    name: String,
+    exporter: String
+}
+
+/// Frames are shared by all profiles, one a name.
+#[derive(Default)]
+struct Frames {
+    frames: Vec<Frame>,
+    idxs: HashMap<String, usize>
+}
+
+impl Frames {
+
+    fn get_idx(self: &mut Self, name: String) -> usize {
+        if let Some(idx) = self.idxs.get(&name) {
+            return *idx;
+        }
+        self.frames.push(Frame {name: name.clone()});
+        self.idxs.insert(name, self.frames.len() - 1);
+        self.frames.len() - 1
+    }
+}
+
+fn create_profile(frames: &mut Frames, label: &str, obs: &Observation, lane_idx: usize, lane: &[&Task]) -> Profile {
+
+    let mut events: Vec<Event> = Vec::new();
+    for task in lane {
+        let series_frame = frames.get_idx(format!("Series {}", task.get_series() + 1));
+        let task_frame = frames.get_idx(format!("Task {}", task.get_idx() + 1));
+        let (start, finish) = (obs.to_relative(task.get_start()).as_millis_f64(),
+                               obs.to_relative(task.get_finish()).as_millis_f64());
+        events.push(Event {kind: "O", frame: series_frame, at: start});
+        events.push(Event {kind: "O", frame: task_frame, at: start});
+        events.push(Event {kind: "C", frame: task_frame, at: finish});
+        events.push(Event {kind: "C", frame: series_frame, at: finish});
+    }
+
+    Profile {
+        kind: "evented",
+        name: prefix_label(label, &format!("{} tasks, lane {}", obs.count_tasks(), lane_idx + 1)),
+        unit: "milliseconds",
+        start_value: 0.0,
+        end_value: obs.get_total_duration().as_millis_f64(),
+        events
+    }
+}
+
+/// A profile a lane of every observation, tasks nested in their series, as speedscope.app opens it.
+pub fn format_speedscope(runs: &[(&str, &Report)]) -> String {
+
+    let mut frames = Frames::default();
+    let mut profiles: Vec<Profile> = Vec::new();
+
+    for (label, report) in runs {
+        for obs in &report.observations {
+            for (lane_idx, lane) in pack_lanes(obs).iter().enumerate() {
+                profiles.push(create_profile(&mut frames, label, obs, lane_idx, lane));
+            }
+        }
+    }
+
+    let speedscope = Speedscope {
+        schema: SPEEDSCOPE_SCHEMA,
+        shared: Shared {frames: frames.frames},
+        profiles,
+        name: "Schedules of concurrency profit".to_string(),
+        exporter: format!("conctest {}", env!("CARGO_PKG_VERSION"))
+    };
+
+    serde_json::to_string(&speedscope).unwrap() + "\n"
+}
+
+
+// Collapsed stacks
+
+/// Frames delimit stacks, so they are stripped of semicolons.
+fn escape_frame(name: &str) -> String {
+    name.replace(';', ",")
+}
+
+/// A line a task, its stack from the observation down, weighed in microseconds, as flamegraph.pl
+/// and inferno fold them. Order over time is lost, time spent stays.
+pub fn format_collapsed_stacks(runs: &[(&str, &Report)]) -> String {
+
+    let mut text: String = "".to_string();
+
+    for (label, report) in runs {
+        for obs in &report.observations {
+            let run_frame = if label.is_empty() {"".to_string()} else {escape_frame(label) + ";"};
+            for task in &obs.tasks {
+                text += &format!("{}{} tasks;Series {};Task {} {}\n", run_frame, obs.count_tasks(),
+                                 task.get_series() + 1, task.get_idx() + 1,
+                                 task.get_duration().as_duration().as_micros());
+            }
+        }
+    }
+
+    text
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::FakeWorkload;
+
+    #[test]
+    fn lanes_never_overlap() {
+        let obs = FakeWorkload::create(2, 4, 100).observe(7);
+        let lanes = pack_lanes(&obs);
+        assert_eq!(lanes.iter().map(|lane| lane.len()).sum::<usize>(), 7);
+        assert!(lanes.len() <= obs.count_concurrent_tasks_max().unwrap());
+        for lane in &lanes {
+            assert!(lane.windows(2).all(|pair| pair[0].get_finish() <= pair[1].get_start()));
+        }
+    }
+
+    #[test]
+    fn speedscope_profiles_nest_tasks_in_series() {
+
+        let report = FakeWorkload::create(2, 2, 100).create_report(1..=3);
+        let json: serde_json::Value = serde_json::from_str(&format_speedscope(&[("", &report)])).unwrap();
+
+        let profiles = json["profiles"].as_array().unwrap();
+        assert_eq!(profiles[0]["name"], "1 tasks, lane 1");
+        assert_eq!(profiles.iter().map(|profile| profile["events"].as_array().unwrap().len()).sum::<usize>(),
+                   4*(1 + 2 + 3));
+        let frames = json["shared"]["frames"].as_array().unwrap();
+        assert_eq!(frames[profiles[0]["events"][0]["frame"].as_u64().unwrap() as usize]["name"], "Series 1");
+        assert_eq!(profiles[0]["events"][2]["at"], 100.0);
+    }
+
+    #[test]
+    fn collapsed_stacks_weigh_tasks() {
+        let report = FakeWorkload::create(2, 2, 100).create_report(1..=2);
+        let text = format_collapsed_stacks(&[("linux", &report)]);
+        assert_eq!(text.lines().next(), Some("linux;1 tasks;Series 1;Task 1 100000"));
+        assert_eq!(text.lines().count(), 3);
+    }
+}
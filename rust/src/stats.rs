@@ -0,0 +1,293 @@
+// * * ** *** ***** ******** ************* *********************
+// Estimating uncertainty of repeated measurements
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+
+
+// Bootstrapping confidence intervals
+
+const BOOTSTRAP_RESAMPLES: usize = 2000;
+const CONFIDENCE_LEVEL: f64 = 0.95;
+
+fn calc_mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>()/values.len() as f64
+}
+
+/// Resamples values with replacement and takes percentiles of the resampled means.
+/// The seed keeps intervals reproducible for the same values.
+pub fn bootstrap_mean_interval(values: &[f64], seed: u64) -> Option<(f64, f64)> {
+
+    if values.len() < 2 {
+        return None;
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut means: Vec<f64> = (0..BOOTSTRAP_RESAMPLES).map(|_| {
+        let resample: Vec<f64> = (0..values.len())
+            .map(|_| values[rng.gen_range(0..values.len())])
+            .collect();
+        calc_mean(&resample)
+    }).collect();
+    means.sort_by(|a, b| a.total_cmp(b));
+
+    let tail = (1.0 - CONFIDENCE_LEVEL)/2.0;
+    let low_idx = (tail*BOOTSTRAP_RESAMPLES as f64) as usize;
+    let high_idx = ((1.0 - tail)*BOOTSTRAP_RESAMPLES as f64) as usize - 1;
+
+    Some((means[low_idx], means[high_idx]))
+}
+
+pub fn get_half_width(interval: (f64, f64)) -> f64 {
+    (interval.1 - interval.0)/2.0
+}
+
+
+// Streaming statistics
+
+/// Welford's running mean and sum of squared deviations, taking values one by one in constant memory.
+#[derive(Clone, Default)]
+pub struct StreamingStats {
+    count: usize,
+    mean: f64,
+    m2: f64
+}
+
+impl StreamingStats {
+
+    pub fn add(self: &mut Self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta/self.count as f64;
+        self.m2 += delta*(value - self.mean);
+    }
+
+    pub fn count(self: &Self) -> usize {
+        self.count
+    }
+
+    /// Same as calc_standard_deviation gives for all the values at once.
+    pub fn get_standard_deviation(self: &Self, deviation_kind: DeviationKind) -> f64 {
+        let divisor = match deviation_kind {
+            DeviationKind::Sample => self.count as f64 - 1.0,
+            DeviationKind::Population => self.count as f64
+        };
+        if divisor > 0.0 {(self.m2/divisor).sqrt()} else {0.0}
+    }
+}
+
+
+// Testing significance of differences
+
+const SIGNIFICANCE_LEVEL: f64 = 0.05;
+const BETA_ITERATIONS_MAX: usize = 200;
+const BETA_EPSILON: f64 = 1e-12;
+
+fn calc_variance(values: &[f64]) -> f64 {
+    let mean = calc_mean(values);
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>()/(values.len() - 1) as f64
+}
+
+/// The Lanczos approximation of ln Γ(x).
+fn ln_gamma(x: f64) -> f64 {
+
+    const COEFFICIENTS: [f64; 6] = [76.18009172947146, -86.50532032941677, 24.01409824083091,
+                                    -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5];
+
+    let tmp = x + 5.5 - (x + 0.5)*(x + 5.5).ln();
+    let series = COEFFICIENTS.iter().enumerate()
+        .fold(1.000000000190015, |sum, (idx, c)| sum + c/(x + 1.0 + idx as f64));
+
+    -tmp + (2.5066282746310005*series/x).ln()
+}
+
+/// A continued fraction for the incomplete beta function, evaluated by the modified Lentz's method.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+
+    let tiny = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b)*x/(a + 1.0);
+    d = 1.0/if d.abs() < tiny {tiny} else {d};
+    let mut fraction = d;
+
+    for m in 1..BETA_ITERATIONS_MAX + 1 {
+
+        let m = m as f64;
+
+        for numerator in [m*(b - m)*x/((a + 2.0*m - 1.0)*(a + 2.0*m)),
+                          -(a + m)*(a + b + m)*x/((a + 2.0*m)*(a + 2.0*m + 1.0))] {
+            d = 1.0 + numerator*d;
+            d = 1.0/if d.abs() < tiny {tiny} else {d};
+            c = 1.0 + numerator/c;
+            c = if c.abs() < tiny {tiny} else {c};
+            fraction *= c*d;
+        }
+
+        if (c*d - 1.0).abs() < BETA_EPSILON {
+            break;
+        }
+    }
+
+    fraction
+}
+
+fn regularized_incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+
+    if x <= 0.0 {
+        return 0.0;
+    } else if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a*x.ln() + b*(1.0 - x).ln()).exp();
+
+    if x < (a + 1.0)/(a + b + 2.0) {
+        front*beta_continued_fraction(a, b, x)/a
+    } else {
+        1.0 - front*beta_continued_fraction(b, a, 1.0 - x)/b
+    }
+}
+
+/// A two-sided p-value of Welch's t-test, which does not assume equal variances.
+pub fn welch_t_test(a: &[f64], b: &[f64]) -> Option<f64> {
+
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+
+    let (a_error, b_error) = (calc_variance(a)/a.len() as f64, calc_variance(b)/b.len() as f64);
+    let error = a_error + b_error;
+    let difference = calc_mean(a) - calc_mean(b);
+
+    if error == 0.0 {
+        return Some(if difference == 0.0 {1.0} else {0.0});
+    }
+
+    let t = difference/error.sqrt();
+    let freedom = error.powi(2)/(a_error.powi(2)/(a.len() - 1) as f64 + 
+                                 b_error.powi(2)/(b.len() - 1) as f64);
+
+    Some(regularized_incomplete_beta(freedom/2.0, 0.5, freedom/(freedom + t*t)))
+}
+
+pub fn is_significant(p_value: f64) -> bool {
+    p_value < SIGNIFICANCE_LEVEL
+}
+
+
+// Fitting Amdahl's law
+
+/// The serial fraction best fitting speedups on numbers of workers, by least squares on
+/// 1/speedup - 1/n = f*(1 - 1/n), which Amdahl's law makes linear in f.
+pub fn fit_serial_fraction(points: &[(f64, f64)]) -> Option<f64> {
+
+    let (numerator, denominator) = points.iter()
+        .filter(|(n, speedup)| *n > 1.0 && *speedup > 0.0)
+        .fold((0.0, 0.0), |(numerator, denominator), (n, speedup)| {
+            let x = 1.0 - 1.0/n;
+            (numerator + x*(1.0/speedup - 1.0/n), denominator + x*x)
+        });
+
+    if denominator > 0.0 {
+        Some((numerator/denominator).clamp(0.0, 1.0))
+    } else {
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use proptest::prelude::*;
+
+    const DURATIONS: [f64; 6] = [101.0, 99.0, 103.0, 98.0, 100.0, 102.0];
+
+    #[test]
+    fn serial_fraction_of_amdahl_speedups_is_found() {
+        let points: Vec<(f64, f64)> = [1.0, 2.0, 4.0, 8.0].iter()
+            .map(|n| (*n, 1.0/(0.1 + 0.9/n)))
+            .collect();
+        assert!((fit_serial_fraction(&points).unwrap() - 0.1).abs() < 1e-9);
+        assert_eq!(fit_serial_fraction(&[(1.0, 1.0)]), None);
+    }
+
+    #[test]
+    fn interval_needs_two_values() {
+        assert_eq!(bootstrap_mean_interval(&[], 1), None);
+        assert_eq!(bootstrap_mean_interval(&[100.0], 1), None);
+    }
+
+    #[test]
+    fn interval_is_reproducible_and_holds_mean() {
+        let interval = bootstrap_mean_interval(&DURATIONS, 1).unwrap();
+        assert_eq!(Some(interval), bootstrap_mean_interval(&DURATIONS, 1));
+        assert!(interval.0 <= calc_mean(&DURATIONS) && calc_mean(&DURATIONS) <= interval.1);
+        assert!(get_half_width(interval) > 0.0);
+    }
+
+    #[test]
+    fn gamma_of_integers_is_factorial() {
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-9);
+        assert!((ln_gamma(1.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn same_samples_are_not_significantly_different() {
+        assert_eq!(welch_t_test(&DURATIONS, &DURATIONS), Some(1.0));
+        assert_eq!(welch_t_test(&DURATIONS, &[100.0]), None);
+    }
+
+    #[test]
+    fn shifted_samples_are_significantly_different() {
+        let shifted: Vec<f64> = DURATIONS.iter().map(|d| d + 20.0).collect();
+        let p_value = welch_t_test(&DURATIONS, &shifted).unwrap();
+        assert!(is_significant(p_value), "p-value {} is not significant", p_value);
+    }
+
+    #[test]
+    fn constant_samples_differ_certainly() {
+        assert_eq!(welch_t_test(&[1.0, 1.0], &[2.0, 2.0]), Some(0.0));
+    }
+
+    fn arb_durations() -> impl Strategy<Value = Vec<f64>> {
+        prop::collection::vec(0.0f64..10000.0, 2..32)
+    }
+
+    proptest! {
+
+        #[test]
+        fn interval_lies_within_values(values in arb_durations(), seed in any::<u64>()) {
+            let (low, high) = bootstrap_mean_interval(&values, seed).unwrap();
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            // Means of equal values may round past them
+            let tolerance = 1e-9*max;
+            prop_assert!(min - tolerance <= low && low <= high && high <= max + tolerance);
+        }
+
+        #[test]
+        fn p_value_is_a_probability(a in arb_durations(), b in arb_durations()) {
+            let p_value = welch_t_test(&a, &b).unwrap();
+            prop_assert!((0.0..=1.0 + 1e-9).contains(&p_value), "p-value {}", p_value);
+        }
+
+        #[test]
+        fn streaming_deviation_matches_batch_one(values in arb_durations()) {
+            let mut stats = StreamingStats::default();
+            values.iter().for_each(|value| stats.add(*value));
+            let durations: Vec<TaskDuration> = values.iter()
+                .map(|value| TaskDuration::from_nanos((value*1e6) as u128))
+                .collect();
+            let deviation = calc_standard_deviation(&durations, DeviationKind::Sample);
+            prop_assert!((stats.get_standard_deviation(DeviationKind::Sample) - deviation).abs() < 1e-3);
+        }
+
+        #[test]
+        fn p_value_is_symmetric(a in arb_durations(), b in arb_durations()) {
+            let (ab, ba) = (welch_t_test(&a, &b).unwrap(), welch_t_test(&b, &a).unwrap());
+            prop_assert!((ab - ba).abs() < 1e-9);
+        }
+    }
+}
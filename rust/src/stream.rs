@@ -0,0 +1,128 @@
+// * * ** *** ***** ******** ************* *********************
+// Streaming observations to asynchronous embedders
+// * * ** *** ***** ******** ************* *********************
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use futures_core::Stream;
+
+use embed::ObservationRecord;
+
+use super::*;
+
+
+// Sharing observations between the measuring thread and the stream
+
+#[derive(Default)]
+struct StreamState {
+    observations: VecDeque<ObservationRecord>,
+    waker: Option<Waker>,
+    finished: bool,
+    dropped: bool
+}
+
+type SharedState = Arc<Mutex<StreamState>>;
+
+fn push_observation(state: &SharedState, obs: ObservationRecord) {
+    let mut state = state.lock().unwrap();
+    state.observations.push_back(obs);
+    if let Some(waker) = state.waker.take() {
+        waker.wake();
+    }
+}
+
+fn finish_stream(state: &SharedState) {
+    let mut state = state.lock().unwrap();
+    state.finished = true;
+    if let Some(waker) = state.waker.take() {
+        waker.wake();
+    }
+}
+
+
+// Stream of observations
+
+/// Yields totals of observations of the numbers of tasks configured as they complete.
+pub struct ObservationStream {
+    state: SharedState
+}
+
+impl Stream for ObservationStream {
+
+    type Item = ObservationRecord;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<ObservationRecord>> {
+
+        let mut state = self.state.lock().unwrap();
+
+        match state.observations.pop_front() {
+            Some(obs) => Poll::Ready(Some(obs)),
+            None if state.finished => Poll::Ready(None),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Dropping the stream stops measuring after the observation in progress.
+impl Drop for ObservationStream {
+    fn drop(self: &mut Self) {
+        self.state.lock().unwrap().dropped = true;
+    }
+}
+
+/// Measures on a dedicated thread, so an async runtime is never blocked by observing.
+pub fn observe_stream(config: RunConfig) -> ObservationStream {
+
+    let state: SharedState = Arc::new(Mutex::new(StreamState::default()));
+    let measuring_state = Arc::clone(&state);
+
+    thread::spawn(move || {
+        for n_tasks in config.get_tasks() {
+            if measuring_state.lock().unwrap().dropped {
+                break;
+            }
+            let obs = observe_repeatedly(n_tasks, &config);
+            if config.is_cancelled() {
+                break;
+            }
+            push_observation(&measuring_state, ObservationRecord::create(&obs));
+        }
+        finish_stream(&measuring_state);
+    });
+
+    ObservationStream {state}
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    /// Polls as an executor would, without one, sleeping while the measuring thread works.
+    fn collect_stream(mut stream: ObservationStream) -> Vec<ObservationRecord> {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut observations: Vec<ObservationRecord> = Vec::new();
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                Poll::Ready(Some(obs)) => observations.push(obs),
+                Poll::Ready(None) => return observations,
+                Poll::Pending => thread::sleep(Duration::from_millis(1))
+            }
+        }
+    }
+
+    #[test]
+    fn streams_yield_every_observation_in_order() {
+        let config = RunConfig::new().tasks(1..=3).cycles(1000000).series_size(2).backend("simulated");
+        let observations = collect_stream(observe_stream(config));
+        assert_eq!(observations.iter().map(|obs| obs.n_tasks).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(observations[0].total_duration_ms, 97);
+    }
+}
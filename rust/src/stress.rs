@@ -0,0 +1,133 @@
+// * * ** *** ***** ******** ************* *********************
+// Stressing cores to check sustained performance
+// * * ** *** ***** ******** ************* *********************
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::thread;
+
+use super::*;
+
+
+// Keeping workers busy
+
+const STRESS_CHUNK_CYCLES: usize = 100000;
+const STRESS_SAMPLE_PERIOD: Duration = Duration::from_secs(1);
+
+fn keep_busy(worker_idx: usize, seed: u64, counter: &AtomicU64, stopped: &AtomicBool) {
+
+    let mut triplet = random_triplet(&mut StdRng::seed_from_u64(derive_task_seed(seed, 0, worker_idx)));
+
+    while !stopped.load(Ordering::Relaxed) {
+        let member = iterate(triplet, STRESS_CHUNK_CYCLES);
+        triplet = (triplet.1, triplet.2, member);
+        counter.fetch_add(STRESS_CHUNK_CYCLES as u64, Ordering::Relaxed);
+    }
+}
+
+
+// Sampling rates of workers
+
+fn create_stress_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Elapsed, sec.", 13, Align::Right, 0),
+        Column::create("Thread", 6, Align::Right, 0),
+        Column::create("Cycles/sec", 16, Align::Right, 0),
+        Column::create("Of the mean", 11, Align::Right, 1)
+    ], colored)
+}
+
+/// A row a thread, so a thread slowing down over time stands out against the others.
+fn print_stress_sample(table: &ConsoleTable, elapsed: TaskDuration, rates: &[u64]) {
+
+    let mean = rates.iter().sum::<u64>() as f64/rates.len().max(1) as f64;
+
+    for (idx, rate) in rates.iter().enumerate() {
+        let elapsed_cell = if idx == 0 {elapsed.as_duration().as_secs().to_string()} else {"".to_string()};
+        let deviation = if mean > 0.0 {*rate as f64/mean - 1.0} else {0.0};
+        table.print_row(&vec![
+            Cell::Plain(elapsed_cell),
+            Cell::Plain((idx + 1).to_string()),
+            Cell::Plain(format_count(*rate as usize)),
+            Cell::Signed(format_percent_cell(1.0 + deviation), deviation)
+        ]);
+    }
+}
+
+fn create_worker_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Thread", 6, Align::Right, 0),
+        Column::create("Mean cycles/sec", 16, Align::Right, 0)
+    ], colored)
+}
+
+fn print_worker_totals(colored: bool, counters: &[AtomicU64], duration: TaskDuration) {
+
+    let table = create_worker_table(colored);
+    table.print_header();
+
+    for (idx, counter) in counters.iter().enumerate() {
+        let rate = 1000*counter.load(Ordering::Relaxed)/duration.as_millis().max(1);
+        table.print_row(&vec![
+            Cell::Plain((idx + 1).to_string()),
+            Cell::Plain(format_count(rate as usize))
+        ]);
+    }
+
+    table.print_footer();
+}
+
+
+// Running a stress
+
+pub fn run_stress(args: &Args) -> i32 {
+
+    let n_threads = if args.get_tasks_max() > 0 {args.get_tasks_max()} else {count_cpus()};
+    let counters: Vec<AtomicU64> = (0..n_threads).map(|_| AtomicU64::new(0)).collect();
+    let stopped = AtomicBool::new(false);
+
+    info!("Stressing {} threads for {} sec.", n_threads, args.get_duration().as_duration().as_secs());
+
+    let watch = SystemTime::now();
+
+    thread::scope(|scope| {
+
+        for (worker_idx, counter) in counters.iter().enumerate() {
+            let stopped = &stopped;
+            let seed = args.get_seed();
+            let name = format!("conctest-stress-{}", worker_idx + 1);
+            platform::create_worker_builder(name).spawn_scoped(scope, move || {
+                let _ = platform::prepare_worker_thread();
+                keep_busy(worker_idx, seed, counter, stopped)
+            }).expect("Spawning a worker failed");
+        }
+
+        let table = create_stress_table(args.get_colored());
+        table.print_header();
+
+        let mut previous: Vec<u64> = vec![0; n_threads];
+        let mut previous_elapsed = TaskDuration::ZERO;
+
+        while read_elapsed(&watch) < args.get_duration() {
+            thread::sleep(STRESS_SAMPLE_PERIOD);
+            let elapsed = read_elapsed(&watch);
+            let current: Vec<u64> = counters.iter().map(|c| c.load(Ordering::Relaxed)).collect();
+            let period = (elapsed - previous_elapsed).as_millis().max(1);
+            let rates: Vec<u64> = current.iter().zip(&previous)
+                .map(|(current, previous)| 1000*(current - previous)/period)
+                .collect();
+            if previous_elapsed > TaskDuration::ZERO {
+                table.print_separator();
+            }
+            print_stress_sample(&table, elapsed, &rates);
+            previous = current;
+            previous_elapsed = elapsed;
+        }
+
+        stopped.store(true, Ordering::Relaxed);
+        table.print_footer();
+    });
+
+    print_worker_totals(args.get_colored(), &counters, read_elapsed(&watch));
+
+    EXIT_SUCCESS
+}
@@ -0,0 +1,141 @@
+// * * ** *** ***** ******** ************* *********************
+// Running several named configurations from a suite file
+// * * ** *** ***** ******** ************* *********************
+
+use std::fs;
+use serde::Deserialize;
+
+use super::*;
+
+
+// Describing runs of a suite
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SuiteRun {
+    name: String,
+    tasks: usize,
+    cycles: usize,
+    series: usize,
+    backend: Option<String>,
+    task_timeout: Option<String>,
+    observation_timeout: Option<String>,
+    retries: Option<usize>,
+    max_runtime: Option<String>,
+    seed: Option<u64>
+}
+
+impl SuiteRun {
+
+    /// Settings of a run override the ones given on the command line.
+    fn apply(self: &Self, args: &Args) -> Args {
+
+        let mut run_args = args.clone();
+
+        run_args.tasks_max = self.tasks;
+        run_args.n_cycles = self.cycles;
+        run_args.series_size = self.series;
+        run_args.label = self.name.clone();
+        run_args.backend = self.backend.clone().unwrap_or(args.get_backend());
+        run_args.out_file_path = "".to_string();
+
+        let task_timeout = self.task_timeout.as_deref()
+            .map_or(Some(args.get_timeouts().task), parse_duration_ms);
+        let observation_timeout = self.observation_timeout.as_deref()
+            .map_or(Some(args.get_timeouts().observation), parse_duration_ms);
+        run_args.timeouts = task_timeout.zip(observation_timeout)
+            .map(|(task, observation)| Timeouts::create(task, observation));
+
+        run_args.retries_max = self.retries.unwrap_or(args.get_retries_max());
+        run_args.max_runtime = self.max_runtime.as_deref()
+            .map_or(args.max_runtime, parse_duration_ms);
+        run_args.seed = self.seed.or(args.seed);
+
+        run_args
+    }
+}
+
+#[derive(Deserialize)]
+struct Suite {
+    run: Vec<SuiteRun>
+}
+
+fn load_suite(suite_file_path: &String) -> Suite {
+    match fs::read_to_string(Path::new(suite_file_path)) {
+        Ok(text) => match toml::from_str(&text) {
+            Ok(suite) => suite,
+            Err(e) => {
+                panic!("Error while parsing a suite file: {}", e);
+            }
+        },
+        Err(e) => {
+            panic!("Error while reading a suite file: {}", e);
+        }
+    }
+}
+
+
+// Running a suite and combining reports
+
+fn format_suite_report(reports: &[Report]) -> String {
+    reports.iter()
+        .map(format_report)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn run_suite(args: &Args) -> i32 {
+
+    let suite = load_suite(&args.get_suite_file_path());
+    let mut reports: Vec<Report> = Vec::with_capacity(suite.run.len());
+    let mut exit_code = EXIT_SUCCESS;
+
+    for run in &suite.run {
+
+        let run_args = run.apply(args);
+
+        if !run_args.is_valid() {
+            warn!("Run {} skipped, its parameters are invalid", run.name);
+            continue;
+        }
+
+        info!("\nRun: {}", run.name);
+
+        let environment_before = EnvironmentSnapshot::capture();
+        let mut report = Report::create(run_args.get_tasks_max());
+        register_run_metadata(&mut report, &run_args, read_timestamp(&SystemTime::now()));
+        let report = test_concurrency_profit(&run_args, report);
+
+        if run_args.get_show_schedule() {
+            print_schedules(&report);
+        }
+        if run_args.get_work_span() {
+            workspan::print_work_span(&report, run_args.get_colored());
+        }
+        history::append_history(&run_args.get_history_file_path(), &report);
+        exit_code = exit_code.max(assess_measuring(&run_args, &report, &environment_before));
+
+        reports.push(report);
+    }
+
+    save_report_text(&args.get_out_file_path(), &format_suite_report(&reports));
+
+    exit_code
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::{assert_golden, FakeWorkload};
+
+    #[test]
+    fn suite_report_matches_golden_file() {
+        let reports = vec![
+            FakeWorkload::create(2, 2, 100).create_report(1..=3),
+            FakeWorkload::create(4, 4, 50).create_report(1..=3)
+        ];
+        assert_golden("suite.csv", &format_suite_report(&reports));
+    }
+}
@@ -0,0 +1,173 @@
+// * * ** *** ***** ******** ************* *********************
+// Rendering charts and schedules as SVG
+// * * ** *** ***** ******** ************* *********************
+
+use embed::{ReportRecords, TaskRecord};
+
+use super::*;
+
+
+// Drawing
+
+const CHART_WIDTH: f64 = 800.0;
+const CHART_HEIGHT: f64 = 400.0;
+const CHART_MARGIN: f64 = 50.0;
+const SCHEDULE_ROW_HEIGHT: f64 = 16.0;
+
+const SERIES_COLORS: [&str; 4] = ["#4e79a7", "#59a14f", "#9c755f", "#76b7b2"];
+const TIMED_OUT_COLOR: &str = "#f28e2b";
+const FAILED_COLOR: &str = "#e15759";
+
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn open_svg(width: f64, height: f64, title: &str) -> String {
+    format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.0}\" height=\"{:.0}\" \
+             font-family=\"sans-serif\" font-size=\"12\">\n\
+             <text x=\"{:.0}\" y=\"20\" text-anchor=\"middle\" font-size=\"14\">{}</text>\n",
+            width, height, width/2.0, escape_xml(title))
+}
+
+fn draw_axes(x_label: &str, y_label: &str, width: f64, height: f64) -> String {
+    format!("<line x1=\"{m:.0}\" y1=\"{b:.0}\" x2=\"{r:.0}\" y2=\"{b:.0}\" stroke=\"black\"/>\n\
+             <line x1=\"{m:.0}\" y1=\"{m:.0}\" x2=\"{m:.0}\" y2=\"{b:.0}\" stroke=\"black\"/>\n\
+             <text x=\"{cx:.0}\" y=\"{ly:.0}\" text-anchor=\"middle\">{}</text>\n\
+             <text x=\"15\" y=\"{cy:.0}\" text-anchor=\"middle\" transform=\"rotate(-90 15 {cy:.0})\">{}</text>\n",
+            escape_xml(x_label), escape_xml(y_label),
+            m = CHART_MARGIN, b = height - CHART_MARGIN, r = width - CHART_MARGIN,
+            cx = width/2.0, ly = height - 15.0, cy = height/2.0)
+}
+
+/// Tasks are colored by series, interrupted ones stand out whatever their series.
+fn get_task_color(task: &TaskRecord) -> &'static str {
+    match task.status.as_str() {
+        "completed" => SERIES_COLORS[(task.series - 1) % SERIES_COLORS.len()],
+        "timed out" => TIMED_OUT_COLOR,
+        _ => FAILED_COLOR
+    }
+}
+
+
+// Charts
+
+/// Speedup of every observation against the ideal one, points telling their numbers on hover.
+pub fn render_speedup_chart(records: &ReportRecords) -> String {
+    render_speedup_comparison(&[("", records)])
+}
+
+/// Speedups of several runs on one chart, a line per run, labelled in a legend unless alone.
+pub fn render_speedup_comparison(runs: &[(&str, &ReportRecords)]) -> String {
+
+    let observations = || runs.iter().flat_map(|(_, records)| records.observations.iter());
+    let n_tasks_max = observations().map(|obs| obs.n_tasks).max().unwrap_or(1) as f64;
+    let speedup_max = observations().map(|obs| obs.speedup).fold(n_tasks_max, f64::max);
+
+    let (plot_width, plot_height) = (CHART_WIDTH - 2.0*CHART_MARGIN, CHART_HEIGHT - 2.0*CHART_MARGIN);
+    let scale_x = |n_tasks: f64| CHART_MARGIN + plot_width*n_tasks/n_tasks_max;
+    let scale_y = |speedup: f64| CHART_HEIGHT - CHART_MARGIN - plot_height*speedup/speedup_max;
+
+    let mut svg = open_svg(CHART_WIDTH, CHART_HEIGHT, "Speedup vs. tasks");
+    svg += &draw_axes("Tasks", "Speedup", CHART_WIDTH, CHART_HEIGHT);
+    svg += &format!("<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#bbb\" \
+                     stroke-dasharray=\"4\"><title>Ideal speedup</title></line>\n",
+                    scale_x(0.0), scale_y(0.0), scale_x(n_tasks_max), scale_y(n_tasks_max));
+
+    for (run_idx, (label, records)) in runs.iter().enumerate() {
+
+        let color = SERIES_COLORS[run_idx % SERIES_COLORS.len()];
+        let prefix = if !label.is_empty() {format!("{}, ", escape_xml(label))} else {"".to_string()};
+
+        let points: Vec<String> = records.observations.iter()
+            .map(|obs| format!("{:.1},{:.1}", scale_x(obs.n_tasks as f64), scale_y(obs.speedup)))
+            .collect();
+        svg += &format!("<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+                        points.join(" "), color);
+
+        for obs in &records.observations {
+            svg += &format!("<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"4\" fill=\"{}\">\
+                             <title>{}{} tasks: speedup {:.2}, total {} ms, profit {:.1}%</title></circle>\n",
+                            scale_x(obs.n_tasks as f64), scale_y(obs.speedup), color, prefix,
+                            obs.n_tasks, obs.speedup, obs.total_duration_ms, 100.0*obs.profit);
+        }
+
+        if runs.len() > 1 {
+            let y = CHART_MARGIN + 16.0*run_idx as f64;
+            svg += &format!("<rect x=\"{:.0}\" y=\"{:.0}\" width=\"12\" height=\"12\" fill=\"{}\"/>\
+                             <text x=\"{:.0}\" y=\"{:.0}\">{}</text>\n",
+                            CHART_MARGIN + 10.0, y, color, CHART_MARGIN + 28.0, y + 10.0, escape_xml(label));
+        }
+    }
+
+    svg + "</svg>\n"
+}
+
+/// A Gantt chart of an observation, a row per task and a bar per run, nothing for unknown ones.
+pub fn render_schedule(records: &ReportRecords, n_tasks: usize) -> Option<String> {
+
+    let tasks: Vec<&TaskRecord> = records.tasks.iter().filter(|task| task.n_tasks == n_tasks).collect();
+    if tasks.is_empty() {
+        return None;
+    }
+
+    let total_ms = tasks.iter().map(|task| task.finished_ms).max().unwrap_or(0).max(1) as f64;
+    let height = 2.0*CHART_MARGIN + SCHEDULE_ROW_HEIGHT*tasks.len() as f64;
+    let plot_width = CHART_WIDTH - 2.0*CHART_MARGIN;
+    let scale_x = |ms: u64| CHART_MARGIN + plot_width*ms as f64/total_ms;
+
+    let mut svg = open_svg(CHART_WIDTH, height, &format!("Schedule of {} tasks", n_tasks));
+    svg += &draw_axes(&format!("Time, ms (total {:.0})", total_ms), "Task", CHART_WIDTH, height);
+
+    for (row, task) in tasks.iter().enumerate() {
+        let (x, y) = (scale_x(task.started_ms), CHART_MARGIN + SCHEDULE_ROW_HEIGHT*row as f64);
+        svg += &format!("<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\">\
+                         <title>Task {}, series {}: {}..{} ms, {}{}</title></rect>\n",
+                        x, y + 2.0, (scale_x(task.finished_ms) - x).max(1.0), SCHEDULE_ROW_HEIGHT - 4.0,
+                        get_task_color(task), task.task, task.series, task.started_ms, task.finished_ms,
+                        escape_xml(&task.status),
+                        if !task.thread.is_empty() {format!(", {}", escape_xml(&task.thread))} else {"".to_string()});
+    }
+
+    Some(svg + "</svg>\n")
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::{assert_golden, FakeWorkload};
+
+    fn create_records() -> ReportRecords {
+        ReportRecords::create(&FakeWorkload::create(2, 4, 100).create_report(1..=4))
+    }
+
+    #[test]
+    fn speedup_chart_matches_golden_file() {
+        assert_golden("speedup.svg", &render_speedup_chart(&create_records()));
+    }
+
+    #[test]
+    fn schedule_matches_golden_file() {
+        assert_golden("schedule.svg", &render_schedule(&create_records(), 3).unwrap());
+    }
+
+    #[test]
+    fn comparisons_draw_a_line_per_run() {
+        let (a, b) = (create_records(), create_records());
+        let svg = render_speedup_comparison(&[("linux", &a), ("macos", &b)]);
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert!(svg.contains(">macos</text>"));
+        assert!(svg.contains("<title>linux, 4 tasks:"));
+    }
+
+    #[test]
+    fn unknown_observations_have_no_schedule() {
+        assert!(render_schedule(&create_records(), 5).is_none());
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        assert_eq!(escape_xml("failed: <\"a\" & b>"), "failed: &lt;&quot;a&quot; &amp; b&gt;");
+    }
+}
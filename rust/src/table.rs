@@ -0,0 +1,167 @@
+// * * ** *** ***** ******** ************* *********************
+// Rendering tables in a console
+// * * ** *** ***** ******** ************* *********************
+
+use std::env;
+use std::io::IsTerminal;
+
+
+// Deciding on colors and width
+
+const ANSI_GREEN: &str = "\x1b[32m";
+const ANSI_RED: &str = "\x1b[31m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+pub fn is_color_enabled(no_color_option: bool) -> bool {
+    !no_color_option &&
+    env::var("NO_COLOR").map_or(true, |value| value.is_empty()) &&
+    std::io::stdout().is_terminal()
+}
+
+fn get_terminal_width() -> usize {
+    if std::io::stdout().is_terminal() {
+        match terminal_size::terminal_size() {
+            Some((terminal_size::Width(width), _)) => width as usize,
+            None => usize::MAX
+        }
+    } else {
+        usize::MAX
+    }
+}
+
+
+// Describing columns and cells
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Align {
+    Left,
+    Right
+}
+
+#[derive(Copy, Clone)]
+pub struct Column {
+    title: &'static str,
+    width: usize,
+    align: Align,
+    priority: usize
+}
+
+impl Column {
+
+    /// Columns of a lower priority are hidden first when a terminal is too narrow.
+    pub fn create(title: &'static str, width: usize, align: Align, priority: usize) -> Column {
+        Column{title, width, align, priority}
+    }
+}
+
+pub enum Cell {
+    Plain(String),
+    Signed(String, f64)
+}
+
+impl Cell {
+
+    fn get_text(self: &Self) -> &String {
+        match self {
+            Cell::Plain(text) => text,
+            Cell::Signed(text, _) => text
+        }
+    }
+
+    fn get_color(self: &Self) -> Option<&'static str> {
+        match self {
+            Cell::Signed(_, value) if *value > 0.0 => Some(ANSI_GREEN),
+            Cell::Signed(_, value) if *value < 0.0 => Some(ANSI_RED),
+            _ => None
+        }
+    }
+}
+
+
+// Printing a table row by row
+
+pub struct ConsoleTable {
+    columns: Vec<Column>,
+    visible: Vec<bool>,
+    colored: bool
+}
+
+impl ConsoleTable {
+
+    fn get_width(self: &Self) -> usize {
+        let visible_columns = self.columns.iter().zip(&self.visible).filter(|(_, v)| **v);
+        let (n_columns, width) = visible_columns
+            .fold((0, 0), |(n, width), (column, _)| (n + 1, width + column.width));
+        width + n_columns.max(1) - 1
+    }
+
+    fn fit_width(self: &mut Self, max_width: usize) {
+        while self.get_width() > max_width {
+            let least_important = self.columns.iter().zip(&self.visible)
+                .enumerate()
+                .filter(|(_, (column, visible))| **visible && column.priority > 0)
+                .min_by_key(|(_, (column, _))| column.priority);
+            match least_important {
+                Some((idx, _)) => self.visible[idx] = false,
+                None => return
+            }
+        }
+    }
+
+    fn align_text(text: &str, column: &Column) -> String {
+        match column.align {
+            Align::Left => format!("{:<width$}", text, width = column.width),
+            Align::Right => format!("{:>width$}", text, width = column.width)
+        }
+    }
+
+    fn format_row(self: &Self, cells: &Vec<Cell>) -> String {
+        self.columns.iter().zip(&self.visible).zip(cells)
+            .filter(|((_, visible), _)| **visible)
+            .map(|((column, _), cell)| {
+                let text = Self::align_text(cell.get_text(), column);
+                match cell.get_color() {
+                    Some(color) if self.colored => format!("{}{}{}", color, text, ANSI_RESET),
+                    _ => text
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn print_line(self: &Self, filler: &str) {
+        println!("{}", filler.repeat(self.get_width()));
+    }
+
+    pub fn print_header(self: &Self) {
+        let titles = self.columns.iter().map(|column| Cell::Plain(column.title.to_string()));
+        self.print_line("=");
+        println!("{}", self.format_row(&titles.collect()));
+        self.print_line("=");
+    }
+
+    pub fn print_row(self: &Self, cells: &Vec<Cell>) {
+        println!("{}", self.format_row(cells));
+    }
+
+    pub fn print_separator(self: &Self) {
+        self.print_line("-");
+    }
+
+    /// Labels go first, so narrow terminals keep them.
+    pub fn print_labelled_separator(self: &Self, filler: &str, label: &str) {
+        let head = format!("{} {} ", filler.repeat(2), label);
+        println!("{}{}", head, filler.repeat(self.get_width().saturating_sub(head.chars().count())));
+    }
+
+    pub fn print_footer(self: &Self) {
+        self.print_line("=");
+    }
+
+    pub fn create(columns: Vec<Column>, colored: bool) -> ConsoleTable {
+        let visible = vec![true; columns.len()];
+        let mut table = ConsoleTable{columns, visible, colored};
+        table.fit_width(get_terminal_width());
+        table
+    }
+}
@@ -0,0 +1,109 @@
+// * * ** *** ***** ******** ************* *********************
+// Rendering saved reports with templates of users
+// * * ** *** ***** ******** ************* *********************
+
+use std::error::Error;
+use std::fs;
+
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use embed::ReportRecords;
+
+use super::*;
+
+
+// Data model
+
+/// A run as templates see it: its label and records, metadata as pairs of a name and a value,
+/// totals of observations and tasks as bindings get them.
+#[derive(Serialize)]
+struct TemplateRun<'a> {
+    label: &'a str,
+    #[serde(flatten)]
+    records: ReportRecords
+}
+
+/// All runs are given as runs, the first one is also given as report, so templates of single
+/// reports need no loop.
+fn create_context(runs: &[(String, Report)]) -> Context {
+
+    let template_runs: Vec<TemplateRun> = runs.iter()
+        .map(|(label, report)| TemplateRun {label, records: ReportRecords::create(report)})
+        .collect();
+
+    let mut context = Context::new();
+    if let Some(first) = template_runs.first() {
+        context.insert("report", first);
+    }
+    context.insert("runs", &template_runs);
+    context
+}
+
+
+// Rendering
+
+/// Templates of HTML pages have values escaped, other templates have them as they are.
+fn is_autoescaped(template_file_path: &str) -> bool {
+    matches!(Path::new(template_file_path).extension().and_then(|extension| extension.to_str()),
+             Some("html" | "htm"))
+}
+
+/// Tera tells what went wrong in the causes of its errors, lines of templates among them.
+fn format_template_error(e: &tera::Error) -> String {
+    let mut text = format!("Error while rendering a template: {}", e);
+    let mut source = e.source();
+    while let Some(cause) = source {
+        text += &format!(": {}", cause);
+        source = cause.source();
+    }
+    text
+}
+
+pub fn format_templated_runs(runs: &[(String, Report)], template_text: &str,
+                             autoescaped: bool) -> Result<String, String> {
+    Tera::one_off(template_text, &create_context(runs), autoescaped)
+        .map_err(|e| format_template_error(&e))
+}
+
+pub fn render_template(runs: &[(String, Report)], template_file_path: &str) -> Result<String, String> {
+    let template_text = fs::read_to_string(template_file_path)
+        .map_err(|e| format!("Error while reading {}: {}", template_file_path, e))?;
+    format_templated_runs(runs, &template_text, is_autoescaped(template_file_path))
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::FakeWorkload;
+
+    #[test]
+    fn templates_see_the_report_data_model() {
+
+        let runs = vec![("linux".to_string(), FakeWorkload::create(2, 4, 100).create_report(1..=4))];
+        let template_text = "{{ report.label }}:{% for obs in report.observations %} \
+                             {{ obs.n_tasks }}={{ obs.max_running }}{% endfor %}; \
+                             {{ report.tasks | length }} tasks; {{ report.metadata.0.0 }}";
+
+        assert_eq!(format_templated_runs(&runs, template_text, false).unwrap(),
+                   "linux: 1=1 2=2 3=2 4=2; 10 tasks; Started");
+    }
+
+    #[test]
+    fn templates_escape_html_pages_only() {
+        let runs = vec![("<b>".to_string(), FakeWorkload::create(2, 2, 100).create_report(1..=1))];
+        assert_eq!(format_templated_runs(&runs, "{% for run in runs %}{{ run.label }}{% endfor %}", true).unwrap(),
+                   "&lt;b&gt;");
+        assert_eq!(format_templated_runs(&runs, "{{ report.label }}", false).unwrap(), "<b>");
+        assert!(is_autoescaped("brand/report.html") && !is_autoescaped("report.tpl"));
+    }
+
+    #[test]
+    fn broken_templates_are_reported() {
+        let runs = vec![("linux".to_string(), FakeWorkload::create(2, 2, 100).create_report(1..=1))];
+        assert!(format_templated_runs(&runs, "{{ report.nothing }}", false).is_err());
+        assert!(format_templated_runs(&runs, "{% for %}", false).is_err());
+    }
+}
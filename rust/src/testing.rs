@@ -0,0 +1,117 @@
+// * * ** *** ***** ******** ************* *********************
+// Faking workloads and keeping golden files for tests
+// * * ** *** ***** ******** ************* *********************
+
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use super::*;
+use super::simulate::{MachineModel, SimulatedClock};
+
+
+// Faking workloads
+
+const FAKE_EPOCH: Timestamp = Timestamp::from_millis(1700000000000);
+const FAKE_CYCLES: usize = 1000;
+const FAKE_SEED: u64 = 42;
+
+const FAKE_METADATA: [(&str, &str); 6] = [
+    ("Started", "2023-11-14T22:13:20Z"),
+    ("Commit", "abc1234"),
+    ("Label", "fake"),
+    ("Cycles in a task", "1000"),
+    ("Seed", "42"),
+    ("Backend", "threads")
+];
+
+/// An ideal machine running tasks of a fixed duration, as many at a time as it has CPUs.
+/// Tasks take virtual time, so observations come out the same on every machine.
+pub struct FakeWorkload {
+    pub model: MachineModel,
+    pub series_size: usize,
+    pub task_duration: TaskDuration
+}
+
+impl FakeWorkload {
+
+    pub fn create(n_cpus: usize, series_size: usize, task_duration_ms: u64) -> FakeWorkload {
+        FakeWorkload {
+            model: MachineModel::create(n_cpus),
+            series_size,
+            task_duration: TaskDuration::from_millis(task_duration_ms)
+        }
+    }
+
+    pub fn run_task(self: &Self, task_idx: usize) -> Task {
+        let start = FAKE_EPOCH + self.model.place_task(task_idx, self.series_size, self.task_duration);
+        Task::create(task_idx, start, self.task_duration)
+    }
+
+    /// Observes through the measurement engine, only the tasks are faked. Thread IDs and
+    /// CPU utilization differ from run to run, so they are dropped.
+    pub fn observe(self: &Self, n_tasks: usize) -> Observation {
+
+        let clock = SimulatedClock::create(FAKE_EPOCH);
+        let mut obs = observe_workload(&clock, n_tasks, FAKE_CYCLES, self.series_size, FAKE_SEED,
+                                       &|task_idx, _, task_seed| {
+                                           let mut task = self.run_task(task_idx);
+                                           task.set_seed(task_seed);
+                                           task
+                                       });
+
+        for task in obs.tasks.iter_mut() {
+            let thread_name = task.get_thread_name().clone();
+            task.set_thread(thread_name, 0);
+        }
+        obs.set_cpu_utilization(None);
+        obs.set_memory_usage(None);
+
+        obs
+    }
+
+    pub fn create_report(self: &Self, tasks: RangeInclusive<usize>) -> Report {
+
+        let mut report = Report::create(*tasks.end());
+
+        for (key, value) in FAKE_METADATA {
+            report.set_metadata(key, value);
+        }
+        report.set_metadata("Effective CPUs", &self.model.n_cpus.to_string());
+
+        for n_tasks in tasks {
+            report.register_observation(self.observe(n_tasks));
+        }
+
+        report
+    }
+}
+
+
+// Comparing with golden files
+
+const ENV_UPDATE_GOLDEN: &str = "CONCTEST_UPDATE_GOLDEN";
+
+fn get_golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(name)
+}
+
+/// A changed format is accepted by running tests with CONCTEST_UPDATE_GOLDEN=1
+/// and reviewing the diff of golden files.
+pub fn assert_golden(name: &str, actual: &str) {
+
+    let path = get_golden_path(name);
+
+    if !read_env(ENV_UPDATE_GOLDEN).is_empty() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = match fs::read_to_string(&path) {
+        Ok(text) => text.replace("\r\n", "\n"),
+        Err(e) => panic!("Error while reading a golden file {}: {}", path.display(), e)
+    };
+
+    assert!(actual == expected, "{} differs from the golden file:\n{}", name, actual);
+}
@@ -0,0 +1,73 @@
+// * * ** *** ***** ******** ************* *********************
+// Streaming observations to the Tracy profiler
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+
+
+// Connecting
+
+/// Tracy connects to builds with the tracy feature as they start, and other builds
+/// stream nothing.
+#[cfg(feature = "tracy")]
+pub fn start() {
+    let _ = tracy_client::Client::start();
+}
+
+#[cfg(not(feature = "tracy"))]
+pub fn start() {}
+
+
+// Zones
+
+/// A zone of a task or of a series, closed as dropped. Zones are named as they run, since
+/// tasks and series are counted at run time, and they belong to the threads opening them.
+pub struct Zone {
+    #[cfg(feature = "tracy")]
+    _span: Option<tracy_client::Span>
+}
+
+#[cfg(feature = "tracy")]
+fn enter_zone(name: &str, line: u32) -> Zone {
+    Zone {
+        _span: tracy_client::Client::running()
+            .map(|client| client.span_alloc(Some(name), "observe_workload", file!(), line, 0))
+    }
+}
+
+#[cfg(feature = "tracy")]
+pub fn enter_task(n_tasks: usize, task_idx: usize) -> Zone {
+    enter_zone(&format!("Task {} of {}", task_idx + 1, n_tasks), line!())
+}
+
+#[cfg(not(feature = "tracy"))]
+pub fn enter_task(_n_tasks: usize, _task_idx: usize) -> Zone {
+    Zone {}
+}
+
+#[cfg(feature = "tracy")]
+pub fn enter_series(n_tasks: usize, series_idx: usize) -> Zone {
+    enter_zone(&format!("Series {} of {} tasks", series_idx + 1, n_tasks), line!())
+}
+
+#[cfg(not(feature = "tracy"))]
+pub fn enter_series(_n_tasks: usize, _series_idx: usize) -> Zone {
+    Zone {}
+}
+
+
+// Plots
+
+/// Profits are known once observations are registered with their reports, so they are
+/// plotted an observation a point, next to the tasks the observation ran.
+#[cfg(feature = "tracy")]
+pub fn plot_observation(obs: &Observation) {
+    if let Some(client) = tracy_client::Client::running() {
+        client.plot(tracy_client::plot_name!("Tasks"), obs.count_tasks() as f64);
+        client.plot(tracy_client::plot_name!("Concurrency profit, %"), 100.0*obs.get_concurrency_profit());
+        client.plot(tracy_client::plot_name!("Speedup"), obs.get_speedup());
+    }
+}
+
+#[cfg(not(feature = "tracy"))]
+pub fn plot_observation(_obs: &Observation) {}
@@ -0,0 +1,354 @@
+// * * ** *** ***** ******** ************* *********************
+// Watching observations live in a terminal dashboard
+// * * ** *** ***** ******** ************* *********************
+
+use std::sync::mpsc;
+use std::thread;
+use std::time::*;
+use ratatui::DefaultTerminal;
+use ratatui::Frame;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Gauge, Paragraph, Row, Sparkline, Table};
+
+use super::*;
+
+
+// Sampling utilization of cores
+
+const CORE_SAMPLING_INTERVAL: Duration = Duration::from_millis(500);
+const CORE_SAMPLES_MAX: usize = 240;
+const CORES_SHOWN_MAX: usize = 16;
+
+/// Percentages of busy time of every core, a sample an interval, the latest last.
+struct CoreUtilization {
+    sampler: CpuSampler,
+    watch: SystemTime,
+    samples: Vec<Vec<u64>>
+}
+
+impl CoreUtilization {
+
+    fn sample(self: &mut Self) {
+
+        if self.watch.elapsed().unwrap_or(Duration::ZERO) < CORE_SAMPLING_INTERVAL {
+            return;
+        }
+
+        if let Some(utilization) = self.sampler.finish() {
+            self.samples.resize(utilization.per_core.len(), Vec::new());
+            for (samples, share) in self.samples.iter_mut().zip(&utilization.per_core) {
+                samples.push((100.0*share).round().min(100.0) as u64);
+                if samples.len() > CORE_SAMPLES_MAX {
+                    samples.remove(0);
+                }
+            }
+        }
+
+        self.sampler = CpuSampler::start();
+        self.watch = SystemTime::now();
+    }
+
+    fn create() -> CoreUtilization {
+        CoreUtilization {sampler: CpuSampler::start(), watch: SystemTime::now(), samples: Vec::new()}
+    }
+}
+
+
+// Receiving progress from the measuring thread
+
+enum Progress {
+    Started(usize),
+    Observed(Box<Observation>),
+    Skipped(usize)
+}
+
+struct Dashboard {
+    report: Report,
+    out_file_path: String,
+    tasks_max: usize,
+    n_tasks_current: usize,
+    observation_watch: SystemTime,
+    cores: CoreUtilization,
+    finished: bool
+}
+
+impl Dashboard {
+
+    fn start_observation(self: &mut Self, n_tasks: usize) {
+        self.n_tasks_current = n_tasks;
+        self.observation_watch = SystemTime::now();
+    }
+
+    fn register_observation(self: &mut Self, obs: Observation) {
+        let n_tasks = obs.count_tasks();
+        self.report.register_observation(obs);
+        tracy::plot_observation(self.report.find_observation(n_tasks).unwrap());
+        save_report(&self.out_file_path, &self.report);
+        self.update_finished();
+    }
+
+    fn register_skipped(self: &mut Self, n_tasks: usize) {
+        self.report.register_skipped(n_tasks);
+        save_report(&self.out_file_path, &self.report);
+        self.update_finished();
+    }
+
+    fn count_processed(self: &Self) -> usize {
+        self.report.count_observations() + self.report.skipped.len()
+    }
+
+    fn update_finished(self: &mut Self) {
+        self.finished = self.count_processed() == self.tasks_max;
+    }
+
+    fn create(args: &Args, report: Report) -> Dashboard {
+        let mut dashboard = Dashboard {
+            report,
+            out_file_path: args.get_out_file_path(),
+            tasks_max: args.get_tasks_max(),
+            n_tasks_current: 0,
+            observation_watch: SystemTime::now(),
+            cores: CoreUtilization::create(),
+            finished: false
+        };
+        dashboard.update_finished();
+        dashboard
+    }
+}
+
+
+// Drawing the dashboard
+
+fn draw_profit_table(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+
+    let rows: Vec<Row> = dashboard.report.observations.iter().map(|obs| {
+        Row::new(vec![
+            obs.count_tasks().to_string(),
+            obs.get_mean_task_duration().to_string(),
+            obs.get_standard_deviation().to_string(),
+            obs.get_total_duration().to_string(),
+            format!("{:.1}", obs.get_tasks_per_sec()),
+            format_percent_cell(obs.get_concurrency_cost()),
+            format_percent_cell(obs.get_concurrency_profit())
+        ])
+    }).collect();
+
+    let header = Row::new(vec!["Tasks", "Mean task duration", "Std. dev.",
+                               "Total duration", "Tasks/sec", "Cost", "Profit"]);
+
+    let widths = [Constraint::Length(6), Constraint::Length(19), Constraint::Length(10),
+                  Constraint::Length(15), Constraint::Length(10), Constraint::Length(6), 
+                  Constraint::Length(7)];
+
+    let n_visible = area.height.saturating_sub(3) as usize;
+    let n_skipped = rows.len().saturating_sub(n_visible);
+
+    frame.render_widget(
+        Table::new(rows.into_iter().skip(n_skipped), widths)
+            .header(header)
+            .block(Block::bordered().title("Concurrency profit")),
+        area);
+}
+
+/// A row a core, as many as fit, each a sparkline of its latest samples.
+fn draw_utilization(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+
+    let samples = &dashboard.cores.samples;
+    let title = match samples.len() {
+        0 => "CPU utilization per core, unavailable".to_string(),
+        n_cores if n_cores > CORES_SHOWN_MAX => 
+            format!("CPU utilization per core, %, {} of {} cores", CORES_SHOWN_MAX, n_cores),
+        _ => "CPU utilization per core, %".to_string()
+    };
+    let block = Block::bordered().title(title);
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let width = inner.width.saturating_sub(9) as usize;
+    for (idx, core_samples) in samples.iter().take(inner.height as usize).enumerate() {
+        let row = Rect {y: inner.y + idx as u16, height: 1, ..inner};
+        let [label_area, sparkline_area] = Layout::horizontal([
+            Constraint::Length(9),
+            Constraint::Min(1)
+        ]).areas(row);
+        frame.render_widget(Paragraph::new(format!("cpu{:<3}{:>3}", idx,
+                                                   core_samples.last().copied().unwrap_or(0))),
+                            label_area);
+        frame.render_widget(Sparkline::default()
+                                .data(&core_samples[core_samples.len().saturating_sub(width)..])
+                                .max(100),
+                            sparkline_area);
+    }
+}
+
+fn draw_progress(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+
+    let n_processed = dashboard.count_processed();
+
+    let label = if dashboard.finished {
+        "Finished, press any key to exit".to_string()
+    } else {
+        format!("Observing {} tasks for {} ms ({} of {})",
+                dashboard.n_tasks_current,
+                read_elapsed(&dashboard.observation_watch),
+                n_processed + 1,
+                dashboard.tasks_max)
+    };
+
+    frame.render_widget(
+        Gauge::default()
+            .ratio(n_processed as f64/dashboard.tasks_max as f64)
+            .label(label)
+            .block(Block::bordered().title("Progress")),
+        area);
+}
+
+fn draw_schedule(frame: &mut Frame, area: Rect, dashboard: &Dashboard) {
+
+    let mut lines: Vec<Line> = Vec::new();
+
+    if let Some(obs) = dashboard.report.observations.last() {
+        for task in &obs.tasks {
+            lines.push(Line::from(format!("{:5} |{}| {:>6} ms",
+                                          task.get_idx() + 1,
+                                          format_schedule_bar(obs, task),
+                                          task.get_duration())));
+        }
+    }
+
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::bordered().title("Latest schedule")),
+        area);
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard) {
+
+    let [table_area, utilization_area, progress_area, schedule_area] = Layout::vertical([
+        Constraint::Min(6),
+        Constraint::Length(dashboard.cores.samples.len().clamp(1, CORES_SHOWN_MAX) as u16 + 2),
+        Constraint::Length(3),
+        Constraint::Length(dashboard.tasks_max.min(16) as u16 + 2)
+    ]).areas(frame.area());
+
+    draw_profit_table(frame, table_area, dashboard);
+    draw_utilization(frame, utilization_area, dashboard);
+    draw_progress(frame, progress_area, dashboard);
+    draw_schedule(frame, schedule_area, dashboard);
+}
+
+
+// Running observations behind the dashboard
+
+fn spawn_measuring(args: Args, report: &Report) -> mpsc::Receiver<Progress> {
+
+    let (sender, receiver) = mpsc::channel();
+
+    let processed: Vec<usize> = report.observations.iter()
+        .map(|obs| obs.count_tasks())
+        .collect();
+    let mut task_duration_min: Option<TaskDuration> = if processed.is_empty() {
+        None
+    } else {
+        Some(report.get_task_duration_min())
+    };
+
+    thread::spawn(move || {
+        let config = args.get_run_config();
+        let (tasks_max, n_cycles, series_size) = 
+            (config.get_tasks_max(), config.get_n_cycles(), config.get_series_size());
+        let repeats = config.get_repeats();
+        let mut budget = RuntimeBudget::create(args.get_max_runtime(), n_cycles*repeats);
+        if let Some(task_duration) = task_duration_min {
+            budget.refine(task_duration*repeats);
+        }
+        for n_tasks in config.get_tasks() {
+            if processed.contains(&n_tasks) {
+                continue;
+            }
+            if !budget.allows(n_tasks, tasks_max, series_size) {
+                if sender.send(Progress::Skipped(n_tasks)).is_err() {
+                    return;
+                }
+                continue;
+            }
+            if sender.send(Progress::Started(n_tasks)).is_err() {
+                return;
+            }
+            let obs = observe_repeatedly(n_tasks, &config);
+            if cancel::is_cancelled() {
+                return;
+            }
+            let task_duration = *task_duration_min.get_or_insert(obs.get_total_duration());
+            budget.refine(task_duration*repeats);
+            if sender.send(Progress::Observed(Box::new(obs))).is_err() {
+                return;
+            }
+        }
+    });
+
+    receiver
+}
+
+fn is_key_pressed() -> bool {
+    match event::poll(Duration::from_millis(100)) {
+        Ok(true) => match event::read() {
+            Ok(Event::Key(key)) => key.kind == KeyEventKind::Press,
+            _ => false
+        },
+        _ => false
+    }
+}
+
+fn is_exit_key_pressed() -> bool {
+    match event::poll(Duration::from_millis(100)) {
+        Ok(true) => match event::read() {
+            Ok(Event::Key(key)) =>
+                key.kind == KeyEventKind::Press &&
+                (key.code == KeyCode::Char('q') || key.code == KeyCode::Esc),
+            _ => false
+        },
+        _ => false
+    }
+}
+
+fn run_dashboard(terminal: &mut DefaultTerminal, dashboard: &mut Dashboard,
+                 receiver: mpsc::Receiver<Progress>) {
+
+    while !dashboard.finished {
+
+        for progress in receiver.try_iter() {
+            match progress {
+                Progress::Started(n_tasks) => dashboard.start_observation(n_tasks),
+                Progress::Observed(obs) => dashboard.register_observation(*obs),
+                Progress::Skipped(n_tasks) => dashboard.register_skipped(n_tasks)
+            }
+        }
+
+        dashboard.cores.sample();
+
+        let _ = terminal.draw(|frame| draw(frame, dashboard));
+
+        if is_exit_key_pressed() {
+            return;
+        }
+    }
+
+    let _ = terminal.draw(|frame| draw(frame, dashboard));
+
+    while !is_key_pressed() {}
+}
+
+pub fn test_concurrency_profit(args: &Args, report: Report) -> Report {
+
+    let receiver = spawn_measuring(args.clone(), &report);
+    let mut dashboard = Dashboard::create(args, report);
+
+    let mut terminal = ratatui::init();
+    run_dashboard(&mut terminal, &mut dashboard, receiver);
+    ratatui::restore();
+    print_run_summary(&dashboard.report);
+
+    dashboard.report
+}
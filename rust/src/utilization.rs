@@ -0,0 +1,97 @@
+// * * ** *** ***** ******** ************* *********************
+// Sampling utilization of CPUs while observing
+// * * ** *** ***** ******** ************* *********************
+
+use std::fs;
+
+
+// Reading CPU times
+
+const CPU_TIMES_PATH: &str = "/proc/stat";
+
+/// Cumulative busy and idle times of a CPU in clock ticks, waiting for I/O counts as idle.
+/// Steal is the time a hypervisor gave the CPU of a virtual machine to other guests.
+#[derive(Copy, Clone)]
+struct CpuTimes {
+    busy: u64,
+    idle: u64,
+    steal: u64
+}
+
+fn parse_cpu_times(fields: &[&str]) -> Option<CpuTimes> {
+
+    let ticks: Vec<u64> = fields.iter().map(|field| field.parse().ok()).collect::<Option<_>>()?;
+    if ticks.len() < 5 {
+        return None;
+    }
+
+    // user, nice, system, idle, iowait, irq, softirq, steal; guests are already in user and nice
+    let idle = ticks[3] + ticks[4];
+    let steal = ticks.get(7).copied().unwrap_or(0);
+    let total: u64 = ticks.iter().take(8).sum();
+
+    Some(CpuTimes {busy: total - idle - steal, idle, steal})
+}
+
+/// The first entry sums up all CPUs, the rest follow the order of cores.
+fn read_cpu_times() -> Option<Vec<CpuTimes>> {
+
+    let text = fs::read_to_string(CPU_TIMES_PATH).ok()?;
+
+    text.lines()
+        .filter(|line| line.starts_with("cpu"))
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            parse_cpu_times(&fields[1..])
+        })
+        .collect()
+}
+
+
+// Comparing CPU times before and after an observation
+
+pub struct CpuUtilization {
+    pub total: f64,
+    pub steal: f64,
+    pub per_core: Vec<f64>
+}
+
+/// Shares of busy and stolen time in all the time passed.
+fn calc_utilization(before: &CpuTimes, after: &CpuTimes) -> (f64, f64) {
+    let busy = after.busy.saturating_sub(before.busy) as f64;
+    let idle = after.idle.saturating_sub(before.idle) as f64;
+    let steal = after.steal.saturating_sub(before.steal) as f64;
+    let total = busy + idle + steal;
+    if total > 0.0 {(busy/total, steal/total)} else {(0.0, 0.0)}
+}
+
+pub struct CpuSampler {
+    before: Option<Vec<CpuTimes>>
+}
+
+impl CpuSampler {
+
+    pub fn start() -> CpuSampler {
+        CpuSampler {before: read_cpu_times()}
+    }
+
+    /// Gives nothing where CPU times are unavailable, e.g. outside Linux.
+    pub fn finish(self: &Self) -> Option<CpuUtilization> {
+
+        let before = self.before.as_ref()?;
+        let after = read_cpu_times()?;
+        if before.len() != after.len() || before.is_empty() {
+            return None;
+        }
+
+        let (total, steal) = calc_utilization(&before[0], &after[0]);
+
+        Some(CpuUtilization {
+            total,
+            steal,
+            per_core: before[1..].iter().zip(&after[1..])
+                .map(|(before, after)| calc_utilization(before, after).0)
+                .collect()
+        })
+    }
+}
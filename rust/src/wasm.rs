@@ -0,0 +1,32 @@
+// * * ** *** ***** ******** ************* *********************
+// Rendering saved reports in a browser
+// * * ** *** ***** ******** ************* *********************
+
+use wasm_bindgen::prelude::*;
+
+use super::embed;
+use super::svg;
+
+
+// Rendering
+
+fn parse_records(json: &str) -> Result<embed::ReportRecords, JsError> {
+    embed::parse_records(json).map_err(|e| JsError::new(&e))
+}
+
+/// Numbers of tasks of the observations in a report, for a page to choose schedules from.
+#[wasm_bindgen(js_name = listObservations)]
+pub fn list_observations(json: &str) -> Result<Vec<usize>, JsError> {
+    Ok(parse_records(json)?.observations.iter().map(|obs| obs.n_tasks).collect())
+}
+
+#[wasm_bindgen(js_name = renderSpeedupChart)]
+pub fn render_speedup_chart(json: &str) -> Result<String, JsError> {
+    Ok(svg::render_speedup_chart(&parse_records(json)?))
+}
+
+#[wasm_bindgen(js_name = renderSchedule)]
+pub fn render_schedule(json: &str, n_tasks: usize) -> Result<String, JsError> {
+    svg::render_schedule(&parse_records(json)?, n_tasks)
+        .ok_or_else(|| JsError::new(&format!("No observation of {} tasks in the report", n_tasks)))
+}
@@ -0,0 +1,683 @@
+// * * ** *** ***** ******** ************* *********************
+// Choosing what tasks work on
+// * * ** *** ***** ******** ************* *********************
+
+use std::io::Write;
+use std::sync::RwLock;
+
+use flate2::Compression;
+use flate2::write::DeflateEncoder;
+
+use super::*;
+
+
+// Workloads
+
+pub const DEFAULT_WORKLOAD: &str = "standard";
+
+/// The standard workload keeps the FPU busy, while the branchy one keeps the branch predictor
+/// guessing wrong, so the two scale differently on SMT siblings. The syscalls one spends
+/// its time entering and leaving the kernel, and the pagefaults one in its memory management.
+/// The compress one mixes integer work and memory traffic, as real programs do,
+/// and the json one allocates a lot, as services parsing requests do. The regex one
+/// streams text through automata, whose tables compete for caches, and the stencil one
+/// sweeps an image row by row, so prefetchers know what comes next. The gpu one runs
+/// the standard arithmetic, and then the same total work offloaded to a GPU. The rwlock one
+/// reads and writes a table all tasks share, so they contend for its lock.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum WorkloadKind {
+    Standard,
+    Branchy,
+    Syscalls,
+    Pagefaults,
+    Compress,
+    Json,
+    Regex,
+    Stencil,
+    Gpu,
+    Rwlock
+}
+
+impl WorkloadKind {
+
+    pub fn parse(s: &str) -> Option<WorkloadKind> {
+        match s {
+            "standard" => Some(WorkloadKind::Standard),
+            "branchy" => Some(WorkloadKind::Branchy),
+            "syscalls" => Some(WorkloadKind::Syscalls),
+            "pagefaults" => Some(WorkloadKind::Pagefaults),
+            "compress" => Some(WorkloadKind::Compress),
+            "json" => Some(WorkloadKind::Json),
+            "regex" => Some(WorkloadKind::Regex),
+            "stencil" => Some(WorkloadKind::Stencil),
+            "gpu" => Some(WorkloadKind::Gpu),
+            "rwlock" => Some(WorkloadKind::Rwlock),
+            _ => None
+        }
+    }
+
+    pub fn get_name(self: &Self) -> &'static str {
+        match self {
+            WorkloadKind::Standard => "standard",
+            WorkloadKind::Branchy => "branchy",
+            WorkloadKind::Syscalls => "syscalls",
+            WorkloadKind::Pagefaults => "pagefaults",
+            WorkloadKind::Compress => "compress",
+            WorkloadKind::Json => "json",
+            WorkloadKind::Regex => "regex",
+            WorkloadKind::Stencil => "stencil",
+            WorkloadKind::Gpu => "gpu",
+            WorkloadKind::Rwlock => "rwlock"
+        }
+    }
+}
+
+static WORKLOAD_KIND: OnceLock<WorkloadKind> = OnceLock::new();
+
+pub fn set_workload_kind(workload_kind: WorkloadKind) {
+    let _ = WORKLOAD_KIND.set(workload_kind);
+}
+
+pub fn get_workload_kind() -> WorkloadKind {
+    *WORKLOAD_KIND.get().unwrap_or(&WorkloadKind::Standard)
+}
+
+pub const DEFAULT_SYSCALLS: usize = 1;
+
+static N_SYSCALLS: OnceLock<usize> = OnceLock::new();
+
+pub fn set_n_syscalls(n_syscalls: usize) {
+    let _ = N_SYSCALLS.set(n_syscalls);
+}
+
+/// System calls a cycle of the syscalls workload makes.
+pub fn get_n_syscalls() -> usize {
+    *N_SYSCALLS.get().unwrap_or(&DEFAULT_SYSCALLS)
+}
+
+pub const DEFAULT_MAP_SIZE: usize = 64*1024;
+
+static MAP_SIZE: OnceLock<usize> = OnceLock::new();
+
+pub fn set_map_size(map_size: usize) {
+    let _ = MAP_SIZE.set(map_size);
+}
+
+/// Bytes a cycle of the pagefaults workload maps and touches.
+pub fn get_map_size() -> usize {
+    *MAP_SIZE.get().unwrap_or(&DEFAULT_MAP_SIZE)
+}
+
+pub const BUFFER_DATA: &[&str] = &["random", "repetitive"];
+
+pub const DEFAULT_BUFFER_SIZE: usize = 64*1024;
+
+/// Random data hardly compresses, while a repeated phrase compresses well and fast.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum BufferData {
+    Random,
+    Repetitive
+}
+
+impl BufferData {
+
+    pub fn parse(s: &str) -> Option<BufferData> {
+        match s {
+            "random" => Some(BufferData::Random),
+            "repetitive" => Some(BufferData::Repetitive),
+            _ => None
+        }
+    }
+
+    pub fn get_name(self: &Self) -> &'static str {
+        match self {
+            BufferData::Random => "random",
+            BufferData::Repetitive => "repetitive"
+        }
+    }
+}
+
+static BUFFER: OnceLock<(usize, BufferData)> = OnceLock::new();
+
+pub fn set_buffer(buffer_size: usize, buffer_data: BufferData) {
+    let _ = BUFFER.set((buffer_size, buffer_data));
+}
+
+/// Size and data of the buffer a cycle of the compress workload deflates.
+pub fn get_buffer() -> (usize, BufferData) {
+    *BUFFER.get().unwrap_or(&(DEFAULT_BUFFER_SIZE, BufferData::Random))
+}
+
+pub const DEFAULT_IMAGE_SIZE: &str = "512x512";
+
+/// Width and height, e.g. 1920x1080, both at least 3 for a pixel with all its neighbours.
+pub fn parse_image_size(s: &str) -> Option<(usize, usize)> {
+    let (width, height) = s.split_once('x')?;
+    let size = (width.parse::<usize>().ok()?, height.parse::<usize>().ok()?);
+    if size.0 >= 3 && size.1 >= 3 {Some(size)} else {None}
+}
+
+pub fn format_image_size(image_size: (usize, usize)) -> String {
+    format!("{}x{}", image_size.0, image_size.1)
+}
+
+static IMAGE_SIZE: OnceLock<(usize, usize)> = OnceLock::new();
+
+pub fn set_image_size(image_size: (usize, usize)) {
+    let _ = IMAGE_SIZE.set(image_size);
+}
+
+/// Width and height of the image a cycle of the stencil workload blurs.
+pub fn get_image_size() -> (usize, usize) {
+    *IMAGE_SIZE.get().unwrap_or(&(512, 512))
+}
+
+pub const DEFAULT_WRITE_PERCENT: usize = 10;
+
+static WRITE_PERCENT: OnceLock<usize> = OnceLock::new();
+
+pub fn set_write_percent(write_percent: usize) {
+    let _ = WRITE_PERCENT.set(write_percent);
+}
+
+/// Percentage of cycles of the rwlock workload taking the write lock.
+pub fn get_write_percent() -> usize {
+    *WRITE_PERCENT.get().unwrap_or(&DEFAULT_WRITE_PERCENT)
+}
+
+
+// Preparing and running tasks
+
+/// What a task works on, prepared before its stopwatch starts.
+pub enum TaskInput {
+    Triplets(Vec<Triplet>),
+    Integers(u64),
+    Syscalls(usize),
+    Pages(usize),
+    Buffer(Vec<u8>),
+    Document(&'static str),
+    Text(Vec<Regex>, String),
+    Image(Vec<f32>, usize),
+    Mix(u64, usize)
+}
+
+pub fn prepare_task(workload_kind: WorkloadKind, task_seed: u64) -> TaskInput {
+    match workload_kind {
+        WorkloadKind::Standard | WorkloadKind::Gpu =>
+            TaskInput::Triplets(rng::seed_triplets(rng::get_rng_kind(), task_seed, batch::get_n_triplets())),
+        WorkloadKind::Branchy => TaskInput::Integers(task_seed),
+        WorkloadKind::Syscalls => TaskInput::Syscalls(get_n_syscalls()),
+        WorkloadKind::Pagefaults => TaskInput::Pages(get_map_size()),
+        WorkloadKind::Compress => {
+            let (buffer_size, buffer_data) = get_buffer();
+            TaskInput::Buffer(create_buffer(buffer_size, buffer_data, task_seed))
+        }
+        WorkloadKind::Json => TaskInput::Document(DOCUMENT),
+        WorkloadKind::Regex => TaskInput::Text(compile_patterns(), create_log(LOG_SIZE, task_seed)),
+        WorkloadKind::Stencil => {
+            let (width, height) = get_image_size();
+            TaskInput::Image(create_image(width, height, task_seed), width)
+        }
+        WorkloadKind::Rwlock => TaskInput::Mix(task_seed, get_write_percent())
+    }
+}
+
+/// The result of a task, or nothing if the deadline passed first.
+pub fn run_task(input: &TaskInput, n_cycles: usize, deadline: Option<Timestamp>,
+                convergence_mode: ConvergenceMode) -> Option<f64> {
+    match input {
+        TaskInput::Triplets(triplets) if triplets.len() == 1 =>
+            iterate_until(triplets[0], n_cycles, deadline, convergence_mode),
+        TaskInput::Triplets(triplets) =>
+            batch::iterate_batch_until(triplets, n_cycles, deadline, convergence_mode),
+        TaskInput::Integers(seed) => step_branchy_until(*seed, n_cycles, deadline),
+        TaskInput::Syscalls(n_syscalls) => enter_kernel_until(*n_syscalls, n_cycles, deadline),
+        TaskInput::Pages(map_size) => fault_pages_until(*map_size, n_cycles, deadline),
+        TaskInput::Buffer(buffer) => compress_until(buffer, n_cycles, deadline),
+        TaskInput::Document(document) => parse_json_until(document, n_cycles, deadline),
+        TaskInput::Text(patterns, log) => match_until(patterns, log, n_cycles, deadline),
+        TaskInput::Image(image, width) => blur_until(image, *width, n_cycles, deadline),
+        TaskInput::Mix(seed, write_percent) => mix_until(*seed, *write_percent, n_cycles, deadline)
+    }
+}
+
+
+// Branching on random integers
+
+/// A state machine stepping on integers of xorshift, every step taking a branch
+/// the data decides, so about half of them are mispredicted.
+fn step_branchy_until(seed: u64, n_cycles: usize, deadline: Option<Timestamp>) -> Option<f64> {
+
+    // Xorshift never leaves zero
+    let mut x = seed | 1;
+    let mut state = 0u64;
+    let mut sum = 0u64;
+
+    for step in 0..n_cycles {
+
+        if step % DEADLINE_CHECK_PERIOD == 0 {
+            platform::check_stack();
+            if is_deadline_passed(deadline) {
+                return None;
+            }
+        }
+
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+
+        state = match (state, x & 3) {
+            (0, 0) | (1, 2) => {sum = sum.wrapping_add(x); 1}
+            (0, _) => {sum ^= x >> 3; 2}
+            (1, _) => {sum = sum.rotate_left(5); 3}
+            (2, 1) => {sum = sum.wrapping_mul(3); 0}
+            (2, _) => {sum = sum.wrapping_sub(x); 3}
+            (_, 0) => {sum ^= state; 0}
+            _ => {sum = sum.wrapping_add(1); 2}
+        };
+    }
+
+    Some((sum >> 11) as f64)
+}
+
+
+// Entering the kernel
+
+/// Cycles of system calls doing next to nothing, so what they cost is entering and leaving
+/// the kernel, and whatever the kernel serializes between threads.
+fn enter_kernel_until(n_syscalls: usize, n_cycles: usize, deadline: Option<Timestamp>) -> Option<f64> {
+
+    let mut sum = 0u64;
+
+    for step in 0..n_cycles {
+
+        if step % DEADLINE_CHECK_PERIOD == 0 {
+            platform::check_stack();
+            if is_deadline_passed(deadline) {
+                return None;
+            }
+        }
+
+        for _ in 0..n_syscalls {
+            sum = sum.wrapping_add(platform::enter_kernel());
+        }
+    }
+
+    Some(sum as f64)
+}
+
+
+// Faulting pages in
+
+/// Cycles of mapping fresh memory, so every page touched faults in a zeroed page and unmapping
+/// returns it, both taking locks of the address space that threads of a process share.
+fn fault_pages_until(map_size: usize, n_cycles: usize, deadline: Option<Timestamp>) -> Option<f64> {
+
+    let mut sum = 0u64;
+
+    for step in 0..n_cycles {
+
+        if step % DEADLINE_CHECK_PERIOD == 0 {
+            platform::check_stack();
+            if is_deadline_passed(deadline) {
+                return None;
+            }
+        }
+
+        sum += platform::map_and_touch(map_size);
+    }
+
+    Some(sum as f64)
+}
+
+
+// Compressing buffers
+
+const PHRASE_SIZE: usize = 64;
+
+fn create_buffer(buffer_size: usize, buffer_data: BufferData, seed: u64) -> Vec<u8> {
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    match buffer_data {
+        BufferData::Random => {
+            let mut buffer = vec![0u8; buffer_size];
+            rng.fill(buffer.as_mut_slice());
+            buffer
+        }
+        BufferData::Repetitive => {
+            let phrase: Vec<u8> = (0..PHRASE_SIZE).map(|_| rng.gen_range(b'a'..=b'z')).collect();
+            phrase.iter().copied().cycle().take(buffer_size).collect()
+        }
+    }
+}
+
+/// Cycles of deflating the buffer anew, at the default level as gzip does.
+fn compress_until(buffer: &[u8], n_cycles: usize, deadline: Option<Timestamp>) -> Option<f64> {
+
+    let mut sum = 0u64;
+    let mut compressed: Vec<u8> = Vec::with_capacity(buffer.len());
+
+    for step in 0..n_cycles {
+
+        if step % DEADLINE_CHECK_PERIOD == 0 {
+            platform::check_stack();
+            if is_deadline_passed(deadline) {
+                return None;
+            }
+        }
+
+        compressed.clear();
+        let mut encoder = DeflateEncoder::new(&mut compressed, Compression::default());
+        encoder.write_all(buffer).unwrap();
+        encoder.finish().unwrap();
+        sum += compressed.len() as u64;
+    }
+
+    Some(sum as f64)
+}
+
+
+// Parsing JSON
+
+/// A page of orders as a web service would answer it.
+const DOCUMENT: &str = include_str!("document.json");
+
+fn count_values(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::Array(values) => 1 + values.iter().map(count_values).sum::<usize>(),
+        serde_json::Value::Object(fields) => 1 + fields.values().map(count_values).sum::<usize>(),
+        _ => 1
+    }
+}
+
+/// Cycles of parsing the document into a tree of values, allocating every string,
+/// array, and object anew, so threads contend for the allocator as well.
+fn parse_json_until(document: &str, n_cycles: usize, deadline: Option<Timestamp>) -> Option<f64> {
+
+    let mut sum = 0u64;
+
+    for step in 0..n_cycles {
+
+        if step % DEADLINE_CHECK_PERIOD == 0 {
+            platform::check_stack();
+            if is_deadline_passed(deadline) {
+                return None;
+            }
+        }
+
+        let value: serde_json::Value = serde_json::from_str(document).unwrap();
+        sum += count_values(&value) as u64;
+    }
+
+    Some(sum as f64)
+}
+
+
+
+// Matching regexes
+
+const LOG_SIZE: usize = 16*1024;
+
+/// What one would search access logs for: addresses, dates, failed requests, and slow ones.
+const PATTERNS: &[&str] = &[
+    r"\b\d{1,3}\.\d{1,3}\.\d{1,3}\.\d{1,3}\b",
+    r"\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}",
+    r"[a-z0-9]+@[a-z]+\.(com|org|net)",
+    r"(?m) [45]\d\d \d+ms$",
+    r"(?i)(timeout|refused|reset)",
+    r"/orders/\d+/items/\d+"
+];
+
+const METHODS: &[&str] = &["GET", "GET", "GET", "POST", "PUT", "DELETE"];
+
+const STATUSES: &[u32] = &[200, 200, 200, 200, 201, 204, 304, 404, 500, 503];
+
+const ERRORS: &[&str] = &["", "", "", "", "upstream timeout", "connection refused", "Connection Reset"];
+
+fn compile_patterns() -> Vec<Regex> {
+    PATTERNS.iter().map(|pattern| Regex::new(pattern).unwrap()).collect()
+}
+
+/// Lines of an access log, as many as fit the size.
+fn create_log(log_size: usize, seed: u64) -> String {
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut log = String::with_capacity(log_size + 256);
+
+    loop {
+        let line = format!("{}.{}.{}.{} - user{}@example.{} [2024-{:02}-{:02}T{:02}:{:02}:{:02}] \"{} /orders/{}/items/{}\" {} {} {}ms\n",
+                           rng.gen_range(1..=254), rng.gen_range(0..=255), rng.gen_range(0..=255), rng.gen_range(1..=254),
+                           rng.gen_range(0..1000), if rng.gen_bool(0.5) {"com"} else {"org"},
+                           rng.gen_range(1..=12), rng.gen_range(1..=28),
+                           rng.gen_range(0..24), rng.gen_range(0..60), rng.gen_range(0..60),
+                           METHODS[rng.gen_range(0..METHODS.len())], rng.gen_range(1..100000), rng.gen_range(1..100),
+                           ERRORS[rng.gen_range(0..ERRORS.len())], STATUSES[rng.gen_range(0..STATUSES.len())],
+                           rng.gen_range(1..2000));
+        if log.len() + line.len() > log_size {
+            return log;
+        }
+        log.push_str(&line);
+    }
+}
+
+/// Cycles of finding every match of every pattern in the log, so the text streams through
+/// each automaton in turn.
+fn match_until(patterns: &[Regex], log: &str, n_cycles: usize, deadline: Option<Timestamp>) -> Option<f64> {
+
+    let mut sum = 0u64;
+
+    for step in 0..n_cycles {
+
+        if step % DEADLINE_CHECK_PERIOD == 0 {
+            platform::check_stack();
+            if is_deadline_passed(deadline) {
+                return None;
+            }
+        }
+
+        for pattern in patterns {
+            sum += pattern.find_iter(log).count() as u64;
+        }
+    }
+
+    Some(sum as f64)
+}
+
+
+
+// Blurring images
+
+fn create_image(width: usize, height: usize, seed: u64) -> Vec<f32> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..width*height).map(|_| rng.gen::<f32>()).collect()
+}
+
+/// Cycles of a 3x3 box blur of the image into another one, which the next cycle blurs
+/// back. Edge pixels lack neighbours and stay as they are.
+fn blur_until(image: &[f32], width: usize, n_cycles: usize, deadline: Option<Timestamp>) -> Option<f64> {
+
+    let height = image.len()/width;
+    let mut source = image.to_vec();
+    let mut target = image.to_vec();
+
+    for step in 0..n_cycles {
+
+        if step % DEADLINE_CHECK_PERIOD == 0 {
+            platform::check_stack();
+            if is_deadline_passed(deadline) {
+                return None;
+            }
+        }
+
+        for y in 1..height - 1 {
+            let (above, row, below) = ((y - 1)*width, y*width, (y + 1)*width);
+            for x in 1..width - 1 {
+                let sum = source[above + x - 1] + source[above + x] + source[above + x + 1] +
+                          source[row + x - 1] + source[row + x] + source[row + x + 1] +
+                          source[below + x - 1] + source[below + x] + source[below + x + 1];
+                target[row + x] = sum/9.0;
+            }
+        }
+
+        std::mem::swap(&mut source, &mut target);
+    }
+
+    Some(source.iter().map(|pixel| *pixel as f64).sum())
+}
+
+
+// Mixing reads and writes
+
+const TABLE_SIZE: usize = 1024;
+const READS_PER_CYCLE: usize = 8;
+
+/// Shared by all tasks of the process, so readers and writers of different tasks meet.
+static TABLE: RwLock<[u64; TABLE_SIZE]> = RwLock::new([0; TABLE_SIZE]);
+
+/// Cycles of either writing a slot of the table or reading a few neighbouring ones, as integers
+/// of xorshift decide, so writers wait for readers to leave and readers for writers.
+fn mix_until(seed: u64, write_percent: usize, n_cycles: usize, deadline: Option<Timestamp>) -> Option<f64> {
+
+    // Xorshift never leaves zero
+    let mut x = seed | 1;
+    let mut sum = 0u64;
+
+    for step in 0..n_cycles {
+
+        if step % DEADLINE_CHECK_PERIOD == 0 {
+            platform::check_stack();
+            if is_deadline_passed(deadline) {
+                return None;
+            }
+        }
+
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+
+        let slot = (x >> 17) as usize % TABLE_SIZE;
+
+        // A task panicking while holding the lock leaves the table as good as before
+        if x % 100 < write_percent as u64 {
+            TABLE.write().unwrap_or_else(|poisoned| poisoned.into_inner())[slot] = x;
+        } else {
+            let table = TABLE.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+            for offset in 0..READS_PER_CYCLE {
+                sum = sum.wrapping_add(table[(slot + offset) % TABLE_SIZE]);
+            }
+        }
+    }
+
+    Some((sum >> 11) as f64)
+}
+
+
+// Displaying lock contention
+
+/// Throughput stops growing with tasks where contention for the lock outweighs them.
+pub fn print_lock_contention(report: &Report) {
+
+    if report.get_metadata("Workload").map(|name| name.as_str()) != Some(WorkloadKind::Rwlock.get_name()) {
+        return;
+    }
+
+    let peak = report.observations.iter()
+        .max_by(|obs1, obs2| obs1.get_cycles_per_sec().total_cmp(&obs2.get_cycles_per_sec()));
+
+    if let (Some(first), Some(peak)) = (report.observations.first(), peak) {
+        info!("Reads and writes with {}% writes: {} cycles/sec at {} tasks, peaking at {} with {} tasks",
+              report.get_metadata("Write ratio").map_or("", |value| value.as_str()),
+              format_count(first.get_cycles_per_sec() as usize), first.count_tasks(),
+              format_count(peak.get_cycles_per_sec() as usize), peak.count_tasks());
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn workloads_are_named_as_registered() {
+        for entry in registry::WORKLOADS {
+            assert_eq!(WorkloadKind::parse(entry.name).map(|kind| kind.get_name()), Some(entry.name));
+        }
+        assert!(WorkloadKind::parse("unknown").is_none());
+    }
+
+    #[test]
+    fn branchy_tasks_depend_on_their_seed_only() {
+        assert_eq!(step_branchy_until(7, 10000, None), step_branchy_until(7, 10000, None));
+        assert_ne!(step_branchy_until(7, 10000, None), step_branchy_until(8, 10000, None));
+        assert!(step_branchy_until(7, 10000, Some(Timestamp::EPOCH)).is_none());
+    }
+
+    #[test]
+    fn syscalls_are_made_every_cycle() {
+        assert_eq!(enter_kernel_until(3, 100, None), Some(300.0*platform::enter_kernel() as f64));
+        assert_eq!(enter_kernel_until(0, 100, None), Some(0.0));
+    }
+
+    #[test]
+    fn every_page_mapped_is_touched() {
+        assert_eq!(platform::map_and_touch(1), 1);
+        assert_eq!(fault_pages_until(1024*1024, 3, None), Some(3.0*platform::map_and_touch(1024*1024) as f64));
+        assert!(platform::map_and_touch(1024*1024) >= 16);
+    }
+
+    #[test]
+    fn repetitive_buffers_compress_better() {
+        let random = create_buffer(4096, BufferData::Random, 7);
+        let repetitive = create_buffer(4096, BufferData::Repetitive, 7);
+        assert_eq!((random.len(), repetitive.len()), (4096, 4096));
+        assert_eq!(random, create_buffer(4096, BufferData::Random, 7));
+        assert!(compress_until(&repetitive, 1, None).unwrap() < compress_until(&random, 1, None).unwrap()/10.0);
+    }
+
+    #[test]
+    fn bundled_document_parses() {
+        let n_values = parse_json_until(DOCUMENT, 1, None).unwrap();
+        assert_eq!(n_values, 997.0);
+        assert_eq!(parse_json_until(DOCUMENT, 3, None), Some(3.0*n_values));
+    }
+
+    #[test]
+    fn every_pattern_matches_the_log() {
+        let log = create_log(LOG_SIZE, 7);
+        assert!(log.len() <= LOG_SIZE && log.len() > LOG_SIZE - 256);
+        assert_eq!(log, create_log(LOG_SIZE, 7));
+        for pattern in compile_patterns() {
+            assert!(pattern.is_match(&log), "{}", pattern);
+        }
+        let n_matches = match_until(&compile_patterns(), &log, 1, None).unwrap();
+        assert_eq!(match_until(&compile_patterns(), &log, 3, None), Some(3.0*n_matches));
+    }
+
+    #[test]
+    fn images_are_sized_by_width_and_height() {
+        assert_eq!(parse_image_size("1920x1080"), Some((1920, 1080)));
+        assert_eq!(parse_image_size(DEFAULT_IMAGE_SIZE), Some(get_image_size()));
+        assert_eq!(parse_image_size("2x512"), None);
+        assert_eq!(parse_image_size("512"), None);
+        assert_eq!(format_image_size((1920, 1080)), "1920x1080");
+    }
+
+    #[test]
+    fn blurring_keeps_flat_images_flat() {
+        assert_eq!(blur_until(&[0.5; 12], 4, 10, None), Some(6.0));
+        let image = create_image(16, 8, 7);
+        assert_eq!(image.len(), 128);
+        assert_eq!(blur_until(&image, 16, 0, None), Some(image.iter().map(|pixel| *pixel as f64).sum()));
+        assert!(blur_until(&image, 16, 10, Some(Timestamp::EPOCH)).is_none());
+    }
+
+    #[test]
+    fn writes_fill_the_shared_table() {
+        assert_eq!(mix_until(7, 100, 1000, None), Some(0.0));
+        assert!(TABLE.read().unwrap().iter().any(|value| *value != 0));
+        assert!(mix_until(7, 0, 1000, None).unwrap() > 0.0);
+        assert!(mix_until(7, 0, 1000, Some(Timestamp::EPOCH)).is_none());
+    }
+}
@@ -0,0 +1,110 @@
+// * * ** *** ***** ******** ************* *********************
+// Framing observations in the work-span model
+// * * ** *** ***** ******** ************* *********************
+
+use super::*;
+
+
+// Work and span
+
+/// Work is the time a single CPU takes for all tasks, span is the time infinitely many take,
+/// and the time achieved is what the CPUs at hand took.
+pub struct WorkSpan {
+    pub work: TaskDuration,
+    pub span: TaskDuration,
+    pub achieved: TaskDuration
+}
+
+impl WorkSpan {
+
+    /// The span of independent tasks is the longest one, and of dependent ones the critical path.
+    pub fn create(report: &Report, obs: &Observation) -> WorkSpan {
+        WorkSpan {
+            work: obs.sum_duration(),
+            span: dag::get_report_critical_path(report, obs).unwrap_or_else(|| obs.get_max_task_duration()),
+            achieved: obs.get_total_duration()
+        }
+    }
+
+    /// Work over span, the most CPUs the tasks keep busy.
+    pub fn get_parallelism(self: &Self) -> f64 {
+        self.work.ratio(self.span).unwrap_or(0.0)
+    }
+
+    pub fn get_speedup(self: &Self) -> f64 {
+        self.work.ratio(self.achieved).unwrap_or(0.0)
+    }
+
+    /// Speedup is bounded by the CPUs at hand as well as by the parallelism.
+    pub fn get_speedup_bound(self: &Self, n_cpus: usize) -> f64 {
+        self.get_parallelism().min(n_cpus as f64)
+    }
+}
+
+
+// Displaying
+
+fn create_work_span_table(colored: bool) -> ConsoleTable {
+    ConsoleTable::create(vec![
+        Column::create("Tasks", 5, Align::Right, 0),
+        Column::create("Work T1", 10, Align::Right, 0),
+        Column::create("Span Tinf", 10, Align::Right, 0),
+        Column::create("Parallelism", 11, Align::Right, 0),
+        Column::create("Achieved TP", 11, Align::Right, 0),
+        Column::create("Speedup", 7, Align::Right, 0),
+        Column::create("Bound", 7, Align::Right, 1)
+    ], colored)
+}
+
+/// Work, span, and their ratio for every observation, as parallel computing courses put them.
+pub fn print_work_span(report: &Report, colored: bool) {
+
+    let n_cpus = count_report_cpus(report);
+
+    println!("\nWork-span model, T1 work, Tinf span, TP achieved on {} CPUs (ms):", n_cpus);
+
+    let table = create_work_span_table(colored);
+    table.print_header();
+
+    for obs in &report.observations {
+        let work_span = WorkSpan::create(report, obs);
+        table.print_row(&vec![
+            Cell::Plain(obs.count_tasks().to_string()),
+            Cell::Plain(work_span.work.to_string()),
+            Cell::Plain(work_span.span.to_string()),
+            Cell::Plain(format!("{:.2}", work_span.get_parallelism())),
+            Cell::Plain(work_span.achieved.to_string()),
+            Cell::Plain(format!("{:.2}", work_span.get_speedup())),
+            Cell::Plain(format!("{:.2}", work_span.get_speedup_bound(n_cpus)))
+        ]);
+    }
+
+    table.print_footer();
+}
+
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use testing::FakeWorkload;
+
+    #[test]
+    fn independent_tasks_span_the_longest_one() {
+        let report = FakeWorkload::create(2, 4, 100).create_report(1..=4);
+        let work_span = WorkSpan::create(&report, report.find_observation(4).unwrap());
+        assert_eq!((work_span.work.as_millis(), work_span.span.as_millis()), (400, 100));
+        assert_eq!(work_span.get_parallelism(), 4.0);
+        assert_eq!(work_span.get_speedup_bound(2), 2.0);
+        assert!((work_span.get_speedup() - 400.0/201.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn dependent_tasks_span_the_critical_path() {
+        let mut report = FakeWorkload::create(2, 4, 100).create_report(1..=4);
+        report.set_metadata("Dependencies", "chain");
+        let work_span = WorkSpan::create(&report, report.find_observation(4).unwrap());
+        assert_eq!(work_span.span.as_millis(), 400);
+        assert_eq!(work_span.get_parallelism(), 1.0);
+    }
+}
@@ -0,0 +1,19 @@
+set terminal pngcairo size 800,1000
+set output "chart.png"
+$data << EOD
+1 1.000 100 1.000 1.000 100.0 100.0
+2 1.980 101 1.980 1.980 101.0 101.0
+3 1.500 200 1.500 1.500 200.0 200.0
+4 1.990 201 1.990 1.990 201.0 201.0
+6 1.974 304 1.967 1.993 301.0 305.0
+EOD
+set multiplot layout 2,1
+set grid
+set xlabel "Tasks"
+set title "Speedup vs. tasks"
+set ylabel "Speedup"
+plot $data using 1:2:4:5 with yerrorlines title "Speedup"
+set title "Total duration vs. tasks"
+set ylabel "Total duration, ms"
+plot $data using 1:3:6:7 with yerrorlines title "Total duration"
+unset multiplot